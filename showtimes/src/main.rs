@@ -1,13 +1,16 @@
 #![doc = include_str!("../README.md")]
 
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::{Router, response::IntoResponse, routing::get};
 use routes::graphql::{GRAPHQL_ROUTE, GRAPHQL_WS_ROUTE};
 use serde_json::json;
 use showtimes_fs::s3::S3FsCredentials;
 use showtimes_shared::Config;
-use tasks::{shutdown_all_tasks, tasks_rss_premium, tasks_rss_standard};
+use tasks::{
+    shutdown_all_tasks, tasks_api_key_usage, tasks_rss_premium, tasks_rss_standard,
+    tasks_search_outbox,
+};
 // use tasks::{spawn_with, RSSTasks};
 use tokio::{net::TcpListener, sync::Mutex};
 use tokio_cron_scheduler::{Job, JobScheduler};
@@ -18,6 +21,7 @@ mod onion;
 mod routes;
 mod state;
 mod tasks;
+mod usage;
 
 const ASSET_ICON: &[u8] = include_bytes!("../assets/icon.ico");
 
@@ -224,6 +228,14 @@ async fn entrypoint() -> anyhow::Result<()> {
     )
     .await?;
     clickhouse_conn.create_tables().await?;
+    let clickhouse_conn = match config.database.event_broker {
+        showtimes_shared::config::EventBroker::Memory => clickhouse_conn,
+        showtimes_shared::config::EventBroker::Redis => {
+            tracing::info!("🔌📡 Loading Redis event broker...");
+            let broker = showtimes_events::RedisBroker::new(&redis_conn).await?;
+            clickhouse_conn.with_broker(broker)
+        }
+    };
 
     // Initialize the filesystem
     tracing::info!("🔌📁 Loading filesystem...");
@@ -274,6 +286,33 @@ async fn entrypoint() -> anyhow::Result<()> {
         showtimes_search::create_connection(&config.meilisearch.url, &config.meilisearch.api_key)
             .await?;
 
+    tracing::info!("🔌🪝 Loading webhook dispatcher...");
+    let mut webhook_dispatcher = showtimes_webhooks::WebhookDispatcher::new()?;
+    if let Some(max_retries) = config.webhooks.max_retries {
+        webhook_dispatcher = webhook_dispatcher.with_retries(max_retries, Duration::from_secs(1));
+    }
+    if let Some(max_failures) = config.webhooks.max_failures {
+        webhook_dispatcher = webhook_dispatcher.with_max_failures(max_failures);
+    }
+    if let Some(public_url) = &config.webhooks.public_url {
+        webhook_dispatcher = webhook_dispatcher.with_asset_url(public_url);
+    }
+
+    let mut rss_dispatcher = showtimes_webhooks::RSSDispatcher::new()?;
+    if let Some(max_retries) = config.webhooks.max_retries {
+        rss_dispatcher = rss_dispatcher.with_retries(max_retries, Duration::from_secs(1));
+    }
+
+    let fansubdb_sync = config.fansubdb.as_ref().map(|fsdb| {
+        tracing::info!("🔌🔃 Loading FansubDB sync...");
+        let client = showtimes_fansubdb::FansubDBClient::new(&fsdb.email, &fsdb.password);
+        let mut fansubdb_sync = showtimes_fansubdb::FansubDBSync::new(client);
+        if let Some(max_retries) = fsdb.max_retries {
+            fansubdb_sync = fansubdb_sync.with_retries(max_retries, Duration::from_secs(30));
+        }
+        fansubdb_sync
+    });
+
     tracing::info!("🔌🐍 Loading external metadata services...");
     let anilist_provider = showtimes_metadata::AnilistProvider::new(true);
     let tmdb_provider = config
@@ -291,6 +330,13 @@ async fn entrypoint() -> anyhow::Result<()> {
     let schema = crate::routes::graphql::create_schema(&mongo_conn.db);
 
     tracing::info!("🔌 Initializing state...");
+    let clickhouse_conn = Arc::new(clickhouse_conn);
+    let webhook_task = webhook_dispatcher
+        .clone()
+        .listen(Arc::clone(&mongo_conn.db), Arc::clone(&clickhouse_conn));
+    let fansubdb_task = fansubdb_sync
+        .as_ref()
+        .map(|fsdb| fsdb.clone().listen(Arc::clone(&mongo_conn.db)));
     let state = state::ShowtimesState {
         db: mongo_conn.db,
         storage: Arc::new(fs),
@@ -303,7 +349,11 @@ async fn entrypoint() -> anyhow::Result<()> {
         anilist_provider: Arc::new(Mutex::new(anilist_provider)),
         tmdb_provider,
         vndb_provider,
-        clickhouse: Arc::new(clickhouse_conn),
+        clickhouse: clickhouse_conn,
+        webhooks: Arc::new(webhook_dispatcher),
+        rss_delivery: Arc::new(rss_dispatcher),
+        fansubdb: fansubdb_sync.map(Arc::new),
+        api_key_usage: Arc::new(usage::APIKeyUsage::default()),
     };
     let shared_state = Arc::new(state);
 
@@ -401,6 +451,42 @@ async fn entrypoint() -> anyhow::Result<()> {
         active_jobs.push(rss_premi_uuid);
     }
 
+    let search_outbox_dur = Duration::from_secs(30);
+    let cloned_state = Arc::clone(&shared_state);
+    let job_search_outbox = Job::new_repeated_async(search_outbox_dur, move |_uuid, _lock| {
+        Box::pin({
+            let value = cloned_state.clone();
+            async move {
+                match tasks_search_outbox(value).await {
+                    Ok(_) => (),
+                    Err(e) => {
+                        tracing::error!("Search outbox task failed: {}", e);
+                    }
+                }
+            }
+        })
+    })?;
+    let search_outbox_uuid = scheduler.add(job_search_outbox).await?;
+    active_jobs.push(search_outbox_uuid);
+
+    let api_key_usage_dur = Duration::from_secs(60);
+    let cloned_state = Arc::clone(&shared_state);
+    let job_api_key_usage = Job::new_repeated_async(api_key_usage_dur, move |_uuid, _lock| {
+        Box::pin({
+            let value = cloned_state.clone();
+            async move {
+                match tasks_api_key_usage(value).await {
+                    Ok(_) => (),
+                    Err(e) => {
+                        tracing::error!("API key usage task failed: {}", e);
+                    }
+                }
+            }
+        })
+    })?;
+    let api_key_usage_uuid = scheduler.add(job_api_key_usage).await?;
+    active_jobs.push(api_key_usage_uuid);
+
     tracing::info!("⚡ Starting task scheduler...");
     scheduler.start().await?;
 
@@ -413,14 +499,27 @@ async fn entrypoint() -> anyhow::Result<()> {
         GRAPHQL_ROUTE
     );
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .unwrap();
 
     // Stop tasks
     tracing::info!("🔕 Shutting down task scheduler...");
     shutdown_all_tasks(&mut scheduler, &active_jobs).await?;
+    // Flush the remaining API key usage
+    if let Err(e) = tasks_api_key_usage(Arc::clone(&shared_state)).await {
+        tracing::error!("Failed to flush API key usage: {}", e);
+    }
+    tracing::info!("🔕 Shutting down webhook dispatcher...");
+    webhook_task.abort();
+    if let Some(fansubdb_task) = fansubdb_task {
+        tracing::info!("🔕 Shutting down FansubDB sync...");
+        fansubdb_task.abort();
+    }
 
     Ok(())
 }
@@ -1,8 +1,11 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, OnceLock},
+};
 
 use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    extract::{State, WebSocketUpgrade},
+    extract::{ConnectInfo, State, WebSocketUpgrade},
     http::HeaderMap,
     response::{Html, IntoResponse, Response},
 };
@@ -14,7 +17,10 @@ use showtimes_gql_common::{
 use showtimes_gql_mutations::MutationRoot;
 use showtimes_gql_queries::QueryRoot;
 use showtimes_gql_subscriptions::SubscriptionRoot;
-use showtimes_session::{manager::SessionKind, oauth2::discord::DiscordClient};
+use showtimes_session::{
+    manager::{SessionKind, SessionMetadata},
+    oauth2::discord::DiscordClient,
+};
 use showtimes_shared::Config;
 
 use crate::state::SharedShowtimesState;
@@ -30,6 +36,7 @@ pub const GRAPHQL_WS_ROUTE: &str = "/graphql/ws";
 static DISCORD_CLIENT: OnceLock<Arc<DiscordClient>> = OnceLock::new();
 static STUBBED_OWNER: OnceLock<showtimes_db::m::User> = OnceLock::new();
 static GRAPHQL_SDL: OnceLock<String> = OnceLock::new();
+const MAX_USER_AGENT_LENGTH: usize = 512;
 
 /// Create the GraphQL schema
 pub fn create_schema(db_pool: &DatabaseShared) -> ShowtimesGQLSchema {
@@ -62,6 +69,10 @@ pub fn create_schema(db_pool: &DatabaseShared) -> ShowtimesGQLSchema {
             data_loader::ServerPremiumLoader::new(db_pool),
             tokio::spawn,
         ))
+        .data(showtimes_gql_common::DataLoader::new(
+            data_loader::WebhookLoader::new(db_pool),
+            tokio::spawn,
+        ))
         .finish()
 }
 
@@ -109,6 +120,41 @@ fn get_token_or_bearer(headers: &HeaderMap, config: &Config) -> Option<(SessionK
     })
 }
 
+/// Get the client information for the session.
+///
+/// The proxy headers are only used when the peer is one of the trusted proxies.
+fn get_session_metadata(headers: &HeaderMap, addr: SocketAddr, config: &Config) -> SessionMetadata {
+    let header_str = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let user_agent = header_str("User-Agent").map(|value| {
+        value
+            .chars()
+            .take(MAX_USER_AGENT_LENGTH)
+            .collect::<String>()
+    });
+    let ip = if config.trusted_proxies.contains(&addr.ip()) {
+        header_str("X-Forwarded-For")
+            .and_then(|value| value.split(',').next())
+            .map(|value| value.trim())
+            .or_else(|| header_str("X-Real-IP"))
+            .and_then(|value| value.parse::<std::net::IpAddr>().ok())
+            .unwrap_or(addr.ip())
+    } else {
+        addr.ip()
+    };
+
+    SessionMetadata {
+        user_agent,
+        ip: Some(ip.to_string()),
+    }
+}
+
 fn get_orchestrator(headers: &HeaderMap) -> showtimes_gql_common::Orchestrator {
     match headers.get("x-orchestrator") {
         None => showtimes_gql_common::Orchestrator::Standalone,
@@ -124,6 +170,7 @@ fn get_orchestrator(headers: &HeaderMap) -> showtimes_gql_common::Orchestrator {
 /// This handler will handle all GraphQL requests, it will also handle the authentication
 pub async fn graphql_handler(
     State(state): State<SharedShowtimesState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
@@ -141,6 +188,10 @@ pub async fn graphql_handler(
     req = req.data(discord_client.clone());
     req = req.data(state.meili.clone());
     req = req.data(state.clickhouse.clone());
+    req = req.data(state.webhooks.clone());
+    if let Some(fansubdb) = state.fansubdb.as_ref() {
+        req = req.data(fansubdb.clone());
+    }
     req = req.data(state.session.clone());
     req = req.data(state.jwt.clone());
     req = req.data(state.storage.clone());
@@ -159,6 +210,7 @@ pub async fn graphql_handler(
                 // Always provide user info
                 match load_authenticated_user(&session, &state.db).await {
                     Ok(user) => {
+                        insert_session_api_key(&state, &session, &user, &mut req.data);
                         req = req.data(session.get_claims().clone());
                         req = req.data(session);
                         req = req.data(user);
@@ -177,6 +229,7 @@ pub async fn graphql_handler(
 
     // Set orchestrator
     req = req.data(get_orchestrator(&headers));
+    req = req.data(get_session_metadata(&headers, addr, &state.config));
 
     // Check for x-refresh-token header
     let mut active_refresh = None;
@@ -297,8 +350,7 @@ async fn on_ws_init(
             {
                 Ok(session) => {
                     tracing::debug!("[WS] Got session (from payload): {:?}", session);
-                    data.insert(session.get_claims().clone());
-                    data.insert(session);
+                    insert_ws_session(&state, session, &mut data).await?;
                 }
                 Err(err) => {
                     tracing::error!("[WS] Error getting session (from payload): {:?}", err);
@@ -314,8 +366,7 @@ async fn on_ws_init(
                     match state.session.lock().await.get_session(token, kind).await {
                         Ok(session) => {
                             tracing::debug!("[WS] Got session (from header): {:?}", session);
-                            data.insert(session.get_claims().clone());
-                            data.insert(session);
+                            insert_ws_session(&state, session, &mut data).await?;
                         }
                         Err(err) => {
                             tracing::error!("[WS] Error getting session (from header): {:?}", err);
@@ -360,6 +411,78 @@ async fn on_ws_init(
     Ok(data)
 }
 
+/// Load the user of the session and insert it with the session into the Websocket data
+async fn insert_ws_session(
+    state: &SharedShowtimesState,
+    session: showtimes_session::ShowtimesUserSession,
+    data: &mut GQLData,
+) -> Result<(), GQLError> {
+    let user = load_authenticated_user(&session, &state.db)
+        .await
+        .map_err(|resp| {
+            tracing::error!("[WS] Error loading authenticated user: {:?}", &resp.errors);
+            match resp.errors.into_iter().next() {
+                Some(err) => GQLError {
+                    message: err.message,
+                    source: err.source,
+                    extensions: err.extensions,
+                },
+                None => GQLError::new("Error loading authenticated user"),
+            }
+        })?;
+
+    insert_session_api_key(state, &session, &user, data);
+    data.insert(session.get_claims().clone());
+    data.insert(session);
+    data.insert(user);
+    Ok(())
+}
+
+/// Insert the API key used by the session and limit the data loaders to the API key scope
+///
+/// The scoped data loaders override the shared one from the schema.
+fn insert_session_api_key(
+    state: &SharedShowtimesState,
+    session: &showtimes_session::ShowtimesUserSession,
+    user: &showtimes_db::m::User,
+    data: &mut GQLData,
+) {
+    let Some((raw_key, api_key)) = find_session_api_key(session, user) else {
+        return;
+    };
+
+    state.api_key_usage.touch(user.id, raw_key);
+    if api_key.is_server_scoped() {
+        data.insert(showtimes_gql_common::DataLoader::new(
+            data_loader::ServerDataLoader::new(&state.db)
+                .with_allowed_servers(api_key.servers.clone()),
+            tokio::spawn,
+        ));
+        data.insert(showtimes_gql_common::DataLoader::new(
+            data_loader::ProjectDataLoader::new(&state.db)
+                .with_allowed_servers(api_key.servers.clone()),
+            tokio::spawn,
+        ));
+    }
+    data.insert(api_key.clone());
+}
+
+/// Find the API key used by the session in the user API keys
+fn find_session_api_key<'a>(
+    session: &showtimes_session::ShowtimesUserSession,
+    user: &'a showtimes_db::m::User,
+) -> Option<(showtimes_shared::APIKey, &'a showtimes_db::m::APIKey)> {
+    if session.get_claims().get_audience() != showtimes_session::ShowtimesAudience::APIKey {
+        return None;
+    }
+
+    let raw_key = showtimes_shared::APIKey::try_from(session.get_claims().get_metadata()).ok()?;
+    user.api_key
+        .iter()
+        .find(|k| k.key == raw_key)
+        .map(|api_key| (raw_key, api_key))
+}
+
 async fn load_authenticated_user(
     session: &showtimes_session::ShowtimesUserSession,
     database: &DatabaseShared,
@@ -470,7 +593,30 @@ async fn load_authenticated_user(
     };
 
     match load_method {
-        Some(user) => Ok(user),
+        Some(user) => {
+            // Expired API keys are rejected here, so they never get a session
+            if let Some((raw_key, api_key)) = find_session_api_key(session, &user)
+                && api_key.is_expired()
+            {
+                let gql_error = showtimes_gql_common::errors::GQLError::new(
+                    "API key is expired",
+                    GQLErrorCode::APIKeyExpired,
+                )
+                .extend(|e| {
+                    e.set("user", user.id.to_string());
+                    e.set("key", raw_key.to_string());
+                    if let Some(expires_at) = api_key.expires_at {
+                        e.set("expires_at", expires_at.to_string());
+                    }
+                    e.set("audience", audience.to_string());
+                })
+                .build();
+
+                return Err(error_to_gql_response(gql_error));
+            }
+
+            Ok(user)
+        }
         None => {
             let gql_error = showtimes_gql_common::errors::GQLError::new(
                 "User not found",
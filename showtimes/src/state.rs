@@ -30,4 +30,12 @@ pub struct ShowtimesState {
     pub vndb_provider: Option<Arc<showtimes_metadata::VndbProvider>>,
     /// ClickHouse events broker
     pub clickhouse: showtimes_events::SharedSHClickHouse,
+    /// Webhook delivery dispatcher
+    pub webhooks: Arc<showtimes_webhooks::WebhookDispatcher>,
+    /// RSS entries delivery dispatcher
+    pub rss_delivery: Arc<showtimes_webhooks::RSSDispatcher>,
+    /// FansubDB release synchronizer, only available when configured
+    pub fansubdb: Option<Arc<showtimes_fansubdb::FansubDBSync>>,
+    /// Pending API key usage, flushed to the database periodically
+    pub api_key_usage: Arc<crate::usage::APIKeyUsage>,
 }
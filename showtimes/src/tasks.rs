@@ -1,10 +1,22 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use ahash::{HashMap, HashMapExt};
-use showtimes_db::{m::RSSFeed, mongodb::bson::doc};
+use showtimes_db::{
+    m::RSSFeed,
+    mongodb::bson::{Bson, DateTime, doc, to_bson},
+};
 use showtimes_rss::parse_feed;
 use tokio_cron_scheduler::{JobScheduler, JobSchedulerError};
 
+/// The base delay for failing RSS feeds
+const RSS_RETRY_BASE: Duration = Duration::from_secs(60 * 5);
+
+/// The maximum delay between each retry of a failing RSS feed
+const RSS_MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60 * 12);
+
+/// The default consecutive failures before a RSS feed is disabled
+const DEFAULT_RSS_MAX_FAILURES: u32 = 10;
+
 pub async fn shutdown_all_tasks(
     sched: &mut JobScheduler,
     jobs: &[uuid::Uuid],
@@ -30,22 +42,33 @@ async fn rss_single_task(
         feed.creator
     );
 
-    let mut header_maps = reqwest::header::HeaderMap::new();
-    if let Some(last_mod) = &feed.last_mod {
-        header_maps.insert(
-            reqwest::header::LAST_MODIFIED,
-            reqwest::header::HeaderValue::from_str(last_mod)?,
-        );
-    }
-    if let Some(etag) = &feed.etag {
-        header_maps.insert(
-            reqwest::header::ETAG,
-            reqwest::header::HeaderValue::from_str(etag)?,
-        );
-    }
+    // Compile first so an invalid filter is reported as a feed failure
+    let filters = feed.compile_filters()?;
+
+    let cache_headers =
+        showtimes_rss::FeedCacheHeaders::new(feed.etag.clone(), feed.last_mod.clone());
+    let fetched = parse_feed(feed.url.to_string(), Some(&cache_headers)).await?;
 
-    let feed_data: showtimes_rss::FeedParsed<'_> =
-        parse_feed(feed.url.to_string(), Some(header_maps)).await?;
+    let mut fetch_stats = feed.fetch_stats.clone();
+    fetch_stats.record(
+        fetched.stats.status,
+        fetched.stats.bytes,
+        fetched.stats.duration,
+    );
+
+    let feed_data: showtimes_rss::FeedParsed<'_> = match fetched.result {
+        showtimes_rss::FeedFetchResult::Modified(feed_data) => feed_data,
+        showtimes_rss::FeedFetchResult::NotModified => {
+            tracing::debug!(
+                "RSS feed is not modified: {} (for {}, took {:?})",
+                &feed.url,
+                feed.creator,
+                fetched.stats.duration
+            );
+            rss_track_fetch(&feed, &handler, &fetch_stats, None, None).await?;
+            return Ok(());
+        }
+    };
 
     tracing::debug!(
         "Parsed a total of {} entries for RSS feed: {} (for {})",
@@ -84,30 +107,213 @@ async fn rss_single_task(
         // Drop
         std::mem::drop(rss_manager);
 
-        // Publish events
+        // Publish events, filtered entries are still marked as seen above
         let rss_events: Vec<showtimes_events::m::RSSEvent> = new_entries
             .iter()
+            .filter(|x| filters.matches(|key| x.get(key).map(|v| v.to_texts()).unwrap_or_default()))
             .map(|x| showtimes_events::m::RSSEvent::from_entry(feed.id, feed.creator, x))
             .collect();
 
-        state.clickhouse.create_rss_many_async(rss_events);
+        tracing::debug!(
+            "Publishing {} of {} new entries for RSS feed: {} (for {})",
+            rss_events.len(),
+            new_entries.len(),
+            &feed.url,
+            feed.creator
+        );
+
+        if !rss_events.is_empty() {
+            if !feed.delivery_webhooks().is_empty() {
+                // Feed is usually ordered from the newest, post the oldest first
+                let deliver_events: Vec<_> = rss_events.iter().rev().cloned().collect();
+                tokio::task::spawn(rss_deliver_entries(
+                    feed.clone(),
+                    deliver_events,
+                    Arc::clone(&state.rss_delivery),
+                    Arc::clone(&handler),
+                ));
+            }
+
+            state.clickhouse.create_rss_many_async(rss_events);
+        }
     }
 
-    // Update etag and last modified
-    let mut cloned_feed = feed.clone();
-    let mut changed = false;
-    if let Some(last_mod) = feed_data.last_modified {
-        cloned_feed.last_mod = Some(last_mod);
-        changed = true;
+    tracing::debug!(
+        "Updating RSS feed: {} (for {}, {} bytes in {:?})",
+        &feed.url,
+        feed.creator,
+        fetched.stats.bytes,
+        fetched.stats.duration
+    );
+    // Update etag, last modified, and the fetch stats
+    rss_track_fetch(
+        &feed,
+        &handler,
+        &fetch_stats,
+        feed_data.etag,
+        feed_data.last_modified,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Record a successful fetch of the feed, only the fields owned by the poller are updated
+///
+/// Same as [`rss_track_failure`], the health is updated per field so a reset
+/// done in the meantime is not reverted.
+async fn rss_track_fetch(
+    feed: &RSSFeed,
+    handler: &showtimes_db::RSSFeedHandler,
+    fetch_stats: &showtimes_db::m::RSSFeedFetchStats,
+    etag: Option<String>,
+    last_mod: Option<String>,
+) -> anyhow::Result<()> {
+    let mut update = doc! {
+        "fetch_stats": to_bson(fetch_stats)?,
+        "health.last_success": DateTime::now(),
+        "health.failures": 0,
+        "health.next_retry": Bson::Null,
+        "updated": DateTime::now(),
+    };
+    if let Some(etag) = etag {
+        update.insert("etag", etag);
     }
-    if let Some(etag) = feed_data.etag {
-        cloned_feed.etag = Some(etag);
-        changed = true;
+    if let Some(last_mod) = last_mod {
+        update.insert("last_mod", last_mod);
+    }
+
+    // The feed might be modified by the user in the meantime, do not save the whole feed
+    handler
+        .get_collection()
+        .update_one(doc! { "id": feed.id.to_string() }, doc! { "$set": update })
+        .await?;
+
+    Ok(())
+}
+
+/// Deliver the new entries into the Discord channel webhooks of the feed
+async fn rss_deliver_entries(
+    feed: RSSFeed,
+    events: Vec<showtimes_events::m::RSSEvent>,
+    dispatcher: Arc<showtimes_webhooks::RSSDispatcher>,
+    handler: Arc<showtimes_db::RSSFeedHandler>,
+) {
+    let results = dispatcher.deliver(&feed, &events).await;
+    let failed = results.iter().filter(|d| d.result.is_err()).count();
+
+    let mut last_error = None;
+    for delivery in results.iter() {
+        if let Err(err) = &delivery.result {
+            tracing::warn!(
+                "Failed to deliver RSS entry {} of {} to channel {}: {}",
+                delivery.hash,
+                feed.id,
+                delivery.channel,
+                err
+            );
+            last_error = Some(format!(
+                "Failed to deliver entry {} to channel {}: {}",
+                delivery.hash, delivery.channel, err
+            ));
+        }
     }
 
-    if changed {
-        tracing::debug!("Updating RSS feed: {} (for {})", &feed.url, feed.creator);
-        handler.save(&mut cloned_feed, None).await?;
+    // The entries are already marked as seen, so at least surface the failure in the feed health
+    if let Some(last_error) = last_error {
+        let update = doc! {
+            "health.last_error": last_error,
+            "health.last_error_at": DateTime::now(),
+        };
+
+        if let Err(err) = handler
+            .get_collection()
+            .update_one(doc! { "id": feed.id.to_string() }, doc! { "$set": update })
+            .await
+        {
+            tracing::error!(
+                "Failed to record RSS delivery failure for {}: {}",
+                feed.id,
+                err
+            );
+        }
+    }
+
+    tracing::debug!(
+        "Delivered {} of {} RSS entries for RSS feed: {} (for {})",
+        results.len() - failed,
+        results.len(),
+        &feed.url,
+        feed.creator
+    );
+}
+
+/// The exponential backoff delay for failing RSS feeds
+fn rss_backoff(failures: u32) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(16);
+    RSS_RETRY_BASE
+        .saturating_mul(factor)
+        .min(RSS_MAX_RETRY_DELAY)
+}
+
+/// Record a failed fetch of the feed, disabling it after too many consecutive failures
+async fn rss_track_failure(
+    mut feed: RSSFeed,
+    state: Arc<crate::state::ShowtimesState>,
+    handler: Arc<showtimes_db::RSSFeedHandler>,
+    error: String,
+) -> anyhow::Result<()> {
+    let max_failures = state
+        .config
+        .rss
+        .max_failures
+        .unwrap_or(DEFAULT_RSS_MAX_FAILURES);
+
+    let delay = rss_backoff(feed.health.failures.saturating_add(1));
+    feed.health.record_failure(error.clone(), delay);
+    let should_disable = feed.health.failures >= max_failures;
+
+    let mut update = doc! { "health": to_bson(&feed.health)? };
+    if should_disable {
+        update.insert("enabled", false);
+        update.insert("updated", DateTime::now());
+    }
+
+    // Only update the health information, the feed might be modified by the user in the meantime
+    handler
+        .get_collection()
+        .update_one(doc! { "id": feed.id.to_string() }, doc! { "$set": update })
+        .await?;
+
+    if !should_disable {
+        tracing::debug!(
+            "RSS feed {} (for {}) failed {} time(s), retrying in {:?}",
+            &feed.url,
+            feed.creator,
+            feed.health.failures,
+            delay
+        );
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "Disabling RSS feed {} (for {}) after {} consecutive failures",
+        &feed.url,
+        feed.creator,
+        feed.health.failures
+    );
+    feed.enabled = false;
+
+    if let Err(ch_err) = state
+        .clickhouse
+        .create_event(
+            showtimes_events::m::EventKind::RSSFeedDisabled,
+            showtimes_events::m::RSSFeedDisabledEvent::new(&feed, Some(error)),
+            None,
+        )
+        .await
+    {
+        tracing::error!("Failed to create RSS feed disabled event: {}", ch_err);
     }
 
     Ok(())
@@ -171,14 +377,20 @@ async fn tasks_rss_common(
         feeds.sort_by_key(|x| x.created);
         feeds.truncate(rss_limit.try_into()?);
 
-        for feed in feeds.iter() {
+        // Skip failing feeds that are still waiting for the next retry
+        for feed in feeds.iter().filter(|x| !x.health.is_backing_off()) {
             let cloned_state = Arc::clone(&state);
             let cloned_handler = Arc::clone(&handler_rss);
             let feed = feed.clone();
             tokio::spawn(async move {
                 let feed_url = feed.url.clone();
                 let creator = feed.creator.to_string();
-                let res = rss_single_task(feed, cloned_state, cloned_handler).await;
+                let res = rss_single_task(
+                    feed.clone(),
+                    Arc::clone(&cloned_state),
+                    Arc::clone(&cloned_handler),
+                )
+                .await;
                 match res {
                     Ok(_) => {
                         tracing::debug!(
@@ -195,6 +407,18 @@ async fn tasks_rss_common(
                             is_premium,
                             &creator
                         );
+
+                        if let Err(track_err) =
+                            rss_track_failure(feed, cloned_state, cloned_handler, e.to_string())
+                                .await
+                        {
+                            tracing::error!(
+                                "Failed to track failure for RSS feed `{}`: {} (for {})",
+                                &feed_url,
+                                track_err,
+                                &creator
+                            );
+                        }
                     }
                 }
             });
@@ -217,3 +441,74 @@ pub async fn tasks_rss_premium(
     tracing::debug!("Running tasks_rss_premium");
     tasks_rss_common(state, true).await
 }
+
+pub async fn tasks_search_outbox(
+    state: Arc<crate::state::ShowtimesState>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing::debug!("Running tasks_search_outbox");
+    let stats = showtimes_search::outbox::process_outbox(
+        &state.db,
+        &state.meili,
+        showtimes_search::outbox::DEFAULT_OUTBOX_BATCH,
+    )
+    .await?;
+
+    if stats.applied > 0 || stats.failed > 0 {
+        tracing::info!(
+            "Search outbox processed, {} applied and {} failed",
+            stats.applied,
+            stats.failed
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn tasks_api_key_usage(
+    state: Arc<crate::state::ShowtimesState>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing::debug!("Running tasks_api_key_usage");
+    let usages = state.api_key_usage.drain();
+    if usages.is_empty() {
+        return Ok(());
+    }
+
+    let handler = showtimes_db::UserHandler::new(&state.db);
+    let total = usages.len();
+    let mut failed = 0usize;
+    for (api_key, user, used_at) in usages {
+        // Older keys are stored as-is while newer keys are stored hashed
+        let stored_keys = match (
+            to_bson(&api_key.to_string()),
+            to_bson(&showtimes_db::m::APIKeyHashed::from(api_key)),
+        ) {
+            (Ok(raw_key), Ok(hashed_key)) => vec![raw_key, hashed_key],
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!("Failed to serialize API key usage for user {}: {}", user, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        // Only update the specific API key, the user might be modified in the meantime
+        if let Err(e) = handler
+            .get_collection()
+            .update_one(
+                doc! { "id": user.to_string(), "api_key.key": { "$in": stored_keys } },
+                doc! { "$set": { "api_key.$.last_used": used_at } },
+            )
+            .await
+        {
+            tracing::error!("Failed to update API key usage for user {}: {}", user, e);
+            failed += 1;
+        }
+    }
+
+    tracing::debug!(
+        "API key usage flushed, {} updated and {} failed",
+        total - failed,
+        failed
+    );
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Batched tracking of the API key usage
+//!
+//! Writing `last_used` on every authenticated request would put a write on
+//! every single request, so the usage is collected in memory and flushed
+//! periodically by [`crate::tasks::tasks_api_key_usage`].
+
+use std::sync::Mutex;
+
+use ahash::HashMap;
+use showtimes_db::mongodb::bson::DateTime;
+use showtimes_shared::ulid::Ulid;
+
+/// The pending usage of API keys, keyed by the API key itself
+#[derive(Default)]
+pub struct APIKeyUsage {
+    pending: Mutex<HashMap<showtimes_shared::APIKey, (Ulid, DateTime)>>,
+}
+
+impl APIKeyUsage {
+    /// Mark the API key of the user as used now
+    pub fn touch(&self, user: Ulid, api_key: showtimes_shared::APIKey) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(api_key, (user, DateTime::now()));
+    }
+
+    /// Take all the pending usage to be flushed
+    pub fn drain(&self) -> Vec<(showtimes_shared::APIKey, Ulid, DateTime)> {
+        self.pending
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(api_key, (user, used_at))| (api_key, user, used_at))
+            .collect()
+    }
+}
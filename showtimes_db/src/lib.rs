@@ -72,4 +72,6 @@ impl_handler_model!(
     "m::ServerCollaborationInvite"
 );
 impl_handler_model!(m::RSSFeed, RSSFeedHandler, "m::RSSFeed");
+impl_handler_model!(m::Webhook, WebhookHandler, "m::Webhook");
 impl_handler_model!(m::Migration, MigrationHandler, "m::Migration");
+impl_handler_model!(m::SearchOutbox, SearchOutboxHandler, "m::SearchOutbox");
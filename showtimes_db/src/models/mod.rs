@@ -1,5 +1,6 @@
 pub mod common;
 pub mod migrations;
+pub mod outbox;
 pub mod project;
 pub mod rss;
 pub mod server;
@@ -8,6 +9,7 @@ pub mod webhooks;
 
 pub use common::*;
 pub use migrations::*;
+pub use outbox::*;
 pub use project::*;
 pub use rss::*;
 pub use server::*;
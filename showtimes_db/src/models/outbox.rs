@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use showtimes_derive::EnumName;
+use showtimes_shared::ulid_serializer;
+
+use crate::impl_trait_model;
+
+use super::ShowModelHandler;
+
+/// The search model that should be synchronized
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SearchOutboxKind {
+    /// A project, see [`super::Project`]
+    Project,
+    /// A server, see [`super::Server`]
+    Server,
+    /// A user, see [`super::User`]
+    User,
+    /// A collaboration sync, see [`super::ServerCollaborationSync`]
+    CollaborationSync,
+    /// A collaboration invite, see [`super::ServerCollaborationInvite`]
+    CollaborationInvite,
+}
+
+/// The action that should be applied to the search index
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SearchOutboxAction {
+    /// Add or replace the document with the latest data in the database.
+    ///
+    /// When the data is missing from the database, the document is deleted instead.
+    Upsert,
+    /// Delete the document from the search index
+    Delete,
+}
+
+/// A pending search index operation
+///
+/// This is recorded alongside the database write and applied by the outbox worker,
+/// so the search index will eventually be consistent even when Meilisearch is down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOutbox {
+    /// The ID of the outbox entry
+    #[serde(with = "ulid_serializer", default = "ulid_serializer::default")]
+    pub id: showtimes_shared::ulid::Ulid,
+    /// The search model kind
+    pub kind: SearchOutboxKind,
+    /// The action to apply
+    pub action: SearchOutboxAction,
+    /// The ID of the model
+    #[serde(with = "ulid_serializer")]
+    pub target: showtimes_shared::ulid::Ulid,
+    /// How many times this entry has failed to be applied
+    #[serde(default)]
+    pub attempts: u32,
+    /// The last error when applying this entry
+    pub last_error: Option<String>,
+    /// When this entry can be applied again
+    #[serde(
+        with = "showtimes_shared::bson_datetime_jiff_timestamp",
+        default = "jiff::Timestamp::now"
+    )]
+    pub next_attempt: jiff::Timestamp,
+    /// The writer has not confirmed that the database write landed yet
+    ///
+    /// Pending entries are left alone by the outbox worker, so it does not
+    /// apply and remove them before the write is visible.
+    #[serde(default)]
+    pub pending: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    _id: Option<mongodb::bson::oid::ObjectId>,
+    #[serde(
+        with = "showtimes_shared::bson_datetime_jiff_timestamp",
+        default = "jiff::Timestamp::now"
+    )]
+    pub created: jiff::Timestamp,
+    #[serde(
+        with = "showtimes_shared::bson_datetime_jiff_timestamp",
+        default = "jiff::Timestamp::now"
+    )]
+    pub updated: jiff::Timestamp,
+}
+
+impl SearchOutbox {
+    /// Create a new outbox entry
+    pub fn new(
+        kind: SearchOutboxKind,
+        action: SearchOutboxAction,
+        target: showtimes_shared::ulid::Ulid,
+    ) -> Self {
+        let now = jiff::Timestamp::now();
+        Self {
+            id: ulid_serializer::default(),
+            kind,
+            action,
+            target,
+            attempts: 0,
+            last_error: None,
+            next_attempt: now,
+            pending: true,
+            _id: None,
+            created: now,
+            updated: now,
+        }
+    }
+
+    /// Create a new outbox entry to add or replace the document
+    pub fn upsert(kind: SearchOutboxKind, target: showtimes_shared::ulid::Ulid) -> Self {
+        Self::new(kind, SearchOutboxAction::Upsert, target)
+    }
+
+    /// Create a new outbox entry to delete the document
+    pub fn delete(kind: SearchOutboxKind, target: showtimes_shared::ulid::Ulid) -> Self {
+        Self::new(kind, SearchOutboxAction::Delete, target)
+    }
+
+    /// Mark this entry as failed and schedule the next attempt
+    ///
+    /// This also hands the entry over to the outbox worker.
+    pub fn mark_failed(&mut self, error: impl Into<String>, delay: std::time::Duration) {
+        self.pending = false;
+        self.attempts = self.attempts.saturating_add(1);
+        self.last_error = Some(error.into());
+        let delay = jiff::SignedDuration::try_from(delay).unwrap_or(jiff::SignedDuration::MAX);
+        self.next_attempt = jiff::Timestamp::now()
+            .saturating_add(delay)
+            .unwrap_or(jiff::Timestamp::MAX);
+    }
+}
+
+impl_trait_model!(SearchOutbox, "ShowtimesSearchOutbox", _id, updated);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_entry_is_pending() {
+        let entry = SearchOutbox::upsert(SearchOutboxKind::Project, ulid_serializer::default());
+
+        assert!(entry.pending);
+        assert_eq!(entry.attempts, 0);
+        assert!(entry.last_error.is_none());
+    }
+
+    #[test]
+    fn test_mark_failed() {
+        let mut entry = SearchOutbox::delete(SearchOutboxKind::Server, ulid_serializer::default());
+        let before = jiff::Timestamp::now();
+        entry.mark_failed("meilisearch is down", std::time::Duration::from_secs(30));
+
+        assert!(!entry.pending);
+        assert_eq!(entry.attempts, 1);
+        assert_eq!(entry.last_error.as_deref(), Some("meilisearch is down"));
+        assert!(entry.next_attempt >= before + jiff::SignedDuration::from_secs(30));
+    }
+
+    #[test]
+    fn test_legacy_entry_is_not_pending() {
+        let entry = SearchOutbox::upsert(SearchOutboxKind::User, ulid_serializer::default());
+        let mut document = mongodb::bson::to_document(&entry).unwrap();
+        document.remove("pending");
+
+        let entry: SearchOutbox = mongodb::bson::from_document(document).unwrap();
+        assert!(!entry.pending);
+    }
+}
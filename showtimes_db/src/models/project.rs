@@ -124,7 +124,7 @@ impl ProjectKind {
 }
 
 /// The list of enums holding the project types.
-#[derive(Debug, Copy, Clone, Default, showtimes_derive::SerdeAutomata)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, showtimes_derive::SerdeAutomata)]
 #[serde_automata(serialize_rename_all = "SCREAMING_SNAKE_CASE", case_sensitive = false)]
 pub enum ProjectType {
     /// The project is a movie.
@@ -1,11 +1,15 @@
 use crate::impl_trait_model;
 
-use super::{IntegrationId, ShowModelHandler};
+use super::{IntegrationId, IntegrationType, ShowModelHandler};
 use serde::{Deserialize, Serialize};
+use showtimes_derive::EnumName;
 use showtimes_shared::ulid_serializer;
 
 const DEFAULT_MESSAGE_DISPLAY: &str = ":newspaper::mega: | Rilisan Baru: **{title}**\n{link}";
 
+/// The maximum compiled size of a filter regex, in bytes
+const FILTER_REGEX_SIZE_LIMIT: usize = 256 * 1024;
+
 /// A structure to hold the display information for a RSS feed.
 ///
 /// This structure follows Discord rich embeds formatting.
@@ -107,6 +111,305 @@ impl Default for RSSFeedDisplay {
     }
 }
 
+/// The entry field that is checked by a RSS filter rule
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RSSFeedFilterField {
+    /// The entry title
+    Title,
+    /// The entry link, including the alternative links
+    Link,
+    /// The entry authors
+    Author,
+    /// The entry categories
+    Category,
+}
+
+impl RSSFeedFilterField {
+    /// The keys of the parsed feed entry that are checked for this field
+    pub fn entry_keys(&self) -> &'static [&'static str] {
+        match self {
+            RSSFeedFilterField::Title => &["title"],
+            RSSFeedFilterField::Link => &["link", "links"],
+            RSSFeedFilterField::Author => &["authors"],
+            RSSFeedFilterField::Category => &["categories"],
+        }
+    }
+}
+
+/// How the pattern of a RSS filter rule is matched
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RSSFeedFilterKind {
+    /// Case-insensitive substring match
+    Keyword,
+    /// Case-insensitive regular expression match
+    Regex,
+}
+
+/// What to do with the entry when the rule matches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RSSFeedFilterAction {
+    /// The rule passes when the pattern matches
+    Include,
+    /// The rule passes when the pattern does not match
+    Exclude,
+}
+
+/// How the rules inside a filter group are combined
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumName)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RSSFeedFilterMode {
+    /// Every rule must pass (AND)
+    #[default]
+    All,
+    /// At least one rule must pass (OR)
+    Any,
+}
+
+/// A single RSS filter rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RSSFeedFilterRule {
+    /// The entry field to check
+    pub field: RSSFeedFilterField,
+    /// How the pattern is matched
+    pub kind: RSSFeedFilterKind,
+    /// The keyword or regular expression
+    pub pattern: String,
+    /// Include or exclude the entry when matched
+    pub action: RSSFeedFilterAction,
+}
+
+/// A group of RSS filter rules
+///
+/// An entry is published only when every group passes, an empty group always passes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RSSFeedFilterGroup {
+    /// How the rules are combined
+    #[serde(default)]
+    pub mode: RSSFeedFilterMode,
+    /// The rules of the group
+    #[serde(default)]
+    pub rules: Vec<RSSFeedFilterRule>,
+}
+
+enum RSSFeedFilterMatcher {
+    Keyword(String),
+    Regex(regex::Regex),
+}
+
+struct RSSFeedFilterCompiledRule {
+    keys: &'static [&'static str],
+    matcher: RSSFeedFilterMatcher,
+    exclude: bool,
+}
+
+impl RSSFeedFilterCompiledRule {
+    fn passes(&self, values: &impl Fn(&str) -> Vec<String>) -> bool {
+        let matched = self.keys.iter().any(|&key| {
+            values(key).iter().any(|value| match &self.matcher {
+                RSSFeedFilterMatcher::Keyword(keyword) => value.to_lowercase().contains(keyword),
+                RSSFeedFilterMatcher::Regex(regex) => regex.is_match(value),
+            })
+        });
+
+        matched != self.exclude
+    }
+}
+
+/// The compiled RSS filter groups, ready to be evaluated against the feed entries
+pub struct RSSFeedFilterSet {
+    groups: Vec<(RSSFeedFilterMode, Vec<RSSFeedFilterCompiledRule>)>,
+}
+
+impl RSSFeedFilterSet {
+    /// Compile the filter groups, this will fail if any of the regex is invalid
+    pub fn compile(groups: &[RSSFeedFilterGroup]) -> Result<Self, regex::Error> {
+        let groups = groups
+            .iter()
+            .map(|group| {
+                let rules = group
+                    .rules
+                    .iter()
+                    .map(|rule| {
+                        let matcher = match rule.kind {
+                            RSSFeedFilterKind::Keyword => {
+                                RSSFeedFilterMatcher::Keyword(rule.pattern.to_lowercase())
+                            }
+                            RSSFeedFilterKind::Regex => RSSFeedFilterMatcher::Regex(
+                                regex::RegexBuilder::new(&rule.pattern)
+                                    .case_insensitive(true)
+                                    .size_limit(FILTER_REGEX_SIZE_LIMIT)
+                                    .build()?,
+                            ),
+                        };
+
+                        Ok(RSSFeedFilterCompiledRule {
+                            keys: rule.field.entry_keys(),
+                            matcher,
+                            exclude: rule.action == RSSFeedFilterAction::Exclude,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, regex::Error>>()?;
+
+                Ok((group.mode, rules))
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+
+        Ok(Self { groups })
+    }
+
+    /// Check if there is no filter at all
+    pub fn is_empty(&self) -> bool {
+        self.groups.iter().all(|(_, rules)| rules.is_empty())
+    }
+
+    /// Check if the entry passes the filter
+    ///
+    /// `values` should return the text values of the provided entry key.
+    pub fn matches(&self, values: impl Fn(&str) -> Vec<String>) -> bool {
+        self.groups.iter().all(|(mode, rules)| {
+            if rules.is_empty() {
+                return true;
+            }
+
+            match mode {
+                RSSFeedFilterMode::All => rules.iter().all(|rule| rule.passes(&values)),
+                RSSFeedFilterMode::Any => rules.iter().any(|rule| rule.passes(&values)),
+            }
+        })
+    }
+}
+
+/// The polling statistics of a RSS feed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RSSFeedFetchStats {
+    /// The HTTP status code of the last request
+    pub last_status: u16,
+    /// The amount of bytes downloaded on the last request
+    pub last_bytes: u64,
+    /// How long the last request took, in milliseconds
+    pub last_duration_ms: u64,
+    /// When the feed was last requested
+    #[serde(with = "showtimes_shared::bson_datetime_jiff_timestamp")]
+    pub last_fetched: jiff::Timestamp,
+    /// The total amount of requests made
+    pub fetches: u64,
+    /// The total amount of requests that returned not modified
+    pub not_modified: u64,
+    /// The total amount of bytes downloaded
+    pub bytes_downloaded: u64,
+    /// The estimated amount of bytes saved by not modified responses,
+    /// based on the size of the last full response.
+    pub bytes_saved: u64,
+    /// The size of the last full response
+    pub last_full_bytes: u64,
+}
+
+impl RSSFeedFetchStats {
+    /// Record a request into the statistics
+    pub fn record(&mut self, status: u16, bytes: u64, duration: std::time::Duration) {
+        let not_modified = status == 304;
+
+        self.last_status = status;
+        self.last_bytes = bytes;
+        self.last_duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.last_fetched = jiff::Timestamp::now();
+        self.fetches = self.fetches.saturating_add(1);
+        self.bytes_downloaded = self.bytes_downloaded.saturating_add(bytes);
+        if not_modified {
+            self.not_modified = self.not_modified.saturating_add(1);
+            self.bytes_saved = self.bytes_saved.saturating_add(self.last_full_bytes);
+        } else {
+            self.last_full_bytes = bytes;
+        }
+    }
+}
+
+/// The health information of a RSS feed
+///
+/// Failing feeds are retried with exponential backoff and disabled
+/// automatically after too many consecutive failures.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RSSFeedHealth {
+    /// When the feed was last fetched successfully
+    #[serde(with = "showtimes_shared::bson_datetime_jiff_timestamp::optional")]
+    pub last_success: Option<jiff::Timestamp>,
+    /// The last error message when fetching or delivering the feed
+    pub last_error: Option<String>,
+    /// When the last error happened
+    #[serde(with = "showtimes_shared::bson_datetime_jiff_timestamp::optional")]
+    pub last_error_at: Option<jiff::Timestamp>,
+    /// The amount of consecutive failures
+    pub failures: u32,
+    /// The feed will not be fetched until this time
+    #[serde(with = "showtimes_shared::bson_datetime_jiff_timestamp::optional")]
+    pub next_retry: Option<jiff::Timestamp>,
+}
+
+impl RSSFeedHealth {
+    /// Record a successful fetch, this will reset the failures count
+    pub fn record_success(&mut self) {
+        self.last_success = Some(jiff::Timestamp::now());
+        self.failures = 0;
+        self.next_retry = None;
+    }
+
+    /// Record a failed fetch and schedule the next retry
+    pub fn record_failure(&mut self, error: impl Into<String>, delay: std::time::Duration) {
+        let now = jiff::Timestamp::now();
+        let delay = jiff::SignedDuration::try_from(delay).unwrap_or(jiff::SignedDuration::MAX);
+
+        self.last_error = Some(error.into());
+        self.last_error_at = Some(now);
+        self.failures = self.failures.saturating_add(1);
+        self.next_retry = Some(now.saturating_add(delay).unwrap_or(jiff::Timestamp::MAX));
+    }
+
+    /// Check if the feed is still waiting for the next retry
+    pub fn is_backing_off(&self) -> bool {
+        self.next_retry
+            .is_some_and(|retry| retry > jiff::Timestamp::now())
+    }
+}
+
+/// A Discord webhook used to deliver the RSS entries into a channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RSSFeedWebhook {
+    /// The Discord channel ID, this must match one of the feed
+    /// [`IntegrationType::DiscordChannel`] integration
+    pub channel: String,
+    /// The Discord webhook URL of the channel
+    pub url: String,
+}
+
+impl RSSFeedWebhook {
+    /// Create a new Discord webhook for a channel
+    pub fn new(channel: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            channel: channel.into(),
+            url: url.into(),
+        }
+    }
+
+    /// The webhook URL with the token masked, safe to be shown back to the user
+    ///
+    /// Discord webhook URL is `.../webhooks/{id}/{token}`, only the token is masked.
+    pub fn masked_url(&self) -> String {
+        match self.url.trim_end_matches('/').rsplit_once('/') {
+            Some((base, token)) if !token.is_empty() => format!("{base}/********"),
+            _ => "********".to_string(),
+        }
+    }
+}
+
 /// A model to hold RSS information for a server.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RSSFeed {
@@ -118,6 +421,8 @@ pub struct RSSFeed {
     /// The RSS integrations, usually Discord text channels.
     pub integrations: Vec<IntegrationId>,
     /// Is this feed enabled or not
+    ///
+    /// Feed will be disabled automatically after too many consecutive failures
     pub enabled: bool,
     /// The display information for the RSS feed
     pub display: RSSFeedDisplay,
@@ -125,6 +430,18 @@ pub struct RSSFeed {
     pub last_mod: Option<String>,
     /// Last E-Tag of the RSS feed
     pub etag: Option<String>,
+    /// The polling statistics of the RSS feed
+    #[serde(default)]
+    pub fetch_stats: RSSFeedFetchStats,
+    /// The health information of the RSS feed
+    #[serde(default)]
+    pub health: RSSFeedHealth,
+    /// The filter groups, evaluated before new entries are published
+    #[serde(default)]
+    pub filters: Vec<RSSFeedFilterGroup>,
+    /// The Discord webhooks for the channel integrations
+    #[serde(default)]
+    pub webhooks: Vec<RSSFeedWebhook>,
     /// The feed creator (server ID)
     #[serde(with = "ulid_serializer")]
     pub creator: showtimes_shared::ulid::Ulid,
@@ -156,6 +473,10 @@ impl RSSFeed {
             display: RSSFeedDisplay::default(),
             last_mod: None,
             etag: None,
+            fetch_stats: RSSFeedFetchStats::default(),
+            health: RSSFeedHealth::default(),
+            filters: Vec::new(),
+            webhooks: Vec::new(),
             creator,
             _id: None,
             created: cur_time,
@@ -175,7 +496,262 @@ impl RSSFeed {
         self.integrations = integrations;
     }
 
+    /// Get the webhooks of the Discord channel integrations
+    ///
+    /// Channels without a webhook are skipped, since they can only be
+    /// delivered by the bot subscribing to the feed.
+    pub fn delivery_webhooks(&self) -> Vec<&RSSFeedWebhook> {
+        self.integrations
+            .iter()
+            .filter(|i| i.kind() == &IntegrationType::DiscordChannel)
+            .filter_map(|i| self.webhooks.iter().find(|w| w.channel == i.id()))
+            .collect()
+    }
+
+    /// Compile the filter groups of the feed
+    pub fn compile_filters(&self) -> Result<RSSFeedFilterSet, regex::Error> {
+        RSSFeedFilterSet::compile(&self.filters)
+    }
+
+    /// Enable or disable the feed
+    ///
+    /// Enabling the feed will also reset the failures count and the backoff
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
+        if enabled {
+            self.health.failures = 0;
+            self.health.next_retry = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        field: RSSFeedFilterField,
+        kind: RSSFeedFilterKind,
+        pattern: &str,
+        action: RSSFeedFilterAction,
+    ) -> RSSFeedFilterRule {
+        RSSFeedFilterRule {
+            field,
+            kind,
+            pattern: pattern.to_string(),
+            action,
+        }
+    }
+
+    fn keyword(pattern: &str, action: RSSFeedFilterAction) -> RSSFeedFilterRule {
+        rule(
+            RSSFeedFilterField::Title,
+            RSSFeedFilterKind::Keyword,
+            pattern,
+            action,
+        )
+    }
+
+    fn group(mode: RSSFeedFilterMode, rules: Vec<RSSFeedFilterRule>) -> RSSFeedFilterGroup {
+        RSSFeedFilterGroup { mode, rules }
+    }
+
+    fn entry(title: &str) -> impl Fn(&str) -> Vec<String> {
+        let title = title.to_string();
+        move |key| match key {
+            "title" => vec![title.clone()],
+            "categories" => vec!["Anime".to_string(), "1080p".to_string()],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_filter_include_exclude() {
+        let include = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![keyword("Frieren", RSSFeedFilterAction::Include)],
+        )])
+        .unwrap();
+
+        assert!(include.matches(entry("Sousou no Frieren - 01")));
+        assert!(!include.matches(entry("Dungeon Meshi - 01")));
+
+        let exclude = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![keyword("batch", RSSFeedFilterAction::Exclude)],
+        )])
+        .unwrap();
+
+        assert!(exclude.matches(entry("Sousou no Frieren - 01")));
+        assert!(!exclude.matches(entry("Sousou no Frieren (Batch)")));
+    }
+
+    #[test]
+    fn test_filter_mode_all() {
+        let filters = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![
+                keyword("frieren", RSSFeedFilterAction::Include),
+                keyword("1080p", RSSFeedFilterAction::Include),
+            ],
+        )])
+        .unwrap();
+
+        assert!(filters.matches(entry("Sousou no Frieren - 01 [1080p]")));
+        assert!(!filters.matches(entry("Sousou no Frieren - 01 [720p]")));
+    }
+
+    #[test]
+    fn test_filter_mode_any() {
+        let filters = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::Any,
+            vec![
+                keyword("frieren", RSSFeedFilterAction::Include),
+                keyword("meshi", RSSFeedFilterAction::Include),
+            ],
+        )])
+        .unwrap();
+
+        assert!(filters.matches(entry("Sousou no Frieren - 01")));
+        assert!(filters.matches(entry("Dungeon Meshi - 01")));
+        assert!(!filters.matches(entry("Kusuriya no Hitorigoto - 01")));
+    }
+
+    #[test]
+    fn test_filter_every_group_must_pass() {
+        let filters = RSSFeedFilterSet::compile(&[
+            group(
+                RSSFeedFilterMode::Any,
+                vec![keyword("frieren", RSSFeedFilterAction::Include)],
+            ),
+            group(
+                RSSFeedFilterMode::All,
+                vec![keyword("batch", RSSFeedFilterAction::Exclude)],
+            ),
+        ])
+        .unwrap();
+
+        assert!(filters.matches(entry("Sousou no Frieren - 01")));
+        assert!(!filters.matches(entry("Sousou no Frieren (Batch)")));
+    }
+
+    #[test]
+    fn test_filter_empty() {
+        let filters = RSSFeedFilterSet::compile(&[]).unwrap();
+        assert!(filters.is_empty());
+        assert!(filters.matches(entry("Anything")));
+
+        let filters = RSSFeedFilterSet::compile(&[group(RSSFeedFilterMode::Any, vec![])]).unwrap();
+        assert!(filters.is_empty());
+        assert!(filters.matches(entry("Anything")));
+    }
+
+    #[test]
+    fn test_filter_empty_field() {
+        // The entry has no author, so an include never passes and an exclude always passes
+        let include = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![rule(
+                RSSFeedFilterField::Author,
+                RSSFeedFilterKind::Keyword,
+                "someone",
+                RSSFeedFilterAction::Include,
+            )],
+        )])
+        .unwrap();
+        assert!(!include.matches(entry("Sousou no Frieren - 01")));
+
+        let exclude = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![rule(
+                RSSFeedFilterField::Author,
+                RSSFeedFilterKind::Keyword,
+                "someone",
+                RSSFeedFilterAction::Exclude,
+            )],
+        )])
+        .unwrap();
+        assert!(exclude.matches(entry("Sousou no Frieren - 01")));
+    }
+
+    #[test]
+    fn test_filter_other_field() {
+        let filters = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![rule(
+                RSSFeedFilterField::Category,
+                RSSFeedFilterKind::Keyword,
+                "anime",
+                RSSFeedFilterAction::Include,
+            )],
+        )])
+        .unwrap();
+
+        assert!(filters.matches(entry("Anything")));
+    }
+
+    #[test]
+    fn test_filter_regex_case_insensitive() {
+        let filters = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![rule(
+                RSSFeedFilterField::Title,
+                RSSFeedFilterKind::Regex,
+                r"^\[subsplease\] .+ - \d+",
+                RSSFeedFilterAction::Include,
+            )],
+        )])
+        .unwrap();
+
+        assert!(filters.matches(entry("[SubsPlease] Sousou no Frieren - 01 (1080p)")));
+        assert!(!filters.matches(entry("[Erai-raws] Sousou no Frieren - 01 (1080p)")));
+    }
+
+    #[test]
+    fn test_filter_keyword_is_not_regex() {
+        let filters = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![keyword("[SubsPlease]", RSSFeedFilterAction::Include)],
+        )])
+        .unwrap();
+
+        assert!(filters.matches(entry("[subsplease] Sousou no Frieren - 01")));
+        assert!(!filters.matches(entry("SubsPlease Sousou no Frieren - 01")));
+    }
+
+    #[test]
+    fn test_filter_invalid_regex() {
+        let result = RSSFeedFilterSet::compile(&[group(
+            RSSFeedFilterMode::All,
+            vec![rule(
+                RSSFeedFilterField::Title,
+                RSSFeedFilterKind::Regex,
+                "(unclosed",
+                RSSFeedFilterAction::Include,
+            )],
+        )]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_webhook_masked_url() {
+        let webhook = RSSFeedWebhook::new(
+            "1234",
+            "https://discord.com/api/webhooks/5678/super-secret-token",
+        );
+
+        assert_eq!(
+            webhook.masked_url(),
+            "https://discord.com/api/webhooks/5678/********"
+        );
+        assert!(!webhook.masked_url().contains("super-secret-token"));
+    }
+
+    #[test]
+    fn test_webhook_masked_url_invalid() {
+        let webhook = RSSFeedWebhook::new("1234", "not-a-url");
+
+        assert_eq!(webhook.masked_url(), "********");
     }
 }
@@ -1,7 +1,7 @@
 use jwt_lc_rs::SigningAlgorithm;
 use serde::{Deserialize, Serialize};
 use showtimes_derive::EnumName;
-use showtimes_shared::ulid_serializer;
+use showtimes_shared::{ulid_list_serializer, ulid_serializer};
 
 use crate::impl_trait_model;
 
@@ -44,6 +44,8 @@ pub enum APIKeyCapability {
     #[serde(rename = "MANAGE_RSS")]
     #[enum_name(rename = "MANAGE_RSS")]
     ManageRSS,
+    /// A combination of create, update, delete, and test for webhooks
+    ManageWebhooks,
     /// A combination of update for users
     ManageUsers,
     /// A delete capability for servers
@@ -62,6 +64,8 @@ pub enum APIKeyCapability {
     QueryStats,
     /// Query for search data
     QuerySearch,
+    /// Subscribe to events of the servers the user belongs to
+    QueryEvents,
 }
 
 impl APIKeyCapability {
@@ -71,6 +75,7 @@ impl APIKeyCapability {
             APIKeyCapability::ManageServers,
             APIKeyCapability::ManageProjects,
             APIKeyCapability::ManageRSS,
+            APIKeyCapability::ManageWebhooks,
             APIKeyCapability::ManageUsers,
             APIKeyCapability::DeleteServers,
             APIKeyCapability::DeleteProjects,
@@ -79,6 +84,7 @@ impl APIKeyCapability {
             APIKeyCapability::QueryProjects,
             APIKeyCapability::QueryStats,
             APIKeyCapability::QuerySearch,
+            APIKeyCapability::QueryEvents,
         ]
     }
 
@@ -89,6 +95,7 @@ impl APIKeyCapability {
             APIKeyCapability::QueryProjects,
             APIKeyCapability::QueryStats,
             APIKeyCapability::QuerySearch,
+            APIKeyCapability::QueryEvents,
         ]
     }
 
@@ -98,6 +105,7 @@ impl APIKeyCapability {
             APIKeyCapability::ManageServers,
             APIKeyCapability::ManageProjects,
             APIKeyCapability::ManageRSS,
+            APIKeyCapability::ManageWebhooks,
             APIKeyCapability::ManageUsers,
         ]
     }
@@ -133,6 +141,11 @@ impl APIKeyCapability {
         &[APIKeyCapability::ManageRSS]
     }
 
+    /// Get all operation related to webhooks
+    pub fn webhooks() -> &'static [APIKeyCapability] {
+        &[APIKeyCapability::ManageWebhooks]
+    }
+
     /// Get all operation related to users
     pub fn users() -> &'static [APIKeyCapability] {
         &[APIKeyCapability::ManageUsers]
@@ -146,6 +159,26 @@ pub struct APIKey {
     pub key: APIKeyHashed,
     /// The API key capabilities
     pub capabilities: Vec<APIKeyCapability>,
+    /// The label of the API key
+    #[serde(default)]
+    pub name: Option<String>,
+    /// When the API key expires, `None` means it never expires
+    #[serde(
+        default,
+        with = "showtimes_shared::bson_datetime_jiff_timestamp::optional"
+    )]
+    pub expires_at: Option<jiff::Timestamp>,
+    /// When the API key was last used to authenticate
+    ///
+    /// This is updated in batches, so it might lag behind a bit.
+    #[serde(
+        default,
+        with = "showtimes_shared::bson_datetime_jiff_timestamp::optional"
+    )]
+    pub last_used: Option<jiff::Timestamp>,
+    /// The servers the API key is allowed to access, empty means all servers of the user
+    #[serde(default, with = "ulid_list_serializer")]
+    pub servers: Vec<showtimes_shared::ulid::Ulid>,
 }
 
 impl APIKey {
@@ -157,9 +190,47 @@ impl APIKey {
         APIKey {
             key: key.into(),
             capabilities,
+            name: None,
+            expires_at: None,
+            last_used: None,
+            servers: Vec::new(),
         }
     }
 
+    /// Set the label of the API key
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set when the API key expires
+    pub fn with_expires_at(mut self, expires_at: jiff::Timestamp) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Limit the API key to the following servers
+    pub fn with_servers(mut self, servers: Vec<showtimes_shared::ulid::Ulid>) -> Self {
+        self.servers = servers;
+        self
+    }
+
+    /// Check if the API key is already expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= jiff::Timestamp::now())
+    }
+
+    /// Check if the API key is limited to specific servers
+    pub fn is_server_scoped(&self) -> bool {
+        !self.servers.is_empty()
+    }
+
+    /// Check if the API key is allowed to access the server
+    pub fn can_access_server(&self, server: showtimes_shared::ulid::Ulid) -> bool {
+        self.servers.is_empty() || self.servers.contains(&server)
+    }
+
     /// Check if API key has specific capability
     pub fn can(&self, capability: APIKeyCapability) -> bool {
         self.capabilities.contains(&capability)
@@ -177,10 +248,7 @@ impl APIKey {
 
     /// Stub an API key
     pub fn stub() -> Self {
-        APIKey {
-            key: showtimes_shared::APIKey::new().into(),
-            capabilities: Vec::new(),
-        }
+        APIKey::new(showtimes_shared::APIKey::new(), Vec::new())
     }
 
     /// Update API key
@@ -191,10 +259,10 @@ impl APIKey {
 
 impl Default for APIKey {
     fn default() -> Self {
-        APIKey {
-            key: showtimes_shared::APIKey::new().into(),
-            capabilities: APIKeyCapability::all().to_vec(),
-        }
+        APIKey::new(
+            showtimes_shared::APIKey::new(),
+            APIKeyCapability::all().to_vec(),
+        )
     }
 }
 
@@ -459,3 +527,72 @@ fn hash_api_key(api_key: &showtimes_shared::APIKey) -> Result<String, jwt_lc_rs:
 
     hmac.sign(api_key.as_api_key().as_bytes())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_key() -> APIKey {
+        APIKey::new(
+            showtimes_shared::APIKey::new(),
+            vec![APIKeyCapability::ManageProjects],
+        )
+    }
+
+    #[test]
+    fn test_api_key_expiry() {
+        let key = make_key();
+        assert!(!key.is_expired());
+
+        let past = jiff::Timestamp::now() - jiff::SignedDuration::from_secs(60);
+        let key = make_key().with_expires_at(past);
+        assert!(key.is_expired());
+
+        let future = jiff::Timestamp::now() + jiff::SignedDuration::from_secs(3600);
+        let key = make_key().with_expires_at(future);
+        assert!(!key.is_expired());
+    }
+
+    #[test]
+    fn test_api_key_expiry_roundtrip() {
+        let past = jiff::Timestamp::from_second(1_700_000_000).unwrap();
+        let key = make_key().with_expires_at(past);
+
+        let serialized = mongodb::bson::to_document(&key).unwrap();
+        let deserialized: APIKey = mongodb::bson::from_document(serialized).unwrap();
+
+        assert_eq!(deserialized.expires_at, Some(past));
+        assert!(deserialized.is_expired());
+    }
+
+    #[test]
+    fn test_api_key_unscoped() {
+        let key = make_key();
+
+        assert!(!key.is_server_scoped());
+        assert!(key.can_access_server(showtimes_shared::ulid::Ulid::new()));
+    }
+
+    #[test]
+    fn test_api_key_server_scoped() {
+        let allowed = showtimes_shared::ulid::Ulid::new();
+        let other = showtimes_shared::ulid::Ulid::new();
+        let key = make_key().with_servers(vec![allowed]);
+
+        assert!(key.is_server_scoped());
+        assert!(key.can_access_server(allowed));
+        assert!(!key.can_access_server(other));
+    }
+
+    #[test]
+    fn test_api_key_scope_roundtrip() {
+        let allowed = showtimes_shared::ulid::Ulid::new();
+        let key = make_key().with_servers(vec![allowed]);
+
+        let serialized = mongodb::bson::to_document(&key).unwrap();
+        let deserialized: APIKey = mongodb::bson::from_document(serialized).unwrap();
+
+        assert_eq!(deserialized.servers, vec![allowed]);
+        assert!(deserialized.can_access_server(allowed));
+    }
+}
@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
-use showtimes_shared::ulid_serializer;
+use showtimes_derive::EnumName;
+use showtimes_shared::{ulid_list_serializer, ulid_serializer};
 
 use crate::{
     errors::{StringValidationError, StringValidationErrorKind},
     impl_trait_model,
 };
 
-use super::ShowModelHandler;
+use super::{EpisodeProgress, Project, ProjectStatus, ProjectType, ShowModelHandler};
 
 /// The target for the webhook
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -14,13 +15,22 @@ use super::ShowModelHandler;
 pub enum WebhookTarget {
     /// Discord webhook target
     Discord,
+    /// Generic JSON webhook target, signed with the webhook secret
+    Generic,
+    /// Slack incoming webhook target
+    Slack,
+    /// Matrix webhook target, compatible with matrix-hookshot
+    Matrix,
+    /// Telegram Bot API `sendMessage` target
+    Telegram,
 }
 
 /// Supported webhook actions
 ///
 /// This is all actions that are supported by the webhook
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumName)]
 #[serde(rename_all = "kebab-case")]
+#[enum_name(rename_all = "kebab-case")]
 pub enum WebhookAction {
     /// A new project is created
     ProjectCreate,
@@ -47,6 +57,62 @@ impl WebhookAction {
     ];
 }
 
+/// The filter to scope which events are delivered by a webhook
+///
+/// An empty list means no filtering for that field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookFilter {
+    /// Only deliver events from these projects
+    #[serde(default, with = "ulid_list_serializer")]
+    pub projects: Vec<showtimes_shared::ulid::Ulid>,
+    /// Only deliver events from these project types
+    ///
+    /// When both `projects` and `kinds` are set, the project only need to match one of them.
+    #[serde(default)]
+    pub kinds: Vec<ProjectType>,
+    /// Only deliver progress events when one of these role keys is finished
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Skip events from projects that are paused
+    #[serde(default)]
+    pub skip_paused: bool,
+}
+
+impl WebhookFilter {
+    /// Check if the project is accepted by the filter
+    pub fn matches_project(&self, project: &Project) -> bool {
+        if self.skip_paused && project.status == ProjectStatus::Paused {
+            return false;
+        }
+
+        if self.projects.is_empty() && self.kinds.is_empty() {
+            return true;
+        }
+
+        self.projects.contains(&project.id) || self.kinds.contains(&project.kind)
+    }
+
+    /// Check if the progress change is accepted by the filter
+    ///
+    /// Accepted when one of the filtered roles is newly finished.
+    pub fn matches_progress(&self, before: &EpisodeProgress, after: &EpisodeProgress) -> bool {
+        if self.roles.is_empty() {
+            return true;
+        }
+
+        after.statuses.iter().any(|status| {
+            let was_finished = before
+                .statuses
+                .iter()
+                .find(|b| b.key() == status.key())
+                .map(|b| b.finished())
+                .unwrap_or(false);
+
+            status.finished() && !was_finished && self.roles.iter().any(|r| r == status.key())
+        })
+    }
+}
+
 /// The webhook model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Webhook {
@@ -74,6 +140,27 @@ pub struct Webhook {
     pub avatar: Option<String>,
     /// The supported actions for this Webhook
     pub actions: Vec<WebhookAction>,
+    /// The locale code used to render the webhook message (e.g. `id-ID`)
+    ///
+    /// Default to `None` which will use the default locale
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Is this webhook enabled or not
+    ///
+    /// Webhook will be disabled automatically after too many consecutive failures
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// The amount of consecutive delivery failures
+    #[serde(default)]
+    pub failures: u32,
+    /// The secret used to sign the payload
+    ///
+    /// Only used by [`WebhookTarget::Generic`]
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// The filter to scope the delivered events
+    #[serde(default)]
+    pub filter: WebhookFilter,
     /// The associated server ID for this webhook
     #[serde(with = "ulid_serializer")]
     pub creator: showtimes_shared::ulid::Ulid,
@@ -101,6 +188,10 @@ impl Webhook {
         creator: showtimes_shared::ulid::Ulid,
     ) -> Self {
         let now = jiff::Timestamp::now();
+        let secret = match kind {
+            WebhookTarget::Generic => Some(Self::generate_secret()),
+            _ => None,
+        };
         Self {
             id: showtimes_shared::ulid::Ulid::new(),
             url: url.into(),
@@ -108,6 +199,11 @@ impl Webhook {
             name: default_webhook_name(),
             avatar: None,
             actions: WebhookAction::DEFAULT_ACTIONS.to_vec(),
+            locale: None,
+            enabled: true,
+            failures: 0,
+            secret,
+            filter: WebhookFilter::default(),
             creator,
             _id: None,
             created: now,
@@ -132,6 +228,73 @@ impl Webhook {
         self.actions = actions;
         self
     }
+
+    /// Set the locale of the webhook
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Set the filter of the webhook
+    pub fn with_filter(mut self, filter: WebhookFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Generate a new random secret for signing the payload
+    pub fn generate_secret() -> String {
+        format!(
+            "whsec_{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        )
+    }
+
+    /// The secret with everything but the last 4 characters masked
+    ///
+    /// This can be used to identify the secret without exposing it,
+    /// short secrets are fully masked.
+    pub fn masked_secret(&self) -> Option<String> {
+        self.secret.as_ref().map(|secret| {
+            let chars: Vec<char> = secret.chars().collect();
+            let visible: String = if chars.len() >= 16 {
+                chars[chars.len() - 4..].iter().collect()
+            } else {
+                String::new()
+            };
+            let prefix = if secret.starts_with("whsec_") {
+                "whsec_"
+            } else {
+                ""
+            };
+
+            format!("{prefix}********{visible}")
+        })
+    }
+
+    /// Change the target of the webhook
+    ///
+    /// Switching to [`WebhookTarget::Generic`] will generate a secret if there is none.
+    pub fn set_kind(&mut self, kind: WebhookTarget) {
+        self.kind = kind;
+        if kind == WebhookTarget::Generic && self.secret.is_none() {
+            self.secret = Some(Self::generate_secret());
+        }
+    }
+
+    /// Enable or disable the webhook
+    ///
+    /// Enabling the webhook will also reset the failures count
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if enabled {
+            self.failures = 0;
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 fn default_webhook_name() -> String {
@@ -157,3 +320,46 @@ where
         Ok(default_webhook_name())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_secret() {
+        let mut webhook = Webhook::new(
+            "https://example.com/hook",
+            WebhookTarget::Generic,
+            showtimes_shared::ulid_serializer::default(),
+        );
+        let secret = "whsec_0123456789abcdef".to_string();
+        webhook.secret = Some(secret.clone());
+
+        let masked = webhook.masked_secret().unwrap();
+        assert_eq!(masked, "whsec_********cdef");
+        assert_ne!(masked, secret);
+    }
+
+    #[test]
+    fn test_masked_secret_short() {
+        let mut webhook = Webhook::new(
+            "https://example.com/hook",
+            WebhookTarget::Generic,
+            showtimes_shared::ulid_serializer::default(),
+        );
+        webhook.secret = Some("abc".to_string());
+
+        assert_eq!(webhook.masked_secret().as_deref(), Some("********"));
+    }
+
+    #[test]
+    fn test_masked_secret_none() {
+        let webhook = Webhook::new(
+            "https://example.com/hook",
+            WebhookTarget::Discord,
+            showtimes_shared::ulid_serializer::default(),
+        );
+
+        assert!(webhook.masked_secret().is_none());
+    }
+}
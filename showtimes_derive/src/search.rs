@@ -103,8 +103,115 @@ fn get_searchmodel_attr(attrs: Vec<Attribute>) -> Result<SearchModelAttr, syn::E
     })
 }
 
+/// Check if the field is marked with `#[search(skip)]`
+fn is_search_skip(field: &syn::Field) -> Result<bool, syn::Error> {
+    for attr in &field.attrs {
+        if attr.path().is_ident("search") {
+            let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            for meta in &nested {
+                match meta {
+                    Meta::Path(path) if path.is_ident("skip") => return Ok(true),
+                    _ => {
+                        return Err(syn::Error::new(
+                            meta.span(),
+                            "Only `#[search(skip)]` is supported on fields",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Check if the field is never serialized by serde
+fn is_serde_skip_serializing(field: &syn::Field) -> Result<bool, syn::Error> {
+    for attr in &field.attrs {
+        if attr.path().is_ident("serde") {
+            let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            let skipped = nested.iter().any(|meta| {
+                matches!(meta, Meta::Path(path) if path.is_ident("skip") || path.is_ident("skip_serializing"))
+            });
+            if skipped {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Collect the fields marked with `#[search(skip)]`
+///
+/// The field must also be skipped by serde, since the whole model is sent to Meilisearch.
+fn get_skipped_fields(fields: &syn::FieldsNamed) -> Result<Vec<String>, syn::Error> {
+    let mut skipped = Vec::new();
+    for field in &fields.named {
+        if !is_search_skip(field)? {
+            continue;
+        }
+
+        if field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("primary_key"))
+        {
+            return Err(syn::Error::new(
+                field.span(),
+                "The #[primary_key] field cannot be marked with `#[search(skip)]`",
+            ));
+        }
+
+        if !is_serde_skip_serializing(field)? {
+            return Err(syn::Error::new(
+                field.span(),
+                "Field marked with `#[search(skip)]` must also be marked with `#[serde(skip_serializing)]` or `#[serde(skip)]`",
+            ));
+        }
+
+        if let Some(ident) = &field.ident {
+            skipped.push(ident.to_string());
+        }
+    }
+
+    Ok(skipped)
+}
+
+/// Make sure the skipped fields are not used in any of the index attributes
+fn check_skipped_attrs(
+    model_attr: &SearchModelAttr,
+    skipped: &[String],
+    span: proc_macro2::Span,
+) -> Result<(), syn::Error> {
+    let all_attrs = model_attr
+        .filterable
+        .iter()
+        .chain(model_attr.searchable.iter())
+        .chain(model_attr.sortable.iter())
+        .chain(model_attr.displayed.iter())
+        .chain(model_attr.distinct.iter());
+
+    for attr in all_attrs {
+        let root = attr.split('.').next().unwrap_or(attr);
+        if skipped.iter().any(|s| s == root) {
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "`{attr}` is marked with `#[search(skip)]` and cannot be used in the index"
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// The main function to expand the `SearchModel` derive macro
 ///
+/// Fields marked with `#[search(skip)]` must also be skipped by serde,
+/// and cannot be used in any of the index attributes.
+///
 /// # Examples
 /// ```
 /// #[derive(SearchModel)]
@@ -114,12 +221,15 @@ fn get_searchmodel_attr(attrs: Vec<Attribute>) -> Result<SearchModelAttr, syn::E
 ///    id: String,
 ///    name: String,
 ///    created: i64,
+///    #[search(skip)]
+///    #[serde(skip_serializing, default)]
+///    secret: String,
 /// }
 /// ```
 pub(crate) fn expand_searchmodel(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
 
-    let (model_attr, pk_field) = match &ast.data {
+    let (model_attr, pk_field, skipped_fields) = match &ast.data {
         syn::Data::Struct(data) => {
             // Get the fields of the struct
             let fields = match &data.fields {
@@ -152,9 +262,19 @@ pub(crate) fn expand_searchmodel(ast: &syn::DeriveInput) -> TokenStream {
 
             // Get the search model attributes
             let search_attrs = get_searchmodel_attr(ast.attrs.clone());
+            let skipped_fields = match get_skipped_fields(fields) {
+                Ok(skipped) => skipped,
+                Err(err) => return err.to_compile_error().into(),
+            };
 
             match search_attrs {
-                Ok(data) => (data, pk_field.unwrap()),
+                Ok(data) => {
+                    if let Err(err) = check_skipped_attrs(&data, &skipped_fields, ast.span()) {
+                        return err.to_compile_error().into();
+                    }
+
+                    (data, pk_field.unwrap(), skipped_fields)
+                }
                 Err(err) => return err.to_compile_error().into(),
             }
         }
@@ -236,6 +356,11 @@ pub(crate) fn expand_searchmodel(ast: &syn::DeriveInput) -> TokenStream {
                 }
             }
 
+            /// Get the fields that are never sent to the index
+            pub fn search_skipped() -> &'static [&'static str] {
+                &[#(#skipped_fields),*]
+            }
+
             /// Get the primary key of the model
             pub fn primary_key() -> &'static str {
                 #pk_field_name
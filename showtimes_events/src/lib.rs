@@ -6,12 +6,15 @@ use std::sync::Arc;
 
 pub mod brokers;
 pub mod models;
+mod redis_broker;
 mod streams;
 pub use brokers::MemoryBroker;
 pub use brokers::RSSBroker;
 use clickhouse::Client;
 pub use clickhouse::error::Error as ClickHouseError;
+use futures_util::{Stream, StreamExt};
 pub use models as m;
+pub use redis_broker::RedisBroker;
 
 /// The shared [`SHClickHouse`] client
 pub type SharedSHClickHouse = Arc<SHClickHouse>;
@@ -19,10 +22,35 @@ pub type SharedSHClickHouse = Arc<SHClickHouse>;
 const DATABASE_NAME: &str = "nt_showtimes";
 pub(crate) const TABLE_NAME: &str = "events";
 pub(crate) const RSS_TABLE_NAME: &str = "rss_feed";
+pub(crate) const WEBHOOK_TABLE_NAME: &str = "webhook_deliveries";
+
+/// The event kind enum for the events table
+///
+/// This should be kept in sync with [`m::EventKind`]
+const EVENT_KIND_ENUM: &str = r#"Enum8(
+    'user_created' = 1,
+    'user_updated' = 2,
+    'user_deleted' = 3,
+    'server_created' = 10,
+    'server_updated' = 11,
+    'server_deleted' = 12,
+    'project_created' = 20,
+    'project_updated' = 21,
+    'project_deleted' = 22,
+    'project_episodes' = 30,
+    'collaboration_created' = 40,
+    'collaboration_accepted' = 41,
+    'collaboration_rejected' = 42,
+    'collaboration_deleted' = 43,
+    'collaboration_retracted' = 44,
+    'webhook_disabled' = 50,
+    'rss_feed_disabled' = 60,
+)"#;
 
 /// The main ClickHouse client handler for Showtimes
 pub struct SHClickHouse {
     client: Client,
+    broker: Option<Arc<RedisBroker>>,
 }
 
 impl SHClickHouse {
@@ -55,7 +83,45 @@ impl SHClickHouse {
 
         let client = client.clone().with_database(DATABASE_NAME);
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            broker: None,
+        })
+    }
+
+    /// Share the events with other instances using the provided [`RedisBroker`]
+    ///
+    /// The events are still published to the [`MemoryBroker`] and [`RSSBroker`]
+    /// for the services that should only act on the events of this instance.
+    pub fn with_broker(mut self, broker: RedisBroker) -> Self {
+        self.broker = Some(Arc::new(broker));
+        self
+    }
+
+    /// Subscribe to the events of the specified kind from all instances
+    ///
+    /// Fallback to the [`MemoryBroker`] when there is no [`RedisBroker`] configured.
+    pub fn subscribe<T>(&self, kind: m::EventKind) -> impl Stream<Item = m::SHEvent<T>> + use<T>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + Clone + Debug + 'static,
+    {
+        match &self.broker {
+            Some(broker) => broker.subscribe::<T>(kind).left_stream(),
+            None => MemoryBroker::<T>::subscribe().right_stream(),
+        }
+    }
+
+    /// Subscribe to the RSS events of the specified feed from all instances
+    ///
+    /// Fallback to the [`RSSBroker`] when there is no [`RedisBroker`] configured.
+    pub fn subscribe_rss(
+        &self,
+        feed_id: showtimes_shared::ulid::Ulid,
+    ) -> impl Stream<Item = m::RSSEvent> + use<> {
+        match &self.broker {
+            Some(broker) => broker.subscribe_rss(feed_id).left_stream(),
+            None => RSSBroker::subscribe(feed_id).right_stream(),
+        }
     }
 
     /// Create the necessary tables in the database
@@ -66,23 +132,7 @@ impl SHClickHouse {
                 r#"
                 CREATE TABLE IF NOT EXISTS {TABLE_NAME} (
                     id UUID,
-                    kind Enum8(
-                        'user_created' = 1,
-                        'user_updated' = 2,
-                        'user_deleted' = 3,
-                        'server_created' = 10,
-                        'server_updated' = 11,
-                        'server_deleted' = 12,
-                        'project_created' = 20,
-                        'project_updated' = 21,
-                        'project_deleted' = 22,
-                        'project_episodes' = 30,
-                        'collaboration_created' = 40,
-                        'collaboration_accepted' = 41,
-                        'collaboration_rejected' = 42,
-                        'collaboration_deleted' = 43,
-                        'collaboration_retracted' = 44,
-                    ),
+                    kind {EVENT_KIND_ENUM},
                     data String,
                     actor Nullable(String),
                     timestamp DateTime
@@ -93,6 +143,14 @@ impl SHClickHouse {
             .execute()
             .await?;
 
+        // Make sure older tables have the latest event kind
+        self.client
+            .query(&format!(
+                "ALTER TABLE {TABLE_NAME} MODIFY COLUMN kind {EVENT_KIND_ENUM}"
+            ))
+            .execute()
+            .await?;
+
         // RSS table
         self.client
             .query(&format!(
@@ -109,6 +167,28 @@ impl SHClickHouse {
                 "#,
             ))
             .execute()
+            .await?;
+
+        // Webhook deliveries table
+        self.client
+            .query(&format!(
+                r#"
+                CREATE TABLE IF NOT EXISTS {WEBHOOK_TABLE_NAME} (
+                    id UUID,
+                    webhook_id UUID,
+                    server_id UUID,
+                    action LowCardinality(String),
+                    attempt UInt8,
+                    status Nullable(UInt16),
+                    latency UInt32,
+                    body String,
+                    error Nullable(String),
+                    timestamp DateTime
+                ) ENGINE = MergeTree()
+                ORDER BY (webhook_id, timestamp)
+                "#,
+            ))
+            .execute()
             .await
     }
 
@@ -122,6 +202,11 @@ impl SHClickHouse {
         self.client
             .query(&format!("DROP TABLE IF EXISTS {RSS_TABLE_NAME}"))
             .execute()
+            .await?;
+
+        self.client
+            .query(&format!("DROP TABLE IF EXISTS {WEBHOOK_TABLE_NAME}"))
+            .execute()
             .await
     }
 
@@ -181,6 +266,9 @@ impl SHClickHouse {
 
         for ev in all_events {
             // Publish one by one
+            if let Some(broker) = &self.broker {
+                broker.publish(&ev).await;
+            }
             MemoryBroker::publish(ev);
         }
 
@@ -199,6 +287,7 @@ impl SHClickHouse {
         T: serde::Serialize + Send + Sync + Clone + Debug + 'static,
     {
         let client = self.client.clone();
+        let broker = self.broker.clone();
         tokio::task::spawn(async move {
             let all_events: Vec<models::SHEvent<T>> = data
                 .iter()
@@ -209,6 +298,9 @@ impl SHClickHouse {
 
             for ev in all_events {
                 // Publish one by one
+                if let Some(broker) = &broker {
+                    broker.publish(&ev).await;
+                }
                 MemoryBroker::publish(ev);
             }
 
@@ -229,6 +321,9 @@ impl SHClickHouse {
 
         for ev in data {
             // Publish one by one
+            if let Some(broker) = &self.broker {
+                broker.publish_rss(&ev).await;
+            }
             RSSBroker::publish(ev.feed_id(), ev);
         }
 
@@ -242,11 +337,15 @@ impl SHClickHouse {
         data: Vec<crate::m::RSSEvent>,
     ) -> tokio::task::JoinHandle<Result<(), clickhouse::error::Error>> {
         let client = self.client.clone();
+        let broker = self.broker.clone();
         tokio::task::spawn(async move {
             push_rss(&client, &data).await?;
 
             for ev in data {
                 // Publish one by one
+                if let Some(broker) = &broker {
+                    broker.publish_rss(&ev).await;
+                }
                 RSSBroker::publish(ev.feed_id(), ev);
             }
 
@@ -254,6 +353,27 @@ impl SHClickHouse {
         })
     }
 
+    /// Store the webhook delivery attempts to the database
+    ///
+    /// # Arguments
+    /// * `data` - The delivery attempts of the webhook
+    pub async fn create_webhook_deliveries(
+        &self,
+        data: Vec<crate::m::WebhookDeliveryEvent>,
+    ) -> Result<(), clickhouse::error::Error> {
+        push_webhook_deliveries(&self.client, &data).await
+    }
+
+    /// A similar function to [`SHClickHouse::create_webhook_deliveries`] but will run
+    /// on non-blocking manner or in another thread
+    pub fn create_webhook_deliveries_async(
+        &self,
+        data: Vec<crate::m::WebhookDeliveryEvent>,
+    ) -> tokio::task::JoinHandle<Result<(), clickhouse::error::Error>> {
+        let client = self.client.clone();
+        tokio::task::spawn(async move { push_webhook_deliveries(&client, &data).await })
+    }
+
     /// Query the events from the database with proper pagination
     pub fn query<T>(&self, kind: m::EventKind) -> streams::SHClickStream<T>
     where
@@ -267,6 +387,105 @@ impl SHClickHouse {
         streams::SHRSSClickStream::init(self.client.clone(), feed_id)
     }
 
+    /// Query the webhook delivery attempts from the database with proper pagination
+    pub fn query_webhook_deliveries(
+        &self,
+        webhook_id: showtimes_shared::ulid::Ulid,
+    ) -> streams::SHWebhookClickStream {
+        streams::SHWebhookClickStream::init(self.client.clone(), webhook_id)
+    }
+
+    /// Check if some events of the `kind` after the `cursor` are no longer retained
+    ///
+    /// The events are always stored before being published, so a cursor that can't be
+    /// found and is older than the oldest retained event has been removed from the database.
+    pub async fn has_gap(
+        &self,
+        kind: m::EventKind,
+        cursor: showtimes_shared::ulid::Ulid,
+    ) -> Result<bool, clickhouse::error::Error> {
+        #[derive(Debug, serde::Deserialize, clickhouse::Row)]
+        struct Retention {
+            found: u64,
+            total: u64,
+            oldest: u32,
+        }
+
+        let retention = self
+            .client
+            .query(&format!(
+                r#"SELECT
+                       countIf(toUInt128(id) = toUInt128(toUUID(?))) AS found,
+                       count() AS total,
+                       toUInt32(min(timestamp)) AS oldest
+                   FROM {TABLE_NAME}
+                   WHERE (
+                       kind = ?
+                   )"#,
+            ))
+            .bind(showtimes_shared::ulid_to_uuid(cursor).to_string())
+            .bind(kind as u8)
+            .fetch_one::<Retention>()
+            .await?;
+
+        if retention.found > 0 {
+            return Ok(false);
+        }
+
+        if retention.total == 0 {
+            // Everything has been removed
+            return Ok(true);
+        }
+
+        let cursor_ts = cursor.timestamp_ms() / 1000;
+        Ok(cursor_ts <= u64::from(retention.oldest))
+    }
+
+    /// Check if some RSS events of the feed after the `cursor` are no longer retained
+    ///
+    /// Similar to [`SHClickHouse::has_gap`], but the RSS event timestamp is the entry
+    /// publish time so the age of the oldest retained event is taken from its ULID.
+    pub async fn has_rss_gap(
+        &self,
+        feed_id: showtimes_shared::ulid::Ulid,
+        cursor: showtimes_shared::ulid::Ulid,
+    ) -> Result<bool, clickhouse::error::Error> {
+        #[derive(Debug, serde::Deserialize, clickhouse::Row)]
+        struct Retention {
+            found: u64,
+            total: u64,
+            oldest: u64,
+        }
+
+        let retention = self
+            .client
+            .query(&format!(
+                r#"SELECT
+                       countIf(toUInt128(id) = toUInt128(toUUID(?))) AS found,
+                       count() AS total,
+                       toUInt64(min(bitShiftRight(toUInt128(id), 80))) AS oldest
+                   FROM {RSS_TABLE_NAME}
+                   WHERE (
+                       feed_id = toUUID(?)
+                   )"#,
+            ))
+            .bind(showtimes_shared::ulid_to_uuid(cursor).to_string())
+            .bind(showtimes_shared::ulid_to_uuid(feed_id).to_string())
+            .fetch_one::<Retention>()
+            .await?;
+
+        if retention.found > 0 {
+            return Ok(false);
+        }
+
+        if retention.total == 0 {
+            // Everything has been removed, or nothing has been published yet
+            return Ok(true);
+        }
+
+        Ok(cursor.timestamp_ms() <= retention.oldest)
+    }
+
     /// Get single or latest RSS event for a feed from the database
     pub async fn get_latest_rss(
         &self,
@@ -369,3 +588,76 @@ async fn push_rss(
 
     Ok(())
 }
+
+async fn push_webhook_deliveries(
+    client: &Client,
+    data: &[crate::m::WebhookDeliveryEvent],
+) -> Result<(), clickhouse::error::Error> {
+    tracing::debug!(
+        "Preparing to push webhook deliveries to ClickHouse (table = {}, db = {})",
+        WEBHOOK_TABLE_NAME,
+        DATABASE_NAME
+    );
+    let mut insert = client.insert(WEBHOOK_TABLE_NAME)?;
+    for d in data {
+        insert.write(d).await?;
+    }
+    tracing::debug!(
+        "Inserting webhook deliveries with {} attempt(s) (table = {}, db = {})",
+        data.len(),
+        WEBHOOK_TABLE_NAME,
+        DATABASE_NAME
+    );
+    insert.end().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse the `'name' = value` entries of [`EVENT_KIND_ENUM`]
+    fn enum_entries() -> Vec<(String, u8)> {
+        EVENT_KIND_ENUM
+            .trim_start_matches("Enum8(")
+            .trim_end_matches(')')
+            .split(',')
+            .filter_map(|entry| {
+                let (name, value) = entry.split_once('=')?;
+                let name = name.trim().trim_matches('\'').to_string();
+                let value = value.trim().parse::<u8>().ok()?;
+                Some((name, value))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_kind_enum_has_all_kinds() {
+        let entries = enum_entries();
+
+        // Every possible discriminant that can be deserialized is a valid event kind
+        let kinds: Vec<m::EventKind> = (0..=u8::MAX)
+            .filter_map(|value| serde_json::from_str::<m::EventKind>(&value.to_string()).ok())
+            .collect();
+        assert!(!kinds.is_empty());
+
+        for kind in kinds {
+            let value = kind as u8;
+            assert!(
+                entries.iter().any(|(_, v)| *v == value),
+                "{kind} ({value}) is missing from EVENT_KIND_ENUM"
+            );
+        }
+    }
+
+    #[test]
+    fn test_event_kind_enum_has_no_unknown_kinds() {
+        for (name, value) in enum_entries() {
+            assert!(
+                serde_json::from_str::<m::EventKind>(&value.to_string()).is_ok(),
+                "'{name}' = {value} in EVENT_KIND_ENUM is not a valid event kind"
+            );
+        }
+    }
+}
@@ -42,6 +42,12 @@ pub enum EventKind {
     CollaborationDeleted = 43,
     /// Collaboration retracted event, used when the initiator cancels
     CollaborationRetracted = 44,
+    /// Webhook disabled event, used when a webhook is disabled
+    /// automatically after too many consecutive failures
+    WebhookDisabled = 50,
+    /// RSS feed disabled event, used when a feed is disabled
+    /// automatically after too many consecutive failures
+    RSSFeedDisabled = 60,
 }
 
 impl std::fmt::Display for EventKind {
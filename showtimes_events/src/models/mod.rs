@@ -7,6 +7,7 @@ pub mod rss;
 pub mod servers;
 pub(crate) mod timestamp;
 pub mod users;
+pub mod webhooks;
 
 pub use collabs::*;
 pub use common::*;
@@ -14,3 +15,4 @@ pub use projects::*;
 pub use rss::*;
 pub use servers::*;
 pub use users::*;
+pub use webhooks::*;
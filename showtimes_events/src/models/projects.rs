@@ -228,24 +228,38 @@ pub struct ProjectDeletedEvent {
     #[serde(with = "showtimes_shared::ulid_serializer")]
     #[event_copy]
     id: showtimes_shared::ulid::Ulid,
+    /// The server that owned the project, older events does not have this.
+    #[serde(
+        with = "showtimes_shared::ulid_opt_serializer",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[event_copy]
+    server: Option<showtimes_shared::ulid::Ulid>,
 }
 
 impl ProjectDeletedEvent {
     /// Create a new [`ProjectDeletedEvent`] with the given `id`.
     pub fn new(id: showtimes_shared::ulid::Ulid) -> Self {
-        Self { id }
+        Self { id, server: None }
     }
 }
 
 impl From<showtimes_db::m::Project> for ProjectDeletedEvent {
     fn from(value: showtimes_db::m::Project) -> Self {
-        Self { id: value.id }
+        Self {
+            id: value.id,
+            server: Some(value.creator),
+        }
     }
 }
 
 impl From<&showtimes_db::m::Project> for ProjectDeletedEvent {
     fn from(value: &showtimes_db::m::Project) -> Self {
-        Self { id: value.id }
+        Self {
+            id: value.id,
+            server: Some(value.creator),
+        }
     }
 }
 
@@ -5,6 +5,7 @@
 use super::{deserialize_ulid, serialize_ulid};
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
+use showtimes_derive::EventModel;
 use showtimes_rss::{FeedEntry, FeedEntryCloned, FeedValue, transform_to_cloned_feed};
 use std::fmt::Debug;
 
@@ -103,3 +104,30 @@ impl RSSEvent {
         self.timestamp
     }
 }
+
+/// A RSS feed disabled event, emitted when a feed is
+/// automatically disabled after too many consecutive failures
+#[derive(Debug, Clone, Serialize, Deserialize, EventModel)]
+pub struct RSSFeedDisabledEvent {
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    #[event_copy]
+    id: showtimes_shared::ulid::Ulid,
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    #[event_copy]
+    server: showtimes_shared::ulid::Ulid,
+    #[event_copy]
+    failures: u32,
+    reason: Option<String>,
+}
+
+impl RSSFeedDisabledEvent {
+    /// Creates a new RSS feed disabled event
+    pub fn new(feed: &showtimes_db::m::RSSFeed, reason: Option<String>) -> Self {
+        Self {
+            id: feed.id,
+            server: feed.creator,
+            failures: feed.health.failures,
+            reason,
+        }
+    }
+}
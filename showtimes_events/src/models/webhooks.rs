@@ -0,0 +1,173 @@
+//! A collection of webhook events model
+//!
+//! The delivery log is custom made and stored in its own table.
+
+use super::{deserialize_ulid, serialize_ulid};
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+use showtimes_derive::EventModel;
+
+/// The maximum length of the response body that will be stored
+pub const MAX_RESPONSE_BODY_LENGTH: usize = 1024;
+
+/// A single webhook delivery attempt that is stored in ClickHouse
+#[derive(Clone, Debug, Row, Serialize, Deserialize)]
+pub struct WebhookDeliveryEvent {
+    /// The ID of the attempt, this is randomly generated
+    #[serde(
+        deserialize_with = "deserialize_ulid",
+        serialize_with = "serialize_ulid"
+    )]
+    id: showtimes_shared::ulid::Ulid,
+    /// The webhook ID associated with this attempt
+    #[serde(
+        deserialize_with = "deserialize_ulid",
+        serialize_with = "serialize_ulid"
+    )]
+    webhook_id: showtimes_shared::ulid::Ulid,
+    /// The server ID associated with this attempt
+    #[serde(
+        deserialize_with = "deserialize_ulid",
+        serialize_with = "serialize_ulid"
+    )]
+    server_id: showtimes_shared::ulid::Ulid,
+    /// The webhook action that is being delivered
+    action: String,
+    /// The attempt number, starting from 1
+    attempt: u8,
+    /// The response status code, `None` if the request failed
+    status: Option<u16>,
+    /// The latency of the request in milliseconds
+    latency: u32,
+    /// The truncated response body
+    body: String,
+    /// The error message if the attempt failed
+    error: Option<String>,
+    /// The timestamp of the attempt
+    #[serde(with = "super::timestamp")]
+    timestamp: jiff::Timestamp,
+}
+
+impl WebhookDeliveryEvent {
+    /// Create a new webhook delivery attempt log
+    pub fn new(
+        webhook_id: showtimes_shared::ulid::Ulid,
+        server_id: showtimes_shared::ulid::Ulid,
+        action: impl Into<String>,
+        attempt: u8,
+        latency: std::time::Duration,
+    ) -> Self {
+        Self {
+            id: showtimes_shared::ulid_serializer::default(),
+            webhook_id,
+            server_id,
+            action: action.into(),
+            attempt,
+            status: None,
+            latency: u32::try_from(latency.as_millis()).unwrap_or(u32::MAX),
+            body: String::new(),
+            error: None,
+            timestamp: jiff::Timestamp::now(),
+        }
+    }
+
+    /// Set the response status code
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Set the response body, truncated to [`MAX_RESPONSE_BODY_LENGTH`] characters
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = body.chars().take(MAX_RESPONSE_BODY_LENGTH).collect();
+        self
+    }
+
+    /// Set the error message of the attempt
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    /// Get the ID of the attempt
+    pub fn id(&self) -> showtimes_shared::ulid::Ulid {
+        self.id
+    }
+
+    /// Get the webhook ID of the attempt
+    pub fn webhook_id(&self) -> showtimes_shared::ulid::Ulid {
+        self.webhook_id
+    }
+
+    /// Get the server ID of the attempt
+    pub fn server_id(&self) -> showtimes_shared::ulid::Ulid {
+        self.server_id
+    }
+
+    /// Get the webhook action of the attempt
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// Get the attempt number
+    pub fn attempt(&self) -> u8 {
+        self.attempt
+    }
+
+    /// Get the response status code
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Get the latency of the request in milliseconds
+    pub fn latency(&self) -> u32 {
+        self.latency
+    }
+
+    /// Get the truncated response body
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Get the error message of the attempt
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Check if the attempt is successful
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+
+    /// Get the timestamp of the attempt
+    pub fn timestamp(&self) -> jiff::Timestamp {
+        self.timestamp
+    }
+}
+
+/// A webhook disabled event, emitted when a webhook is
+/// automatically disabled after too many consecutive failures
+#[derive(Debug, Clone, Serialize, Deserialize, EventModel)]
+pub struct WebhookDisabledEvent {
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    #[event_copy]
+    id: showtimes_shared::ulid::Ulid,
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    #[event_copy]
+    server: showtimes_shared::ulid::Ulid,
+    #[event_copy]
+    failures: u32,
+    reason: Option<String>,
+}
+
+impl WebhookDisabledEvent {
+    /// Creates a new webhook disabled event
+    pub fn new(webhook: &showtimes_db::m::Webhook, reason: Option<String>) -> Self {
+        Self {
+            id: webhook.id,
+            server: webhook.creator,
+            failures: webhook.failures,
+            reason,
+        }
+    }
+}
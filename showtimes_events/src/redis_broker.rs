@@ -0,0 +1,286 @@
+//! The broker event system that use Redis pub/sub to share the events between
+//! multiple instances of the server.
+//!
+//! Every instance publish the events it produced to Redis and listen to the events
+//! of all instances, including itself, so a subscriber receive the same events
+//! regardless of which instance it's connected to.
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use ahash::{HashMap, HashMapExt};
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_util::{Stream, StreamExt};
+use redis::{AsyncCommands, RedisResult, cmd};
+use serde::{Serialize, de::DeserializeOwned};
+use slab::Slab;
+
+use crate::models::{EventKind, RSSEvent, SHEvent};
+
+const BROKER_BASE: &str = "showtimes:broker";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Use aHash since it's faster compared to SipHash
+type Listeners = HashMap<String, Slab<UnboundedSender<Arc<str>>>>;
+
+struct RedisBrokerStream {
+    id: usize,
+    channel: String,
+    listeners: Arc<Mutex<Listeners>>,
+    receiver: UnboundedReceiver<Arc<str>>,
+}
+
+impl Drop for RedisBrokerStream {
+    fn drop(&mut self) {
+        let mut listeners = self.listeners.lock().unwrap();
+        if let Some(senders) = listeners.get_mut(&self.channel) {
+            senders.remove(self.id);
+            if senders.is_empty() {
+                listeners.remove(&self.channel);
+            }
+        }
+    }
+}
+
+impl Stream for RedisBrokerStream {
+    type Item = Arc<str>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+fn event_channel(kind: EventKind) -> String {
+    format!("{BROKER_BASE}:events:{}", kind.to_name())
+}
+
+fn rss_channel(feed_id: showtimes_shared::ulid::Ulid) -> String {
+    format!("{BROKER_BASE}:rss:{feed_id}")
+}
+
+/// A broker that share the events between instances with Redis pub/sub
+///
+/// The events are not persisted by Redis, use the ClickHouse query stream
+/// to get the events that happened before subscribing.
+pub struct RedisBroker {
+    connection: redis::aio::MultiplexedConnection,
+    listeners: Arc<Mutex<Listeners>>,
+    listener_task: tokio::task::JoinHandle<()>,
+}
+
+impl RedisBroker {
+    /// Create a new Redis broker and start listening to the published events
+    pub async fn new(client: &Arc<redis::Client>) -> RedisResult<Self> {
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        // Test the connection
+        cmd("PING").exec_async(&mut connection).await?;
+
+        // Make sure we can subscribe before starting the listener
+        let pubsub = subscribe_all(client).await?;
+
+        let listeners = Arc::new(Mutex::new(Listeners::new()));
+        let listener_task =
+            tokio::spawn(listen(Arc::clone(client), pubsub, Arc::clone(&listeners)));
+
+        Ok(Self {
+            connection,
+            listeners,
+            listener_task,
+        })
+    }
+
+    async fn publish_payload<T: Serialize>(&self, channel: String, data: &T) {
+        let payload = match serde_json::to_string(data) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Failed to serialize event for {}: {}", channel, e);
+                return;
+            }
+        };
+
+        let mut connection = self.connection.clone();
+        if let Err(e) = connection.publish::<_, _, ()>(&channel, payload).await {
+            tracing::error!("Failed to publish event to {}: {}", channel, e);
+        }
+    }
+
+    /// Publish an event to all instances
+    pub async fn publish<T>(&self, msg: &SHEvent<T>)
+    where
+        T: Serialize + Send + Sync + Clone + std::fmt::Debug + 'static,
+    {
+        self.publish_payload(event_channel(msg.kind()), msg).await
+    }
+
+    /// Publish an RSS event to all instances
+    pub async fn publish_rss(&self, msg: &RSSEvent) {
+        self.publish_payload(rss_channel(msg.feed_id()), msg).await
+    }
+
+    fn subscribe_channel(&self, channel: String) -> RedisBrokerStream {
+        let mut listeners = self.listeners.lock().unwrap();
+        let (tx, rx) = futures_channel::mpsc::unbounded();
+        let id = listeners.entry(channel.clone()).or_default().insert(tx);
+        tracing::trace!("Subscribing for Redis channel {} with ID {}", channel, id);
+
+        RedisBrokerStream {
+            id,
+            channel,
+            listeners: Arc::clone(&self.listeners),
+            receiver: rx,
+        }
+    }
+
+    /// Subscribe to the events of the specified kind and returns a [`Stream`].
+    pub fn subscribe<T>(&self, kind: EventKind) -> impl Stream<Item = SHEvent<T>> + use<T>
+    where
+        T: DeserializeOwned + Send + Sync + Clone + std::fmt::Debug + 'static,
+    {
+        self.subscribe_channel(event_channel(kind))
+            .filter_map(|payload| std::future::ready(parse_payload::<SHEvent<T>>(&payload)))
+    }
+
+    /// Subscribe to the RSS events of the specified feed and returns a [`Stream`].
+    pub fn subscribe_rss(
+        &self,
+        feed_id: showtimes_shared::ulid::Ulid,
+    ) -> impl Stream<Item = RSSEvent> + use<> {
+        self.subscribe_channel(rss_channel(feed_id))
+            .filter_map(|payload| std::future::ready(parse_payload::<RSSEvent>(&payload)))
+    }
+}
+
+impl Drop for RedisBroker {
+    fn drop(&mut self) {
+        self.listener_task.abort();
+    }
+}
+
+fn parse_payload<T: DeserializeOwned>(payload: &str) -> Option<T> {
+    match serde_json::from_str::<T>(payload) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to parse event of type {:?} from Redis: {}",
+                std::any::type_name::<T>(),
+                e
+            );
+            None
+        }
+    }
+}
+
+async fn subscribe_all(client: &redis::Client) -> RedisResult<redis::aio::PubSub> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.psubscribe(format!("{BROKER_BASE}:*")).await?;
+    Ok(pubsub)
+}
+
+/// Forward the messages from Redis to the subscribers, reconnecting when the connection is lost.
+async fn listen(
+    client: Arc<redis::Client>,
+    pubsub: redis::aio::PubSub,
+    listeners: Arc<Mutex<Listeners>>,
+) {
+    let mut pubsub = Some(pubsub);
+    loop {
+        let current = match pubsub.take() {
+            Some(current) => current,
+            None => match subscribe_all(&client).await {
+                Ok(current) => {
+                    tracing::info!("Reconnected to Redis event broker");
+                    current
+                }
+                Err(e) => {
+                    tracing::error!("Failed to reconnect to Redis event broker: {}", e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                    continue;
+                }
+            },
+        };
+
+        let mut messages = current.into_on_message();
+        while let Some(msg) = messages.next().await {
+            let payload: Arc<str> = match msg.get_payload::<String>() {
+                Ok(payload) => payload.into(),
+                Err(e) => {
+                    tracing::warn!("Received invalid payload from Redis event broker: {}", e);
+                    continue;
+                }
+            };
+
+            let mut listeners = listeners.lock().unwrap();
+            if let Some(senders) = listeners.get_mut(msg.get_channel_name()) {
+                tracing::debug!(
+                    "Forwarding message of {} to {} subscribers",
+                    msg.get_channel_name(),
+                    senders.len()
+                );
+                for (_, sender) in senders.iter_mut() {
+                    sender.start_send(Arc::clone(&payload)).ok();
+                }
+            }
+        }
+
+        tracing::warn!("Lost connection to Redis event broker, reconnecting...");
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use showtimes_rss::{FeedEntry, FeedValue};
+
+    use super::*;
+    use crate::models::ProjectDeletedEvent;
+
+    #[test]
+    fn test_parse_payload_event_roundtrip() {
+        let project = showtimes_shared::ulid::Ulid::new();
+        let event = SHEvent::new(EventKind::ProjectDeleted, ProjectDeletedEvent::new(project));
+
+        let payload = serde_json::to_string(&event).unwrap();
+        let parsed: SHEvent<ProjectDeletedEvent> = parse_payload(&payload).unwrap();
+
+        assert_eq!(parsed.id(), event.id());
+        assert!(matches!(parsed.kind(), EventKind::ProjectDeleted));
+        assert_eq!(parsed.data().id(), project);
+        assert_eq!(parsed.actor(), event.actor());
+        assert_eq!(
+            parsed.timestamp().as_second(),
+            event.timestamp().as_second()
+        );
+    }
+
+    #[test]
+    fn test_parse_payload_rss_roundtrip() {
+        let feed = showtimes_shared::ulid::Ulid::new();
+        let server = showtimes_shared::ulid::Ulid::new();
+        let mut entry = FeedEntry::default();
+        entry.insert("id", FeedValue::String("entry-1".to_string()));
+        entry.insert("title", FeedValue::String("Episode 1".to_string()));
+        let event = RSSEvent::from_entry(feed, server, &entry);
+
+        let payload = serde_json::to_string(&event).unwrap();
+        let parsed: RSSEvent = parse_payload(&payload).unwrap();
+
+        assert_eq!(parsed.id(), event.id());
+        assert_eq!(parsed.feed_id(), feed);
+        assert_eq!(parsed.server_id(), server);
+        assert_eq!(parsed.hash_key(), "entry-1");
+        assert!(matches!(
+            parsed.entry().get("title"),
+            Some(FeedValue::String(title)) if title == "Episode 1"
+        ));
+    }
+
+    #[test]
+    fn test_parse_payload_invalid() {
+        assert!(parse_payload::<RSSEvent>("not json").is_none());
+        assert!(parse_payload::<SHEvent<ProjectDeletedEvent>>("{}").is_none());
+    }
+}
@@ -3,9 +3,9 @@ use std::fmt::Debug;
 use clickhouse::Client;
 
 use crate::{
-    RSS_TABLE_NAME, TABLE_NAME,
+    RSS_TABLE_NAME, TABLE_NAME, WEBHOOK_TABLE_NAME,
     m::EventKind,
-    models::{RSSEvent, SHEvent},
+    models::{RSSEvent, SHEvent, WebhookDeliveryEvent},
 };
 
 #[derive(Clone)]
@@ -438,3 +438,200 @@ impl SHRSSClickStream {
         Ok(all_events)
     }
 }
+
+#[derive(Clone)]
+pub struct SHWebhookClickStream {
+    client: Client,
+    webhook_id: uuid::Uuid,
+    // User defined
+    start_after: Option<uuid::Uuid>,
+    // Our internal state
+    internal_offset: Option<usize>,
+    per_page: usize,
+    initialize: bool,
+    upper_bound: Option<usize>,
+    current: Option<Vec<WebhookDeliveryEvent>>,
+}
+
+impl SHWebhookClickStream {
+    pub(crate) fn init(client: Client, webhook_id: showtimes_shared::ulid::Ulid) -> Self {
+        tracing::debug!(
+            "Initializing SHWebhookClickStream for webhook {:?}",
+            webhook_id
+        );
+        Self {
+            client,
+            webhook_id: showtimes_shared::ulid_to_uuid(webhook_id),
+            start_after: None,
+            internal_offset: None,
+            current: None,
+            initialize: false,
+            upper_bound: None,
+            per_page: 50,
+        }
+    }
+
+    /// Set the per page count
+    pub fn per_page(mut self, per_page: usize) -> Self {
+        if self.initialize {
+            // Ignore if already initialized
+            return self;
+        }
+
+        self.per_page = per_page;
+        self
+    }
+
+    /// Start after a specific ULID
+    pub fn start_after(mut self, start_after: showtimes_shared::ulid::Ulid) -> Self {
+        if self.initialize {
+            // Ignore if already initialized
+            return self;
+        }
+
+        self.start_after = Some(showtimes_shared::ulid_to_uuid(start_after));
+        self
+    }
+
+    pub async fn current(&self) -> Option<Vec<WebhookDeliveryEvent>> {
+        self.current.clone()
+    }
+
+    async fn calculate(&mut self) -> Result<(), clickhouse::error::Error> {
+        if self.upper_bound.is_some() {
+            return Ok(());
+        }
+
+        #[derive(Debug, serde::Deserialize, clickhouse::Row)]
+        struct InternalCounter {
+            upper_bound: u64,
+        }
+
+        let result_await = match self.start_after {
+            Some(start_after) => self
+                .client
+                .query(&format!(
+                    r#"SELECT count() AS ?fields FROM {WEBHOOK_TABLE_NAME}
+                    WHERE (
+                        toUInt128(id) > toUInt128(toUUID(?)) AND
+                        webhook_id = toUUID(?)
+                    )
+                    "#,
+                ))
+                .bind(start_after.to_string())
+                .bind(self.webhook_id.to_string())
+                .fetch_one::<InternalCounter>(),
+            None => self
+                .client
+                .query(&format!(
+                    r#"SELECT count() AS ?fields FROM {WEBHOOK_TABLE_NAME}
+                    WHERE (
+                        webhook_id = toUUID(?)
+                    )
+                    "#,
+                ))
+                .bind(self.webhook_id.to_string())
+                .fetch_one::<InternalCounter>(),
+        };
+
+        tracing::debug!("Starting upper bound query for: {}", self.webhook_id);
+        let count = result_await.await?;
+
+        tracing::debug!("Result upper bound query for: {:?}", count);
+        self.upper_bound = Some(count.upper_bound as usize);
+
+        Ok(())
+    }
+
+    pub async fn advance(&mut self) -> Result<Vec<WebhookDeliveryEvent>, clickhouse::error::Error> {
+        // Do initial count
+        self.calculate().await?;
+
+        let offset = self.internal_offset.unwrap_or(0);
+
+        tracing::debug!(
+            "Requesting SHWebhookClickStream for webhook_id {} with offset {}",
+            self.webhook_id,
+            offset
+        );
+        let all_events = match self.start_after {
+            Some(start_after) => {
+                self.client
+                    .query(&format!(
+                        r#"SELECT ?fields FROM {WEBHOOK_TABLE_NAME}
+                           WHERE (
+                               toUInt128(id) > toUInt128(toUUID(?)) AND
+                               webhook_id = toUUID(?)
+                           )
+                           ORDER BY toUInt128(id) ASC
+                           OFFSET ? ROW FETCH FIRST ? ROWS ONLY"#,
+                    ))
+                    .bind(start_after.to_string())
+                    .bind(self.webhook_id.to_string())
+                    .bind(offset)
+                    .bind(self.per_page)
+                    .fetch_all::<WebhookDeliveryEvent>()
+                    .await?
+            }
+            None => {
+                self.client
+                    .query(&format!(
+                        r#"SELECT ?fields FROM {WEBHOOK_TABLE_NAME}
+                           WHERE (
+                               webhook_id = toUUID(?)
+                           )
+                           ORDER BY toUInt128(id) ASC
+                           OFFSET ? ROW FETCH FIRST ? ROWS ONLY"#,
+                    ))
+                    .bind(self.webhook_id.to_string())
+                    .bind(offset)
+                    .bind(self.per_page)
+                    .fetch_all::<WebhookDeliveryEvent>()
+                    .await?
+            }
+        };
+
+        tracing::debug!(
+            "Got {} SHWebhookClickStream for webhook_id {} with offset {}",
+            all_events.len(),
+            self.webhook_id,
+            offset
+        );
+
+        self.initialize = true;
+        self.internal_offset = Some(offset + self.per_page);
+        self.current = Some(all_events.clone());
+
+        Ok(all_events)
+    }
+
+    // Check if exhausted
+    pub fn is_exhausted(&self) -> bool {
+        if self.initialize && self.current.is_none() {
+            return true;
+        }
+
+        if let Some(current) = &self.current {
+            current.len() < self.per_page
+        } else {
+            false
+        }
+    }
+
+    /// Fetch all events
+    pub async fn fetch_all(
+        &mut self,
+    ) -> Result<Vec<WebhookDeliveryEvent>, clickhouse::error::Error> {
+        let mut all_events = Vec::new();
+        loop {
+            let events = self.advance().await?;
+            if events.is_empty() || self.is_exhausted() {
+                break;
+            }
+
+            all_events.extend(events);
+        }
+
+        Ok(all_events)
+    }
+}
@@ -0,0 +1,285 @@
+//! The client for FansubDB API.
+//!
+//! This is incomplete and only made to support what Showtimes needed.
+
+use std::sync::Arc;
+
+use serde::{
+    Serialize,
+    de::{DeserializeOwned, IgnoredAny},
+};
+use tokio::sync::RwLock;
+
+use crate::{
+    errors::{DetailedSerdeError, FansubDBError, FansubDBResult},
+    models::{
+        FansubDBCreated, FansubDBProject, FansubDBProjectCreate, FansubDBProjectUpdate,
+        FansubDBResponse, FansubDBToken,
+    },
+};
+
+const FANSUBDB_API_URL: &str = "https://db.silveryasha.id/api";
+
+/// The main client to interact with FansubDB
+///
+/// The token is fetched lazily and will be refreshed once when the API reject it.
+#[derive(Debug, Clone)]
+pub struct FansubDBClient {
+    client: reqwest::Client,
+    base_url: String,
+    email: String,
+    password: String,
+    token: Arc<RwLock<Option<String>>>,
+}
+
+impl FansubDBClient {
+    /// Create a new FansubDB client
+    ///
+    /// # Arguments
+    /// * `email` - The email of the contributor account
+    /// * `password` - The password of the contributor account
+    pub fn new(email: impl Into<String>, password: impl Into<String>) -> Self {
+        let ua_bind = reqwest::header::HeaderValue::from_str(&format!(
+            "showtimes-rs-fansubdb/{} (+https://github.com/naoTimesdev/showtimes-rs)",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .expect("Failed to build the User-Agent header for FansubDB API");
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::ACCEPT,
+            reqwest::header::HeaderValue::from_static("application/json"),
+        );
+        headers.insert(reqwest::header::USER_AGENT, ua_bind);
+
+        let client = reqwest::ClientBuilder::new()
+            .http2_adaptive_window(true)
+            .default_headers(headers)
+            .use_rustls_tls()
+            .build()
+            .expect("Failed to build reqwest client for FansubDB API");
+
+        FansubDBClient {
+            client,
+            base_url: FANSUBDB_API_URL.to_string(),
+            email: email.into(),
+            password: password.into(),
+            token: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Change the base URL of the API, mainly used for testing
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Authenticate with the stored credentials and store the token
+    pub async fn authenticate(&self) -> FansubDBResult<()> {
+        let payload = serde_json::json!({
+            "email": self.email,
+            "password": self.password,
+        });
+
+        let req = self
+            .client
+            .post(format!("{}/pintusorga", self.base_url))
+            .json(&payload)
+            .send()
+            .await?;
+
+        let token = match Self::parse::<FansubDBToken>(req, "/pintusorga").await {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                return Err(FansubDBError::Unauthorized("No token received".to_string()));
+            }
+            Err(FansubDBError::Response(status, message))
+                if status == reqwest::StatusCode::BAD_REQUEST =>
+            {
+                return Err(FansubDBError::Unauthorized(message));
+            }
+            Err(err) => return Err(err),
+        };
+
+        *self.token.write().await = Some(token.token);
+        Ok(())
+    }
+
+    async fn token(&self) -> FansubDBResult<String> {
+        if let Some(token) = self.token.read().await.as_ref() {
+            return Ok(token.clone());
+        }
+
+        self.authenticate().await?;
+        self.token
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| FansubDBError::Unauthorized("No token received".to_string()))
+    }
+
+    async fn parse<T: DeserializeOwned>(
+        req: reqwest::Response,
+        endpoint: &str,
+    ) -> FansubDBResult<Option<T>> {
+        let status = req.status();
+        let headers = req.headers().clone();
+        let url = req.url().clone();
+
+        let is_json = match headers.get(reqwest::header::CONTENT_TYPE) {
+            Some(header) => {
+                let header_str = header
+                    .to_str()
+                    .map_err(|_| FansubDBError::HeaderToString("content-type".to_string()))?;
+
+                header_str.starts_with("application/json")
+            }
+            None => false,
+        };
+
+        let raw_text = req.text().await?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(FansubDBError::Unauthorized(raw_text));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(FansubDBError::NotFound(endpoint.to_string()));
+        }
+        if !is_json {
+            return Err(FansubDBError::Response(status, raw_text));
+        }
+
+        let res = serde_json::from_str::<FansubDBResponse<T>>(&raw_text).map_err(|err| {
+            FansubDBError::new_serde(DetailedSerdeError::new(
+                err, status, &headers, &url, raw_text,
+            ))
+        })?;
+
+        if !status.is_success() || !res.is_success() {
+            return Err(FansubDBError::Response(
+                status,
+                res.message.unwrap_or_else(|| "Unknown error".to_string()),
+            ));
+        }
+
+        Ok(res.data)
+    }
+
+    async fn request<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        body: Option<&B>,
+    ) -> FansubDBResult<Option<T>> {
+        let mut retried = false;
+        loop {
+            let token = self.token().await?;
+            let mut req = self
+                .client
+                .request(method.clone(), format!("{}{}", self.base_url, endpoint))
+                .bearer_auth(token);
+            if let Some(body) = body {
+                req = req.json(body);
+            }
+
+            match Self::parse::<T>(req.send().await?, endpoint).await {
+                // The token might be expired, re-authenticate once
+                Err(FansubDBError::Unauthorized(_)) if !retried => {
+                    retried = true;
+                    *self.token.write().await = None;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Get all projects of a fansub group
+    ///
+    /// # Arguments
+    /// * `fansub_id` - The fansub group ID, this is the `FansubDB` integration
+    pub async fn get_fansub_projects(
+        &self,
+        fansub_id: u64,
+    ) -> FansubDBResult<Vec<FansubDBProject>> {
+        let projects = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/projek/fansub/{fansub_id}"),
+                None::<&()>,
+            )
+            .await?;
+
+        Ok(projects.unwrap_or_default())
+    }
+
+    /// Get a single project by ID
+    ///
+    /// # Arguments
+    /// * `id` - The project ID
+    pub async fn get_project(&self, id: u64) -> FansubDBResult<FansubDBProject> {
+        let endpoint = format!("/projek/{id}");
+        let project = self
+            .request(reqwest::Method::GET, &endpoint, None::<&()>)
+            .await?;
+
+        project.ok_or(FansubDBError::NotFound(endpoint))
+    }
+
+    /// Create a new project, returning the new project ID
+    ///
+    /// # Arguments
+    /// * `payload` - The project information
+    pub async fn create_project(&self, payload: &FansubDBProjectCreate) -> FansubDBResult<u64> {
+        let created: Option<FansubDBCreated> = self
+            .request(reqwest::Method::POST, "/projek", Some(payload))
+            .await?;
+
+        created.map(|created| created.id).ok_or_else(|| {
+            FansubDBError::Response(
+                reqwest::StatusCode::OK,
+                "Missing created project ID".to_string(),
+            )
+        })
+    }
+
+    /// Update an existing project
+    ///
+    /// # Arguments
+    /// * `id` - The project ID
+    /// * `payload` - The fields to update
+    pub async fn update_project(
+        &self,
+        id: u64,
+        payload: &FansubDBProjectUpdate,
+    ) -> FansubDBResult<()> {
+        self.request::<IgnoredAny, _>(
+            reqwest::Method::PUT,
+            &format!("/projek/{id}"),
+            Some(payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Update the release state of a project from the amount of released episodes
+    ///
+    /// # Arguments
+    /// * `id` - The project ID
+    /// * `released` - The amount of released episodes
+    /// * `total` - The total episodes, `0` if unknown
+    pub async fn update_release(&self, id: u64, released: u64, total: u64) -> FansubDBResult<()> {
+        self.update_project(id, &FansubDBProjectUpdate::from_release(released, total))
+            .await
+    }
+
+    /// Delete a project
+    ///
+    /// # Arguments
+    /// * `id` - The project ID
+    pub async fn delete_project(&self, id: u64) -> FansubDBResult<()> {
+        self.request::<IgnoredAny, ()>(reqwest::Method::DELETE, &format!("/projek/{id}"), None)
+            .await?;
+
+        Ok(())
+    }
+}
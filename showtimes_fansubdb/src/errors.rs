@@ -0,0 +1,139 @@
+//! A collection of errors data
+
+/// The wrapper for [`FansubDBError`]
+pub type FansubDBResult<T> = Result<T, FansubDBError>;
+
+/// Error type for FansubDB API
+///
+/// This enum can be used to wrap the possible errors that can happen when
+/// interacting with the FansubDB API.
+#[derive(Debug)]
+pub enum FansubDBError {
+    /// Error related to request
+    Request(reqwest::Error),
+    /// Error related to deserialization
+    Serde(Box<DetailedSerdeError>),
+    /// The API returned an error response, with the status code and the message
+    Response(reqwest::StatusCode, String),
+    /// The credentials or token is rejected by the API
+    Unauthorized(String),
+    /// The requested resource is not found, the input is the endpoint
+    NotFound(String),
+    /// Conversion to string failure from header
+    HeaderToString(String),
+}
+
+impl FansubDBError {
+    pub(crate) fn new_serde(e: DetailedSerdeError) -> Self {
+        Self::Serde(Box::new(e))
+    }
+}
+
+impl From<DetailedSerdeError> for FansubDBError {
+    fn from(value: DetailedSerdeError) -> Self {
+        FansubDBError::Serde(Box::new(value))
+    }
+}
+
+impl From<reqwest::Error> for FansubDBError {
+    fn from(value: reqwest::Error) -> Self {
+        FansubDBError::Request(value)
+    }
+}
+
+impl std::fmt::Display for FansubDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FansubDBError::Request(e) => write!(f, "{e}"),
+            FansubDBError::Serde(e) => write!(f, "{e}"),
+            FansubDBError::Response(status, message) => {
+                write!(f, "received error response ({status}): {message}")
+            }
+            FansubDBError::Unauthorized(message) => write!(f, "unauthorized: {message}"),
+            FansubDBError::NotFound(endpoint) => write!(f, "not found: {endpoint}"),
+            FansubDBError::HeaderToString(header) => {
+                write!(f, "failed to convert header to string: {header}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FansubDBError {}
+
+/// Error type that happens when parsing the response from the API
+///
+/// This is specifically for [`serde`] errors.
+///
+/// When formatted as a string, it will show the error message, status code, headers, and a JSON excerpt.
+pub struct DetailedSerdeError {
+    inner: serde_json::Error,
+    status_code: reqwest::StatusCode,
+    headers: reqwest::header::HeaderMap,
+    url: reqwest::Url,
+    raw_text: String,
+}
+
+impl std::fmt::Debug for DetailedSerdeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DetailedSerdeError")
+            .field("inner", &self.inner)
+            .field("status_code", &self.status_code)
+            .field("headers", &self.headers)
+            .field("url", &self.url)
+            .field("excerpt", &self.get_json_excerpt())
+            .finish()
+    }
+}
+
+impl DetailedSerdeError {
+    /// Create a new instance of the error
+    pub(crate) fn new(
+        inner: serde_json::Error,
+        status_code: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        url: &reqwest::Url,
+        raw_text: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            status_code,
+            headers: headers.clone(),
+            url: url.clone(),
+            raw_text: raw_text.into(),
+        }
+    }
+
+    /// Get the JSON excerpt from the raw text
+    ///
+    /// This will return a string that contains where the deserialization error happened.
+    ///
+    /// It will take 25 characters before and after the error position.
+    pub fn get_json_excerpt(&self) -> String {
+        let split_lines = self.raw_text.split('\n').collect::<Vec<&str>>();
+        let row_line = self.inner.line().saturating_sub(1);
+        let line = match split_lines.get(row_line) {
+            Some(line) => line,
+            None => return String::new(),
+        };
+
+        let position = self.inner.column();
+        let start_idx = position.saturating_sub(25).min(line.len());
+        let end_idx = position.saturating_add(25).min(line.len());
+
+        line.get(start_idx..end_idx).unwrap_or_default().to_string()
+    }
+}
+
+impl std::fmt::Display for DetailedSerdeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Serde Error: {}\nStatus Code: {}\nHeaders: {:?}\nURL: {}\nJSON excerpt: {}",
+            self.inner,
+            self.status_code,
+            self.headers,
+            self.url,
+            self.get_json_excerpt()
+        )
+    }
+}
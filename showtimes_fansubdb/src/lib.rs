@@ -1,14 +1,14 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+#![warn(missing_docs, clippy::empty_docs, rustdoc::broken_intra_doc_links)]
+#![doc = include_str!("../README.md")]
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod client;
+pub mod errors;
+pub mod models;
+pub mod sync;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+/// The FansubDB client
+pub use client::FansubDBClient;
+/// Re-export the models module
+pub use models as m;
+/// The FansubDB release synchronizer
+pub use sync::FansubDBSync;
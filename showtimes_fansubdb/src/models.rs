@@ -0,0 +1,172 @@
+//! A type definition for the FansubDB API
+//!
+//! This is incomplete and only made to support what Showtimes needed.
+
+use serde::{Deserialize, Serialize};
+
+/// The status of a project in FansubDB
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FansubDBProjectStatus {
+    /// The project is planned but not started yet
+    #[serde(rename = "Tentatif")]
+    Tentative,
+    /// The project is ongoing, at least one episode is released
+    #[serde(rename = "Jalan")]
+    Ongoing,
+    /// All episodes of the project has been released
+    #[serde(rename = "Tamat")]
+    Finished,
+    /// The project is dropped
+    #[serde(rename = "Drop")]
+    Dropped,
+}
+
+impl FansubDBProjectStatus {
+    /// Get the status from the amount of released episodes
+    ///
+    /// A `total` of `0` means the total episodes is unknown.
+    pub fn from_release(released: u64, total: u64) -> Self {
+        if released == 0 {
+            FansubDBProjectStatus::Tentative
+        } else if total > 0 && released >= total {
+            FansubDBProjectStatus::Finished
+        } else {
+            FansubDBProjectStatus::Ongoing
+        }
+    }
+}
+
+/// The fansub group information
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FansubDBFansub {
+    /// The fansub ID
+    pub id: u64,
+    /// The fansub name
+    pub name: String,
+}
+
+/// The anime or shows information
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FansubDBAnime {
+    /// The anime ID, this is the `FansubDBShows` integration
+    pub id: u64,
+    /// The MyAnimeList ID of the anime
+    pub mal_id: Option<u64>,
+    /// The anime title
+    pub title: String,
+    /// The anime type (e.g. `TV`, `Movie`, `OVA`)
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// The total episodes of the anime, if known
+    pub episode: Option<u64>,
+}
+
+/// The project information of a fansub group
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FansubDBProject {
+    /// The project ID, this is the `FansubDBProject` integration
+    pub id: u64,
+    /// The anime of the project
+    pub anime: FansubDBAnime,
+    /// The fansub groups that work on the project
+    #[serde(default)]
+    pub fansub: Vec<FansubDBFansub>,
+    /// The project status
+    pub status: FansubDBProjectStatus,
+    /// The last released episode
+    #[serde(default)]
+    pub progress: Option<u64>,
+    /// The subtitle type (e.g. `Softsub`, `Hardsub`)
+    pub subtitle: Option<String>,
+    /// The URL to the project page of the fansub
+    pub url: Option<String>,
+}
+
+/// The payload to create a new project
+#[derive(Debug, Clone, Serialize)]
+pub struct FansubDBProjectCreate {
+    /// The anime ID
+    pub anime_id: u64,
+    /// The fansub groups ID that work on the project
+    pub fansub: Vec<u64>,
+    /// The project status
+    pub status: FansubDBProjectStatus,
+    /// The subtitle type, default to `Softsub`
+    pub subtitle: String,
+    /// The URL to the project page of the fansub
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl FansubDBProjectCreate {
+    /// Create a new tentative project payload
+    pub fn new(anime_id: u64, fansub_id: u64) -> Self {
+        Self {
+            anime_id,
+            fansub: vec![fansub_id],
+            status: FansubDBProjectStatus::Tentative,
+            subtitle: "Softsub".to_string(),
+            url: None,
+        }
+    }
+}
+
+/// The payload to update an existing project, only the set fields will be updated
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FansubDBProjectUpdate {
+    /// The project status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<FansubDBProjectStatus>,
+    /// The last released episode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u64>,
+    /// The URL to the project page of the fansub
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl FansubDBProjectUpdate {
+    /// Create the release state update from the amount of released episodes
+    ///
+    /// A `total` of `0` means the total episodes is unknown.
+    pub fn from_release(released: u64, total: u64) -> Self {
+        Self {
+            status: Some(FansubDBProjectStatus::from_release(released, total)),
+            progress: Some(released),
+            url: None,
+        }
+    }
+}
+
+/// The authentication token information
+#[derive(Debug, Clone, Deserialize)]
+pub struct FansubDBToken {
+    /// The bearer token
+    pub token: String,
+}
+
+/// The created resource information
+#[derive(Debug, Clone, Deserialize)]
+pub struct FansubDBCreated {
+    /// The ID of the created resource
+    pub id: u64,
+}
+
+/// The response envelope of the FansubDB API
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct FansubDBResponse<T> {
+    /// The response type, either `success` or `error`
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    /// The response data
+    pub(crate) data: Option<T>,
+    /// The error message
+    pub(crate) message: Option<String>,
+}
+
+impl<T> FansubDBResponse<T> {
+    /// Check if the response is a success
+    pub(crate) fn is_success(&self) -> bool {
+        self.kind == "success"
+    }
+}
@@ -0,0 +1,366 @@
+//! Synchronize the release state of Showtimes projects into FansubDB
+//!
+//! Projects that have a `FansubDBProject` integration will push their release
+//! state whenever an episode is marked as released or unreleased.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ahash::{HashMap, HashMapExt};
+use futures_util::StreamExt;
+use showtimes_db::{
+    DatabaseShared, ProjectHandler,
+    m::{IntegrationType, Project, ProjectStatus},
+    mongodb::bson::doc,
+};
+use showtimes_events::{MemoryBroker, m::ProjectEpisodeUpdatedEvent};
+use showtimes_shared::ulid::Ulid;
+use tokio::sync::Mutex;
+
+use crate::{
+    client::FansubDBClient,
+    errors::FansubDBError,
+    models::{FansubDBProjectStatus, FansubDBProjectUpdate},
+};
+
+/// The default amount of retries before a pending sync is dropped
+pub const DEFAULT_MAX_RETRIES: u8 = 5;
+
+/// The default base delay for the exponential backoff
+const DEFAULT_RETRY_BASE: Duration = Duration::from_secs(30);
+
+/// The maximum delay between each retry
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 30);
+
+/// How often the pending queue is checked
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Error that happens when synchronizing a project
+#[derive(Debug)]
+pub enum FansubDBSyncError {
+    /// The FansubDB API request failed
+    Api(FansubDBError),
+    /// Failed to load the project from the database
+    Database(showtimes_db::mongodb::error::Error),
+    /// The project does not have a `FansubDBProject` integration
+    MissingIntegration(Ulid),
+    /// The `FansubDBProject` integration is not a valid ID
+    InvalidIntegration(String),
+}
+
+impl FansubDBSyncError {
+    /// Check if the sync can be retried later
+    ///
+    /// Only network issues, rate limits and server errors are retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FansubDBSyncError::Api(FansubDBError::Request(_)) => true,
+            FansubDBSyncError::Api(FansubDBError::Response(status, _)) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            FansubDBSyncError::Database(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for FansubDBSyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FansubDBSyncError::Api(e) => write!(f, "{e}"),
+            FansubDBSyncError::Database(e) => write!(f, "database error: {e}"),
+            FansubDBSyncError::MissingIntegration(id) => {
+                write!(f, "project {id} has no FansubDB project integration")
+            }
+            FansubDBSyncError::InvalidIntegration(id) => {
+                write!(f, "invalid FansubDB project ID: {id}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FansubDBSyncError {}
+
+impl From<FansubDBError> for FansubDBSyncError {
+    fn from(value: FansubDBError) -> Self {
+        FansubDBSyncError::Api(value)
+    }
+}
+
+impl From<showtimes_db::mongodb::error::Error> for FansubDBSyncError {
+    fn from(value: showtimes_db::mongodb::error::Error) -> Self {
+        FansubDBSyncError::Database(value)
+    }
+}
+
+/// The release state of a project as it should be stored in FansubDB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FansubDBReleaseState {
+    /// The FansubDB project ID
+    pub id: u64,
+    /// The project status
+    pub status: FansubDBProjectStatus,
+    /// The amount of released episodes
+    pub progress: u64,
+}
+
+impl FansubDBReleaseState {
+    /// Compute the release state from a Showtimes project
+    ///
+    /// An archived project that is not fully released is considered dropped.
+    pub fn from_project(project: &Project) -> Result<Self, FansubDBSyncError> {
+        let integration = project
+            .integrations
+            .iter()
+            .find(|i| i.kind() == &IntegrationType::FansubDBProject)
+            .ok_or(FansubDBSyncError::MissingIntegration(project.id))?;
+
+        let id = integration
+            .id()
+            .parse::<u64>()
+            .map_err(|_| FansubDBSyncError::InvalidIntegration(integration.id().to_string()))?;
+
+        let total = project.progress.len() as u64;
+        let progress = project
+            .progress
+            .iter()
+            .filter(|ep| ep.is_finished())
+            .count() as u64;
+
+        let status = match FansubDBProjectStatus::from_release(progress, total) {
+            FansubDBProjectStatus::Finished => FansubDBProjectStatus::Finished,
+            _ if project.status == ProjectStatus::Archived => FansubDBProjectStatus::Dropped,
+            status => status,
+        };
+
+        Ok(Self {
+            id,
+            status,
+            progress,
+        })
+    }
+
+    /// Convert into the update payload
+    pub fn to_update(&self) -> FansubDBProjectUpdate {
+        FansubDBProjectUpdate {
+            status: Some(self.status),
+            progress: Some(self.progress),
+            url: None,
+        }
+    }
+}
+
+/// The result of a full reconciliation
+#[derive(Debug, Clone, Copy)]
+pub struct FansubDBReconcile {
+    /// The release state that is now stored in FansubDB
+    pub state: FansubDBReleaseState,
+    /// Whether FansubDB was out of sync and got updated
+    pub changed: bool,
+}
+
+/// A project that failed to sync and is waiting to be retried
+#[derive(Debug, Clone, Copy)]
+struct PendingSync {
+    attempt: u8,
+    next_at: Instant,
+}
+
+/// The FansubDB release synchronizer
+///
+/// Failed syncs are queued per project and retried with exponential backoff,
+/// the latest project state is always used when retrying.
+///
+/// The pending queue only lives in memory, the queued syncs are lost when the
+/// server restarts. Use [`FansubDBSync::reconcile`] (or the `syncFansubDB`
+/// mutation) to bring a project back in sync afterwards.
+#[derive(Debug, Clone)]
+pub struct FansubDBSync {
+    client: FansubDBClient,
+    pending: Arc<Mutex<HashMap<Ulid, PendingSync>>>,
+    max_retries: u8,
+    retry_base: Duration,
+}
+
+impl FansubDBSync {
+    /// Create a new synchronizer with the provided client
+    pub fn new(client: FansubDBClient) -> Self {
+        Self {
+            client,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_base: DEFAULT_RETRY_BASE,
+        }
+    }
+
+    /// Set the maximum retries and the base delay for the exponential backoff
+    pub fn with_retries(mut self, max_retries: u8, base_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_base = base_delay;
+        self
+    }
+
+    /// The underlying FansubDB client
+    pub fn client(&self) -> &FansubDBClient {
+        &self.client
+    }
+
+    /// The projects that are waiting to be retried
+    ///
+    /// This is not persisted, see the [`FansubDBSync`] documentation.
+    pub async fn pending(&self) -> Vec<Ulid> {
+        self.pending.lock().await.keys().copied().collect()
+    }
+
+    /// Push the release state of the project into FansubDB
+    pub async fn push(&self, project: &Project) -> Result<FansubDBReleaseState, FansubDBSyncError> {
+        let state = FansubDBReleaseState::from_project(project)?;
+        self.client
+            .update_project(state.id, &state.to_update())
+            .await?;
+
+        Ok(state)
+    }
+
+    /// Compare the state in FansubDB with the project and update it when it differs
+    ///
+    /// This also clears the project from the pending queue.
+    pub async fn reconcile(
+        &self,
+        project: &Project,
+    ) -> Result<FansubDBReconcile, FansubDBSyncError> {
+        let state = FansubDBReleaseState::from_project(project)?;
+        let remote = self.client.get_project(state.id).await?;
+
+        let changed = remote.status != state.status || remote.progress != Some(state.progress);
+        if changed {
+            self.client
+                .update_project(state.id, &state.to_update())
+                .await?;
+        }
+
+        self.pending.lock().await.remove(&project.id);
+        Ok(FansubDBReconcile { state, changed })
+    }
+
+    /// Push the project, queueing it for a retry when it failed
+    ///
+    /// This never returns an error, since it is used in the background.
+    pub async fn process(&self, project: &Project) {
+        match self.push(project).await {
+            Ok(state) => {
+                tracing::debug!(
+                    "Synced project {} to FansubDB {} ({:?}, {})",
+                    project.id,
+                    state.id,
+                    state.status,
+                    state.progress
+                );
+                self.pending.lock().await.remove(&project.id);
+            }
+            Err(FansubDBSyncError::MissingIntegration(_)) => {
+                self.pending.lock().await.remove(&project.id);
+            }
+            Err(err) => self.queue(project.id, err).await,
+        }
+    }
+
+    async fn queue(&self, project_id: Ulid, err: FansubDBSyncError) {
+        let mut pending = self.pending.lock().await;
+        let attempt = pending.get(&project_id).map_or(1, |p| p.attempt + 1);
+
+        if !err.is_retryable() || attempt > self.max_retries {
+            tracing::error!(
+                "Failed to sync project {} to FansubDB, giving up after {} attempt(s): {}",
+                project_id,
+                attempt,
+                err
+            );
+            pending.remove(&project_id);
+            return;
+        }
+
+        let delay = self.backoff(attempt);
+        tracing::warn!(
+            "Failed to sync project {} to FansubDB, retrying in {:?}: {}",
+            project_id,
+            delay,
+            err
+        );
+        pending.insert(
+            project_id,
+            PendingSync {
+                attempt,
+                next_at: Instant::now() + delay,
+            },
+        );
+    }
+
+    /// The exponential backoff delay for the provided attempt
+    fn backoff(&self, attempt: u8) -> Duration {
+        let factor = 1u32 << u32::from(attempt.saturating_sub(1)).min(16);
+        self.retry_base.saturating_mul(factor).min(MAX_RETRY_DELAY)
+    }
+
+    async fn load_and_process(&self, db: &DatabaseShared, project_id: Ulid) {
+        let handler = ProjectHandler::new(db);
+        match handler.find_by(doc! { "id": project_id.to_string() }).await {
+            Ok(Some(project)) => self.process(&project).await,
+            Ok(None) => {
+                tracing::warn!("Project {} not found for FansubDB sync", project_id);
+                self.pending.lock().await.remove(&project_id);
+            }
+            Err(err) => self.queue(project_id, err.into()).await,
+        }
+    }
+
+    /// Retry every pending project that is due
+    pub async fn retry_pending(&self, db: &DatabaseShared) {
+        let now = Instant::now();
+        let due: Vec<Ulid> = self
+            .pending
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, p)| p.next_at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for project_id in due {
+            self.load_and_process(db, project_id).await;
+        }
+    }
+
+    /// Start listening to the episode events from the [`MemoryBroker`]
+    ///
+    /// Only episodes that are released or unreleased will trigger a sync,
+    /// the pending queue is checked periodically in the same task.
+    pub fn listen(self, db: DatabaseShared) -> tokio::task::JoinHandle<()> {
+        tokio::task::spawn(async move {
+            let stream = MemoryBroker::<ProjectEpisodeUpdatedEvent>::subscribe();
+            let mut stream = std::pin::pin!(stream);
+            let mut ticker = tokio::time::interval(RETRY_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    event = stream.next() => {
+                        let event = match event {
+                            Some(event) => event,
+                            None => break,
+                        };
+
+                        if event.data().finished().is_some() {
+                            self.load_and_process(&db, event.data().id()).await;
+                        }
+                    }
+                    _ = ticker.tick() => {
+                        self.retry_pending(&db).await;
+                    }
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,353 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use showtimes_db::m::{
+    EpisodeProgress, IntegrationId, IntegrationType, Project, ProjectStatus, ProjectType,
+};
+use showtimes_fansubdb::{
+    FansubDBClient, FansubDBSync,
+    errors::FansubDBError,
+    m::{FansubDBProjectCreate, FansubDBProjectStatus},
+    sync::{FansubDBReleaseState, FansubDBSyncError},
+};
+use showtimes_test_support::{StandInRequest, StandInResponse, spawn_stand_in};
+
+fn success(data: serde_json::Value) -> StandInResponse {
+    StandInResponse::json(
+        200,
+        serde_json::json!({ "type": "success", "data": data }).to_string(),
+    )
+}
+
+fn login(request: &StandInRequest) -> Option<StandInResponse> {
+    (request.path == "/api/pintusorga").then(|| success(serde_json::json!({ "token": "tok-1" })))
+}
+
+fn sample_project() -> serde_json::Value {
+    serde_json::json!({
+        "id": 120,
+        "anime": {
+            "id": 55,
+            "mal_id": 52991,
+            "title": "Sousou no Frieren",
+            "type": "TV",
+            "episode": 28
+        },
+        "fansub": [{ "id": 7, "name": "naoTimes" }],
+        "status": "Jalan",
+        "progress": 3,
+        "subtitle": "Softsub",
+        "url": null
+    })
+}
+
+#[tokio::test]
+async fn test_fansub_projects() {
+    let (url, mut rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|request: &StandInRequest| {
+            login(request).unwrap_or_else(|| success(serde_json::json!([sample_project()])))
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+    let projects = client.get_fansub_projects(7).await.unwrap();
+
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].id, 120);
+    assert_eq!(projects[0].anime.mal_id, Some(52991));
+    assert_eq!(projects[0].status, FansubDBProjectStatus::Ongoing);
+    assert_eq!(projects[0].fansub[0].name, "naoTimes");
+
+    let auth = rx.recv().await.unwrap();
+    assert_eq!(auth.method, "POST");
+    let credentials: serde_json::Value = serde_json::from_str(&auth.body).unwrap();
+    assert_eq!(credentials["email"], "user@example.com");
+
+    let list = rx.recv().await.unwrap();
+    assert_eq!(list.method, "GET");
+    assert_eq!(list.path, "/api/projek/fansub/7");
+    assert_eq!(list.authorization.as_deref(), Some("Bearer tok-1"));
+}
+
+#[tokio::test]
+async fn test_create_and_release() {
+    let (url, mut rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|request: &StandInRequest| {
+            login(request).unwrap_or_else(|| match request.method.as_str() {
+                "POST" => success(serde_json::json!({ "id": 121 })),
+                _ => success(serde_json::Value::Null),
+            })
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+
+    let id = client
+        .create_project(&FansubDBProjectCreate::new(55, 7))
+        .await
+        .unwrap();
+    assert_eq!(id, 121);
+
+    client.update_release(id, 28, 28).await.unwrap();
+
+    // Skip the login request
+    rx.recv().await.unwrap();
+
+    let create = rx.recv().await.unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&create.body).unwrap();
+    assert_eq!(payload["anime_id"], 55);
+    assert_eq!(payload["fansub"][0], 7);
+    assert_eq!(payload["status"], "Tentatif");
+
+    let update = rx.recv().await.unwrap();
+    assert_eq!(update.method, "PUT");
+    assert_eq!(update.path, "/api/projek/121");
+    let payload: serde_json::Value = serde_json::from_str(&update.body).unwrap();
+    assert_eq!(payload["status"], "Tamat");
+    assert_eq!(payload["progress"], 28);
+    assert!(payload.get("url").is_none());
+}
+
+#[tokio::test]
+async fn test_reauthenticate_expired_token() {
+    let logins = Arc::new(AtomicUsize::new(0));
+    let responder_logins = Arc::clone(&logins);
+    let (url, _rx) = spawn_stand_in(
+        "/api",
+        Arc::new(move |request: &StandInRequest| {
+            if request.path == "/api/pintusorga" {
+                let count = responder_logins.fetch_add(1, Ordering::SeqCst) + 1;
+                return success(serde_json::json!({ "token": format!("tok-{count}") }));
+            }
+
+            // Only the second token is valid
+            match request.authorization.as_deref() {
+                Some("Bearer tok-2") => success(sample_project()),
+                _ => StandInResponse::json(
+                    401,
+                    serde_json::json!({ "type": "error", "message": "Token expired" }).to_string(),
+                ),
+            }
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+    let project = client.get_project(120).await.unwrap();
+
+    assert_eq!(project.id, 120);
+    assert_eq!(logins.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_error_response() {
+    let (url, _rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|request: &StandInRequest| {
+            login(request).unwrap_or_else(|| match request.path.as_str() {
+                "/api/projek/404" => StandInResponse::json(
+                    404,
+                    serde_json::json!({ "type": "error", "message": "Not found" }).to_string(),
+                ),
+                _ => StandInResponse::json(
+                    422,
+                    serde_json::json!({ "type": "error", "message": "Anime tidak ditemukan" })
+                        .to_string(),
+                ),
+            })
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+
+    let not_found = client.get_project(404).await.unwrap_err();
+    assert!(matches!(not_found, FansubDBError::NotFound(endpoint) if endpoint == "/projek/404"));
+
+    let invalid = client
+        .create_project(&FansubDBProjectCreate::new(1, 7))
+        .await
+        .unwrap_err();
+    match invalid {
+        FansubDBError::Response(status, message) => {
+            assert_eq!(status.as_u16(), 422);
+            assert_eq!(message, "Anime tidak ditemukan");
+        }
+        other => panic!("Unexpected error: {other}"),
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_credentials() {
+    let (url, _rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|_: &StandInRequest| {
+            StandInResponse::json(
+                400,
+                serde_json::json!({ "type": "error", "message": "Email atau password salah" })
+                    .to_string(),
+            )
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "wrong").with_base_url(url);
+    let err = client.get_fansub_projects(7).await.unwrap_err();
+
+    assert!(
+        matches!(err, FansubDBError::Unauthorized(message) if message == "Email atau password salah")
+    );
+}
+
+#[test]
+fn test_status_from_release() {
+    assert_eq!(
+        FansubDBProjectStatus::from_release(0, 12),
+        FansubDBProjectStatus::Tentative
+    );
+    assert_eq!(
+        FansubDBProjectStatus::from_release(5, 12),
+        FansubDBProjectStatus::Ongoing
+    );
+    assert_eq!(
+        FansubDBProjectStatus::from_release(12, 12),
+        FansubDBProjectStatus::Finished
+    );
+    // Unknown total episodes is never finished
+    assert_eq!(
+        FansubDBProjectStatus::from_release(30, 0),
+        FansubDBProjectStatus::Ongoing
+    );
+}
+
+/// A project with `total` episodes where the first `released` episodes are finished
+fn sample_local_project(released: u64, total: u64) -> Project {
+    let mut project = Project::new(
+        "Sousou no Frieren",
+        ProjectType::Series,
+        showtimes_shared::ulid::Ulid::new(),
+    )
+    .unwrap();
+    project.progress = (1..=total)
+        .map(|number| EpisodeProgress::new(number, number <= released))
+        .collect();
+    project.add_integration(IntegrationId::new("120", IntegrationType::FansubDBProject));
+    project
+}
+
+#[test]
+fn test_release_state_from_project() {
+    let state = FansubDBReleaseState::from_project(&sample_local_project(3, 12)).unwrap();
+    assert_eq!(state.id, 120);
+    assert_eq!(state.status, FansubDBProjectStatus::Ongoing);
+    assert_eq!(state.progress, 3);
+
+    let state = FansubDBReleaseState::from_project(&sample_local_project(12, 12)).unwrap();
+    assert_eq!(state.status, FansubDBProjectStatus::Finished);
+
+    // Archived before everything is released means dropped
+    let mut archived = sample_local_project(5, 12);
+    archived.status = ProjectStatus::Archived;
+    let state = FansubDBReleaseState::from_project(&archived).unwrap();
+    assert_eq!(state.status, FansubDBProjectStatus::Dropped);
+    assert_eq!(state.progress, 5);
+
+    let mut missing = sample_local_project(1, 12);
+    missing.integrations.clear();
+    assert!(matches!(
+        FansubDBReleaseState::from_project(&missing),
+        Err(FansubDBSyncError::MissingIntegration(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sync_queue_failure() {
+    let available = Arc::new(AtomicBool::new(false));
+    let responder_available = Arc::clone(&available);
+    let (url, _rx) = spawn_stand_in(
+        "/api",
+        Arc::new(move |request: &StandInRequest| {
+            login(request).unwrap_or_else(|| {
+                if responder_available.load(Ordering::SeqCst) {
+                    success(serde_json::Value::Null)
+                } else {
+                    StandInResponse::json(503, "Service Unavailable")
+                }
+            })
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+    let sync = FansubDBSync::new(client);
+    let project = sample_local_project(1, 12);
+
+    // Failure is queued instead of propagated
+    sync.process(&project).await;
+    assert_eq!(sync.pending().await, vec![project.id]);
+
+    available.store(true, Ordering::SeqCst);
+    sync.process(&project).await;
+    assert!(sync.pending().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_drop_rejected() {
+    let (url, _rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|request: &StandInRequest| {
+            login(request).unwrap_or_else(|| {
+                StandInResponse::json(
+                    422,
+                    serde_json::json!({ "type": "error", "message": "Status tidak valid" })
+                        .to_string(),
+                )
+            })
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+    let sync = FansubDBSync::new(client);
+
+    // Rejected payload will never succeed, so it should not be retried
+    sync.process(&sample_local_project(1, 12)).await;
+    assert!(sync.pending().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_reconcile() {
+    let (url, mut rx) = spawn_stand_in(
+        "/api",
+        Arc::new(|request: &StandInRequest| {
+            login(request).unwrap_or_else(|| match request.method.as_str() {
+                "GET" => success(sample_project()),
+                _ => success(serde_json::Value::Null),
+            })
+        }),
+    )
+    .await;
+
+    let client = FansubDBClient::new("user@example.com", "secret").with_base_url(url);
+    let sync = FansubDBSync::new(client);
+
+    // Remote is already at 3 released episodes
+    let result = sync.reconcile(&sample_local_project(3, 12)).await.unwrap();
+    assert!(!result.changed);
+
+    let result = sync.reconcile(&sample_local_project(4, 12)).await.unwrap();
+    assert!(result.changed);
+    assert_eq!(result.state.progress, 4);
+
+    let mut methods = vec![];
+    while let Ok(request) = rx.try_recv() {
+        methods.push(request.method);
+    }
+    assert_eq!(methods, vec!["POST", "GET", "GET", "PUT"]);
+}
@@ -157,13 +157,29 @@ impl Loader<showtimes_shared::APIKey> for UserDataLoader {
 /// A data loader for the project model
 pub struct ProjectDataLoader {
     col: showtimes_db::ProjectHandler,
+    allowed_servers: Vec<Ulid>,
 }
 
 impl ProjectDataLoader {
     /// Create a new user data loader
     pub fn new(col: &DatabaseShared) -> Self {
         let col = showtimes_db::ProjectHandler::new(col);
-        ProjectDataLoader { col }
+        ProjectDataLoader {
+            col,
+            allowed_servers: Vec::new(),
+        }
+    }
+
+    /// Only load projects that belong to the following servers
+    ///
+    /// Used for API keys that are limited to specific servers.
+    pub fn with_allowed_servers(mut self, servers: Vec<Ulid>) -> Self {
+        self.allowed_servers = servers;
+        self
+    }
+
+    fn is_allowed(&self, project: &showtimes_db::m::Project) -> bool {
+        self.allowed_servers.is_empty() || self.allowed_servers.contains(&project.creator)
     }
 }
 
@@ -197,8 +213,10 @@ impl Loader<ServerOwnerId> for ProjectDataLoader {
                 e.set("where_req", GQLDataLoaderWhere::ProjectLoaderOwnerId);
             })?;
 
-        let mapped_res: HashMap<ServerOwnerId, Vec<showtimes_db::m::Project>> =
-            all_results.iter().fold(HashMap::new(), |mut acc, item| {
+        let mapped_res: HashMap<ServerOwnerId, Vec<showtimes_db::m::Project>> = all_results
+            .iter()
+            .filter(|item| self.is_allowed(item))
+            .fold(HashMap::new(), |mut acc, item| {
                 acc.entry(ServerOwnerId::new(item.creator))
                     .or_default()
                     .push(item.clone());
@@ -238,6 +256,7 @@ impl Loader<Ulid> for ProjectDataLoader {
 
         let mapped_res: HashMap<Ulid, showtimes_db::m::Project> = all_results
             .iter()
+            .filter(|proj| self.is_allowed(proj))
             .map(|proj| (proj.id, proj.clone()))
             .collect();
 
@@ -281,13 +300,29 @@ impl ServerAndOwnerId {
 /// A data loader for the server model
 pub struct ServerDataLoader {
     col: showtimes_db::ServerHandler,
+    allowed_servers: Vec<Ulid>,
 }
 
 impl ServerDataLoader {
     /// Create a new user data loader
     pub fn new(col: &DatabaseShared) -> Self {
         let col = showtimes_db::ServerHandler::new(col);
-        ServerDataLoader { col }
+        ServerDataLoader {
+            col,
+            allowed_servers: Vec::new(),
+        }
+    }
+
+    /// Only load the following servers
+    ///
+    /// Used for API keys that are limited to specific servers.
+    pub fn with_allowed_servers(mut self, servers: Vec<Ulid>) -> Self {
+        self.allowed_servers = servers;
+        self
+    }
+
+    fn is_allowed(&self, server: &showtimes_db::m::Server) -> bool {
+        self.allowed_servers.is_empty() || self.allowed_servers.contains(&server.id)
     }
 }
 
@@ -319,8 +354,11 @@ impl Loader<Ulid> for ServerDataLoader {
                 e.set("where_req", GQLDataLoaderWhere::ServerLoaderId);
             })?;
 
-        let mapped_res: HashMap<Ulid, showtimes_db::m::Server> =
-            all_results.iter().map(|u| (u.id, u.clone())).collect();
+        let mapped_res: HashMap<Ulid, showtimes_db::m::Server> = all_results
+            .iter()
+            .filter(|u| self.is_allowed(u))
+            .map(|u| (u.id, u.clone()))
+            .collect();
 
         Ok(mapped_res)
     }
@@ -335,13 +373,14 @@ impl Loader<ServerOwnerId> for ServerDataLoader {
         keys: &[ServerOwnerId],
     ) -> Result<HashMap<ServerOwnerId, Self::Value>, Self::Error> {
         let keys_to_string = keys.iter().map(|k| (*k).to_string()).collect::<Vec<_>>();
+        // Do not limit the result to the number of keys, an owner can have
+        // more than one server and would get the list truncated.
         let result = self
             .col
             .get_collection()
             .find(doc! {
                 "owners.id": { "$in": keys_to_string.clone() }
             })
-            .limit(keys.len() as i64)
             .await
             .extend_error(GQLErrorCode::ServerRequestFails, |e| {
                 e.set("ids", keys_to_string.clone());
@@ -357,8 +396,10 @@ impl Loader<ServerOwnerId> for ServerDataLoader {
                 e.set("where_req", GQLDataLoaderWhere::ServerLoaderOwnerId);
             })?;
 
-        let mapped_res: HashMap<ServerOwnerId, Vec<showtimes_db::m::Server>> =
-            all_results.iter().fold(HashMap::new(), |mut acc, item| {
+        let mapped_res: HashMap<ServerOwnerId, Vec<showtimes_db::m::Server>> = all_results
+            .iter()
+            .filter(|item| self.is_allowed(item))
+            .fold(HashMap::new(), |mut acc, item| {
                 item.owners.iter().for_each(|o| {
                     acc.entry(ServerOwnerId::new(o.id))
                         .or_default()
@@ -409,8 +450,10 @@ impl Loader<ServerAndOwnerId> for ServerDataLoader {
                 e.set("where_req", GQLDataLoaderWhere::ServerLoaderIdOrOwnerId);
             })?;
 
-        let mapped_res: HashMap<ServerAndOwnerId, showtimes_db::m::Server> =
-            all_results.iter().fold(HashMap::new(), |mut acc, item| {
+        let mapped_res: HashMap<ServerAndOwnerId, showtimes_db::m::Server> = all_results
+            .iter()
+            .filter(|item| self.is_allowed(item))
+            .fold(HashMap::new(), |mut acc, item| {
                 item.owners.iter().for_each(|o| {
                     acc.entry(ServerAndOwnerId::new(item.id, o.id))
                         .or_insert(item.clone());
@@ -919,3 +962,132 @@ impl Loader<ServerPremiumServer> for ServerPremiumLoader {
         Ok(mapped_res)
     }
 }
+
+/// A data loader for the webhook model
+pub struct WebhookLoader {
+    col: showtimes_db::WebhookHandler,
+}
+
+impl WebhookLoader {
+    /// Create a new webhook data loader
+    pub fn new(col: &DatabaseShared) -> Self {
+        let col = showtimes_db::WebhookHandler::new(col);
+        WebhookLoader { col }
+    }
+
+    /// Get the collection handler
+    pub fn get_inner(&self) -> &showtimes_db::WebhookHandler {
+        &self.col
+    }
+}
+
+impl Loader<Ulid> for WebhookLoader {
+    type Value = showtimes_db::m::Webhook;
+    type Error = FieldError;
+
+    async fn load(&self, keys: &[Ulid]) -> Result<HashMap<Ulid, Self::Value>, Self::Error> {
+        let keys_to_string = keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        let result = self
+            .col
+            .get_collection()
+            .find(doc! {
+                "id": { "$in": keys_to_string.clone() }
+            })
+            .await
+            .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+                e.set("ids", keys_to_string.clone());
+                e.set("where", GQLDataLoaderWhere::WebhookLoaderId);
+            })?;
+
+        let all_results = result
+            .try_collect::<Vec<showtimes_db::m::Webhook>>()
+            .await
+            .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+                e.set("ids", keys_to_string.clone());
+                e.set("where", GQLDataLoaderWhere::WebhookLoaderCollect);
+                e.set("where_req", GQLDataLoaderWhere::WebhookLoaderId);
+            })?;
+
+        let mapped_res: HashMap<Ulid, showtimes_db::m::Webhook> = all_results
+            .iter()
+            .map(|hook| (hook.id, hook.clone()))
+            .collect();
+
+        Ok(mapped_res)
+    }
+}
+
+/// A data loader server key for the webhook
+///
+/// Based on the server ULID
+#[derive(Clone)]
+pub struct WebhookServer(Ulid);
+
+impl std::hash::Hash for WebhookServer {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl std::cmp::PartialEq for WebhookServer {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl std::cmp::Eq for WebhookServer {}
+
+impl Deref for WebhookServer {
+    type Target = Ulid;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Ulid> for WebhookServer {
+    fn from(id: Ulid) -> Self {
+        WebhookServer(id)
+    }
+}
+
+impl Loader<WebhookServer> for WebhookLoader {
+    type Value = Vec<showtimes_db::m::Webhook>;
+    type Error = FieldError;
+
+    async fn load(
+        &self,
+        keys: &[WebhookServer],
+    ) -> Result<HashMap<WebhookServer, Self::Value>, Self::Error> {
+        let keys_to_string = keys.iter().map(|k| k.0.to_string()).collect::<Vec<_>>();
+        let result = self
+            .col
+            .get_collection()
+            .find(doc! {
+                "creator": { "$in": keys_to_string.clone() }
+            })
+            .await
+            .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+                e.set("creator", keys_to_string.clone());
+                e.set("where", GQLDataLoaderWhere::WebhookLoaderServerId);
+            })?;
+
+        let all_results: Vec<showtimes_db::m::Webhook> = result
+            .try_collect::<Vec<showtimes_db::m::Webhook>>()
+            .await
+            .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+                e.set("creator", keys_to_string.clone());
+                e.set("where", GQLDataLoaderWhere::WebhookLoaderCollect);
+                e.set("where_req", GQLDataLoaderWhere::WebhookLoaderServerId);
+            })?;
+
+        let mapped_res: HashMap<WebhookServer, Vec<showtimes_db::m::Webhook>> =
+            all_results.iter().fold(HashMap::new(), |mut acc, item| {
+                acc.entry(WebhookServer::from(item.creator))
+                    .or_default()
+                    .push(item.clone());
+                acc
+            });
+
+        Ok(mapped_res)
+    }
+}
@@ -90,6 +90,8 @@ pub enum GQLErrorCode {
     APIKeyMissingCapability = 133,
     /// API key is not allowed to be used
     APIKeyNotAllowed = 134,
+    /// API key is already expired
+    APIKeyExpired = 135,
     /// Internal server error
     InternalServerError = 140,
 
@@ -98,10 +100,14 @@ pub enum GQLErrorCode {
     EventAdvanceFailure = 200,
     /// Failed to advance or request next batch of RSS events
     EventRSSAdvanceFailure = 201,
+    /// Failed to advance or request next batch of webhook deliveries
+    EventWebhookAdvanceFailure = 202,
 
     // --> Task scheduler related
     /// Failed when requesting task scheduler
     TaskSchedulerError = 300,
+    /// Failed to queue the search index operations
+    SearchOutboxQueueError = 301,
 
     // --> Integration related
     /// Common integration error
@@ -130,6 +136,8 @@ pub enum GQLErrorCode {
     SessionCreateError = 601,
     /// Failed to delete session
     SessionDeleteError = 602,
+    /// Session not found or not owned by the user
+    SessionNotFound = 603,
     /// Failed to exchange login token with Discord
     SessionExchangeError = 610,
     /// Failed to get user info from Discord
@@ -190,6 +198,8 @@ pub enum GQLErrorCode {
     ServerDeleteError = 2014,
     /// Failed to delete server in search database
     ServerDeleteSearchError = 2015,
+    /// Failed to query server from search database
+    ServerQuerySearchError = 2016,
     /// Server fetch disabled
     ServerFetchDisabled = 2020,
 
@@ -235,6 +245,8 @@ pub enum GQLErrorCode {
     ProjectDeleteError = 3014,
     /// Failed to delete project in search database
     ProjectDeleteSearchError = 3015,
+    /// Failed to query project from search database
+    ProjectQuerySearchError = 3016,
     /// Project fetch disabled
     ProjectFetchDisabled = 3020,
     /// Project init failed
@@ -343,6 +355,44 @@ pub enum GQLErrorCode {
     RSSFeedInvalidURL = 7023,
     /// RSS feed limit reached
     RSSFeedLimitReached = 7024,
+    /// RSS feed filter is invalid
+    RSSFeedInvalidFilter = 7025,
+    /// RSS feed Discord webhook is invalid
+    RSSFeedInvalidWebhook = 7026,
+
+    // -> Webhook related
+    /// Failed when requesting webhook
+    WebhookRequestFails = 8000,
+    /// Webhook not found in database
+    WebhookNotFound = 8001,
+    /// Webhook already exists
+    WebhookAlreadyExists = 8002,
+    /// Failed to create webhook
+    WebhookCreateError = 8010,
+    /// Failed to update webhook
+    WebhookUpdateError = 8011,
+    /// Failed to delete webhook
+    WebhookDeleteError = 8012,
+    /// Webhook URL is invalid
+    WebhookInvalidURL = 8020,
+    /// Webhook actions is empty or invalid
+    WebhookInvalidActions = 8021,
+    /// Failed to deliver the webhook
+    WebhookDeliveryError = 8022,
+    /// Webhook locale is not supported
+    WebhookInvalidLocale = 8023,
+    /// Webhook target does not support the operation
+    WebhookInvalidTarget = 8024,
+    /// Webhook filter is invalid
+    WebhookInvalidFilter = 8025,
+
+    // -> FansubDB related
+    /// Failed when requesting FansubDB
+    FansubDBRequestFails = 9000,
+    /// FansubDB integration is not configured on this instance
+    FansubDBNotConfigured = 9001,
+    /// Project FansubDB integration is missing or invalid
+    FansubDBInvalidIntegration = 9002,
 }
 
 impl GQLErrorCode {
@@ -390,6 +440,8 @@ pub enum GQLDataLoaderWhere {
     ProjectLoaderPaginated,
     /// Server loader paginated count queries
     ProjectLoaderPaginatedCount,
+    /// Project loader schedule queries
+    ProjectLoaderSchedule,
     /// Server collab loader (ULID ID)
     ServerSyncLoaderId,
     /// Server collab loader (Server ID)
@@ -414,6 +466,12 @@ pub enum GQLDataLoaderWhere {
     ServerPremiumLoaderServerId,
     /// Server premium loader db collection
     ServerPremiumLoaderCollect,
+    /// Webhook loader (ULID ID)
+    WebhookLoaderId,
+    /// Webhook loader (Server ID)
+    WebhookLoaderServerId,
+    /// Webhook loader db collection
+    WebhookLoaderCollect,
 }
 
 impl From<GQLErrorCode> for async_graphql::Value {
@@ -109,6 +109,20 @@ impl Guard for AuthAPIKeyMinimumGuard {
                         })
                     })?;
 
+                // Expired keys are rejected on authentication, this only catches
+                // keys that expired during a long-lived websocket connection.
+                if match_key.is_expired() {
+                    return GQLError::new("API key is expired", GQLErrorCode::APIKeyExpired)
+                        .extend(|e| {
+                            e.set("user", user.id.to_string());
+                            e.set("key", parse_api.to_string());
+                            if let Some(expires_at) = match_key.expires_at {
+                                e.set("expires_at", expires_at.to_string());
+                            }
+                        })
+                        .into();
+                }
+
                 match self.permissions {
                     APIKeyVerify::Any(capabilities) => {
                         if match_key.can_any(capabilities) {
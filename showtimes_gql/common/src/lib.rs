@@ -445,6 +445,8 @@ pub enum APIKeyCapabilityGQL {
     #[graphql(name = "MANAGE_RSS")]
     #[enum_name(rename = "MANAGE_RSS")]
     ManageRSS,
+    /// A combination of create, update, delete, and test for webhooks
+    ManageWebhooks,
     /// A combination of update for users
     ManageUsers,
     /// A delete capability for servers
@@ -463,6 +465,8 @@ pub enum APIKeyCapabilityGQL {
     QueryStats,
     /// Query for search data
     QuerySearch,
+    /// Subscribe to events of the servers the user belongs to
+    QueryEvents,
 }
 
 /// A metadata collection to hold integration information with other platform
@@ -493,7 +497,7 @@ impl From<&showtimes_db::m::IntegrationId> for IntegrationIdGQL {
 }
 
 /// A page information for pagination
-#[derive(SimpleObject, Clone, Copy)]
+#[derive(SimpleObject, Clone)]
 pub struct PageInfoGQL {
     /// The total number of pages
     total: u64,
@@ -503,6 +507,11 @@ pub struct PageInfoGQL {
     /// Next cursor to get the next page
     #[graphql(name = "nextCursor")]
     next_cursor: Option<UlidGQL>,
+    /// Next keyset cursor to get the next page, this also encodes the sort key.
+    ///
+    /// Only available on lists that support keyset pagination.
+    #[graphql(name = "nextKeyset")]
+    next_keyset: Option<String>,
 }
 
 impl PageInfoGQL {
@@ -512,15 +521,23 @@ impl PageInfoGQL {
             total,
             per_page,
             next_cursor,
+            next_keyset: None,
         }
     }
 
+    /// Set the next keyset cursor
+    pub fn with_next_keyset(mut self, next_keyset: impl Into<Option<String>>) -> Self {
+        self.next_keyset = next_keyset.into();
+        self
+    }
+
     /// Empty PageInfoGQL
     pub fn empty(per_page: u32) -> Self {
         PageInfoGQL {
             total: 0,
             per_page,
             next_cursor: None,
+            next_keyset: None,
         }
     }
 }
@@ -552,6 +569,14 @@ pub enum SortOrderGQL {
     UpdatedAtAsc,
     /// Sort by Updated At (Descending)
     UpdatedAtDesc,
+    /// Sort by the next unreleased episode air date (Ascending)
+    ///
+    /// Only supported for projects, other lists will sort by ID instead.
+    NextAiredAsc,
+    /// Sort by the next unreleased episode air date (Descending)
+    ///
+    /// Only supported for projects, other lists will sort by ID instead.
+    NextAiredDesc,
 }
 
 impl SortOrderGQL {
@@ -598,6 +623,14 @@ impl SortOrderGQL {
             (SortOrderGQL::UpdatedAtDesc, _) => {
                 doc! { "updated": -1 }
             }
+            (SortOrderGQL::NextAiredAsc, _) => {
+                // Fallback to ID
+                doc! { "id": 1 }
+            }
+            (SortOrderGQL::NextAiredDesc, _) => {
+                // Fallback to ID
+                doc! { "id": -1 }
+            }
         }
     }
 }
@@ -1,12 +1,18 @@
 //! A collection of event executor functions for querying events from ClickHouse
 
-use async_graphql::OutputType;
+use async_graphql::{OutputType, dataloader::DataLoader};
 use serde::{Serialize, de::DeserializeOwned};
-use showtimes_gql_common::{GQLErrorCode, UlidGQL, errors::GQLError, queries::ServerQueryUser};
+use showtimes_gql_common::{
+    GQLErrorCode, UlidGQL,
+    data_loader::{ServerDataLoader, WebhookLoader},
+    errors::GQLError,
+    queries::ServerQueryUser,
+};
 
 use showtimes_gql_events_models::{
     prelude::{EventGQL, QueryNew},
     rss::RSSEventGQL,
+    webhooks::WebhookDeliveryEventGQL,
 };
 
 pub(crate) async fn query_events<O, T>(
@@ -129,3 +135,79 @@ pub(crate) async fn query_rss_events(
 
     Ok(results)
 }
+
+pub(crate) async fn query_webhook_deliveries(
+    ctx: &async_graphql::Context<'_>,
+    webhook_id: UlidGQL,
+    id: Option<UlidGQL>,
+) -> async_graphql::Result<Vec<WebhookDeliveryEventGQL>> {
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let webhook_loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+    let webhook = webhook_loader.load_one(*webhook_id).await?.ok_or_else(|| {
+        GQLError::new("Webhook not found", GQLErrorCode::WebhookNotFound)
+            .extend(|e| e.set("id", webhook_id.to_string()))
+    })?;
+
+    // Only server admins can see the delivery log
+    if user.kind == showtimes_db::m::UserKind::User {
+        let srv_loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+        let srv = srv_loader.load_one(webhook.creator).await?.ok_or_else(|| {
+            GQLError::new("Server not found", GQLErrorCode::ServerNotFound)
+                .extend(|e| e.set("id", webhook.creator.to_string()))
+        })?;
+
+        let allowed = srv
+            .owners
+            .iter()
+            .find(|o| o.id == user.id)
+            .is_some_and(|o| o.privilege >= showtimes_db::m::UserPrivilege::Admin);
+
+        if !allowed {
+            return GQLError::new(
+                "User not allowed to view webhook deliveries",
+                GQLErrorCode::UserInsufficientPrivilege,
+            )
+            .extend(|e| {
+                e.set("id", webhook_id.to_string());
+                e.set("server", webhook.creator.to_string());
+                e.set(
+                    "required",
+                    showtimes_db::m::UserPrivilege::Admin.to_string(),
+                );
+            })
+            .into();
+        }
+    }
+
+    let query_stream = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
+
+    let mut stream = query_stream.query_webhook_deliveries(*webhook_id);
+    if let Some(id) = id {
+        stream = stream.start_after(*id);
+    }
+    let mut results: Vec<WebhookDeliveryEventGQL> = Vec::new();
+
+    while !stream.is_exhausted() {
+        let event_batch = stream.advance().await.map_err(|err| {
+            GQLError::new(
+                format!(
+                    "Failed querying data from webhook deliveries stream: {}",
+                    webhook_id.to_string()
+                ),
+                GQLErrorCode::EventWebhookAdvanceFailure,
+            )
+            .extend(|e| {
+                if let Some(id) = id {
+                    e.set("id", id.to_string());
+                }
+                e.set("webhook_id", webhook_id.to_string());
+                e.set("original", format!("{err}"));
+            })
+        })?;
+
+        results.extend(event_batch.into_iter().map(WebhookDeliveryEventGQL::from));
+    }
+
+    Ok(results)
+}
@@ -13,16 +13,17 @@ use showtimes_gql_events_models::projects::{
     ProjectCreatedEventDataGQL, ProjectDeletedEventDataGQL, ProjectEpisodeUpdatedEventDataGQL,
     ProjectUpdatedEventDataGQL,
 };
-use showtimes_gql_events_models::rss::RSSEventGQL;
+use showtimes_gql_events_models::rss::{RSSEventGQL, RSSFeedDisabledEventDataGQL};
 use showtimes_gql_events_models::servers::{
     ServerCreatedEventDataGQL, ServerDeletedEventDataGQL, ServerUpdatedEventDataGQL,
 };
 use showtimes_gql_events_models::users::{
     UserCreatedEventDataGQL, UserDeletedEventDataGQL, UserUpdatedEventDataGQL,
 };
+use showtimes_gql_events_models::webhooks::{WebhookDeliveryEventGQL, WebhookDisabledEventDataGQL};
 
 mod executor;
-use executor::{query_events, query_events_with_user, query_rss_events};
+use executor::{query_events, query_events_with_user, query_rss_events, query_webhook_deliveries};
 
 /// The root query for events queries.
 ///
@@ -318,4 +319,57 @@ impl QueryEventsRoot {
     ) -> async_graphql::Result<Vec<RSSEventGQL>> {
         query_rss_events(ctx, feed_id, id).await
     }
+
+    /// The webhook disabled event, emitted when a webhook is disabled after too many failures.
+    #[graphql(
+        name = "webhookDisabled",
+        guard = "AuthAPIKeyMinimumGuard::new(APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn webhook_disabled(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(desc = "The starting ID to query")] id: showtimes_gql_common::UlidGQL,
+    ) -> async_graphql::Result<Vec<EventGQL<WebhookDisabledEventDataGQL>>> {
+        query_events::<showtimes_events::m::WebhookDisabledEvent, WebhookDisabledEventDataGQL>(
+            ctx,
+            id,
+            showtimes_events::m::EventKind::WebhookDisabled,
+        )
+        .await
+    }
+
+    /// The RSS feed disabled event, emitted when a feed is disabled after too many failures.
+    #[graphql(
+        name = "rssFeedDisabled",
+        guard = "AuthAPIKeyMinimumGuard::new(APIKeyVerify::Specific(APIKeyCapability::ManageRSS))"
+    )]
+    async fn rss_feed_disabled(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(desc = "The starting ID to query")] id: showtimes_gql_common::UlidGQL,
+    ) -> async_graphql::Result<Vec<EventGQL<RSSFeedDisabledEventDataGQL>>> {
+        query_events::<showtimes_events::m::RSSFeedDisabledEvent, RSSFeedDisabledEventDataGQL>(
+            ctx,
+            id,
+            showtimes_events::m::EventKind::RSSFeedDisabled,
+        )
+        .await
+    }
+
+    /// The delivery history of a webhook, every attempt including retries is recorded.
+    #[graphql(
+        name = "webhookDeliveries",
+        guard = "AuthAPIKeyMinimumGuard::new(APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn webhook_deliveries(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(desc = "The webhook ID to query")] webhook_id: showtimes_gql_common::UlidGQL,
+        #[graphql(
+            desc = "The starting ID to query, when not provided will start from the beginning"
+        )]
+        id: Option<showtimes_gql_common::UlidGQL>,
+    ) -> async_graphql::Result<Vec<WebhookDeliveryEventGQL>> {
+        query_webhook_deliveries(ctx, webhook_id, id).await
+    }
 }
@@ -7,3 +7,4 @@ pub mod projects;
 pub mod rss;
 pub mod servers;
 pub mod users;
+pub mod webhooks;
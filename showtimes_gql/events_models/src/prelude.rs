@@ -13,8 +13,10 @@ use super::{
         ProjectCreatedEventDataGQL, ProjectDeletedEventDataGQL, ProjectEpisodeUpdatedEventDataGQL,
         ProjectUpdatedEventDataGQL,
     },
+    rss::RSSFeedDisabledEventDataGQL,
     servers::{ServerCreatedEventDataGQL, ServerDeletedEventDataGQL, ServerUpdatedEventDataGQL},
     users::{UserCreatedEventDataGQL, UserDeletedEventDataGQL, UserUpdatedEventDataGQL},
+    webhooks::WebhookDisabledEventDataGQL,
 };
 
 /// Implement the [`QueryNew`] trait for a type
@@ -64,6 +66,11 @@ pub enum EventKindGQL {
     CollaborationDeleted = 43,
     /// Collaboration retracted event, used when the initiator cancels
     CollaborationRetracted = 44,
+    /// Webhook disabled event, used when a webhook failed too many times
+    WebhookDisabled = 50,
+    /// RSS feed disabled event, used when a feed failed too many times
+    #[graphql(name = "RSS_FEED_DISABLED")]
+    RSSFeedDisabled = 60,
 }
 
 /// The event structure that is broadcasted and stored
@@ -86,11 +93,20 @@ pub enum EventKindGQL {
 #[graphql(concrete(name = "CollabRejectedEventGQL", params(CollabRejectedEventDataGQL)))]
 #[graphql(concrete(name = "CollabRetractedEventGQL", params(CollabRetractedEventDataGQL)))]
 #[graphql(concrete(name = "CollabDeletedEventGQL", params(CollabDeletedEventDataGQL)))]
+#[graphql(concrete(name = "WebhookDisabledEventGQL", params(WebhookDisabledEventDataGQL)))]
+#[graphql(concrete(name = "RSSFeedDisabledEventGQL", params(RSSFeedDisabledEventDataGQL)))]
 pub struct EventGQL<T: OutputType> {
     /// The event ID
+    ///
+    /// On a gap marker, this is the ID the subscription resumed from
     id: UlidGQL,
     /// The event data information
-    data: T,
+    ///
+    /// This is `null` on a gap marker
+    data: Option<T>,
+    /// A synthetic marker sent by the subscriptions when some events after the
+    /// requested ID are no longer available and might have been missed
+    gap: bool,
     /// The event kind information
     kind: EventKindGQL,
     /// The actor or the person who initiated the event
@@ -123,10 +139,26 @@ where
     ) -> Self {
         Self {
             id: UlidGQL::from(id),
-            data,
+            data: Some(data),
+            gap: false,
             kind,
             actor,
             timestamp: DateTimeGQL::from(timestamp),
         }
     }
+
+    /// Creates a gap marker, used when some events after `id` might have been missed
+    ///
+    /// * `id`: The ULID the subscription resumed from
+    /// * `kind`: The kind of event that is being watched
+    pub fn gap(id: showtimes_shared::ulid::Ulid, kind: EventKindGQL) -> Self {
+        Self {
+            id: UlidGQL::from(id),
+            data: None,
+            gap: true,
+            kind,
+            actor: None,
+            timestamp: DateTimeGQL::from(jiff::Timestamp::now()),
+        }
+    }
 }
@@ -5,6 +5,7 @@ use async_graphql::{Enum, Object, SimpleObject, dataloader::DataLoader};
 use errors::GQLError;
 use showtimes_gql_common::{
     data_loader::{ProjectDataLoader, UserDataLoader},
+    queries::ServerQueryUser,
     *,
 };
 use showtimes_gql_models::{
@@ -12,6 +13,8 @@ use showtimes_gql_models::{
     users::UserGQL,
 };
 
+use crate::prelude::QueryNew;
+
 /// A project created event
 pub struct ProjectCreatedEventDataGQL {
     id: showtimes_shared::ulid::Ulid,
@@ -432,3 +435,29 @@ impl From<&showtimes_events::m::ProjectEpisodeUpdatedEvent> for ProjectEpisodeUp
         }
     }
 }
+
+impl QueryNew<showtimes_events::m::ProjectCreatedEvent> for ProjectCreatedEventDataGQL {
+    fn new(data: &showtimes_events::m::ProjectCreatedEvent, _user: ServerQueryUser) -> Self {
+        Self::from(data)
+    }
+}
+
+impl QueryNew<showtimes_events::m::ProjectUpdatedEvent> for ProjectUpdatedEventDataGQL {
+    fn new(data: &showtimes_events::m::ProjectUpdatedEvent, _user: ServerQueryUser) -> Self {
+        Self::from(data)
+    }
+}
+
+impl QueryNew<showtimes_events::m::ProjectEpisodeUpdatedEvent>
+    for ProjectEpisodeUpdatedEventDataGQL
+{
+    fn new(data: &showtimes_events::m::ProjectEpisodeUpdatedEvent, _user: ServerQueryUser) -> Self {
+        Self::from(data)
+    }
+}
+
+impl QueryNew<showtimes_events::m::ProjectDeletedEvent> for ProjectDeletedEventDataGQL {
+    fn new(data: &showtimes_events::m::ProjectDeletedEvent, _user: ServerQueryUser) -> Self {
+        Self::from(data)
+    }
+}
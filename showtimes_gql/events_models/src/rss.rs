@@ -5,9 +5,10 @@ use std::collections::BTreeMap;
 use async_graphql::{Object, SimpleObject};
 use showtimes_db::m::RSSFeedDisplay;
 use showtimes_gql_common::{
-    DataLoader, DateTimeGQL, GQLErrorCode, GQLErrorExt, IntegrationIdGQL, UlidGQL,
-    data_loader::RSSFeedLoader, errors::GQLError,
+    DataLoader, DateTimeGQL, GQLErrorCode, IntegrationIdGQL, UlidGQL, data_loader::RSSFeedLoader,
+    errors::GQLError,
 };
+use showtimes_gql_models::rss::RSSFeedGQL;
 use showtimes_rss::FeedEntryCloned;
 
 /// A rendered text data for an RSS entry
@@ -81,12 +82,15 @@ pub struct RSSEventGQL {
     server_id: UlidGQL,
     hash: String,
     entry: FeedEntryCloned,
+    gap: bool,
     timestamp: DateTimeGQL,
 }
 
 #[Object(name = "RSSEventGQL")]
 impl RSSEventGQL {
     /// The ID of the event
+    ///
+    /// On a gap marker, this is the ID the subscription resumed from
     async fn id(&self) -> UlidGQL {
         self.id
     }
@@ -106,16 +110,28 @@ impl RSSEventGQL {
         self.hash.clone()
     }
 
+    /// A synthetic marker sent by the subscriptions when some entries after the
+    /// requested ID are no longer available and might have been missed
+    async fn gap(&self) -> bool {
+        self.gap
+    }
+
     /// The timestamp of the entry or the event if it is not timestamped
     async fn timestamp(&self) -> DateTimeGQL {
         self.timestamp
     }
 
     /// The rendered message of the event with integrations information
+    ///
+    /// This is `null` on a gap marker
     async fn rendered(
         &self,
         ctx: &async_graphql::Context<'_>,
-    ) -> async_graphql::Result<RSSEventRenderedGQL> {
+    ) -> async_graphql::Result<Option<RSSEventRenderedGQL>> {
+        if self.gap {
+            return Ok(None);
+        }
+
         let loader = ctx.data_unchecked::<DataLoader<RSSFeedLoader>>();
 
         let item = loader.load_one(*self.feed_id).await?.ok_or_else(|| {
@@ -123,7 +139,30 @@ impl RSSEventGQL {
                 .extend(|e| e.set("id", self.feed_id.to_string()))
         })?;
 
-        RSSEventRenderedGQL::render(item, self.entry.clone())
+        RSSEventRenderedGQL::render(item, self.entry.clone()).map(Some)
+    }
+}
+
+impl RSSEventGQL {
+    /// Creates a gap marker, used when some entries after `id` might have been missed
+    ///
+    /// * `id`: The ULID the subscription resumed from
+    /// * `feed_id`: The RSS feed that is being watched
+    /// * `server_id`: The server of the RSS feed
+    pub fn gap(
+        id: showtimes_shared::ulid::Ulid,
+        feed_id: showtimes_shared::ulid::Ulid,
+        server_id: showtimes_shared::ulid::Ulid,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            feed_id: feed_id.into(),
+            server_id: server_id.into(),
+            hash: String::new(),
+            entry: FeedEntryCloned::default(),
+            gap: true,
+            timestamp: jiff::Timestamp::now().into(),
+        }
     }
 }
 
@@ -157,6 +196,7 @@ impl From<showtimes_events::m::RSSEvent> for RSSEventGQL {
             server_id: value.server_id().into(),
             hash: value.hash_key().to_string(),
             entry: value.entry().clone(),
+            gap: false,
             timestamp: value.timestamp().into(),
         }
     }
@@ -170,6 +210,7 @@ impl From<&showtimes_events::m::RSSEvent> for RSSEventGQL {
             server_id: value.server_id().into(),
             hash: value.hash_key().to_string(),
             entry: value.entry().clone(),
+            gap: false,
             timestamp: value.timestamp().into(),
         }
     }
@@ -181,13 +222,15 @@ fn render_single(
     entries: &BTreeMap<String, showtimes_rss::FeedValue>,
 ) -> async_graphql::Result<RSSEventFormatValueGQL> {
     let args = vec![];
-    let markdown = showtimes_rss::format_text(data, &args, entries).extend_error(
-        GQLErrorCode::RSSFeedRenderError,
-        |f| {
+    let markdown = showtimes_rss::format_text(data, &args, entries).map_err(|err| {
+        GQLError::new(err.to_string(), GQLErrorCode::RSSFeedRenderError).extend(|f| {
             f.set("template", data);
             f.set("on", when);
-        },
-    )?;
+            let (line, column) = err.position();
+            f.set("line", line);
+            f.set("column", column);
+        })
+    })?;
 
     let plain = showtimes_rss::markdown::markdown_to_text(&markdown);
     let html = showtimes_rss::markdown::markdown_to_html(&markdown);
@@ -275,3 +318,63 @@ pub fn render_feed_display_with_entry(
         embed: embed_display,
     })
 }
+
+/// A RSS feed disabled event
+pub struct RSSFeedDisabledEventDataGQL {
+    id: showtimes_shared::ulid::Ulid,
+    server: showtimes_shared::ulid::Ulid,
+    failures: u32,
+    reason: Option<String>,
+}
+
+#[Object]
+impl RSSFeedDisabledEventDataGQL {
+    /// The RSS feed ID
+    async fn id(&self) -> UlidGQL {
+        self.id.into()
+    }
+
+    /// The server ID of the RSS feed
+    async fn server_id(&self) -> UlidGQL {
+        self.server.into()
+    }
+
+    /// The RSS feed information
+    async fn feed(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<RSSFeedGQL> {
+        let loader = ctx.data_unchecked::<DataLoader<RSSFeedLoader>>();
+
+        let feed = loader.load_one(self.id).await?.ok_or_else(|| {
+            GQLError::new("RSS feed not found", GQLErrorCode::RSSFeedNotFound)
+                .extend(|e| e.set("id", self.id.to_string()))
+        })?;
+
+        Ok(RSSFeedGQL::from(feed))
+    }
+
+    /// The amount of consecutive failures when the RSS feed is disabled
+    async fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The last failure reason
+    async fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+impl From<showtimes_events::m::RSSFeedDisabledEvent> for RSSFeedDisabledEventDataGQL {
+    fn from(value: showtimes_events::m::RSSFeedDisabledEvent) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&showtimes_events::m::RSSFeedDisabledEvent> for RSSFeedDisabledEventDataGQL {
+    fn from(value: &showtimes_events::m::RSSFeedDisabledEvent) -> Self {
+        Self {
+            id: value.id(),
+            server: value.server(),
+            failures: value.failures(),
+            reason: value.reason().map(|r| r.to_string()),
+        }
+    }
+}
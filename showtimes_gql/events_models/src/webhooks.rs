@@ -0,0 +1,119 @@
+//! A webhook events and delivery log models
+
+use async_graphql::{Object, SimpleObject, dataloader::DataLoader};
+
+use errors::GQLError;
+use showtimes_gql_common::{data_loader::WebhookLoader, *};
+use showtimes_gql_models::webhooks::WebhookGQL;
+
+/// A single webhook delivery attempt
+#[derive(SimpleObject)]
+#[graphql(name = "WebhookDeliveryEventGQL")]
+pub struct WebhookDeliveryEventGQL {
+    /// The ID of the attempt
+    id: UlidGQL,
+    /// The webhook ID of the attempt
+    webhook_id: UlidGQL,
+    /// The server ID of the attempt
+    server_id: UlidGQL,
+    /// The delivered webhook action (e.g. `project-release`)
+    action: String,
+    /// The attempt number, starting from 1
+    attempt: u8,
+    /// The response status code, `null` if the request failed before getting a response
+    status: Option<u16>,
+    /// The latency of the request in milliseconds
+    latency: u32,
+    /// The truncated response body
+    body: String,
+    /// The error message if the attempt failed
+    error: Option<String>,
+    /// Whether the attempt is successful
+    success: bool,
+    /// The timestamp of the attempt
+    timestamp: DateTimeGQL,
+}
+
+impl From<showtimes_events::m::WebhookDeliveryEvent> for WebhookDeliveryEventGQL {
+    fn from(value: showtimes_events::m::WebhookDeliveryEvent) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&showtimes_events::m::WebhookDeliveryEvent> for WebhookDeliveryEventGQL {
+    fn from(value: &showtimes_events::m::WebhookDeliveryEvent) -> Self {
+        Self {
+            id: value.id().into(),
+            webhook_id: value.webhook_id().into(),
+            server_id: value.server_id().into(),
+            action: value.action().to_string(),
+            attempt: value.attempt(),
+            status: value.status(),
+            latency: value.latency(),
+            body: value.body().to_string(),
+            error: value.error().map(|e| e.to_string()),
+            success: value.is_success(),
+            timestamp: value.timestamp().into(),
+        }
+    }
+}
+
+/// A webhook disabled event
+pub struct WebhookDisabledEventDataGQL {
+    id: showtimes_shared::ulid::Ulid,
+    server: showtimes_shared::ulid::Ulid,
+    failures: u32,
+    reason: Option<String>,
+}
+
+#[Object]
+impl WebhookDisabledEventDataGQL {
+    /// The webhook's ID
+    async fn id(&self) -> UlidGQL {
+        self.id.into()
+    }
+
+    /// The server ID of the webhook
+    async fn server_id(&self) -> UlidGQL {
+        self.server.into()
+    }
+
+    /// The webhook information
+    async fn webhook(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<WebhookGQL> {
+        let loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+        let webhook = loader.load_one(self.id).await?.ok_or_else(|| {
+            GQLError::new("Webhook not found", GQLErrorCode::WebhookNotFound)
+                .extend(|e| e.set("id", self.id.to_string()))
+        })?;
+
+        Ok(WebhookGQL::from(webhook))
+    }
+
+    /// The amount of consecutive failures when the webhook is disabled
+    async fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The last failure reason
+    async fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+impl From<showtimes_events::m::WebhookDisabledEvent> for WebhookDisabledEventDataGQL {
+    fn from(value: showtimes_events::m::WebhookDisabledEvent) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&showtimes_events::m::WebhookDisabledEvent> for WebhookDisabledEventDataGQL {
+    fn from(value: &showtimes_events::m::WebhookDisabledEvent) -> Self {
+        Self {
+            id: value.id(),
+            server: value.server(),
+            failures: value.failures(),
+            reason: value.reason().map(|r| r.to_string()),
+        }
+    }
+}
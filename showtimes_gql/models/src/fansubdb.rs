@@ -0,0 +1,113 @@
+//! A FansubDB related models list
+
+use async_graphql::{Enum, SimpleObject};
+
+use super::projects::ProjectGQL;
+
+/// The result of importing a single FansubDB project
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq)]
+#[graphql(rename_items = "SCREAMING_SNAKE_CASE")]
+pub enum FansubDBImportStatusGQL {
+    /// The project has been created
+    Created,
+    /// The project will be created, only on dry-run
+    Planned,
+    /// The project already exists on the server
+    Exists,
+    /// The project cannot be matched to Anilist
+    Unmatched,
+    /// The project is skipped, dropped projects are not imported
+    Skipped,
+    /// The project failed to be created
+    Failed,
+}
+
+/// A single FansubDB project import information
+#[derive(SimpleObject)]
+pub struct FansubDBImportEntryGQL {
+    /// The FansubDB project ID
+    #[graphql(name = "fansubdbId")]
+    fansubdb_id: u64,
+    /// The anime title from FansubDB
+    title: String,
+    /// The matched Anilist ID
+    #[graphql(name = "anilistId")]
+    anilist_id: Option<i32>,
+    /// The import result
+    status: FansubDBImportStatusGQL,
+    /// The project that is created or will be created.
+    ///
+    /// On dry-run, the project is not saved and will have a temporary ID.
+    project: Option<ProjectGQL>,
+    /// The reason when the project is not imported
+    message: Option<String>,
+}
+
+impl FansubDBImportEntryGQL {
+    /// Create a new import entry
+    pub fn new(
+        fansubdb_id: u64,
+        title: impl Into<String>,
+        status: FansubDBImportStatusGQL,
+    ) -> Self {
+        FansubDBImportEntryGQL {
+            fansubdb_id,
+            title: title.into(),
+            anilist_id: None,
+            status,
+            project: None,
+            message: None,
+        }
+    }
+
+    /// Set the matched Anilist ID
+    pub fn with_anilist(mut self, anilist_id: i32) -> Self {
+        self.anilist_id = Some(anilist_id);
+        self
+    }
+
+    /// Set the project
+    pub fn with_project(mut self, project: &showtimes_db::m::Project) -> Self {
+        self.project = Some(ProjectGQL::from(project).with_disable_collaboration_fetch());
+        self
+    }
+
+    /// Set the reason message
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// The result of importing projects from FansubDB
+#[derive(SimpleObject)]
+pub struct FansubDBImportGQL {
+    /// Whether this is a dry-run, no project is created on dry-run
+    #[graphql(name = "dryRun")]
+    dry_run: bool,
+    /// The amount of projects that are created or will be created
+    count: u64,
+    /// Each FansubDB project import result
+    entries: Vec<FansubDBImportEntryGQL>,
+}
+
+impl FansubDBImportGQL {
+    /// Create a new import result
+    pub fn new(dry_run: bool, entries: Vec<FansubDBImportEntryGQL>) -> Self {
+        let count = entries
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e.status,
+                    FansubDBImportStatusGQL::Created | FansubDBImportStatusGQL::Planned
+                )
+            })
+            .count() as u64;
+
+        FansubDBImportGQL {
+            dry_run,
+            count,
+            entries,
+        }
+    }
+}
@@ -3,9 +3,12 @@
 
 pub mod collaborations;
 pub mod common;
+pub mod fansubdb;
 pub mod projects;
 pub mod rss;
+pub mod schedule;
 pub mod search;
 pub mod servers;
 pub mod stats;
 pub mod users;
+pub mod webhooks;
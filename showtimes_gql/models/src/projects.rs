@@ -15,11 +15,12 @@ use super::{
 };
 
 /// Enum to hold project types or kinds.
-#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, EnumName)]
 #[graphql(
     remote = "showtimes_db::m::ProjectType",
     rename_items = "SCREAMING_SNAKE_CASE"
 )]
+#[enum_name(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ProjectTypeGQL {
     /// The project is a movie.
     Movies,
@@ -27,6 +28,7 @@ pub enum ProjectTypeGQL {
     Series,
     /// Oneshots of a series.
     #[graphql(name = "OVA")]
+    #[enum_name(rename = "OVA")]
     OVAs,
     /// The project is a standard literature books.
     Books,
@@ -426,7 +428,7 @@ impl From<showtimes_db::m::Role> for RoleGQL {
 }
 
 impl ProjectProgressGQL {
-    fn from_db(
+    pub(crate) fn from_db(
         progress: showtimes_db::m::EpisodeProgress,
         roles: Vec<showtimes_db::m::Role>,
     ) -> async_graphql::Result<Self> {
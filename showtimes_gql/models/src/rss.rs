@@ -1,7 +1,8 @@
 //! A RSS feed models list
 
-use async_graphql::Object;
+use async_graphql::{Enum, Object, SimpleObject};
 use showtimes_db::m::RSSFeedEmbedDisplay;
+use showtimes_derive::EnumName;
 use showtimes_gql_common::{
     DataLoader, DateTimeGQL, GQLErrorCode, IntegrationIdGQL, UlidGQL,
     data_loader::ServerDataLoader, errors::GQLError,
@@ -9,6 +10,62 @@ use showtimes_gql_common::{
 
 use crate::servers::ServerGQL;
 
+/// The entry field that is checked by a RSS filter rule
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::RSSFeedFilterField",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum RSSFeedFilterFieldGQL {
+    /// The entry title
+    Title,
+    /// The entry link, including the alternative links
+    Link,
+    /// The entry authors
+    Author,
+    /// The entry categories
+    Category,
+}
+
+/// How the pattern of a RSS filter rule is matched
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::RSSFeedFilterKind",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum RSSFeedFilterKindGQL {
+    /// Case-insensitive substring match
+    Keyword,
+    /// Case-insensitive regular expression match
+    Regex,
+}
+
+/// What to do with the entry when the rule matches
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::RSSFeedFilterAction",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum RSSFeedFilterActionGQL {
+    /// The rule passes when the pattern matches
+    Include,
+    /// The rule passes when the pattern does not match
+    Exclude,
+}
+
+/// How the rules inside a filter group are combined
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::RSSFeedFilterMode",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum RSSFeedFilterModeGQL {
+    /// Every rule must pass (AND)
+    All,
+    /// At least one rule must pass (OR)
+    Any,
+}
+
 /// The RSS feed object
 pub struct RSSFeedGQL(showtimes_db::m::RSSFeed);
 
@@ -43,6 +100,37 @@ impl RSSFeedGQL {
         RSSFeedDisplayGQL::from(&self.0.display)
     }
 
+    /// The filter groups, an entry is only published when every group passes
+    async fn filters(&self) -> Vec<RSSFeedFilterGroupGQL> {
+        self.0
+            .filters
+            .iter()
+            .map(RSSFeedFilterGroupGQL::from)
+            .collect()
+    }
+
+    /// The Discord webhooks used to post new entries into the channel integrations
+    async fn webhooks(&self) -> Vec<RSSFeedWebhookGQL> {
+        self.0
+            .webhooks
+            .iter()
+            .map(RSSFeedWebhookGQL::from)
+            .collect()
+    }
+
+    /// The health information of the RSS feed
+    ///
+    /// Failing feeds are retried with backoff and disabled after too many consecutive failures
+    async fn health(&self) -> RSSFeedHealthGQL {
+        RSSFeedHealthGQL::from(&self.0.health)
+    }
+
+    /// The polling statistics of the RSS feed
+    #[graphql(name = "fetchStats")]
+    async fn fetch_stats(&self) -> RSSFeedFetchStatsGQL {
+        RSSFeedFetchStatsGQL::from(&self.0.fetch_stats)
+    }
+
     /// The associated server of the RSS feed
     async fn server(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<ServerGQL> {
         let loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
@@ -67,6 +155,185 @@ impl RSSFeedGQL {
     }
 }
 
+/// A single RSS filter rule
+#[derive(SimpleObject)]
+#[graphql(name = "RSSFeedFilterRuleGQL")]
+pub struct RSSFeedFilterRuleGQL {
+    /// The entry field to check
+    field: RSSFeedFilterFieldGQL,
+    /// How the pattern is matched
+    kind: RSSFeedFilterKindGQL,
+    /// The keyword or regular expression
+    pattern: String,
+    /// Include or exclude the entry when matched
+    action: RSSFeedFilterActionGQL,
+}
+
+/// A group of RSS filter rules, an empty group always passes
+#[derive(SimpleObject)]
+#[graphql(name = "RSSFeedFilterGroupGQL")]
+pub struct RSSFeedFilterGroupGQL {
+    /// How the rules are combined
+    mode: RSSFeedFilterModeGQL,
+    /// The rules of the group
+    rules: Vec<RSSFeedFilterRuleGQL>,
+}
+
+impl From<&showtimes_db::m::RSSFeedFilterGroup> for RSSFeedFilterGroupGQL {
+    fn from(value: &showtimes_db::m::RSSFeedFilterGroup) -> Self {
+        RSSFeedFilterGroupGQL {
+            mode: value.mode.into(),
+            rules: value
+                .rules
+                .iter()
+                .map(|rule| RSSFeedFilterRuleGQL {
+                    field: rule.field.into(),
+                    kind: rule.kind.into(),
+                    pattern: rule.pattern.clone(),
+                    action: rule.action.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A Discord webhook used to deliver the RSS entries into a channel
+#[derive(SimpleObject)]
+#[graphql(name = "RSSFeedWebhookGQL")]
+pub struct RSSFeedWebhookGQL {
+    /// The Discord channel ID of the integration
+    channel: String,
+    /// The Discord webhook URL, the token is masked
+    url: String,
+}
+
+impl From<&showtimes_db::m::RSSFeedWebhook> for RSSFeedWebhookGQL {
+    fn from(value: &showtimes_db::m::RSSFeedWebhook) -> Self {
+        RSSFeedWebhookGQL {
+            channel: value.channel.clone(),
+            url: value.masked_url(),
+        }
+    }
+}
+
+/// The result of previewing the RSS filter against a feed entry
+#[derive(SimpleObject)]
+#[graphql(name = "RSSFeedFilterPreviewGQL")]
+pub struct RSSFeedFilterPreviewGQL {
+    /// The entry title
+    pub title: Option<String>,
+    /// The entry link
+    pub link: Option<String>,
+    /// The entry published or updated date
+    pub published: Option<DateTimeGQL>,
+    /// Would the entry be published with the filter
+    pub passed: bool,
+}
+
+/// The health information of a RSS feed
+pub struct RSSFeedHealthGQL(showtimes_db::m::RSSFeedHealth);
+
+#[Object(name = "RSSFeedHealthGQL")]
+impl RSSFeedHealthGQL {
+    /// When the feed was last fetched successfully
+    #[graphql(name = "lastSuccess")]
+    async fn last_success(&self) -> Option<DateTimeGQL> {
+        self.0.last_success.map(DateTimeGQL::from)
+    }
+
+    /// The last error message when fetching the feed
+    #[graphql(name = "lastError")]
+    async fn last_error(&self) -> Option<String> {
+        self.0.last_error.clone()
+    }
+
+    /// When the last error happened
+    #[graphql(name = "lastErrorAt")]
+    async fn last_error_at(&self) -> Option<DateTimeGQL> {
+        self.0.last_error_at.map(DateTimeGQL::from)
+    }
+
+    /// The amount of consecutive failures, reset on a successful fetch
+    async fn failures(&self) -> u32 {
+        self.0.failures
+    }
+
+    /// The feed will not be fetched until this time, empty if not failing
+    #[graphql(name = "nextRetry")]
+    async fn next_retry(&self) -> Option<DateTimeGQL> {
+        self.0.next_retry.map(DateTimeGQL::from)
+    }
+}
+
+impl From<&showtimes_db::m::RSSFeedHealth> for RSSFeedHealthGQL {
+    fn from(value: &showtimes_db::m::RSSFeedHealth) -> Self {
+        RSSFeedHealthGQL(value.clone())
+    }
+}
+
+/// The polling statistics of a RSS feed
+pub struct RSSFeedFetchStatsGQL(showtimes_db::m::RSSFeedFetchStats);
+
+#[Object(name = "RSSFeedFetchStatsGQL")]
+impl RSSFeedFetchStatsGQL {
+    /// The HTTP status code of the last request, `0` if never requested
+    #[graphql(name = "lastStatus")]
+    async fn last_status(&self) -> u16 {
+        self.0.last_status
+    }
+
+    /// The amount of bytes downloaded on the last request
+    #[graphql(name = "lastBytes")]
+    async fn last_bytes(&self) -> u64 {
+        self.0.last_bytes
+    }
+
+    /// How long the last request took, in milliseconds
+    #[graphql(name = "lastDurationMs")]
+    async fn last_duration_ms(&self) -> u64 {
+        self.0.last_duration_ms
+    }
+
+    /// When the feed was last requested, empty if never requested
+    #[graphql(name = "lastFetched")]
+    async fn last_fetched(&self) -> Option<DateTimeGQL> {
+        if self.0.fetches > 0 {
+            Some(self.0.last_fetched.into())
+        } else {
+            None
+        }
+    }
+
+    /// The total amount of requests made
+    async fn fetches(&self) -> u64 {
+        self.0.fetches
+    }
+
+    /// The total amount of requests that returned not modified
+    #[graphql(name = "notModified")]
+    async fn not_modified(&self) -> u64 {
+        self.0.not_modified
+    }
+
+    /// The total amount of bytes downloaded
+    #[graphql(name = "bytesDownloaded")]
+    async fn bytes_downloaded(&self) -> u64 {
+        self.0.bytes_downloaded
+    }
+
+    /// The estimated amount of bytes saved by not modified responses
+    #[graphql(name = "bytesSaved")]
+    async fn bytes_saved(&self) -> u64 {
+        self.0.bytes_saved
+    }
+}
+
+impl From<&showtimes_db::m::RSSFeedFetchStats> for RSSFeedFetchStatsGQL {
+    fn from(value: &showtimes_db::m::RSSFeedFetchStats) -> Self {
+        RSSFeedFetchStatsGQL(value.clone())
+    }
+}
+
 /// The RSS feed display information
 pub struct RSSFeedDisplayGQL {
     message: Option<String>,
@@ -0,0 +1,120 @@
+//! A schedule models list
+
+use async_graphql::{Object, dataloader::DataLoader};
+use errors::GQLError;
+use showtimes_gql_common::data_loader::{ProjectDataLoader, ServerDataLoader};
+use showtimes_gql_common::*;
+use showtimes_gql_paginator::schedule::ScheduleEpisode;
+
+use super::{
+    projects::{PosterGQL, ProjectGQL, ProjectProgressGQL, ProjectStatusGQL, ProjectTypeGQL},
+    servers::ServerGQL,
+};
+
+/// An episode or chapter airing in the schedule
+pub struct ScheduleEpisodeGQL {
+    episode: ScheduleEpisode,
+}
+
+#[Object]
+impl ScheduleEpisodeGQL {
+    /// The project ID of the episode
+    #[graphql(name = "projectId")]
+    async fn project_id(&self) -> UlidGQL {
+        self.episode.id.into()
+    }
+
+    /// The project title
+    async fn title(&self) -> String {
+        self.episode.title.clone()
+    }
+
+    /// The project poster
+    async fn poster(&self) -> PosterGQL {
+        self.episode.poster.clone().into()
+    }
+
+    /// The project kind or type.
+    async fn kind(&self) -> ProjectTypeGQL {
+        self.episode.kind.into()
+    }
+
+    /// The project status
+    async fn status(&self) -> ProjectStatusGQL {
+        self.episode.status.into()
+    }
+
+    /// The episode or chapter number.
+    async fn number(&self) -> u64 {
+        self.episode.progress.number
+    }
+
+    /// The air date of the episode or chapter.
+    #[graphql(name = "airDate")]
+    async fn air_date(&self) -> Option<DateTimeGQL> {
+        self.episode.progress.aired.map(|d| d.into())
+    }
+
+    /// The episode progress along with each role status, this can fails if the roles are not found
+    async fn progress(&self) -> async_graphql::Result<ProjectProgressGQL> {
+        ProjectProgressGQL::from_db(self.episode.progress.clone(), self.episode.roles.clone())
+    }
+
+    /// The ratio of finished roles for the episode, from `0.0` to `1.0`
+    async fn completion(&self) -> f64 {
+        let statuses = &self.episode.progress.statuses;
+        if statuses.is_empty() {
+            return if self.episode.progress.finished {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        let finished = statuses.iter().filter(|s| s.finished()).count();
+        finished as f64 / statuses.len() as f64
+    }
+
+    /// The full project information.
+    ///
+    /// The project is loaded lazily, so only request this when needed.
+    async fn project(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<ProjectGQL> {
+        let loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
+
+        let project = loader.load_one(self.episode.id).await?.ok_or_else(|| {
+            GQLError::new("Project not found", GQLErrorCode::ProjectNotFound).extend(|e| {
+                e.set("id", self.episode.id.to_string());
+                e.set("root", "schedule");
+            })
+        })?;
+
+        Ok(project.into())
+    }
+
+    /// The server that owns the project.
+    ///
+    /// If the server is not found, this will throw an error.
+    async fn server(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<ServerGQL> {
+        let loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+        let server = loader
+            .load_one(self.episode.creator)
+            .await?
+            .ok_or_else(|| {
+                GQLError::new("Server not found", GQLErrorCode::ServerNotFound).extend(|e| {
+                    e.set("id", self.episode.creator.to_string());
+                    e.set("project_id", self.episode.id.to_string());
+                    e.set("root", "schedule");
+                })
+            })?;
+
+        let map_server: ServerGQL = server.into();
+        Ok(map_server.with_projects_disabled())
+    }
+}
+
+impl From<ScheduleEpisode> for ScheduleEpisodeGQL {
+    fn from(episode: ScheduleEpisode) -> Self {
+        ScheduleEpisodeGQL { episode }
+    }
+}
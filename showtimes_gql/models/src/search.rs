@@ -4,14 +4,21 @@ use showtimes_derive::EnumName;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use async_graphql::{Enum, Object, SimpleObject};
-use showtimes_gql_common::{GQLErrorCode, ProjectKindGQL, errors::GQLError};
+use async_graphql::{Enum, Object, SimpleObject, dataloader::DataLoader};
+use showtimes_gql_common::{
+    GQLErrorCode, ProjectKindGQL, UlidGQL,
+    data_loader::{ProjectDataLoader, ServerDataLoader, ServerOwnerId},
+    errors::GQLError,
+};
 use showtimes_metadata::{
     AnilistProvider, TMDbProvider, VndbProvider,
     m::{AnilistFuzzyDate, AnilistMedia, AnilistMediaFormat, TMDbMovieResult, VndbNovel},
 };
 
-use super::projects::{ProjectTypeGQL, RoleGQL};
+use super::{
+    projects::{ProjectGQL, ProjectStatusGQL, ProjectTypeGQL, RoleGQL},
+    servers::ServerGQL,
+};
 
 type AnilistProviderShared = Arc<Mutex<AnilistProvider>>;
 type TMDbProviderShared = Arc<TMDbProvider>;
@@ -272,9 +279,13 @@ struct DefaultRolesGQL {
 }
 
 /// The root query for external search to multiple external sources.
+///
+/// This also includes full-text search for projects and servers in Showtimes.
 pub struct QuerySearchRoot;
 
 /// The root query for external search to multiple external sources.
+///
+/// This also includes full-text search for projects and servers in Showtimes.
 #[Object]
 impl QuerySearchRoot {
     /// Search for media from Anilist
@@ -371,6 +382,112 @@ impl QuerySearchRoot {
         }
     }
 
+    /// Search for projects that you have access to.
+    ///
+    /// The results are ordered by relevancy.
+    async fn projects(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(desc = "The query to search for")] query: String,
+        #[graphql(name = "serverIds", desc = "Limit projects to specific server IDs")]
+        server_ids: Option<Vec<UlidGQL>>,
+        #[graphql(desc = "Limit projects to specific type")] kind: Option<ProjectTypeGQL>,
+        #[graphql(desc = "Limit projects to specific status")] status: Option<ProjectStatusGQL>,
+        #[graphql(
+            desc = "The number of projects to return, default to 20",
+            validator(minimum = 1, maximum = 100)
+        )]
+        limit: Option<u32>,
+    ) -> async_graphql::Result<Vec<ProjectGQL>> {
+        let user = ctx.data_unchecked::<showtimes_db::m::User>();
+
+        let allowed_servers = match user.kind {
+            showtimes_db::m::UserKind::User => {
+                let projector = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+                Some(
+                    projector
+                        .load_one(ServerOwnerId::new(user.id))
+                        .await?
+                        .unwrap_or_default(),
+                )
+            }
+            _ => None,
+        };
+
+        let mut queries = showtimes_gql_paginator::search::ProjectSearchQuery::new(&query)
+            .with_current_user(user.clone().into());
+        if let Some(server_ids) = server_ids {
+            let server_ids: Vec<showtimes_shared::ulid::Ulid> =
+                server_ids.into_iter().map(|id| *id).collect();
+            queries.set_creators(&server_ids);
+        }
+        if let Some(kind) = kind {
+            queries.set_kind(kind.into());
+        }
+        if let Some(status) = status {
+            queries.set_status(status.into());
+        }
+        if let Some(limit) = limit {
+            queries.set_limit(limit as usize);
+        }
+        if let Some(allowed_servers) = allowed_servers {
+            queries.set_allowed_servers(allowed_servers);
+        }
+
+        let hits = showtimes_gql_paginator::search::search_projects(ctx, queries).await?;
+
+        // The search index can be stale, so hydrate it from the database
+        let loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
+        let mut projects = loader.load_many(hits.iter().copied()).await?;
+
+        Ok(hits
+            .iter()
+            .filter_map(|id| projects.remove(id))
+            .map(ProjectGQL::from)
+            .collect())
+    }
+
+    /// Search for servers that you have access to.
+    ///
+    /// The results are ordered by relevancy.
+    async fn servers(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(desc = "The query to search for")] query: String,
+        #[graphql(
+            desc = "The number of servers to return, default to 20",
+            validator(minimum = 1, maximum = 100)
+        )]
+        limit: Option<u32>,
+    ) -> async_graphql::Result<Vec<ServerGQL>> {
+        let user = ctx.data_unchecked::<showtimes_db::m::User>();
+        let loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+        let mut queries = showtimes_gql_paginator::search::ServerSearchQuery::new(&query);
+        if let Some(limit) = limit {
+            queries.set_limit(limit as usize);
+        }
+        if user.kind == showtimes_db::m::UserKind::User {
+            let allowed_servers = loader
+                .load_one(ServerOwnerId::new(user.id))
+                .await?
+                .unwrap_or_default();
+            queries.set_allowed_servers(allowed_servers);
+        }
+
+        let hits = showtimes_gql_paginator::search::search_servers(ctx, queries).await?;
+
+        // The search index can be stale, so hydrate it from the database
+        let mut servers = loader.load_many(hits.iter().copied()).await?;
+
+        Ok(hits
+            .iter()
+            .filter_map(|id| servers.remove(id))
+            .map(|s| ServerGQL::from(s).with_current_user(user.id))
+            .collect())
+    }
+
     /// Get all the default roles list that can be used
     #[graphql(name = "defaultRoles")]
     async fn default_roles(&self) -> Vec<DefaultRolesGQL> {
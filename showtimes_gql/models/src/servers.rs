@@ -7,7 +7,10 @@ use showtimes_db::{
     mongodb::bson::doc,
 };
 use showtimes_gql_common::{
-    data_loader::{ServerDataLoader, ServerPremiumLoader, ServerPremiumServer, UserDataLoader},
+    data_loader::{
+        ServerDataLoader, ServerPremiumLoader, ServerPremiumServer, UserDataLoader, WebhookLoader,
+        WebhookServer,
+    },
     queries::MinimalServerUsers,
     *,
 };
@@ -15,7 +18,7 @@ use showtimes_gql_paginator::projects::ProjectQuery;
 
 use crate::common::PaginatedGQL;
 
-use super::{projects::ProjectGQL, users::UserGQL};
+use super::{projects::ProjectGQL, users::UserGQL, webhooks::WebhookGQL};
 
 /// Enum to hold user privileges on a server.
 ///
@@ -220,7 +223,7 @@ impl ServerGQL {
 
         let mapped_nodes: Vec<ProjectGQL> = results.nodes().iter().map(ProjectGQL::from).collect();
 
-        Ok(PaginatedGQL::new(mapped_nodes, *results.page_info()))
+        Ok(PaginatedGQL::new(mapped_nodes, results.page_info().clone()))
     }
 
     /// The list of server premium information
@@ -256,6 +259,54 @@ impl ServerGQL {
             })
             .collect())
     }
+
+    /// The list of server webhooks
+    ///
+    /// Only available for user with admin privilege or higher on the server
+    #[graphql(
+        guard = "guard::AuthAPIKeyMinimumGuard::new(guard::APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn webhooks(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Vec<WebhookGQL>> {
+        let user = ctx.data_opt::<showtimes_db::m::User>().ok_or_else(|| {
+            GQLError::new("Unauthorized", GQLErrorCode::Unauthorized)
+                .extend(|e| e.set("id", self.id.to_string()))
+        })?;
+
+        if user.kind == showtimes_db::m::UserKind::User {
+            let privilege = self
+                .owners
+                .iter()
+                .find(|o| o.id == user.id)
+                .map(|o| o.privilege);
+
+            if privilege.is_none_or(|p| p < showtimes_db::m::UserPrivilege::Admin) {
+                return GQLError::new(
+                    "User not allowed to view webhooks",
+                    GQLErrorCode::UserInsufficientPrivilege,
+                )
+                .extend(|e| {
+                    e.set("id", self.id.to_string());
+                    e.set(
+                        "required",
+                        showtimes_db::m::UserPrivilege::Admin.to_string(),
+                    );
+                    e.set("is_in_server", privilege.is_some());
+                })
+                .into();
+            }
+        }
+
+        let loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+        let results = loader
+            .load_one(WebhookServer::from(self.id))
+            .await?
+            .unwrap_or_default();
+
+        Ok(results.iter().map(WebhookGQL::from).collect())
+    }
 }
 
 /// Implementation of Premium metadata for [`ServerQQL`]
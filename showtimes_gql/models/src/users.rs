@@ -6,6 +6,7 @@ use showtimes_db::m::APIKeyCapability;
 use showtimes_db::mongodb::bson::doc;
 use showtimes_gql_common::{queries::ServerQueryUser, *};
 use showtimes_gql_paginator::servers::ServerQuery;
+use showtimes_session::{ShowtimesUserSession, manager::SharedSessionManager};
 
 use crate::common::PaginatedGQL;
 
@@ -35,6 +36,16 @@ pub struct APIKeyDataGQL {
     api_key: Option<APIKeyGQL>,
     /// The capabilities associated with the API key
     capabilities: Vec<APIKeyCapabilityGQL>,
+    /// The label of the API key
+    name: Option<String>,
+    /// When the API key expires, `null` means it never expires
+    #[graphql(name = "expiresAt")]
+    expires_at: Option<DateTimeGQL>,
+    /// When the API key was last used, this is updated periodically
+    #[graphql(name = "lastUsed")]
+    last_used: Option<DateTimeGQL>,
+    /// The servers the API key is limited to, empty means all servers
+    servers: Vec<UlidGQL>,
 }
 
 #[Object]
@@ -86,6 +97,41 @@ impl UserGQL {
         self.updated.into()
     }
 
+    /// The user's active login sessions, this will be `null` if you're not *this* user or an Admin.
+    #[graphql(
+        guard = "guard::AuthUserAndAPIKeyGuard::new(UserKindGQL::User, guard::APIKeyVerify::NotAllowed)"
+    )]
+    async fn sessions(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Option<Vec<UserSessionInfoGQL>>> {
+        let requester = ctx.data_unchecked::<showtimes_db::m::User>();
+        if requester.id != self.id && requester.kind < showtimes_db::m::UserKind::Admin {
+            return Ok(None);
+        }
+
+        let sess_manager = ctx.data_unchecked::<SharedSessionManager>();
+        let current_token = ctx
+            .data_opt::<ShowtimesUserSession>()
+            .map(|session| session.get_token());
+
+        let sessions = sess_manager
+            .lock()
+            .await
+            .get_user_sessions(self.id)
+            .await
+            .extend_error(GQLErrorCode::SessionError, |f| {
+                f.set("id", self.id.to_string());
+            })?;
+
+        Ok(Some(
+            sessions
+                .iter()
+                .map(|info| UserSessionInfoGQL::new(info, current_token))
+                .collect(),
+        ))
+    }
+
     /// Get the server associated with the user
     #[graphql(
         guard = "guard::AuthAPIKeyMinimumGuard::new(guard::APIKeyVerify::Specific(APIKeyCapability::QueryServers))"
@@ -143,7 +189,7 @@ impl UserGQL {
             })
             .collect();
 
-        Ok(PaginatedGQL::new(mapped_nodes, *results.page_info()))
+        Ok(PaginatedGQL::new(mapped_nodes, results.page_info().clone()))
     }
 }
 
@@ -182,27 +228,18 @@ impl From<&showtimes_db::m::User> for UserGQL {
 }
 
 impl APIKeyDataGQL {
-    /// Create a new API key data object
-    pub fn new(
-        api_key: showtimes_shared::APIKey,
-        capabilities: Vec<showtimes_db::m::APIKeyCapability>,
-    ) -> Self {
+    /// Create a new API key data object from a newly created API key
+    pub fn new(api_key: showtimes_shared::APIKey, key: &showtimes_db::m::APIKey) -> Self {
         APIKeyDataGQL {
             api_key: Some(api_key.into()),
-            capabilities: capabilities.iter().map(|&c| c.into()).collect(),
+            ..APIKeyDataGQL::from(key)
         }
     }
 }
 
 impl From<showtimes_db::m::APIKey> for APIKeyDataGQL {
     fn from(key: showtimes_db::m::APIKey) -> Self {
-        APIKeyDataGQL {
-            api_key: match key.key {
-                showtimes_db::m::APIKeyHashed::Unhashed(api_key) => Some(APIKeyGQL::from(api_key)),
-                _ => None,
-            },
-            capabilities: key.capabilities.iter().map(|&c| c.into()).collect(),
-        }
+        APIKeyDataGQL::from(&key)
     }
 }
 
@@ -214,6 +251,10 @@ impl From<&showtimes_db::m::APIKey> for APIKeyDataGQL {
                 _ => None,
             },
             capabilities: key.capabilities.iter().map(|&c| c.into()).collect(),
+            name: key.name.clone(),
+            expires_at: key.expires_at.map(Into::into),
+            last_used: key.last_used.map(Into::into),
+            servers: key.servers.iter().map(Into::into).collect(),
         }
     }
 }
@@ -265,3 +306,44 @@ impl UserSessionGQL {
         self
     }
 }
+
+/// An active login session of a user
+#[derive(SimpleObject)]
+pub struct UserSessionInfoGQL {
+    /// The session ID, used to revoke the session
+    id: UlidGQL,
+    /// When the session is created
+    #[graphql(name = "issuedAt")]
+    issued_at: DateTimeGQL,
+    /// When the session token is last refreshed
+    #[graphql(name = "refreshedAt")]
+    refreshed_at: Option<DateTimeGQL>,
+    /// When the session can no longer be used or refreshed
+    #[graphql(name = "expiresAt")]
+    expires_at: DateTimeGQL,
+    /// The user agent of the client that created the session
+    #[graphql(name = "userAgent")]
+    user_agent: Option<String>,
+    /// The IP address of the client that created the session
+    ip: Option<String>,
+    /// Is this the session used for the current request
+    current: bool,
+}
+
+impl UserSessionInfoGQL {
+    /// Create a new session info, `current_token` is the token used for the current request
+    pub fn new(
+        info: &showtimes_session::manager::UserSessionInfo,
+        current_token: Option<&str>,
+    ) -> Self {
+        UserSessionInfoGQL {
+            id: info.get_id().into(),
+            issued_at: info.get_issued_at().into(),
+            refreshed_at: info.get_refreshed_at().map(Into::into),
+            expires_at: info.get_expires_at().into(),
+            user_agent: info.get_user_agent().map(|ua| ua.to_string()),
+            ip: info.get_ip().map(|ip| ip.to_string()),
+            current: current_token.is_some_and(|token| info.is_token(token)),
+        }
+    }
+}
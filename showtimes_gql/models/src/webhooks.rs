@@ -0,0 +1,201 @@
+//! A webhook models list
+
+use async_graphql::{Enum, Object};
+use showtimes_derive::EnumName;
+use showtimes_gql_common::{
+    DataLoader, DateTimeGQL, GQLErrorCode, UlidGQL, data_loader::ServerDataLoader, errors::GQLError,
+};
+
+use crate::{projects::ProjectTypeGQL, servers::ServerGQL};
+
+/// Enum to hold the webhook target kind
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::WebhookTarget",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum WebhookTargetGQL {
+    /// Discord webhook target
+    Discord,
+    /// Generic JSON webhook target, signed with HMAC-SHA256
+    Generic,
+    /// Slack incoming webhook target
+    Slack,
+    /// Matrix webhook target, compatible with matrix-hookshot
+    Matrix,
+    /// Telegram Bot API `sendMessage` target
+    Telegram,
+}
+
+/// Enum to hold the supported webhook actions
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq, EnumName)]
+#[graphql(
+    remote = "showtimes_db::m::WebhookAction",
+    rename_items = "SCREAMING_SNAKE_CASE"
+)]
+pub enum WebhookActionGQL {
+    /// A new project is created
+    ProjectCreate,
+    /// A project progress is updated
+    ProjectProgress,
+    /// A project is released
+    ProjectRelease,
+    /// A project is reversed/unreleased
+    ProjectUnreleased,
+    /// A project is dropped
+    ProjectDropped,
+    /// A project is resumed
+    ProjectResumed,
+}
+
+/// The webhook object
+pub struct WebhookGQL {
+    webhook: showtimes_db::m::Webhook,
+    show_secret: bool,
+}
+
+#[Object]
+impl WebhookGQL {
+    /// The webhook ID
+    async fn id(&self) -> UlidGQL {
+        self.webhook.id.into()
+    }
+
+    /// The webhook target kind
+    async fn kind(&self) -> WebhookTargetGQL {
+        self.webhook.kind.into()
+    }
+
+    /// The webhook URL
+    async fn url(&self) -> String {
+        self.webhook.url.clone()
+    }
+
+    /// The name used when sending the webhook
+    async fn name(&self) -> String {
+        self.webhook.name.clone()
+    }
+
+    /// The avatar URL used when sending the webhook
+    async fn avatar(&self) -> Option<String> {
+        self.webhook.avatar.clone()
+    }
+
+    /// The list of actions that will trigger the webhook
+    async fn actions(&self) -> Vec<WebhookActionGQL> {
+        self.webhook.actions.iter().map(|&a| a.into()).collect()
+    }
+
+    /// The locale code used to render the webhook message
+    async fn locale(&self) -> Option<String> {
+        self.webhook.locale.clone()
+    }
+
+    /// The secret used to sign the payload, only available for `GENERIC` target
+    ///
+    /// The signature is sent in `X-Showtimes-Signature` header as `sha256=<hex>`
+    /// of `{timestamp}.{body}`, with the timestamp in `X-Showtimes-Timestamp` header.
+    ///
+    /// The full secret is only returned when the webhook is created or the secret
+    /// is regenerated, otherwise it is masked.
+    async fn secret(&self) -> Option<String> {
+        if self.show_secret {
+            self.webhook.secret.clone()
+        } else {
+            self.webhook.masked_secret()
+        }
+    }
+
+    /// The filter that scope which events are delivered
+    async fn filter(&self) -> WebhookFilterGQL {
+        WebhookFilterGQL(self.webhook.filter.clone())
+    }
+
+    /// Whether the webhook is enabled, disabled webhook will not receive any events
+    async fn enabled(&self) -> bool {
+        self.webhook.enabled
+    }
+
+    /// The amount of consecutive failed deliveries
+    async fn failures(&self) -> u32 {
+        self.webhook.failures
+    }
+
+    /// The associated server of the webhook
+    async fn server(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<ServerGQL> {
+        let loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+        let srv = loader
+            .load_one(self.webhook.creator)
+            .await?
+            .ok_or_else(|| {
+                GQLError::new("Server not found", GQLErrorCode::ServerNotFound)
+                    .extend(|e| e.set("id", self.webhook.creator.to_string()))
+            })?;
+
+        let srv_gql: ServerGQL = srv.into();
+        Ok(srv_gql.with_projects_disabled())
+    }
+
+    /// The webhook creation date
+    async fn created(&self) -> DateTimeGQL {
+        self.webhook.created.into()
+    }
+
+    /// The webhook last update date
+    async fn updated(&self) -> DateTimeGQL {
+        self.webhook.updated.into()
+    }
+}
+
+impl WebhookGQL {
+    /// Return the full secret instead of the masked one
+    pub fn with_secret(mut self) -> Self {
+        self.show_secret = true;
+        self
+    }
+}
+
+impl From<showtimes_db::m::Webhook> for WebhookGQL {
+    fn from(webhook: showtimes_db::m::Webhook) -> Self {
+        WebhookGQL {
+            webhook,
+            show_secret: false,
+        }
+    }
+}
+
+impl From<&showtimes_db::m::Webhook> for WebhookGQL {
+    fn from(webhook: &showtimes_db::m::Webhook) -> Self {
+        WebhookGQL::from(webhook.clone())
+    }
+}
+
+/// The webhook filter, an empty list means no filtering for that field
+pub struct WebhookFilterGQL(showtimes_db::m::WebhookFilter);
+
+#[Object]
+impl WebhookFilterGQL {
+    /// Only deliver events from these projects
+    async fn projects(&self) -> Vec<UlidGQL> {
+        self.0.projects.iter().map(|&id| id.into()).collect()
+    }
+
+    /// Only deliver events from these project types
+    ///
+    /// When both `projects` and `kinds` are set, the project only need to match one of them.
+    async fn kinds(&self) -> Vec<ProjectTypeGQL> {
+        self.0.kinds.iter().map(|&k| k.into()).collect()
+    }
+
+    /// Only deliver progress events when one of these role keys is finished
+    async fn roles(&self) -> Vec<String> {
+        self.0.roles.clone()
+    }
+
+    /// Skip events from projects that are paused
+    #[graphql(name = "skipPaused")]
+    async fn skip_paused(&self) -> bool {
+        self.0.skip_paused
+    }
+}
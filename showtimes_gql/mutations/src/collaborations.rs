@@ -1,10 +1,9 @@
 use async_graphql::{InputObject, dataloader::DataLoader};
 use showtimes_db::{
     CollaborationInviteHandler, DatabaseShared,
-    m::{ServerCollaborationSyncTarget, UserKind},
+    m::{SearchOutbox, SearchOutboxKind, ServerCollaborationSyncTarget, UserKind},
     mongodb::bson::doc,
 };
-use showtimes_search::SearchClientShared;
 
 use showtimes_gql_common::{
     GQLErrorCode, OkResponse, UlidGQL,
@@ -13,7 +12,7 @@ use showtimes_gql_common::{
 };
 use showtimes_gql_models::collaborations::{CollaborationInviteGQL, CollaborationSyncGQL};
 
-use crate::execute_search_events;
+use crate::{execute_search_events, queue_search_outbox};
 
 /// The user input object on what to update
 ///
@@ -102,7 +101,6 @@ pub async fn mutate_collaborations_initiate(
 ) -> async_graphql::Result<CollaborationInviteGQL> {
     let prj_loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
     let db = ctx.data_unchecked::<DatabaseShared>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
 
     let project = prj_loader.load_one(*input.project).await?.ok_or_else(|| {
         GQLError::new("Project not found", GQLErrorCode::ProjectNotFound)
@@ -155,6 +153,16 @@ pub async fn mutate_collaborations_initiate(
     let mut collab_invite =
         showtimes_db::m::ServerCollaborationInvite::new(source_invite, target_invite);
 
+    // Record the search index update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(
+            SearchOutboxKind::CollaborationInvite,
+            collab_invite.id,
+        )],
+    )
+    .await?;
+
     let invite_handler = CollaborationInviteHandler::new(db);
     invite_handler
         .save(&mut collab_invite, None)
@@ -167,12 +175,7 @@ pub async fn mutate_collaborations_initiate(
         })?;
 
     // Save in search index
-    let invite_clone = collab_invite.clone();
-    let meili_clone = meili.clone();
-    let task_search = tokio::task::spawn(async move {
-        let invite_search = showtimes_search::models::ServerCollabInvite::from(invite_clone);
-        invite_search.update_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
     // Save in event
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
@@ -197,7 +200,6 @@ pub async fn mutate_collaborations_accept(
     invite: UlidGQL,
 ) -> async_graphql::Result<CollaborationSyncGQL> {
     let db = ctx.data_unchecked::<DatabaseShared>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
     let prj_loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
     let invite_loader = ctx.data_unchecked::<DataLoader<ServerInviteLoader>>();
 
@@ -240,32 +242,9 @@ pub async fn mutate_collaborations_accept(
         orig_proj.duplicate(target_srv.id)
     };
 
-    // Save the project to DB first for target
-    let prj_handler = showtimes_db::ProjectHandler::new(db);
-    prj_handler
-        .save(&mut target_proj, None)
-        .await
-        .map_err(|e| {
-            GQLError::new(e.to_string(), GQLErrorCode::ProjectUpdateError).extend(|f| {
-                f.set("id", target_proj.id.to_string());
-                f.set("invite_id", invite.to_string());
-                f.set("from", "invite_accept");
-            })
-        })?;
-
-    // Save to search index
-    let prj_search = showtimes_search::models::Project::from(target_proj.clone());
-    prj_search.update_document(meili).await.map_err(|e| {
-        GQLError::new(e.to_string(), GQLErrorCode::ProjectUpdateSearchError).extend(|f| {
-            f.set("id", target_proj.id.to_string());
-            f.set("invite_id", invite.to_string());
-            f.set("from", "invite_accept");
-        })
-    })?;
-
     // Find any pre-existing sync
     let sync_handler = showtimes_db::CollaborationSyncHandler::new(db);
-    let mut sync_ss = sync_handler
+    let sync_ss = sync_handler
         .find_by(doc! {
             "projects.project": orig_proj.id.to_string(),
         })
@@ -278,87 +257,58 @@ pub async fn mutate_collaborations_accept(
             })
         })?;
 
-    let sync_mut = sync_ss.as_mut();
-
-    // Match, update the list then save
-    let sync_gql: CollaborationSyncGQL = if let Some(sync) = sync_mut {
-        sync.projects
-            .push(ServerCollaborationSyncTarget::from(target_proj));
-
-        // Update DB
-        sync_handler.save(sync, None).await.map_err(|e| {
-            GQLError::new(e.to_string(), GQLErrorCode::ServerSyncUpdateError).extend(|f| {
-                f.set("id", sync.id.to_string());
-                f.set("invite_id", invite.to_string());
-                f.set("from", "invite_accept");
-                f.set("is_new", false);
-            })
-        })?;
-
-        // Save in search index
-        let sync_clone = sync.clone();
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            let sync_search = showtimes_search::models::ServerCollabSync::from(sync_clone);
-            sync_search.update_document(&meili_clone).await
-        });
-        // Save in event
-        let task_events = ctx
-            .data_unchecked::<showtimes_events::SharedSHClickHouse>()
-            .create_event_async(
-                showtimes_events::m::EventKind::CollaborationAccepted,
-                showtimes_events::m::CollabAcceptedEvent::new(invite_data.id, sync.id),
-                if user.kind == UserKind::Owner {
-                    None
-                } else {
-                    Some(user.id.to_string())
-                },
-            );
-
-        execute_search_events(task_search, task_events).await?;
-
-        sync.clone().into()
-    } else {
-        // Create a new sync
-        let src_sync = showtimes_db::m::ServerCollaborationSyncTarget::from(orig_proj);
-        let target_sync = showtimes_db::m::ServerCollaborationSyncTarget::from(target_proj);
+    // Match and update the list, or create a new sync
+    let target_sync = ServerCollaborationSyncTarget::from(target_proj.clone());
+    let (mut sync, is_new) = match sync_ss {
+        Some(mut sync) => {
+            sync.projects.push(target_sync);
+            (sync, false)
+        }
+        None => {
+            let src_sync = ServerCollaborationSyncTarget::from(orig_proj);
+            let sync = showtimes_db::m::ServerCollaborationSync::new(vec![src_sync, target_sync]);
+            (sync, true)
+        }
+    };
 
-        let mut sync = showtimes_db::m::ServerCollaborationSync::new(vec![src_sync, target_sync]);
+    // Record the search index changes
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![
+            SearchOutbox::upsert(SearchOutboxKind::Project, target_proj.id),
+            SearchOutbox::upsert(SearchOutboxKind::CollaborationSync, sync.id),
+            SearchOutbox::delete(SearchOutboxKind::CollaborationInvite, invite_data.id),
+        ],
+    )
+    .await?;
 
-        // Save to DB
-        sync_handler.save(&mut sync, None).await.map_err(|e| {
-            GQLError::new(e.to_string(), GQLErrorCode::ServerSyncCreateError).extend(|f| {
-                f.set("id", sync.id.to_string());
+    // Save the project to DB first for target
+    let prj_handler = showtimes_db::ProjectHandler::new(db);
+    prj_handler
+        .save(&mut target_proj, None)
+        .await
+        .map_err(|e| {
+            GQLError::new(e.to_string(), GQLErrorCode::ProjectUpdateError).extend(|f| {
+                f.set("id", target_proj.id.to_string());
                 f.set("invite_id", invite.to_string());
                 f.set("from", "invite_accept");
-                f.set("is_new", true);
             })
         })?;
 
-        // Save in search index
-        let sync_clone = sync.clone();
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            let sync_search = showtimes_search::models::ServerCollabSync::from(sync_clone);
-            sync_search.update_document(&meili_clone).await
-        });
-        // Save in event
-        let task_events = ctx
-            .data_unchecked::<showtimes_events::SharedSHClickHouse>()
-            .create_event_async(
-                showtimes_events::m::EventKind::CollaborationAccepted,
-                showtimes_events::m::CollabAcceptedEvent::new(invite_data.id, sync.id),
-                if user.kind == UserKind::Owner {
-                    None
-                } else {
-                    Some(user.id.to_string())
-                },
-            );
-
-        execute_search_events(task_search, task_events).await?;
-
-        sync.clone().into()
-    };
+    // Save the sync to DB
+    sync_handler.save(&mut sync, None).await.map_err(|e| {
+        let code = if is_new {
+            GQLErrorCode::ServerSyncCreateError
+        } else {
+            GQLErrorCode::ServerSyncUpdateError
+        };
+        GQLError::new(e.to_string(), code).extend(|f| {
+            f.set("id", sync.id.to_string());
+            f.set("invite_id", invite.to_string());
+            f.set("from", "invite_accept");
+            f.set("is_new", is_new);
+        })
+    })?;
 
     // Delete invite
     invite_db.delete(&invite_data).await.map_err(|e| {
@@ -367,14 +317,25 @@ pub async fn mutate_collaborations_accept(
             f.set("from", "invite_accept");
         })
     })?;
-    // Remove from search index
-    let invite_search = showtimes_search::models::ServerCollabInvite::from(invite_data.clone());
-    invite_search.delete_document(meili).await.map_err(|e| {
-        GQLError::new(e.to_string(), GQLErrorCode::ServerInviteDeleteSearchError).extend(|f| {
-            f.set("id", invite.to_string());
-            f.set("from", "invite_accept");
-        })
-    })?;
+
+    // Save in search index
+    let task_search = search_queue.apply();
+    // Save in event
+    let task_events = ctx
+        .data_unchecked::<showtimes_events::SharedSHClickHouse>()
+        .create_event_async(
+            showtimes_events::m::EventKind::CollaborationAccepted,
+            showtimes_events::m::CollabAcceptedEvent::new(invite_data.id, sync.id),
+            if user.kind == UserKind::Owner {
+                None
+            } else {
+                Some(user.id.to_string())
+            },
+        );
+
+    execute_search_events(task_search, task_events).await?;
+
+    let sync_gql: CollaborationSyncGQL = sync.into();
 
     Ok(sync_gql)
 }
@@ -386,7 +347,6 @@ pub async fn mutate_collaborations_cancel(
     is_deny: bool,
 ) -> async_graphql::Result<OkResponse> {
     let db = ctx.data_unchecked::<DatabaseShared>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
     let invite_loader = ctx.data_unchecked::<DataLoader<ServerInviteLoader>>();
 
     let invite_db = showtimes_db::CollaborationInviteHandler::new(db);
@@ -408,6 +368,16 @@ pub async fn mutate_collaborations_cancel(
     };
     check_permissions(ctx, &user, server_id).await?;
 
+    // Record the search index removal
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::delete(
+            SearchOutboxKind::CollaborationInvite,
+            invite_data.id,
+        )],
+    )
+    .await?;
+
     // Deny the invite
     invite_db.delete(&invite_data).await.map_err(|e| {
         GQLError::new(e.to_string(), GQLErrorCode::ServerInviteDeleteError).extend(|f| {
@@ -424,13 +394,7 @@ pub async fn mutate_collaborations_cancel(
     })?;
 
     // Remove from search index
-    let meili_clone = meili.clone();
-    let invite_clone = invite_data.clone();
-
-    let task_search = tokio::task::spawn(async move {
-        let invite_search = showtimes_search::models::ServerCollabInvite::from(invite_clone);
-        invite_search.delete_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
 
     // Save in event
     let event_ch = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
@@ -502,6 +466,16 @@ pub async fn mutate_collaborations_unlink(
 
     // Check if we need to delete the sync
     if sync.length() < 2 {
+        // Record the search index removal
+        let search_queue = queue_search_outbox(
+            ctx,
+            vec![SearchOutbox::delete(
+                SearchOutboxKind::CollaborationSync,
+                sync.id,
+            )],
+        )
+        .await?;
+
         // Delete the sync
         sync_handler.delete(&sync).await.map_err(|e| {
             GQLError::new(e.to_string(), GQLErrorCode::ServerSyncDeleteError).extend(|f| {
@@ -513,12 +487,7 @@ pub async fn mutate_collaborations_unlink(
         })?;
 
         // Remove from search index
-        let sync_clone = sync.clone();
-        let meili_clone = ctx.data_unchecked::<SearchClientShared>().clone();
-        let task_search = tokio::task::spawn(async move {
-            let sync_search = showtimes_search::models::ServerCollabSync::from(sync_clone);
-            sync_search.delete_document(&meili_clone).await
-        });
+        let task_search = search_queue.apply();
 
         // Save in event
         let task_events = ctx
@@ -535,6 +504,16 @@ pub async fn mutate_collaborations_unlink(
 
         execute_search_events(task_search, task_events).await?;
     } else {
+        // Record the search index update
+        let search_queue = queue_search_outbox(
+            ctx,
+            vec![SearchOutbox::upsert(
+                SearchOutboxKind::CollaborationSync,
+                sync.id,
+            )],
+        )
+        .await?;
+
         // Update the server sync with our server removed.
         sync_handler.save(&mut sync, None).await.map_err(|e| {
             GQLError::new(e.to_string(), GQLErrorCode::ServerSyncUpdateError).extend(|f| {
@@ -546,12 +525,7 @@ pub async fn mutate_collaborations_unlink(
         })?;
 
         // Save in search index
-        let sync_clone = sync.clone();
-        let meili_clone = ctx.data_unchecked::<SearchClientShared>().clone();
-        let task_search = tokio::task::spawn(async move {
-            let sync_search = showtimes_search::models::ServerCollabSync::from(sync_clone);
-            sync_search.update_document(&meili_clone).await
-        });
+        let task_search = search_queue.apply();
 
         // Save in event
         let task_events = ctx
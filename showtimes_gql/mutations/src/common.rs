@@ -309,6 +309,77 @@ pub(crate) async fn execute_search_events(
     }
 }
 
+/// The search index operations that have been recorded into the outbox
+pub(crate) struct QueuedSearchOutbox {
+    db: showtimes_db::DatabaseShared,
+    meili: showtimes_search::SearchClientShared,
+    entries: Vec<showtimes_db::m::SearchOutbox>,
+}
+
+impl QueuedSearchOutbox {
+    /// Merge another recorded operations into this one
+    pub(crate) fn extend(&mut self, other: QueuedSearchOutbox) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Try to apply the recorded operations immediately.
+    ///
+    /// This should only be called after the database write succeeded, the recorded entries
+    /// are pending until then and the background worker will not touch them. The returned task
+    /// never fails on Meilisearch errors since the failed entries are kept in the outbox
+    /// and retried by the background worker.
+    pub(crate) fn apply(self) -> tokio::task::JoinHandle<Result<(), showtimes_search::MeiliError>> {
+        tokio::task::spawn(async move {
+            if self.entries.is_empty() {
+                return Ok(());
+            }
+
+            if let Err(err) =
+                showtimes_search::outbox::apply_entries(&self.db, &self.meili, self.entries).await
+            {
+                tracing::error!("Failed to update search outbox entries: {}", err);
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Record the search index operations into the outbox.
+///
+/// This should be called before the database write, an entry for a write that failed
+/// is harmless since the outbox always re-read the record from the database. Call
+/// [`QueuedSearchOutbox::apply`] after the write succeeded to apply them immediately.
+pub(crate) async fn queue_search_outbox(
+    ctx: &async_graphql::Context<'_>,
+    mut entries: Vec<showtimes_db::m::SearchOutbox>,
+) -> async_graphql::Result<QueuedSearchOutbox> {
+    let db = ctx.data_unchecked::<showtimes_db::DatabaseShared>().clone();
+    let meili = ctx
+        .data_unchecked::<showtimes_search::SearchClientShared>()
+        .clone();
+
+    if !entries.is_empty() {
+        showtimes_db::SearchOutboxHandler::new(&db)
+            .insert(&mut entries)
+            .await
+            .extend_error(
+                showtimes_gql_common::GQLErrorCode::SearchOutboxQueueError,
+                |f_ctx| {
+                    f_ctx.set(
+                        "targets",
+                        entries
+                            .iter()
+                            .map(|e| format!("{}:{}", e.kind.to_name(), e.target))
+                            .collect::<Vec<String>>(),
+                    );
+                },
+            )?;
+    }
+
+    Ok(QueuedSearchOutbox { db, meili, entries })
+}
+
 pub(crate) fn is_string_set(value: &Option<String>) -> bool {
     if let Some(value) = value {
         !value.trim().is_empty()
@@ -324,3 +395,65 @@ pub(crate) fn is_vec_set<T>(value: &Option<Vec<T>>) -> bool {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use showtimes_db::m::{SearchOutbox, SearchOutboxKind};
+
+    use super::*;
+
+    /// Create the outbox with unreachable clients, nothing is connected until a request is made
+    async fn unreachable_outbox(entries: Vec<SearchOutbox>) -> QueuedSearchOutbox {
+        let client = showtimes_db::mongodb::Client::with_uri_str(
+            "mongodb://127.0.0.1:9/?serverSelectionTimeoutMS=100",
+        )
+        .await
+        .unwrap();
+        let meili =
+            meilisearch_sdk::client::Client::new("http://127.0.0.1:9", None::<String>).unwrap();
+
+        QueuedSearchOutbox {
+            db: Arc::new(client.database("showtimes_test")),
+            meili: Arc::new(meili),
+            entries,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_empty() {
+        let outbox = unreachable_outbox(vec![]).await;
+
+        assert!(outbox.apply().await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_extend() {
+        let target = showtimes_shared::ulid_serializer::default();
+        let mut outbox = unreachable_outbox(vec![SearchOutbox::upsert(
+            SearchOutboxKind::Project,
+            target,
+        )])
+        .await;
+        let other =
+            unreachable_outbox(vec![SearchOutbox::delete(SearchOutboxKind::Server, target)]).await;
+
+        outbox.extend(other);
+        assert_eq!(outbox.entries.len(), 2);
+        assert!(outbox.entries.iter().all(|e| e.pending));
+    }
+
+    #[tokio::test]
+    async fn test_apply_never_fails() {
+        let target = showtimes_shared::ulid_serializer::default();
+        let outbox = unreachable_outbox(vec![
+            SearchOutbox::upsert(SearchOutboxKind::Project, target),
+            SearchOutbox::delete(SearchOutboxKind::User, target),
+        ])
+        .await;
+
+        // Both the database and Meilisearch are down, the failure is only logged
+        assert!(outbox.apply().await.unwrap().is_ok());
+    }
+}
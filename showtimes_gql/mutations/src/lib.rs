@@ -11,6 +11,7 @@ mod projects;
 mod rss;
 mod servers;
 mod users;
+mod webhooks;
 
 pub(crate) use common::*;
 
@@ -25,12 +26,17 @@ use showtimes_gql_common::{
 use showtimes_gql_events_models::rss::RSSFeedRenderedGQL;
 use showtimes_gql_models::{
     collaborations::{CollaborationInviteGQL, CollaborationSyncGQL},
+    fansubdb::FansubDBImportGQL,
     projects::ProjectGQL,
-    rss::RSSFeedGQL,
+    rss::{RSSFeedFilterPreviewGQL, RSSFeedGQL},
     servers::{ServerGQL, ServerPremiumGQL},
     users::{APIKeyDataGQL, UserGQL, UserSessionGQL},
+    webhooks::{WebhookActionGQL, WebhookGQL},
+};
+use showtimes_session::{
+    ShowtimesUserSession,
+    manager::{SessionMetadata, SharedSessionManager},
 };
-use showtimes_session::{ShowtimesUserSession, manager::SharedSessionManager};
 
 /// The main Mutation Root type for the GraphQL schema. This is where all the mutation are defined.
 pub struct MutationRoot;
@@ -65,7 +71,7 @@ impl MutationRoot {
                 sessions
                     .lock()
                     .await
-                    .remove_session(jwt.get_token())
+                    .revoke_session(jwt.get_token())
                     .await
                     .extend_error(GQLErrorCode::SessionDeleteError, |f_ctx| {
                         f_ctx.set("token", jwt.get_token());
@@ -77,7 +83,7 @@ impl MutationRoot {
                 sessions
                     .lock()
                     .await
-                    .remove_session(&token)
+                    .revoke_session(&token)
                     .await
                     .extend_error(GQLErrorCode::SessionDeleteError, |f_ctx| {
                         f_ctx.set("token", &token);
@@ -93,6 +99,46 @@ impl MutationRoot {
         }
     }
 
+    /// Revoke a session of the current user.
+    ///
+    /// Admin can revoke the session of another user by providing `user`.
+    #[graphql(
+        name = "revokeSession",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::NotAllowed)"
+    )]
+    async fn revoke_session(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The session ID to revoke")] id: showtimes_gql_common::UlidGQL,
+        #[graphql(desc = "The user who owns the session, only for Admin")] user: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
+    ) -> async_graphql::Result<OkResponse> {
+        users::mutate_users_revoke_session(ctx, id, user).await
+    }
+
+    /// Revoke all the sessions of the current user.
+    ///
+    /// Admin can revoke all the sessions of another user by providing `user`.
+    #[graphql(
+        name = "revokeAllSessions",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::NotAllowed)"
+    )]
+    async fn revoke_all_sessions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The user who owns the sessions, only for Admin")] user: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
+        #[graphql(
+            name = "keepCurrent",
+            desc = "Keep the session used for this request, default to false"
+        )]
+        keep_current: Option<bool>,
+    ) -> async_graphql::Result<OkResponse> {
+        users::mutate_users_revoke_all_sessions(ctx, user, keep_current.unwrap_or(false)).await
+    }
+
     /// Create a new server in Showtimes
     #[graphql(
         name = "createServer",
@@ -182,6 +228,14 @@ impl MutationRoot {
         ctx: &Context<'_>,
         #[graphql(desc = "The user ID to update, when NOT provided will use the current user")] id: Option<showtimes_gql_common::UlidGQL>,
         #[graphql(desc = "The API key capabilities")] input: Option<Vec<APIKeyCapabilityGQL>>,
+        #[graphql(desc = "The label of the API key", validator(max_length = 128))] name: Option<
+            String,
+        >,
+        #[graphql(name = "expiresAt", desc = "When the API key should expire")] expires_at: Option<
+            showtimes_gql_common::DateTimeGQL,
+        >,
+        #[graphql(desc = "The servers the API key is limited to, all servers when NOT provided")]
+        servers: Option<Vec<showtimes_gql_common::UlidGQL>>,
     ) -> async_graphql::Result<APIKeyDataGQL> {
         let user = ctx.data_unchecked::<showtimes_db::m::User>();
 
@@ -192,7 +246,13 @@ impl MutationRoot {
             requested
         };
 
-        users::mutate_users_create_api_key(ctx, requested, input).await
+        let options = users::APIKeyCreateOptions {
+            name,
+            expires_at,
+            servers,
+        };
+
+        users::mutate_users_create_api_key(ctx, requested, input, options).await
     }
 
     /// Update server information
@@ -238,6 +298,77 @@ impl MutationRoot {
         projects::mutate_projects_update(ctx, user_behalf.unwrap_or(user.clone()), id, input).await
     }
 
+    /// Import the server projects from FansubDB
+    ///
+    /// The server must have a FansubDB integration, each project is matched to Anilist
+    /// and created with the released episodes marked as finished.
+    #[graphql(
+        name = "importFansubDB",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageProjects))"
+    )]
+    async fn import_fansubdb(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The server ID to import the projects into")]
+        id: showtimes_gql_common::UlidGQL,
+        #[graphql(
+            name = "dryRun",
+            desc = "Only return what would be created without creating anything, default to false"
+        )]
+        dry_run: Option<bool>,
+    ) -> async_graphql::Result<FansubDBImportGQL> {
+        let user = ctx.data_unchecked::<showtimes_db::m::User>();
+
+        let user_behalf = match ctx.data_unchecked::<Orchestrator>() {
+            Orchestrator::Standalone => None,
+            other => {
+                // Only allow if the user is type is Admin or greater
+                if user.kind >= showtimes_db::m::UserKind::Admin {
+                    other.to_user(ctx).await?
+                } else {
+                    None
+                }
+            }
+        };
+
+        projects::mutate_projects_import_fansubdb(
+            ctx,
+            user_behalf.unwrap_or(user.clone()),
+            id,
+            dry_run.unwrap_or(false),
+        )
+        .await
+    }
+
+    /// Force a full reconciliation of the project release state into FansubDB
+    ///
+    /// The project must have a FansubDB project integration.
+    #[graphql(
+        name = "syncFansubDB",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageProjects))"
+    )]
+    async fn sync_fansubdb(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The project ID to sync")] id: showtimes_gql_common::UlidGQL,
+    ) -> async_graphql::Result<OkResponse> {
+        let user = ctx.data_unchecked::<showtimes_db::m::User>();
+
+        let user_behalf = match ctx.data_unchecked::<Orchestrator>() {
+            Orchestrator::Standalone => None,
+            other => {
+                // Only allow if the user is type is Admin or greater
+                if user.kind >= showtimes_db::m::UserKind::Admin {
+                    other.to_user(ctx).await?
+                } else {
+                    None
+                }
+            }
+        };
+
+        projects::mutate_projects_sync_fansubdb(ctx, user_behalf.unwrap_or(user.clone()), id).await
+    }
+
     /// Add new episode automatically to a project
     ///
     /// This will use the last episode as the base for the new episode
@@ -622,6 +753,20 @@ impl MutationRoot {
             .extend_error(GQLErrorCode::SessionCreateError, |f_ctx| {
                 f_ctx.set("id", id.to_string());
             })?;
+        sess_mutex
+            .track_session(
+                user.id,
+                claims.get_token(),
+                claims.get_claims(),
+                None,
+                &ctx.data_opt::<SessionMetadata>()
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+            .await
+            .extend_error(GQLErrorCode::SessionCreateError, |f_ctx| {
+                f_ctx.set("id", id.to_string());
+            })?;
         drop(sess_mutex);
 
         Ok(UserSessionGQL::new(&user, claims.get_token()))
@@ -678,4 +823,80 @@ impl MutationRoot {
     ) -> async_graphql::Result<Option<RSSFeedRenderedGQL>> {
         rss::mutate_rss_feed_preview(ctx, id, input).await
     }
+
+    /// Preview which of the latest RSS feed entries would pass the filter.
+    ///
+    /// This will request the RSS feed directly, when `filters` is not provided
+    /// the saved filter of the feed will be used.
+    #[graphql(
+        name = "previewRssFeedFilters",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageRSS))"
+    )]
+    async fn preview_rss_feed_filters(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The RSS feed ID to preview")] id: showtimes_gql_common::UlidGQL,
+        #[graphql(
+            desc = "The filter groups to preview, maximum of 10 groups",
+            validator(max_items = 10)
+        )]
+        filters: Option<Vec<rss::RSSFeedFilterGroupInputGQL>>,
+    ) -> async_graphql::Result<Vec<RSSFeedFilterPreviewGQL>> {
+        rss::mutate_rss_feed_preview_filters(ctx, id, filters).await
+    }
+
+    /// Create a new webhook on Showtimes
+    #[graphql(
+        name = "createWebhook",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn create_webhook(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The input to create the webhook")] input: webhooks::WebhookCreateInputGQL,
+    ) -> async_graphql::Result<WebhookGQL> {
+        webhooks::mutate_webhook_create(ctx, input).await
+    }
+
+    /// Update a webhook on Showtimes
+    #[graphql(
+        name = "updateWebhook",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn update_webhook(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The webhook ID to update")] id: showtimes_gql_common::UlidGQL,
+        #[graphql(desc = "The input to update the webhook")] input: webhooks::WebhookUpdateInputGQL,
+    ) -> async_graphql::Result<WebhookGQL> {
+        webhooks::mutate_webhook_update(ctx, id, input).await
+    }
+
+    /// Delete a webhook from Showtimes
+    #[graphql(
+        name = "deleteWebhook",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn delete_webhook(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The webhook ID to delete")] id: showtimes_gql_common::UlidGQL,
+    ) -> async_graphql::Result<OkResponse> {
+        webhooks::mutate_webhook_delete(ctx, id).await
+    }
+
+    /// Send a sample payload to the webhook
+    #[graphql(
+        name = "testWebhook",
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::ManageWebhooks))"
+    )]
+    async fn test_webhook(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The webhook ID to test")] id: showtimes_gql_common::UlidGQL,
+        #[graphql(desc = "The sample action to send, default to the first action of the webhook")]
+        action: Option<WebhookActionGQL>,
+    ) -> async_graphql::Result<OkResponse> {
+        webhooks::mutate_webhook_test(ctx, id, action).await
+    }
 }
@@ -6,11 +6,14 @@ use std::{
 use ahash::HashMapExt;
 use async_graphql::{CustomValidator, Enum, InputObject, Upload, dataloader::DataLoader};
 use jiff::ToSpan;
-use showtimes_db::{DatabaseShared, ProjectHandler, m::UserKind, mongodb::bson::doc};
+use showtimes_db::{
+    DatabaseShared, ProjectHandler,
+    m::{SearchOutbox, SearchOutboxKind, UserKind},
+    mongodb::bson::doc,
+};
 use showtimes_derive::EnumName;
 use showtimes_fs::FsPool;
 use showtimes_metadata::m::AnilistMediaFormat;
-use showtimes_search::SearchClientShared;
 use tokio::{io::AsyncSeekExt, sync::Mutex};
 
 use showtimes_gql_common::{
@@ -21,13 +24,14 @@ use showtimes_gql_common::{
     errors::GQLError,
 };
 use showtimes_gql_models::{
+    fansubdb::{FansubDBImportEntryGQL, FansubDBImportGQL, FansubDBImportStatusGQL},
     projects::{ProjectGQL, ProjectStatusGQL},
     search::ExternalSearchSource,
 };
 
 use crate::{
-    IntegrationActionGQL, IntegrationInputGQL, IntegrationValidator, execute_search_events,
-    is_string_set, is_vec_set,
+    IntegrationActionGQL, IntegrationInputGQL, IntegrationValidator, QueuedSearchOutbox,
+    execute_search_events, is_string_set, is_vec_set, queue_search_outbox,
 };
 
 static JST_TZ: LazyLock<jiff::tz::TimeZone> =
@@ -1105,6 +1109,17 @@ async fn check_permissions(
     }
 }
 
+/// The search outbox entries of the project and its collaborators
+fn project_search_outbox(
+    project: &showtimes_db::m::Project,
+    other_projects: &[showtimes_db::m::Project],
+) -> Vec<SearchOutbox> {
+    std::iter::once(project)
+        .chain(other_projects.iter())
+        .map(|p| SearchOutbox::upsert(SearchOutboxKind::Project, p.id))
+        .collect()
+}
+
 async fn fetch_project_collaborators(
     ctx: &async_graphql::Context<'_>,
     project: &showtimes_db::m::Project,
@@ -1391,51 +1406,10 @@ pub async fn mutate_projects_create(
             )
         }
         (None, Some(poster)) => {
-            let cover_bytes = download_cover(&poster).await?;
-
-            // We ignore errors
-            let dominant_colors = showtimes_metadata::image::get_dominant_colors(&cover_bytes)
-                .ok()
-                .unwrap_or_default();
-
-            if let Some(first_col) = dominant_colors.first() {
-                poster_color_collect = Some(*first_col);
-            }
-
-            let cover_format = poster.split('.').next_back().unwrap_or("jpg");
-
-            let cover_key = format!("cover.{cover_format}");
-
-            let stream = std::io::Cursor::new(cover_bytes);
-
-            storages
-                .file_stream_upload(
-                    project.id,
-                    &cover_key,
-                    stream,
-                    Some(&srv.id.to_string()),
-                    Some(showtimes_fs::FsFileKind::Images),
-                )
-                .await
-                .map_err(|err| {
-                    GQLError::new(
-                        format!("Failed to upload image: {err}"),
-                        GQLErrorCode::ImageUploadError,
-                    )
-                    .extend(|e| {
-                        e.set("id", project.id.to_string());
-                        e.set("where", "project");
-                        e.set("original", format!("{err}"));
-                    })
-                })?;
-
-            showtimes_db::m::ImageMetadata::new(
-                showtimes_fs::FsFileKind::Images.to_name(),
-                project.id,
-                &cover_key,
-                cover_format,
-                Some(srv.id.to_string()),
-            )
+            let (poster_meta, poster_color) =
+                upload_cover_from_url(storages, project.id, srv.id, &poster).await?;
+            poster_color_collect = poster_color;
+            poster_meta
         }
         (None, None) => showtimes_db::m::ImageMetadata::new(
             showtimes_fs::FsFileKind::Invalids.to_name(),
@@ -1472,6 +1446,13 @@ pub async fn mutate_projects_create(
             .await
     });
 
+    // Record the index update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(SearchOutboxKind::Project, project.id)],
+    )
+    .await?;
+
     // Save the project
     let prj_handler = ProjectHandler::new(db);
     prj_handler.save(&mut project, None).await.extend_error(
@@ -1484,10 +1465,7 @@ pub async fn mutate_projects_create(
     )?;
 
     // Update index
-    let prj_search = showtimes_search::models::Project::from(&project);
-    let meili_clone = ctx.data_unchecked::<SearchClientShared>().clone();
-    let task_search =
-        tokio::task::spawn(async move { prj_search.update_document(&meili_clone).await });
+    let task_search = search_queue.apply();
 
     execute_search_events(task_search, task_events).await?;
 
@@ -1567,6 +1545,59 @@ async fn download_cover(url: &str) -> async_graphql::Result<Vec<u8>> {
     Ok(bytes_map)
 }
 
+/// Download the cover from the URL and upload it to the storage, returning the
+/// image metadata and the dominant color if available.
+async fn upload_cover_from_url(
+    storages: &FsPool,
+    project_id: showtimes_shared::ulid::Ulid,
+    server_id: showtimes_shared::ulid::Ulid,
+    poster: &str,
+) -> async_graphql::Result<(showtimes_db::m::ImageMetadata, Option<u32>)> {
+    let cover_bytes = download_cover(poster).await?;
+
+    // We ignore errors
+    let dominant_colors = showtimes_metadata::image::get_dominant_colors(&cover_bytes)
+        .ok()
+        .unwrap_or_default();
+
+    let cover_format = poster.split('.').next_back().unwrap_or("jpg");
+
+    let cover_key = format!("cover.{cover_format}");
+
+    let stream = std::io::Cursor::new(cover_bytes);
+
+    storages
+        .file_stream_upload(
+            project_id,
+            &cover_key,
+            stream,
+            Some(&server_id.to_string()),
+            Some(showtimes_fs::FsFileKind::Images),
+        )
+        .await
+        .map_err(|err| {
+            GQLError::new(
+                format!("Failed to upload image: {err}"),
+                GQLErrorCode::ImageUploadError,
+            )
+            .extend(|e| {
+                e.set("id", project_id.to_string());
+                e.set("where", "project");
+                e.set("original", format!("{err}"));
+            })
+        })?;
+
+    let image = showtimes_db::m::ImageMetadata::new(
+        showtimes_fs::FsFileKind::Images.to_name(),
+        project_id,
+        &cover_key,
+        cover_format,
+        Some(server_id.to_string()),
+    );
+
+    Ok((image, dominant_colors.first().copied()))
+}
+
 pub async fn mutate_projects_delete(
     ctx: &async_graphql::Context<'_>,
     user: showtimes_db::m::User,
@@ -1576,7 +1607,6 @@ pub async fn mutate_projects_delete(
     let prj_loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
     let db = ctx.data_unchecked::<DatabaseShared>();
     let storages = ctx.data_unchecked::<Arc<FsPool>>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
 
     // Fetch project
     let prj_info = prj_loader.load_one(*id).await?.ok_or_else(|| {
@@ -1652,6 +1682,16 @@ pub async fn mutate_projects_delete(
         if let Some(collab_project) = collab_project {
             // If only 1 or zero, delete this link
             if collab_info.length() < 2 {
+                // Record the search engine removal
+                let search_queue = queue_search_outbox(
+                    ctx,
+                    vec![SearchOutbox::delete(
+                        SearchOutboxKind::CollaborationSync,
+                        collab_info.id,
+                    )],
+                )
+                .await?;
+
                 // Delete from DB
                 collab_handler.delete(&collab_info).await.extend_error(
                     GQLErrorCode::ServerSyncDeleteError,
@@ -1663,25 +1703,7 @@ pub async fn mutate_projects_delete(
                 )?;
 
                 // Delete from search engine
-                let collab_search =
-                    showtimes_search::models::ServerCollabSync::from(collab_info.clone());
-                collab_search.delete_document(meili).await.extend_error(
-                    GQLErrorCode::ServerSyncDeleteSearchError,
-                    |e| {
-                        e.set("id", prj_info.id.to_string());
-                        e.set("server", srv.id.to_string());
-                        e.set("collab_id", collab_info.id.to_string());
-                    },
-                )?;
-
-                // Delete from search engine
-                let collab_clone = collab_info.clone();
-                let meili_clone = meili.clone();
-                let task_search = tokio::task::spawn(async move {
-                    let collab_search =
-                        showtimes_search::models::ServerCollabSync::from(collab_clone);
-                    collab_search.delete_document(&meili_clone).await
-                });
+                let task_search = search_queue.apply();
                 // Create task events
                 let task_events = ctx
                     .data_unchecked::<showtimes_events::SharedSHClickHouse>()
@@ -1701,6 +1723,16 @@ pub async fn mutate_projects_delete(
 
                 execute_search_events(task_search, task_events).await?;
             } else {
+                // Record the search engine update
+                let search_queue = queue_search_outbox(
+                    ctx,
+                    vec![SearchOutbox::upsert(
+                        SearchOutboxKind::CollaborationSync,
+                        collab_info.id,
+                    )],
+                )
+                .await?;
+
                 // Save the collab
                 collab_handler
                     .save(&mut collab_info, None)
@@ -1712,13 +1744,7 @@ pub async fn mutate_projects_delete(
                     })?;
 
                 // Update search engine
-                let collab_clone = collab_info.clone();
-                let meili_clone = meili.clone();
-                let task_search = tokio::task::spawn(async move {
-                    let collab_search =
-                        showtimes_search::models::ServerCollabSync::from(collab_clone);
-                    collab_search.update_document(&meili_clone).await
-                });
+                let task_search = search_queue.apply();
                 // Create task events
                 let task_events = ctx
                     .data_unchecked::<showtimes_events::SharedSHClickHouse>()
@@ -1766,6 +1792,16 @@ pub async fn mutate_projects_delete(
         .collect::<Vec<String>>();
 
     if !all_ids.is_empty() {
+        // Record the search engine removal
+        let search_queue = queue_search_outbox(
+            ctx,
+            collab_invite_info
+                .iter()
+                .map(|c| SearchOutbox::delete(SearchOutboxKind::CollaborationInvite, c.id))
+                .collect(),
+        )
+        .await?;
+
         // Delete from DB
         collab_invite_handler
             .delete_by(doc! {
@@ -1781,20 +1817,7 @@ pub async fn mutate_projects_delete(
             })?;
 
         // Delete from search engine
-        let index_invite = meili.index(showtimes_search::models::ServerCollabInvite::index_name());
-
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            match index_invite.delete_documents(&all_ids).await {
-                Ok(task_del) => {
-                    match task_del.wait_for_completion(&meili_clone, None, None).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        });
+        let task_search = search_queue.apply();
 
         let deleted_events: Vec<showtimes_events::m::CollabRetractedEvent> = collab_invite_info
             .iter()
@@ -1840,6 +1863,13 @@ pub async fn mutate_projects_delete(
             })?;
     }
 
+    // Record the search engine removal
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::delete(SearchOutboxKind::Project, prj_info.id)],
+    )
+    .await?;
+
     // Delete project
     let prj_handler = ProjectHandler::new(db);
     prj_handler
@@ -1851,18 +1881,13 @@ pub async fn mutate_projects_delete(
         })?;
 
     // Delete from search engine
-    let prj_clone = prj_info.clone();
-    let meili_clone = meili.clone();
-    let task_search = tokio::task::spawn(async move {
-        let project_search = showtimes_search::models::Project::from(prj_clone);
-        project_search.delete_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
     // Create task events
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
         .create_event_async(
             showtimes_events::m::EventKind::ProjectDeleted,
-            showtimes_events::m::ProjectCreatedEvent::from(&prj_info),
+            showtimes_events::m::ProjectDeletedEvent::from(&prj_info),
             if user.kind == UserKind::Owner {
                 None
             } else {
@@ -2345,6 +2370,10 @@ pub async fn mutate_projects_update(
             prj_info.status = new_status.into();
             after_update.set_status(prj_info.status);
 
+            // Record the search update
+            let proj_search = vec![SearchOutbox::upsert(SearchOutboxKind::Project, prj_info.id)];
+            let search_queue = queue_search_outbox(ctx, proj_search).await?;
+
             // Save the project
             prj_handler.save(&mut prj_info, None).await.extend_error(
                 GQLErrorCode::ProjectUpdateError,
@@ -2356,26 +2385,7 @@ pub async fn mutate_projects_update(
             )?;
 
             // Save search results
-            let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-            let proj_search = vec![showtimes_search::models::Project::from(&prj_info)];
-            let o_project_index = meili.index(showtimes_search::models::Project::index_name());
-            let task_search = tokio::task::spawn(async move {
-                match o_project_index
-                    .add_or_update(
-                        &proj_search,
-                        Some(showtimes_search::models::Project::primary_key()),
-                    )
-                    .await
-                {
-                    Ok(o_project_task) => {
-                        match o_project_task.wait_for_completion(&meili, None, None).await {
-                            Ok(_) => Ok(()),
-                            Err(e) => Err(e),
-                        }
-                    }
-                    Err(e) => Err(e),
-                }
-            });
+            let task_search = search_queue.apply();
 
             // Save the project update event
             let task_events = ctx
@@ -2412,6 +2422,11 @@ pub async fn mutate_projects_update(
                 prj_info.status = new_status_db;
                 after_update.set_status(prj_info.status);
 
+                // Record the search update
+                let proj_search =
+                    vec![SearchOutbox::upsert(SearchOutboxKind::Project, prj_info.id)];
+                let search_queue = queue_search_outbox(ctx, proj_search).await?;
+
                 // Save the project
                 prj_handler.save(&mut prj_info, None).await.extend_error(
                     GQLErrorCode::ProjectUpdateError,
@@ -2423,26 +2438,7 @@ pub async fn mutate_projects_update(
                 )?;
 
                 // Save search results
-                let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-                let proj_search = vec![showtimes_search::models::Project::from(&prj_info)];
-                let o_project_index = meili.index(showtimes_search::models::Project::index_name());
-                let task_search = tokio::task::spawn(async move {
-                    match o_project_index
-                        .add_or_update(
-                            &proj_search,
-                            Some(showtimes_search::models::Project::primary_key()),
-                        )
-                        .await
-                    {
-                        Ok(o_project_task) => {
-                            match o_project_task.wait_for_completion(&meili, None, None).await {
-                                Ok(_) => Ok(()),
-                                Err(e) => Err(e),
-                            }
-                        }
-                        Err(e) => Err(e),
-                    }
-                });
+                let task_search = search_queue.apply();
 
                 // Save the project update event
                 let task_events = ctx
@@ -2563,6 +2559,12 @@ pub async fn mutate_projects_update(
     )
     .await?;
 
+    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
+
+    // Record the search results of all projects before saving
+    let search_queue =
+        queue_search_outbox(ctx, project_search_outbox(&prj_info, &other_projects)).await?;
+
     // Save the project
     prj_handler.save(&mut prj_info, None).await.extend_error(
         GQLErrorCode::ProjectUpdateError,
@@ -2573,17 +2575,11 @@ pub async fn mutate_projects_update(
         },
     )?;
 
-    // Search results
-    let mut all_project_search: Vec<showtimes_search::models::Project> =
-        vec![showtimes_search::models::Project::from(&prj_info)];
-
     // Project update events
     let mut all_project_update_events = vec![project_event];
     let mut all_project_episodes_events = vec![];
     all_project_episodes_events.extend(episode_event);
 
-    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
-
     for o_project in other_projects.iter_mut() {
         let (project_event, episode_event) = update_single_project(
             ctx,
@@ -2606,35 +2602,13 @@ pub async fn mutate_projects_update(
             },
         )?;
 
-        // Create search results
-        let project_search = showtimes_search::models::Project::from(&prj_info);
-        all_project_search.push(project_search);
-
         // Push events
         all_project_update_events.push(project_event);
         all_project_episodes_events.extend(episode_event);
     }
 
     // Save the search results
-    let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-    let o_project_index = meili.index(showtimes_search::models::Project::index_name());
-    let task_search = tokio::task::spawn(async move {
-        match o_project_index
-            .add_or_update(
-                &all_project_search,
-                Some(showtimes_search::models::Project::primary_key()),
-            )
-            .await
-        {
-            Ok(o_project_task) => {
-                match o_project_task.wait_for_completion(&meili, None, None).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => Err(e),
-                }
-            }
-            Err(e) => Err(e),
-        }
-    });
+    let task_search = search_queue.apply();
 
     // Save the project update event
     let task_project_events = ctx
@@ -2749,9 +2723,8 @@ pub async fn mutate_projects_episode_add_auto(
         showtimes_events::m::ProjectUpdatedEvent::new(project.id, before_project, after_project)
     }
 
-    // Events and search
+    // Events
     let mut all_events_content = vec![];
-    let mut all_search_contents = vec![];
 
     if prj_info.progress.is_empty() {
         return GQLError::new(
@@ -2764,6 +2737,25 @@ pub async fn mutate_projects_episode_add_auto(
         .into();
     }
 
+    // Fetch collaborators
+    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
+    if let Some(project) = other_projects.iter().find(|p| p.progress.is_empty()) {
+        return GQLError::new(
+            "Collab project has no episodes",
+            GQLErrorCode::ProjectEmptyEpisodes,
+        )
+        .extend(|e| {
+            e.set("id", project.id.to_string());
+            e.set("server", project.creator.to_string());
+            e.set("root_project", id.to_string());
+        })
+        .into();
+    }
+
+    // Record the search results before saving
+    let search_queue =
+        queue_search_outbox(ctx, project_search_outbox(&prj_info, &other_projects)).await?;
+
     // Save the project
     let project_event = update_project_inner(&mut prj_info, count);
     prj_handler.save(&mut prj_info, None).await.extend_error(
@@ -2776,26 +2768,10 @@ pub async fn mutate_projects_episode_add_auto(
         },
     )?;
 
-    // Push to search and events
-    all_search_contents.push(showtimes_search::models::Project::from(&prj_info));
+    // Push to events
     all_events_content.push(project_event);
 
-    // Fetch collaborators
-    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
     for project in other_projects.iter_mut() {
-        if project.progress.is_empty() {
-            return GQLError::new(
-                "Collab project has no episodes",
-                GQLErrorCode::ProjectEmptyEpisodes,
-            )
-            .extend(|e| {
-                e.set("id", project.id.to_string());
-                e.set("server", project.creator.to_string());
-                e.set("root_project", id.to_string());
-            })
-            .into();
-        }
-
         let project_event = update_project_inner(project, count);
         // Save other project
         prj_handler.save(project, None).await.extend_error(
@@ -2809,28 +2785,11 @@ pub async fn mutate_projects_episode_add_auto(
             },
         )?;
 
-        // Push to search and events
-        all_search_contents.push(showtimes_search::models::Project::from(project));
+        // Push to events
         all_events_content.push(project_event);
     }
 
-    let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-    let task_search = tokio::task::spawn(async move {
-        let search_index = meili.index(showtimes_search::models::Project::index_name());
-        match search_index
-            .add_or_update(
-                &all_search_contents,
-                Some(showtimes_search::models::Project::primary_key()),
-            )
-            .await
-        {
-            Ok(search_task) => match search_task.wait_for_completion(&meili, None, None).await {
-                Ok(_) => Ok(()),
-                Err(e) => Err(e),
-            },
-            Err(e) => Err(e),
-        }
-    });
+    let task_search = search_queue.apply();
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
         .create_event_many_async(
@@ -2931,6 +2890,13 @@ pub async fn mutate_projects_episode_add_manual(
         showtimes_events::m::ProjectUpdatedEvent::new(project.id, before_project, after_project)
     }
 
+    // Sync the collaborations
+    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
+
+    // Record the search results before saving
+    let search_queue =
+        queue_search_outbox(ctx, project_search_outbox(&prj_info, &other_projects)).await?;
+
     let project_event = update_project_inner(&mut prj_info, episodes);
     let mut all_events_content = vec![project_event];
 
@@ -2952,10 +2918,6 @@ pub async fn mutate_projects_episode_add_manual(
             e.set("episodes", episodes_dumps);
         },
     )?;
-    let mut all_search_contents = vec![showtimes_search::models::Project::from(&prj_info)];
-
-    // Sync the collaborations
-    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
 
     for project in other_projects.iter_mut() {
         let project_event = update_project_inner(project, episodes);
@@ -2979,31 +2941,12 @@ pub async fn mutate_projects_episode_add_manual(
             },
         )?;
 
-        // Push to search and events
-        all_search_contents.push(showtimes_search::models::Project::from(project));
+        // Push to events
         all_events_content.push(project_event);
     }
 
     // Save the search results
-    let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-    let o_project_index = meili.index(showtimes_search::models::Project::index_name());
-    let task_search = tokio::task::spawn(async move {
-        match o_project_index
-            .add_or_update(
-                &all_search_contents,
-                Some(showtimes_search::models::Project::primary_key()),
-            )
-            .await
-        {
-            Ok(o_project_task) => {
-                match o_project_task.wait_for_completion(&meili, None, None).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => Err(e),
-                }
-            }
-            Err(e) => Err(e),
-        }
-    });
+    let task_search = search_queue.apply();
 
     // Create task events
     let task_events = ctx
@@ -3080,6 +3023,13 @@ pub async fn mutate_projects_episode_remove(
         showtimes_events::m::ProjectUpdatedEvent::new(project.id, before_project, after_project)
     }
 
+    // Sync the collaborations
+    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
+
+    // Record the search results before saving
+    let search_queue =
+        queue_search_outbox(ctx, project_search_outbox(&prj_info, &other_projects)).await?;
+
     let project_event = update_project_inner(&mut prj_info, episodes);
     let mut all_events_content = vec![project_event];
 
@@ -3094,12 +3044,6 @@ pub async fn mutate_projects_episode_remove(
         },
     )?;
 
-    // Create search results
-    let mut all_search_contents = vec![showtimes_search::models::Project::from(&prj_info)];
-
-    // Sync the collaborations
-    let mut other_projects = fetch_project_collaborators(ctx, &prj_info).await?;
-
     for project in other_projects.iter_mut() {
         let project_event = update_project_inner(project, episodes);
         // Save other project
@@ -3113,31 +3057,12 @@ pub async fn mutate_projects_episode_remove(
             },
         )?;
 
-        // Push to search and events
-        all_search_contents.push(showtimes_search::models::Project::from(project));
+        // Push to events
         all_events_content.push(project_event);
     }
 
     // Save the search results
-    let meili = ctx.data_unchecked::<SearchClientShared>().clone();
-    let o_project_index = meili.index(showtimes_search::models::Project::index_name());
-    let task_search = tokio::task::spawn(async move {
-        match o_project_index
-            .add_or_update(
-                &all_search_contents,
-                Some(showtimes_search::models::Project::primary_key()),
-            )
-            .await
-        {
-            Ok(o_project_task) => {
-                match o_project_task.wait_for_completion(&meili, None, None).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => Err(e),
-                }
-            }
-            Err(e) => Err(e),
-        }
-    });
+    let task_search = search_queue.apply();
 
     // Create task events
     let task_events = ctx
@@ -3159,3 +3084,435 @@ pub async fn mutate_projects_episode_remove(
     let prj_gql = ProjectGQL::from(&prj_info);
     Ok(prj_gql)
 }
+
+pub async fn mutate_projects_sync_fansubdb(
+    ctx: &async_graphql::Context<'_>,
+    user: showtimes_db::m::User,
+    id: UlidGQL,
+) -> async_graphql::Result<OkResponse> {
+    let prj_loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
+
+    let fansubdb = ctx
+        .data_opt::<Arc<showtimes_fansubdb::FansubDBSync>>()
+        .ok_or_else(|| {
+            GQLError::new(
+                "FansubDB integration is not configured",
+                GQLErrorCode::FansubDBNotConfigured,
+            )
+            .extend(|e| e.set("id", id.to_string()))
+        })?;
+
+    // Fetch project
+    let prj_info = prj_loader.load_one(*id).await?.ok_or_else(|| {
+        GQLError::new("Project not found", GQLErrorCode::ProjectNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    // Check perms
+    check_permissions(ctx, prj_info.creator, &user, Some(prj_info.id)).await?;
+
+    let result = fansubdb.reconcile(&prj_info).await.map_err(|err| {
+        let code = match &err {
+            showtimes_fansubdb::sync::FansubDBSyncError::MissingIntegration(_)
+            | showtimes_fansubdb::sync::FansubDBSyncError::InvalidIntegration(_) => {
+                GQLErrorCode::FansubDBInvalidIntegration
+            }
+            _ => GQLErrorCode::FansubDBRequestFails,
+        };
+
+        GQLError::new(err.to_string(), code).extend(|e| {
+            e.set("id", id.to_string());
+            e.set("server", prj_info.creator.to_string());
+            e.set("retryable", err.is_retryable());
+        })
+    })?;
+
+    if result.changed {
+        Ok(OkResponse::ok(format!(
+            "FansubDB project {} updated to {:?} with {} released episodes",
+            result.state.id, result.state.status, result.state.progress
+        )))
+    } else {
+        Ok(OkResponse::ok(format!(
+            "FansubDB project {} is already up to date",
+            result.state.id
+        )))
+    }
+}
+
+/// Match a FansubDB anime to Anilist, using the MyAnimeList ID first then the title
+async fn match_fansubdb_anilist(
+    ctx: &async_graphql::Context<'_>,
+    anime: &showtimes_fansubdb::m::FansubDBAnime,
+) -> async_graphql::Result<Option<i32>> {
+    let anilist_loader = ctx.data_unchecked::<Arc<Mutex<showtimes_metadata::AnilistProvider>>>();
+    let mut anilist = anilist_loader.lock().await;
+
+    if let Some(mal_id) = anime.mal_id.and_then(|id| i32::try_from(id).ok()) {
+        match anilist.get_media_by_mal(mal_id).await {
+            Ok(media) => return Ok(Some(media.id)),
+            Err(err) => {
+                tracing::debug!(
+                    "Failed to match MAL ID {} to Anilist, falling back to title: {}",
+                    mal_id,
+                    err
+                );
+            }
+        }
+    }
+
+    let results = anilist.search(&anime.title).await.map_err(|err| {
+        GQLError::new(err.to_string(), GQLErrorCode::MetadataAnilistRequestError).extend(|e| {
+            e.set("query", &anime.title);
+            e.set("source", "anilist");
+        })
+    })?;
+
+    // Only accept an exact match, a wrong guess is worse than no guess
+    let matched = results.iter().find(|media| {
+        if media.kind != showtimes_metadata::m::AnilistMediaType::Anime {
+            return false;
+        }
+
+        if let (Some(mal_id), Some(media_mal)) = (anime.mal_id, media.id_mal) {
+            return mal_id == media_mal as u64;
+        }
+
+        [
+            &media.title.romaji,
+            &media.title.english,
+            &media.title.native,
+        ]
+        .iter()
+        .filter_map(|title| title.as_deref())
+        .any(|title| title.eq_ignore_ascii_case(&anime.title))
+    });
+
+    Ok(matched.map(|media| media.id))
+}
+
+pub async fn mutate_projects_import_fansubdb(
+    ctx: &async_graphql::Context<'_>,
+    user: showtimes_db::m::User,
+    id: UlidGQL,
+    dry_run: bool,
+) -> async_graphql::Result<FansubDBImportGQL> {
+    let db = ctx.data_unchecked::<DatabaseShared>();
+    let storages = ctx.data_unchecked::<Arc<FsPool>>();
+
+    let fansubdb = ctx
+        .data_opt::<Arc<showtimes_fansubdb::FansubDBSync>>()
+        .ok_or_else(|| {
+            GQLError::new(
+                "FansubDB integration is not configured",
+                GQLErrorCode::FansubDBNotConfigured,
+            )
+            .extend(|e| e.set("id", id.to_string()))
+        })?;
+
+    // Check perms
+    let srv = check_permissions(ctx, *id, &user, None).await?;
+
+    let fansub_integration = srv
+        .integrations
+        .iter()
+        .find(|i| i.kind() == &showtimes_db::m::IntegrationType::FansubDB)
+        .ok_or_else(|| {
+            GQLError::new(
+                "Server does not have FansubDB integration",
+                GQLErrorCode::FansubDBInvalidIntegration,
+            )
+            .extend(|e| e.set("id", srv.id.to_string()))
+        })?;
+    let fansub_id = fansub_integration.id().parse::<u64>().map_err(|_| {
+        GQLError::new(
+            "Invalid FansubDB ID on server integration",
+            GQLErrorCode::FansubDBInvalidIntegration,
+        )
+        .extend(|e| {
+            e.set("id", srv.id.to_string());
+            e.set("fansubdb_id", fansub_integration.id());
+        })
+    })?;
+
+    let fsdb_projects = fansubdb
+        .client()
+        .get_fansub_projects(fansub_id)
+        .await
+        .map_err(|err| {
+            GQLError::new(err.to_string(), GQLErrorCode::FansubDBRequestFails).extend(|e| {
+                e.set("id", srv.id.to_string());
+                e.set("fansubdb_id", fansub_id);
+            })
+        })?;
+
+    // Used to avoid importing the same project twice
+    let prj_handler = ProjectHandler::new(db);
+    let mut existing_integrations: Vec<showtimes_db::m::IntegrationId> = prj_handler
+        .find_all_by(doc! { "creator": srv.id.to_string() })
+        .await
+        .extend_error(GQLErrorCode::ProjectRequestFails, |e| {
+            e.set("id", srv.id.to_string());
+        })?
+        .into_iter()
+        .flat_map(|project| project.integrations)
+        .collect();
+
+    let mut entries: Vec<FansubDBImportEntryGQL> = vec![];
+    let mut created_projects: Vec<showtimes_db::m::Project> = vec![];
+    let mut search_queue: Option<QueuedSearchOutbox> = None;
+    for fsdb_project in fsdb_projects {
+        let entry_title = fsdb_project.anime.title.clone();
+
+        let fsdb_integration = showtimes_db::m::IntegrationId::new(
+            fsdb_project.id.to_string(),
+            showtimes_db::m::IntegrationType::FansubDBProject,
+        );
+        if existing_integrations.contains(&fsdb_integration) {
+            entries.push(FansubDBImportEntryGQL::new(
+                fsdb_project.id,
+                entry_title,
+                FansubDBImportStatusGQL::Exists,
+            ));
+            continue;
+        }
+
+        if fsdb_project.status == showtimes_fansubdb::m::FansubDBProjectStatus::Dropped {
+            entries.push(
+                FansubDBImportEntryGQL::new(
+                    fsdb_project.id,
+                    entry_title,
+                    FansubDBImportStatusGQL::Skipped,
+                )
+                .with_message("Project is dropped on FansubDB"),
+            );
+            continue;
+        }
+
+        let anilist_id = match match_fansubdb_anilist(ctx, &fsdb_project.anime).await {
+            Ok(Some(anilist_id)) => anilist_id,
+            Ok(None) => {
+                entries.push(
+                    FansubDBImportEntryGQL::new(
+                        fsdb_project.id,
+                        entry_title,
+                        FansubDBImportStatusGQL::Unmatched,
+                    )
+                    .with_message("No matching media found on Anilist"),
+                );
+                continue;
+            }
+            Err(err) => {
+                entries.push(
+                    FansubDBImportEntryGQL::new(
+                        fsdb_project.id,
+                        entry_title,
+                        FansubDBImportStatusGQL::Failed,
+                    )
+                    .with_message(err.message),
+                );
+                continue;
+            }
+        };
+        let failed_entry = FansubDBImportEntryGQL::new(
+            fsdb_project.id,
+            entry_title,
+            FansubDBImportStatusGQL::Failed,
+        )
+        .with_anilist(anilist_id);
+
+        let anilist_integration = showtimes_db::m::IntegrationId::new(
+            anilist_id.to_string(),
+            showtimes_db::m::IntegrationType::ProviderAnilist,
+        );
+        if existing_integrations.contains(&anilist_integration) {
+            entries.push(
+                FansubDBImportEntryGQL::new(
+                    fsdb_project.id,
+                    &fsdb_project.anime.title,
+                    FansubDBImportStatusGQL::Exists,
+                )
+                .with_anilist(anilist_id)
+                .with_message("Anilist media is already used by another project"),
+            );
+            continue;
+        }
+
+        let metadata_input = ProjectCreateMetadataInputGQL {
+            id: anilist_id.to_string(),
+            kind: ExternalSearchSource::Anilist,
+            episode: fsdb_project
+                .anime
+                .episode
+                .and_then(|ep| i32::try_from(ep).ok()),
+            start_date: None,
+        };
+        let metadata = match fetch_metadata_via_anilist(ctx, &metadata_input).await {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                entries.push(failed_entry.with_message(err.message));
+                continue;
+            }
+        };
+
+        let mut project =
+            match showtimes_db::m::Project::new(&metadata.title, metadata.kind, srv.id) {
+                Ok(project) => project,
+                Err(err) => {
+                    entries.push(failed_entry.with_message(err.to_string()));
+                    continue;
+                }
+            };
+
+        // Pre-mark the released episodes, finished project has everything released
+        let released = match fsdb_project.status {
+            showtimes_fansubdb::m::FansubDBProjectStatus::Finished => u64::MAX,
+            _ => fsdb_project.progress.unwrap_or(0),
+        };
+        let all_roles = metadata.kind.kind().default_roles();
+        let mut all_progress: Vec<showtimes_db::m::EpisodeProgress> = vec![];
+        for episode in metadata.progress {
+            let number = episode.number as u64;
+            let finished = number <= released;
+            let mut progress =
+                showtimes_db::m::EpisodeProgress::new_with_roles(number, finished, &all_roles);
+            if finished {
+                progress
+                    .statuses
+                    .iter_mut()
+                    .for_each(|s| s.set_finished(true));
+            }
+            progress.set_aired(episode.aired_at);
+            all_progress.push(progress);
+        }
+
+        let assignees: Result<Vec<showtimes_db::m::RoleAssignee>, _> = all_roles
+            .iter()
+            .map(|role| showtimes_db::m::RoleAssignee::new(role.key(), None))
+            .collect();
+        project.assignees = match assignees {
+            Ok(assignees) => assignees,
+            Err(err) => {
+                entries.push(failed_entry.with_message(err.to_string()));
+                continue;
+            }
+        };
+        project.roles = all_roles;
+        project.progress = all_progress;
+        project.aliases = metadata.aliases;
+        project.integrations = metadata.integrations;
+        project.add_integration(fsdb_integration);
+        project.add_integration(showtimes_db::m::IntegrationId::new(
+            fsdb_project.anime.id.to_string(),
+            showtimes_db::m::IntegrationType::FansubDBShows,
+        ));
+        if !project.progress.is_empty() && project.progress.iter().all(|p| p.is_finished()) {
+            project.status = showtimes_db::m::ProjectStatus::Archived;
+        }
+
+        existing_integrations.extend(project.integrations.iter().cloned());
+
+        if dry_run {
+            entries.push(
+                FansubDBImportEntryGQL::new(
+                    fsdb_project.id,
+                    &fsdb_project.anime.title,
+                    FansubDBImportStatusGQL::Planned,
+                )
+                .with_anilist(anilist_id)
+                .with_project(&project),
+            );
+            continue;
+        }
+
+        // Poster failure should not stop the import
+        let mut poster_color: Option<u32> = None;
+        let poster_image = match &metadata.poster_url {
+            Some(poster_url) => {
+                match upload_cover_from_url(storages, project.id, srv.id, poster_url).await {
+                    Ok((image, color)) => {
+                        poster_color = color;
+                        Some(image)
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to upload cover for imported project {}: {}",
+                            project.id,
+                            err.message
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let poster_image = poster_image.unwrap_or_else(|| {
+            showtimes_db::m::ImageMetadata::new(
+                showtimes_fs::FsFileKind::Invalids.to_name(),
+                "project",
+                "default.png",
+                "png",
+                None::<String>,
+            )
+        });
+        project.poster =
+            showtimes_db::m::Poster::new_with_color(poster_image, poster_color.unwrap_or(16614485));
+
+        let queued = match queue_search_outbox(
+            ctx,
+            vec![SearchOutbox::upsert(SearchOutboxKind::Project, project.id)],
+        )
+        .await
+        {
+            Ok(queued) => queued,
+            Err(err) => {
+                entries.push(failed_entry.with_message(err.message));
+                continue;
+            }
+        };
+
+        if let Err(err) = prj_handler.save(&mut project, None).await {
+            entries.push(failed_entry.with_message(err.to_string()));
+            continue;
+        }
+
+        match search_queue.as_mut() {
+            Some(search_queue) => search_queue.extend(queued),
+            None => search_queue = Some(queued),
+        }
+
+        entries.push(
+            FansubDBImportEntryGQL::new(
+                fsdb_project.id,
+                &fsdb_project.anime.title,
+                FansubDBImportStatusGQL::Created,
+            )
+            .with_anilist(anilist_id)
+            .with_project(&project),
+        );
+        created_projects.push(project);
+    }
+
+    if let Some(search_queue) = search_queue {
+        let task_search = search_queue.apply();
+
+        let task_events = ctx
+            .data_unchecked::<showtimes_events::SharedSHClickHouse>()
+            .create_event_many_async(
+                showtimes_events::m::EventKind::ProjectCreated,
+                created_projects
+                    .iter()
+                    .map(showtimes_events::m::ProjectCreatedEvent::from)
+                    .collect(),
+                if user.kind == UserKind::Owner {
+                    None
+                } else {
+                    Some(user.id.to_string())
+                },
+            );
+
+        execute_search_events(task_search, task_events).await?;
+    }
+
+    Ok(FansubDBImportGQL::new(dry_run, entries))
+}
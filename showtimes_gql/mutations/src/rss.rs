@@ -9,7 +9,10 @@ use showtimes_gql_common::{
     errors::GQLError,
 };
 use showtimes_gql_events_models::rss::{RSSFeedRenderedGQL, render_feed_display_with_entry};
-use showtimes_gql_models::rss::RSSFeedGQL;
+use showtimes_gql_models::rss::{
+    RSSFeedFilterActionGQL, RSSFeedFilterFieldGQL, RSSFeedFilterKindGQL, RSSFeedFilterModeGQL,
+    RSSFeedFilterPreviewGQL, RSSFeedGQL,
+};
 
 use crate::{
     IntegrationActionGQL, IntegrationInputGQL, IntegrationValidator, is_string_set, is_vec_set,
@@ -29,6 +32,16 @@ pub struct RSSFeedCreateInputGQL {
         custom = "IntegrationValidator::with_limit(vec![IntegrationActionGQL::Add])"
     ))]
     integrations: Option<Vec<IntegrationInputGQL>>,
+    /// The filter groups of the RSS feed.
+    ///
+    /// An entry is only published when every group passes, maximum of 10 groups.
+    #[graphql(validator(max_items = 10))]
+    filters: Option<Vec<RSSFeedFilterGroupInputGQL>>,
+    /// The Discord webhooks of the channel integrations.
+    ///
+    /// New entries are posted directly to the channel through the webhook, maximum of 10 webhooks.
+    #[graphql(validator(max_items = 10))]
+    webhooks: Option<Vec<RSSFeedWebhookInputGQL>>,
 }
 
 impl RSSFeedCreateInputGQL {
@@ -180,6 +193,163 @@ impl RSSFeedDisplayUpdateInputGQL {
     }
 }
 
+/// The maximum amount of entries returned by the filter preview
+const FILTER_PREVIEW_LIMIT: usize = 50;
+
+/// A single RSS filter rule input
+#[derive(InputObject, Clone)]
+#[graphql(name = "RSSFeedFilterRuleInputGQL")]
+pub struct RSSFeedFilterRuleInputGQL {
+    /// The entry field to check
+    field: RSSFeedFilterFieldGQL,
+    /// How the pattern is matched, default to `KEYWORD`
+    kind: Option<RSSFeedFilterKindGQL>,
+    /// The keyword or regular expression, maximum of 200 characters
+    #[graphql(validator(min_length = 1, max_length = 200))]
+    pattern: String,
+    /// Include or exclude the entry when matched, default to `INCLUDE`
+    action: Option<RSSFeedFilterActionGQL>,
+}
+
+/// A group of RSS filter rules input
+#[derive(InputObject, Clone)]
+#[graphql(name = "RSSFeedFilterGroupInputGQL")]
+pub struct RSSFeedFilterGroupInputGQL {
+    /// How the rules are combined, default to `ALL`
+    mode: Option<RSSFeedFilterModeGQL>,
+    /// The rules of the group, maximum of 20 rules
+    #[graphql(validator(max_items = 20))]
+    rules: Vec<RSSFeedFilterRuleInputGQL>,
+}
+
+impl From<&RSSFeedFilterGroupInputGQL> for showtimes_db::m::RSSFeedFilterGroup {
+    fn from(value: &RSSFeedFilterGroupInputGQL) -> Self {
+        showtimes_db::m::RSSFeedFilterGroup {
+            mode: value.mode.map(Into::into).unwrap_or_default(),
+            rules: value
+                .rules
+                .iter()
+                .map(|rule| showtimes_db::m::RSSFeedFilterRule {
+                    field: rule.field.into(),
+                    kind: rule.kind.unwrap_or(RSSFeedFilterKindGQL::Keyword).into(),
+                    pattern: rule.pattern.clone(),
+                    action: rule
+                        .action
+                        .unwrap_or(RSSFeedFilterActionGQL::Include)
+                        .into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Convert and validate the filter groups input
+///
+/// Returns the filter groups with the compiled filter set of it.
+fn parse_filter_groups(
+    input: &[RSSFeedFilterGroupInputGQL],
+) -> Result<
+    (
+        Vec<showtimes_db::m::RSSFeedFilterGroup>,
+        showtimes_db::m::RSSFeedFilterSet,
+    ),
+    GQLError,
+> {
+    let groups: Vec<showtimes_db::m::RSSFeedFilterGroup> = input.iter().map(Into::into).collect();
+
+    let filter_set = showtimes_db::m::RSSFeedFilterSet::compile(&groups).map_err(|e| {
+        GQLError::new(e.to_string(), GQLErrorCode::RSSFeedInvalidFilter)
+            .extend(|f| f.set("groups", groups.len()))
+    })?;
+
+    Ok((groups, filter_set))
+}
+
+/// The allowed hosts for the Discord webhook URL
+const DISCORD_WEBHOOK_HOSTS: [&str; 4] = [
+    "discord.com",
+    "discordapp.com",
+    "ptb.discord.com",
+    "canary.discord.com",
+];
+
+/// A Discord webhook input for a channel integration
+#[derive(InputObject, Clone)]
+#[graphql(name = "RSSFeedWebhookInputGQL")]
+pub struct RSSFeedWebhookInputGQL {
+    /// The Discord channel ID, must be one of the `DISCORD_TEXT_CHANNEL` integration
+    #[graphql(validator(min_length = 1))]
+    channel: String,
+    /// The Discord webhook URL of the channel
+    #[graphql(validator(url))]
+    url: String,
+}
+
+/// Convert and validate the Discord webhooks input against the feed integrations
+///
+/// Since the webhook URL is returned masked, sending back the masked URL of an
+/// existing webhook keeps the current URL.
+fn parse_feed_webhooks(
+    input: &[RSSFeedWebhookInputGQL],
+    integrations: &[showtimes_db::m::IntegrationId],
+    existing: &[showtimes_db::m::RSSFeedWebhook],
+) -> Result<Vec<showtimes_db::m::RSSFeedWebhook>, GQLError> {
+    let mut webhooks: Vec<showtimes_db::m::RSSFeedWebhook> = Vec::with_capacity(input.len());
+
+    for (idx, webhook) in input.iter().enumerate() {
+        let is_channel = integrations.iter().any(|i| {
+            i.kind() == &showtimes_db::m::IntegrationType::DiscordChannel
+                && i.id() == webhook.channel
+        });
+        if !is_channel {
+            return Err(GQLError::new(
+                "Webhook channel is not a Discord channel integration of the feed",
+                GQLErrorCode::RSSFeedInvalidWebhook,
+            )
+            .extend(|f| {
+                f.set("channel", &webhook.channel);
+                f.set("index", idx);
+            }));
+        }
+
+        let current = existing
+            .iter()
+            .find(|w| w.channel == webhook.channel && w.masked_url() == webhook.url);
+        if let Some(current) = current {
+            webhooks.retain(|w| w.channel != webhook.channel);
+            webhooks.push(current.clone());
+            continue;
+        }
+
+        let is_discord = url::Url::parse(&webhook.url).is_ok_and(|url| {
+            url.scheme() == "https"
+                && url
+                    .host_str()
+                    .is_some_and(|host| DISCORD_WEBHOOK_HOSTS.contains(&host))
+                && url.path().starts_with("/api/webhooks/")
+        });
+        if !is_discord {
+            return Err(GQLError::new(
+                "Webhook URL is not a Discord webhook URL",
+                GQLErrorCode::RSSFeedInvalidWebhook,
+            )
+            .extend(|f| {
+                f.set("channel", &webhook.channel);
+                f.set("index", idx);
+            }));
+        }
+
+        // Only keep a single webhook for each channel, the last one wins
+        webhooks.retain(|w| w.channel != webhook.channel);
+        webhooks.push(showtimes_db::m::RSSFeedWebhook::new(
+            &webhook.channel,
+            &webhook.url,
+        ));
+    }
+
+    Ok(webhooks)
+}
+
 /// The RSS feed input object for updating an existing RSS feed
 #[derive(InputObject)]
 #[graphql(name = "RSSFeedUpdateInputGQL")]
@@ -194,6 +364,17 @@ pub struct RSSFeedUpdateInputGQL {
     display: Option<RSSFeedDisplayUpdateInputGQL>,
     /// Enable or disable the RSS feed
     enable: Option<bool>,
+    /// Replace the filter groups of the RSS feed, use an empty list to remove all filters.
+    ///
+    /// An entry is only published when every group passes, maximum of 10 groups.
+    #[graphql(validator(max_items = 10))]
+    filters: Option<Vec<RSSFeedFilterGroupInputGQL>>,
+    /// Replace the Discord webhooks of the channel integrations, use an empty list to remove all webhooks.
+    ///
+    /// New entries are posted directly to the channel through the webhook, maximum of 10 webhooks.
+    /// The masked URL of an existing webhook can be sent back to keep it as is.
+    #[graphql(validator(max_items = 10))]
+    webhooks: Option<Vec<RSSFeedWebhookInputGQL>>,
 }
 
 impl RSSFeedUpdateInputGQL {
@@ -223,6 +404,8 @@ impl RSSFeedUpdateInputGQL {
             || is_vec_set(&self.integrations)
             || self.display.as_ref().is_some_and(|d| d.is_any_set())
             || self.enable.is_some()
+            || self.filters.is_some()
+            || self.webhooks.is_some()
     }
 }
 
@@ -434,6 +617,26 @@ pub async fn mutate_rss_feed_create(
         new_feed.set_integrations(added_integration);
     }
 
+    if let Some(filters) = &input.filters {
+        let (groups, _) = parse_filter_groups(filters).map_err(|e| {
+            e.extend(|f| {
+                f.set("url", &input.url);
+                f.set("server", srv.id.to_string());
+            })
+        })?;
+        new_feed.filters = groups;
+    }
+
+    if let Some(webhooks) = &input.webhooks {
+        new_feed.webhooks =
+            parse_feed_webhooks(webhooks, &new_feed.integrations, &[]).map_err(|e| {
+                e.extend(|f| {
+                    f.set("url", &input.url);
+                    f.set("server", srv.id.to_string());
+                })
+            })?;
+    }
+
     let config = ctx.data_unchecked::<Arc<showtimes_shared::Config>>();
     if !can_enable_rss(config, rss_count, &premium_status) {
         // Cannot enable more feeds
@@ -573,6 +776,16 @@ pub async fn mutate_rss_feed_update(
         }
     }
 
+    if let Some(filters) = &input.filters {
+        let (groups, _) = parse_filter_groups(filters).map_err(|e| {
+            e.extend(|f| {
+                f.set("id", rss_feed.id.to_string());
+                f.set("actor", user.id.to_string());
+            })
+        })?;
+        rss_feed.filters = groups;
+    }
+
     for (idx, integration) in input
         .integrations
         .clone()
@@ -634,6 +847,25 @@ pub async fn mutate_rss_feed_update(
         }
     }
 
+    if let Some(webhooks) = &input.webhooks {
+        rss_feed.webhooks =
+            parse_feed_webhooks(webhooks, &rss_feed.integrations, &rss_feed.webhooks).map_err(
+                |e| {
+                    e.extend(|f| {
+                        f.set("id", rss_feed.id.to_string());
+                        f.set("actor", user.id.to_string());
+                    })
+                },
+            )?;
+    } else {
+        // Drop the webhooks of the removed channel integrations
+        rss_feed.webhooks.retain(|w| {
+            rss_feed.integrations.iter().any(|i| {
+                i.kind() == &showtimes_db::m::IntegrationType::DiscordChannel && i.id() == w.channel
+            })
+        });
+    }
+
     rss_loader
         .loader()
         .get_inner()
@@ -724,3 +956,74 @@ pub async fn mutate_rss_feed_preview(
         Ok(None)
     }
 }
+
+pub async fn mutate_rss_feed_preview_filters(
+    ctx: &async_graphql::Context<'_>,
+    id: UlidGQL,
+    filters: Option<Vec<RSSFeedFilterGroupInputGQL>>,
+) -> async_graphql::Result<Vec<RSSFeedFilterPreviewGQL>> {
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let rss_loader = ctx.data_unchecked::<DataLoader<RSSFeedLoader>>();
+
+    // Fetch feed
+    let rss_feed = rss_loader.load_one(*id).await?.ok_or_else(|| {
+        GQLError::new("RSS Feed not found", GQLErrorCode::RSSFeedNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    check_permissions(ctx, rss_feed.creator, user).await?;
+
+    // Use the provided filters, or the saved one when not provided
+    let filter_set = match &filters {
+        Some(filters) => {
+            let (_, filter_set) = parse_filter_groups(filters)
+                .map_err(|e| e.extend(|f| f.set("id", id.to_string())))?;
+            filter_set
+        }
+        None => rss_feed.compile_filters().map_err(|e| {
+            GQLError::new(e.to_string(), GQLErrorCode::RSSFeedInvalidFilter)
+                .extend(|f| f.set("id", id.to_string()))
+        })?,
+    };
+
+    let fetched = showtimes_rss::parse_feed(rss_feed.url.as_str(), None)
+        .await
+        .map_err(|e| {
+            GQLError::new(e.to_string(), GQLErrorCode::RSSFeedFetchError).extend(|f| {
+                f.set("id", id.to_string());
+                f.set("url", rss_feed.url.to_string());
+            })
+        })?;
+
+    let entries = match fetched.result {
+        showtimes_rss::FeedFetchResult::Modified(feed) => feed.entries,
+        // Should not happen since we are not sending any cache headers
+        showtimes_rss::FeedFetchResult::NotModified => vec![],
+    };
+
+    let previews = entries
+        .iter()
+        .take(FILTER_PREVIEW_LIMIT)
+        .map(|entry| {
+            let passed =
+                filter_set.matches(|key| entry.get(key).map(|v| v.to_texts()).unwrap_or_default());
+            let text_of = |key: &str| match entry.get(key) {
+                Some(showtimes_rss::FeedValue::String(value)) => Some(value.clone()),
+                _ => None,
+            };
+            let published = match entry.get("published").or_else(|| entry.get("updated")) {
+                Some(showtimes_rss::FeedValue::Timestamp(ts)) => Some((*ts).into()),
+                _ => None,
+            };
+
+            RSSFeedFilterPreviewGQL {
+                title: text_of("title"),
+                link: text_of("link"),
+                published,
+                passed,
+            }
+        })
+        .collect();
+
+    Ok(previews)
+}
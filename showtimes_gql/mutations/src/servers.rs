@@ -3,11 +3,10 @@ use std::sync::Arc;
 use async_graphql::{InputObject, Upload, dataloader::DataLoader};
 use showtimes_db::{
     DatabaseShared, ServerHandler,
-    m::{ShowModelHandler, UserKind},
+    m::{SearchOutbox, SearchOutboxKind, ShowModelHandler, UserKind},
     mongodb::bson::doc,
 };
 use showtimes_fs::{FsFileKind, FsPool};
-use showtimes_search::SearchClientShared;
 use tokio::io::AsyncSeekExt;
 
 use showtimes_gql_common::{
@@ -19,7 +18,7 @@ use showtimes_gql_models::servers::{ServerGQL, ServerPremiumGQL};
 
 use crate::{
     IntegrationActionGQL, IntegrationInputGQL, IntegrationValidator, execute_search_events,
-    is_string_set, is_vec_set,
+    is_string_set, is_vec_set, queue_search_outbox,
 };
 
 /// The server input object for creating a new server
@@ -145,7 +144,6 @@ pub async fn mutate_servers_create(
     input: ServerCreateInputGQL,
 ) -> async_graphql::Result<ServerGQL> {
     let db = ctx.data_unchecked::<DatabaseShared>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
     let user = ctx.data_unchecked::<showtimes_db::m::User>();
 
     if user.kind == UserKind::Owner {
@@ -282,6 +280,13 @@ pub async fn mutate_servers_create(
         }
     }
 
+    // Record the search engine update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(SearchOutboxKind::Server, server.id)],
+    )
+    .await?;
+
     // Commit to database
     let srv_handler = ServerHandler::new(db);
     srv_handler
@@ -294,12 +299,7 @@ pub async fn mutate_servers_create(
         })?;
 
     // Commit to search engine
-    let server_clone = server.clone();
-    let meili_clone = meili.clone();
-    let task_search = tokio::task::spawn(async move {
-        let srv_search = showtimes_search::models::Server::from(server_clone);
-        srv_search.update_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
     // Commit to events
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
@@ -379,7 +379,6 @@ pub async fn mutate_servers_update(
 
     let db = ctx.data_unchecked::<DatabaseShared>();
     let storages = ctx.data_unchecked::<Arc<FsPool>>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
     let user = ctx.data_unchecked::<showtimes_db::m::User>();
 
     // Do update
@@ -554,6 +553,16 @@ pub async fn mutate_servers_update(
         server_mut.avatar = Some(image_meta);
     }
 
+    // Record the index update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(
+            SearchOutboxKind::Server,
+            server_mut.id,
+        )],
+    )
+    .await?;
+
     // Update the user
     let srv_handler = ServerHandler::new(db);
     srv_handler.save(&mut server_mut, None).await.extend_error(
@@ -566,12 +575,7 @@ pub async fn mutate_servers_update(
     )?;
 
     // Update index
-    let server_clone = server_mut.clone();
-    let meili_clone = meili.clone();
-    let task_search = tokio::task::spawn(async move {
-        let srv_search = showtimes_search::models::Server::from(server_clone);
-        srv_search.update_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
     // Commit to events
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
@@ -602,7 +606,6 @@ pub async fn mutate_servers_delete(
 ) -> async_graphql::Result<OkResponse> {
     let db = ctx.data_unchecked::<DatabaseShared>();
     let storages = ctx.data_unchecked::<Arc<FsPool>>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
     let user = ctx.data_unchecked::<showtimes_db::m::User>();
 
     // Get server info
@@ -621,9 +624,29 @@ pub async fn mutate_servers_delete(
             f_mut.set("server_id", server.id.to_string());
         })?;
 
-    let mut collab_deleted: Vec<String> = vec![];
+    // Record the search engine changes before touching the collaborations
+    let mut collab_deleted: Vec<SearchOutbox> = vec![];
+    let mut collab_updated: Vec<SearchOutbox> = vec![];
+    for collab in &collab_info {
+        let mut collab_mut = collab.clone();
+        if collab_mut.get_and_remove_server(server.id).is_some() {
+            if collab_mut.length() < 2 {
+                collab_deleted.push(SearchOutbox::delete(
+                    SearchOutboxKind::CollaborationSync,
+                    collab.id,
+                ));
+            } else {
+                collab_updated.push(SearchOutbox::upsert(
+                    SearchOutboxKind::CollaborationSync,
+                    collab.id,
+                ));
+            }
+        }
+    }
+    let collab_deleted_queue = queue_search_outbox(ctx, collab_deleted).await?;
+    let collab_updated_queue = queue_search_outbox(ctx, collab_updated).await?;
+
     let mut collab_deleted_events: Vec<showtimes_events::m::CollabDeletedEvent> = vec![];
-    let mut collab_updated: Vec<showtimes_db::m::ServerCollaborationSync> = vec![];
     let mut collab_updated_events: Vec<showtimes_events::m::CollabDeletedEvent> = vec![];
     for collab in collab_info {
         let mut collab_mut = collab.clone();
@@ -640,14 +663,11 @@ pub async fn mutate_servers_delete(
                     },
                 )?;
 
-                // Delete from search engine
                 collab_deleted_events.push(showtimes_events::m::CollabDeletedEvent::new(
                     collab.id,
                     &srv_collab_data,
                     true,
                 ));
-
-                collab_deleted.push(collab.id.to_string());
             } else {
                 collab_handler
                     .save(&mut collab_mut, None)
@@ -662,27 +682,13 @@ pub async fn mutate_servers_delete(
                     &srv_collab_data,
                     false,
                 ));
-                collab_updated.push(collab_mut);
             }
         }
     }
 
-    if !collab_deleted.is_empty() && !collab_deleted_events.is_empty() {
-        let index_collab = meili.index(showtimes_search::models::ServerCollabSync::index_name());
-
+    if !collab_deleted_events.is_empty() {
         // Search adjustment
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            match index_collab.delete_documents(&collab_deleted).await {
-                Ok(task_del) => {
-                    match task_del.wait_for_completion(&meili_clone, None, None).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        });
+        let task_search = collab_deleted_queue.apply();
 
         // Commit to events
         let task_events = ctx
@@ -700,28 +706,9 @@ pub async fn mutate_servers_delete(
         execute_search_events(task_search, task_events).await?;
     }
 
-    if !collab_updated.is_empty() && !collab_updated_events.is_empty() {
-        let index_collab = meili.index(showtimes_search::models::ServerCollabSync::index_name());
-
+    if !collab_updated_events.is_empty() {
         // Search adjustment
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            match index_collab
-                .add_or_update(
-                    &collab_updated,
-                    Some(showtimes_search::models::ServerCollabSync::index_name()),
-                )
-                .await
-            {
-                Ok(task_del) => {
-                    match task_del.wait_for_completion(&meili_clone, None, None).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        });
+        let task_search = collab_updated_queue.apply();
 
         // Commit to events
         let task_events = ctx
@@ -762,6 +749,16 @@ pub async fn mutate_servers_delete(
         .collect::<Vec<String>>();
 
     if !all_invite_ids.is_empty() {
+        // Record the search engine removal
+        let search_queue = queue_search_outbox(
+            ctx,
+            collab_invite_info
+                .iter()
+                .map(|c| SearchOutbox::delete(SearchOutboxKind::CollaborationInvite, c.id))
+                .collect(),
+        )
+        .await?;
+
         // Delete from DB
         collab_invite_handler
             .delete_by(doc! {
@@ -776,20 +773,7 @@ pub async fn mutate_servers_delete(
             })?;
 
         // Delete from search engine
-        let index_invite = meili.index(showtimes_search::models::ServerCollabInvite::index_name());
-
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            match index_invite.delete_documents(&all_invite_ids).await {
-                Ok(task_del) => {
-                    match task_del.wait_for_completion(&meili_clone, None, None).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        });
+        let task_search = search_queue.apply();
 
         // Create events for retracted
         let retracted_events: Vec<showtimes_events::m::CollabRetractedEvent> = collab_invite_info
@@ -830,6 +814,16 @@ pub async fn mutate_servers_delete(
         .collect::<Vec<String>>();
 
     if !all_project_ids.is_empty() {
+        // Record the search engine removal
+        let search_queue = queue_search_outbox(
+            ctx,
+            project_info
+                .iter()
+                .map(|p| SearchOutbox::delete(SearchOutboxKind::Project, p.id))
+                .collect(),
+        )
+        .await?;
+
         // Delete from DB
         project_handler
             .delete_by(doc! {
@@ -844,25 +838,12 @@ pub async fn mutate_servers_delete(
             })?;
 
         // Delete from search engine
-        let index_project = meili.index(showtimes_search::models::Project::index_name());
-
-        let meili_clone = meili.clone();
-        let task_search = tokio::task::spawn(async move {
-            match index_project.delete_documents(&all_project_ids).await {
-                Ok(task_del) => {
-                    match task_del.wait_for_completion(&meili_clone, None, None).await {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        });
+        let task_search = search_queue.apply();
 
         // Create events for deleted
         let deleted_events: Vec<showtimes_events::m::ProjectDeletedEvent> = project_info
             .iter()
-            .map(|project| showtimes_events::m::ProjectDeletedEvent::new(project.id))
+            .map(showtimes_events::m::ProjectDeletedEvent::from)
             .collect();
 
         // Create task events
@@ -913,6 +894,13 @@ pub async fn mutate_servers_delete(
             f.set("actor", user.id.to_string());
         })?;
 
+    // Record the search engine removal
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::delete(SearchOutboxKind::Server, server.id)],
+    )
+    .await?;
+
     // Delete from DB
     let srv_handler = ServerHandler::new(db);
     srv_handler
@@ -924,12 +912,7 @@ pub async fn mutate_servers_delete(
         })?;
 
     // Delete from search engine
-    let srv_clone = server.clone();
-    let meili_clone = meili.clone();
-    let task_search = tokio::task::spawn(async move {
-        let srv_search = showtimes_search::models::Server::from(srv_clone);
-        srv_search.delete_document(&meili_clone).await
-    });
+    let task_search = search_queue.apply();
     // Commit to events
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
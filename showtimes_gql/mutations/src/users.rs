@@ -1,20 +1,27 @@
 use std::sync::Arc;
 
 use async_graphql::{InputObject, Upload, dataloader::DataLoader};
-use showtimes_db::{DatabaseShared, UserHandler, m::UserKind};
+use showtimes_db::{
+    DatabaseShared, UserHandler,
+    m::{SearchOutbox, SearchOutboxKind, UserKind},
+};
 use showtimes_fs::FsPool;
-use showtimes_search::SearchClientShared;
-use showtimes_session::{manager::SharedSessionManager, oauth2::discord::DiscordClient};
+use showtimes_session::{
+    ShowtimesUserSession,
+    manager::{SessionMetadata, SharedSessionManager},
+    oauth2::discord::DiscordClient,
+};
 use tokio::io::AsyncSeekExt;
 
 use showtimes_gql_common::{
-    APIKeyCapabilityGQL, APIKeyGQL, GQLErrorCode, GQLErrorExt, UserKindGQL,
+    APIKeyCapabilityGQL, APIKeyGQL, DateTimeGQL, GQLErrorCode, GQLErrorExt, OkResponse, UlidGQL,
+    UserKindGQL,
     data_loader::{DiscordIdLoad, UserDataLoader},
     errors::GQLError,
 };
 use showtimes_gql_models::users::{APIKeyDataGQL, UserGQL, UserSessionGQL};
 
-use crate::{execute_search_events, is_string_set};
+use crate::{execute_search_events, is_string_set, queue_search_outbox};
 
 /// The input information of an API key
 #[derive(InputObject)]
@@ -31,6 +38,14 @@ pub struct UserAPIKeyInputGQL {
     /// The privilege level of the API key
     #[graphql(validator(min_items = 1))]
     capabilities: Option<Vec<APIKeyCapabilityGQL>>,
+    /// The label of the API key, an empty string will remove the label
+    #[graphql(validator(max_length = 128))]
+    name: Option<String>,
+    /// When the API key should expire
+    #[graphql(name = "expiresAt")]
+    expires_at: Option<DateTimeGQL>,
+    /// The servers the API key is limited to, an empty list will allow all servers
+    servers: Option<Vec<UlidGQL>>,
 }
 
 impl UserAPIKeyInputGQL {
@@ -40,6 +55,33 @@ impl UserAPIKeyInputGQL {
             || self.capabilities.is_some()
             || self.key.is_some()
             || self.remove.is_some_and(|d| d)
+            || self.name.is_some()
+            || self.expires_at.is_some()
+            || self.servers.is_some()
+    }
+
+    /// Apply the label, expiry and server scope to the API key
+    ///
+    /// Returns `true` if anything has been changed
+    fn apply_metadata(&self, api_key: &mut showtimes_db::m::APIKey) -> bool {
+        let mut changed = false;
+        if let Some(name) = &self.name {
+            api_key.name = if name.trim().is_empty() {
+                None
+            } else {
+                Some(name.trim().to_string())
+            };
+            changed = true;
+        }
+        if let Some(expires_at) = &self.expires_at {
+            api_key.expires_at = Some(**expires_at);
+            changed = true;
+        }
+        if let Some(servers) = &self.servers {
+            api_key.servers = servers.iter().map(|d| **d).collect();
+            changed = true;
+        }
+        changed
     }
 
     fn capabilities(&self) -> Option<Vec<showtimes_db::m::APIKeyCapability>> {
@@ -77,6 +119,28 @@ impl UserAPIKeyInputGQL {
                 async_graphql::Value::List(capabilities_map),
             );
         }
+        if let Some(name) = &self.name {
+            ctx.insert(
+                async_graphql::Name::new("name"),
+                async_graphql::Value::String(name.to_string()),
+            );
+        }
+        if let Some(expires_at) = &self.expires_at {
+            ctx.insert(
+                async_graphql::Name::new("expires_at"),
+                async_graphql::Value::String(expires_at.to_string()),
+            );
+        }
+        if let Some(servers) = &self.servers {
+            let servers_map = servers
+                .iter()
+                .map(|d| async_graphql::Value::String(d.to_string()))
+                .collect::<Vec<async_graphql::Value>>();
+            ctx.insert(
+                async_graphql::Name::new("servers"),
+                async_graphql::Value::List(servers_map),
+            );
+        }
     }
 }
 
@@ -138,6 +202,17 @@ impl UserInputGQL {
     }
 }
 
+/// The extra options when creating a new API key
+#[derive(Default)]
+pub struct APIKeyCreateOptions {
+    /// The label of the API key
+    pub name: Option<String>,
+    /// When the API key should expire
+    pub expires_at: Option<DateTimeGQL>,
+    /// The servers the API key is limited to
+    pub servers: Option<Vec<UlidGQL>>,
+}
+
 /// The user who requested the update
 pub struct UserRequester {
     /// Specific user specified by ULID
@@ -173,7 +248,6 @@ pub async fn mutate_users_update(
     let loader = ctx.data_unchecked::<DataLoader<UserDataLoader>>();
     let db = ctx.data_unchecked::<DatabaseShared>();
     let storages = ctx.data_unchecked::<Arc<FsPool>>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
 
     let user_info = match user.id {
         Some(id) => loader.load_one(id).await?.ok_or_else(|| {
@@ -293,6 +367,9 @@ pub async fn mutate_users_update(
                             matched.capabilities = capabilities.to_vec();
                             any_api_changes = true;
                         }
+                        if api_key.apply_metadata(matched) {
+                            any_api_changes = true;
+                        }
                     }
                 }
                 None => {
@@ -302,9 +379,9 @@ pub async fn mutate_users_update(
                         .unwrap_or_else(|| showtimes_db::m::APIKeyCapability::all().to_vec());
 
                     let key = showtimes_shared::APIKey::new();
-                    user_info
-                        .api_key
-                        .push(showtimes_db::m::APIKey::new(key, capabilities));
+                    let mut new_key = showtimes_db::m::APIKey::new(key, capabilities);
+                    api_key.apply_metadata(&mut new_key);
+                    user_info.api_key.push(new_key);
                     any_api_changes = true;
                 }
             }
@@ -405,6 +482,13 @@ pub async fn mutate_users_update(
         user_info.avatar = Some(image_meta);
     }
 
+    // Record the search engine update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(SearchOutboxKind::User, user_info.id)],
+    )
+    .await?;
+
     // Update the user
     let user_handler = UserHandler::new(db);
     user_handler.save(&mut user_info, None).await.extend_error(
@@ -416,12 +500,7 @@ pub async fn mutate_users_update(
         },
     )?;
 
-    let search_arc = meili.clone();
-    let user_clone = user_info.clone();
-    let task_search = tokio::task::spawn(async move {
-        let user_search = showtimes_search::models::User::from(user_clone);
-        user_search.update_document(&search_arc).await
-    });
+    let task_search = search_queue.apply();
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
         .create_event_async(
@@ -445,6 +524,7 @@ pub async fn mutate_users_create_api_key(
     ctx: &async_graphql::Context<'_>,
     user: UserRequester,
     capabilities: Option<Vec<APIKeyCapabilityGQL>>,
+    options: APIKeyCreateOptions,
 ) -> async_graphql::Result<APIKeyDataGQL> {
     let remapped_caps = match capabilities {
         Some(capabilities) => {
@@ -461,9 +541,19 @@ pub async fn mutate_users_create_api_key(
         _ => showtimes_db::m::APIKeyCapability::queries().to_vec(),
     };
 
+    if let Some(expires_at) = &options.expires_at
+        && **expires_at <= jiff::Timestamp::now()
+    {
+        return GQLError::new(
+            "Expiry date must be in the future",
+            GQLErrorCode::InvalidRequest,
+        )
+        .extend(|e| e.set("expires_at", expires_at.to_string()))
+        .into();
+    }
+
     let loader = ctx.data_unchecked::<DataLoader<UserDataLoader>>();
     let db = ctx.data_unchecked::<DatabaseShared>();
-    let meili = ctx.data_unchecked::<SearchClientShared>();
 
     let user_info = match user.id {
         Some(id) => loader.load_one(id).await?.ok_or_else(|| {
@@ -507,11 +597,27 @@ pub async fn mutate_users_create_api_key(
 
     // generate new key
     let new_key = showtimes_shared::APIKey::new();
-    user_info
-        .api_key
-        .push(showtimes_db::m::APIKey::new(new_key, remapped_caps.clone()));
+    let mut api_key = showtimes_db::m::APIKey::new(new_key, remapped_caps);
+    if let Some(name) = options.name.filter(|name| !name.trim().is_empty()) {
+        api_key = api_key.with_name(name.trim());
+    }
+    if let Some(expires_at) = options.expires_at {
+        api_key = api_key.with_expires_at(*expires_at);
+    }
+    if let Some(servers) = options.servers {
+        api_key = api_key.with_servers(servers.iter().map(|d| **d).collect());
+    }
+    let api_key_gql = APIKeyDataGQL::new(new_key, &api_key);
+    user_info.api_key.push(api_key);
     user_after.set_api_key(&user_info.api_key);
 
+    // Record the search engine update
+    let search_queue = queue_search_outbox(
+        ctx,
+        vec![SearchOutbox::upsert(SearchOutboxKind::User, user_info.id)],
+    )
+    .await?;
+
     // Update the user
     let user_handler = UserHandler::new(db);
     user_handler.save(&mut user_info, None).await.extend_error(
@@ -522,12 +628,7 @@ pub async fn mutate_users_create_api_key(
         },
     )?;
 
-    let search_arc = meili.clone();
-    let user_clone = user_info.clone();
-    let task_search = tokio::task::spawn(async move {
-        let user_search = showtimes_search::models::User::from(user_clone);
-        user_search.update_document(&search_arc).await
-    });
+    let task_search = search_queue.apply();
     let task_events = ctx
         .data_unchecked::<showtimes_events::SharedSHClickHouse>()
         .create_event_async(
@@ -542,7 +643,7 @@ pub async fn mutate_users_create_api_key(
 
     execute_search_events(task_search, task_events).await?;
 
-    Ok(APIKeyDataGQL::new(new_key, remapped_caps))
+    Ok(api_key_gql)
 }
 
 pub async fn mutate_users_authenticate(
@@ -554,6 +655,10 @@ pub async fn mutate_users_authenticate(
     let event_manager = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
     let sess_manager = ctx.data_unchecked::<SharedSessionManager>();
     let jwt_secret = ctx.data_unchecked::<showtimes_session::SharedSigner>();
+    let sess_metadata = ctx
+        .data_opt::<SessionMetadata>()
+        .cloned()
+        .unwrap_or_default();
 
     tracing::info!("Authenticating user with token: {}", &token);
     showtimes_session::verify_session(
@@ -715,6 +820,21 @@ pub async fn mutate_users_authenticate(
                     f.set("state", &state);
                     f.set("is_new", false);
                 })?;
+            sess_mutex
+                .track_session(
+                    user.id,
+                    oauth_user.get_token(),
+                    oauth_user.get_claims(),
+                    Some(&refresh_token),
+                    &sess_metadata,
+                )
+                .await
+                .extend_error(GQLErrorCode::SessionStoreError, |f| {
+                    f.set("id", user.id.to_string());
+                    f.set("token", &token);
+                    f.set("state", &state);
+                    f.set("is_new", false);
+                })?;
             drop(sess_mutex);
 
             Ok(UserSessionGQL::new(&user, oauth_user.get_token())
@@ -811,6 +931,21 @@ pub async fn mutate_users_authenticate(
                     f.set("state", &state);
                     f.set("is_new", true);
                 })?;
+            sess_mutex
+                .track_session(
+                    user.id,
+                    oauth_user.get_token(),
+                    oauth_user.get_claims(),
+                    Some(&refresh_token),
+                    &sess_metadata,
+                )
+                .await
+                .extend_error(GQLErrorCode::SessionStoreError, |f| {
+                    f.set("id", user.id.to_string());
+                    f.set("token", &token);
+                    f.set("state", &state);
+                    f.set("is_new", true);
+                })?;
             drop(sess_mutex);
 
             Ok(UserSessionGQL::new(&user, oauth_user.get_token())
@@ -818,3 +953,97 @@ pub async fn mutate_users_authenticate(
         }
     }
 }
+
+/// Resolve the user whose sessions are being managed.
+///
+/// Only Admin or higher can manage the sessions of other users.
+async fn resolve_session_user(
+    ctx: &async_graphql::Context<'_>,
+    id: Option<UlidGQL>,
+) -> async_graphql::Result<showtimes_shared::ulid::Ulid> {
+    let requester = ctx.data_unchecked::<showtimes_db::m::User>();
+
+    match id {
+        Some(id) if *id != requester.id => {
+            if requester.kind < UserKind::Admin {
+                return GQLError::new(
+                    "Only admin can manage sessions of other users",
+                    GQLErrorCode::UserInsufficientPrivilege,
+                )
+                .extend(|e| {
+                    e.set("id", id.to_string());
+                    e.set("requester", requester.id.to_string());
+                })
+                .into();
+            }
+
+            let loader = ctx.data_unchecked::<DataLoader<UserDataLoader>>();
+            let user = loader.load_one(*id).await?.ok_or_else(|| {
+                GQLError::new("User not found", GQLErrorCode::UserNotFound)
+                    .extend(|e| e.set("id", id.to_string()))
+            })?;
+
+            Ok(user.id)
+        }
+        _ => Ok(requester.id),
+    }
+}
+
+pub async fn mutate_users_revoke_session(
+    ctx: &async_graphql::Context<'_>,
+    id: UlidGQL,
+    user: Option<UlidGQL>,
+) -> async_graphql::Result<OkResponse> {
+    let user_id = resolve_session_user(ctx, user).await?;
+    let sess_manager = ctx.data_unchecked::<SharedSessionManager>();
+
+    let revoked = sess_manager
+        .lock()
+        .await
+        .revoke_user_session(user_id, *id)
+        .await
+        .extend_error(GQLErrorCode::SessionDeleteError, |f| {
+            f.set("id", id.to_string());
+            f.set("user", user_id.to_string());
+        })?;
+
+    if !revoked {
+        return GQLError::new("Session not found", GQLErrorCode::SessionNotFound)
+            .extend(|e| {
+                e.set("id", id.to_string());
+                e.set("user", user_id.to_string());
+            })
+            .into();
+    }
+
+    Ok(OkResponse::ok("Successfully revoked session"))
+}
+
+pub async fn mutate_users_revoke_all_sessions(
+    ctx: &async_graphql::Context<'_>,
+    user: Option<UlidGQL>,
+    keep_current: bool,
+) -> async_graphql::Result<OkResponse> {
+    let user_id = resolve_session_user(ctx, user).await?;
+    let sess_manager = ctx.data_unchecked::<SharedSessionManager>();
+    let keep_token = if keep_current {
+        ctx.data_opt::<ShowtimesUserSession>()
+            .map(|session| session.get_token())
+    } else {
+        None
+    };
+
+    let revoked = sess_manager
+        .lock()
+        .await
+        .revoke_all_user_sessions(user_id, keep_token)
+        .await
+        .extend_error(GQLErrorCode::SessionDeleteError, |f| {
+            f.set("user", user_id.to_string());
+            f.set("keep_current", keep_current);
+        })?;
+
+    Ok(OkResponse::ok(format!(
+        "Successfully revoked {revoked} sessions"
+    )))
+}
@@ -0,0 +1,864 @@
+use std::{net::IpAddr, sync::Arc};
+
+use async_graphql::{InputObject, dataloader::DataLoader};
+use showtimes_db::mongodb::bson::doc;
+
+use showtimes_gql_common::{
+    GQLErrorCode, GQLErrorExt, OkResponse, UlidGQL,
+    data_loader::{ProjectDataLoader, ServerDataLoader, WebhookLoader},
+    errors::GQLError,
+};
+use showtimes_gql_models::{
+    projects::ProjectTypeGQL,
+    webhooks::{WebhookActionGQL, WebhookGQL, WebhookTargetGQL},
+};
+
+use crate::is_string_set;
+
+/// The webhook filter input object
+///
+/// On update, only the provided fields will be replaced.
+#[derive(InputObject)]
+#[graphql(name = "WebhookFilterInputGQL")]
+pub struct WebhookFilterInputGQL {
+    /// Only deliver events from these projects, must be owned by the same server
+    projects: Option<Vec<UlidGQL>>,
+    /// Only deliver events from these project types
+    kinds: Option<Vec<ProjectTypeGQL>>,
+    /// Only deliver progress events when one of these role keys is finished
+    roles: Option<Vec<String>>,
+    /// Skip events from projects that are paused
+    #[graphql(name = "skipPaused")]
+    skip_paused: Option<bool>,
+}
+
+impl WebhookFilterInputGQL {
+    /// Dump the input to the error context
+    fn dump_query(&self, f_mut: &mut async_graphql::ErrorExtensionValues) {
+        if let Some(projects) = &self.projects {
+            f_mut.set(
+                "filter_projects",
+                projects.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+            );
+        }
+        if let Some(kinds) = &self.kinds {
+            f_mut.set(
+                "filter_kinds",
+                kinds.iter().map(|k| k.to_name()).collect::<Vec<_>>(),
+            );
+        }
+        if let Some(roles) = &self.roles {
+            f_mut.set("filter_roles", roles.clone());
+        }
+        if let Some(skip_paused) = self.skip_paused {
+            f_mut.set("filter_skip_paused", skip_paused);
+        }
+    }
+}
+
+/// The webhook input object for creating a new webhook
+#[derive(InputObject)]
+#[graphql(name = "WebhookCreateInputGQL")]
+pub struct WebhookCreateInputGQL {
+    /// The webhook URL
+    #[graphql(validator(url))]
+    url: String,
+    /// The attached server
+    server: UlidGQL,
+    /// The webhook target kind
+    kind: WebhookTargetGQL,
+    /// The name used when sending the webhook, default to `naoTimes`
+    #[graphql(validator(min_length = 1, max_length = 80))]
+    name: Option<String>,
+    /// The avatar URL used when sending the webhook
+    #[graphql(validator(url))]
+    avatar: Option<String>,
+    /// The list of actions that will trigger the webhook
+    ///
+    /// When not provided, will use the default actions
+    actions: Option<Vec<WebhookActionGQL>>,
+    /// The locale code used to render the webhook message (e.g. `id-ID`)
+    locale: Option<String>,
+    /// The filter to scope which events are delivered
+    filter: Option<WebhookFilterInputGQL>,
+}
+
+impl WebhookCreateInputGQL {
+    /// Dump the input to the error context
+    fn dump_query(&self, f_mut: &mut async_graphql::ErrorExtensionValues) {
+        f_mut.set("url", &self.url);
+        f_mut.set("server", self.server.to_string());
+        f_mut.set("kind", self.kind.to_name());
+        if let Some(name) = &self.name {
+            f_mut.set("name", name);
+        }
+        if let Some(avatar) = &self.avatar {
+            f_mut.set("avatar", avatar);
+        }
+        if let Some(actions) = &self.actions {
+            f_mut.set(
+                "actions",
+                actions.iter().map(|a| a.to_name()).collect::<Vec<_>>(),
+            );
+        }
+        if let Some(locale) = &self.locale {
+            f_mut.set("locale", locale);
+        }
+        if let Some(filter) = &self.filter {
+            filter.dump_query(f_mut);
+        }
+    }
+}
+
+/// The webhook input object for updating an existing webhook
+#[derive(InputObject)]
+#[graphql(name = "WebhookUpdateInputGQL")]
+pub struct WebhookUpdateInputGQL {
+    /// The webhook URL
+    #[graphql(validator(url))]
+    url: Option<String>,
+    /// The webhook target kind
+    kind: Option<WebhookTargetGQL>,
+    /// The name used when sending the webhook
+    #[graphql(validator(min_length = 1, max_length = 80))]
+    name: Option<String>,
+    /// The avatar URL used when sending the webhook
+    #[graphql(validator(url))]
+    avatar: Option<String>,
+    /// Unset the avatar, this will take precedence over `avatar`
+    #[graphql(name = "unsetAvatar")]
+    unset_avatar: Option<bool>,
+    /// The list of actions that will trigger the webhook
+    actions: Option<Vec<WebhookActionGQL>>,
+    /// The locale code used to render the webhook message (e.g. `id-ID`)
+    locale: Option<String>,
+    /// Unset the locale, this will take precedence over `locale`
+    #[graphql(name = "unsetLocale")]
+    unset_locale: Option<bool>,
+    /// Enable or disable the webhook, enabling will reset the failures counter
+    enabled: Option<bool>,
+    /// Regenerate the signing secret, only for `GENERIC` target
+    #[graphql(name = "regenerateSecret")]
+    regenerate_secret: Option<bool>,
+    /// The filter to scope which events are delivered
+    filter: Option<WebhookFilterInputGQL>,
+}
+
+impl WebhookUpdateInputGQL {
+    /// Dump the input to the error context
+    fn dump_query(&self, f_mut: &mut async_graphql::ErrorExtensionValues) {
+        if let Some(url) = &self.url {
+            f_mut.set("url", url);
+        }
+        if let Some(kind) = &self.kind {
+            f_mut.set("kind", kind.to_name());
+        }
+        if let Some(name) = &self.name {
+            f_mut.set("name", name);
+        }
+        if let Some(avatar) = &self.avatar {
+            f_mut.set("avatar", avatar);
+        }
+        if let Some(unset_avatar) = self.unset_avatar {
+            f_mut.set("unset_avatar", unset_avatar);
+        }
+        if let Some(actions) = &self.actions {
+            f_mut.set(
+                "actions",
+                actions.iter().map(|a| a.to_name()).collect::<Vec<_>>(),
+            );
+        }
+        if let Some(locale) = &self.locale {
+            f_mut.set("locale", locale);
+        }
+        if let Some(unset_locale) = self.unset_locale {
+            f_mut.set("unset_locale", unset_locale);
+        }
+        if let Some(enabled) = self.enabled {
+            f_mut.set("enabled", enabled);
+        }
+        if let Some(regenerate_secret) = self.regenerate_secret {
+            f_mut.set("regenerate_secret", regenerate_secret);
+        }
+        if let Some(filter) = &self.filter {
+            filter.dump_query(f_mut);
+        }
+    }
+
+    /// Check if any field is set
+    fn is_any_set(&self) -> bool {
+        is_string_set(&self.url)
+            || self.kind.is_some()
+            || is_string_set(&self.name)
+            || is_string_set(&self.avatar)
+            || self.unset_avatar.is_some()
+            || self.actions.is_some()
+            || is_string_set(&self.locale)
+            || self.unset_locale.is_some()
+            || self.enabled.is_some()
+            || self.regenerate_secret.is_some()
+            || self.filter.is_some()
+    }
+}
+
+async fn check_permissions(
+    ctx: &async_graphql::Context<'_>,
+    id: showtimes_shared::ulid::Ulid,
+    user: &showtimes_db::m::User,
+) -> async_graphql::Result<showtimes_db::m::Server> {
+    let srv_loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+    let srv = srv_loader.load_one(id).await?.ok_or_else(|| {
+        GQLError::new("Server not found", GQLErrorCode::ServerNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    let find_user = srv.owners.iter().find(|o| o.id == user.id);
+
+    match (find_user, user.kind) {
+        (Some(user), showtimes_db::m::UserKind::User) => {
+            // Check if we are allowed to adjust webhooks
+            if user.privilege < showtimes_db::m::UserPrivilege::Admin {
+                GQLError::new(
+                    "User not allowed to manage webhooks",
+                    GQLErrorCode::UserInsufficientPrivilege,
+                )
+                .extend(|e| {
+                    e.set("id", id.to_string());
+                    e.set("current", user.privilege.to_string());
+                    e.set(
+                        "required",
+                        showtimes_db::m::UserPrivilege::Admin.to_string(),
+                    );
+                    e.set("is_in_server", true);
+                })
+                .into()
+            } else {
+                Ok(srv)
+            }
+        }
+        (None, showtimes_db::m::UserKind::User) => GQLError::new(
+            "User not allowed to manage webhooks",
+            GQLErrorCode::UserInsufficientPrivilege,
+        )
+        .extend(|e| {
+            e.set("id", id.to_string());
+            e.set("is_in_server", false);
+        })
+        .into(),
+        _ => {
+            // Allow anyone to adjust webhooks
+            Ok(srv)
+        }
+    }
+}
+
+/// Parse and validate the webhook URL, only allow HTTP(S) URLs to a public host
+async fn parse_webhook_url(url: &str) -> Result<url::Url, GQLError> {
+    let url_parsed = match url::Url::parse(url) {
+        Ok(url_parsed) if matches!(url_parsed.scheme(), "http" | "https") => Ok(url_parsed),
+        Ok(url_parsed) => Err(GQLError::new(
+            format!("Unsupported URL scheme: {}", url_parsed.scheme()),
+            GQLErrorCode::WebhookInvalidURL,
+        )),
+        Err(e) => Err(GQLError::new(
+            e.to_string(),
+            GQLErrorCode::WebhookInvalidURL,
+        )),
+    }
+    .map_err(|e| e.extend(|f| f.set("url", url)))?;
+
+    check_webhook_host(&url_parsed)
+        .await
+        .map_err(|e| e.extend(|f| f.set("url", url)))?;
+
+    Ok(url_parsed)
+}
+
+/// Make sure the webhook host is not pointing to our own network
+///
+/// Domains are resolved and every resolved address must be public.
+async fn check_webhook_host(url: &url::Url) -> Result<(), GQLError> {
+    let addresses: Vec<IpAddr> = match url.host() {
+        Some(url::Host::Ipv4(ip)) => vec![IpAddr::V4(ip)],
+        Some(url::Host::Ipv6(ip)) => vec![IpAddr::V6(ip)],
+        Some(url::Host::Domain(domain)) => {
+            let port = url.port_or_known_default().unwrap_or(443);
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| {
+                    GQLError::new(
+                        format!("Failed to resolve webhook host: {e}"),
+                        GQLErrorCode::WebhookInvalidURL,
+                    )
+                    .extend(|f| f.set("host", domain))
+                })?
+                .map(|addr| addr.ip())
+                .collect()
+        }
+        None => vec![],
+    };
+
+    if addresses.is_empty() {
+        return Err(GQLError::new(
+            "Webhook URL has no resolvable host",
+            GQLErrorCode::WebhookInvalidURL,
+        ));
+    }
+
+    match addresses.iter().find(|ip| !is_public_ip(**ip)) {
+        Some(ip) => Err(GQLError::new(
+            "Webhook URL must not point to a loopback, private, or link-local address",
+            GQLErrorCode::WebhookInvalidURL,
+        )
+        .extend(|f| f.set("address", ip.to_string()))),
+        None => Ok(()),
+    }
+}
+
+/// Check if the address is publicly routable
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            // 100.64.0.0/10, shared address space (carrier-grade NAT)
+            let is_shared = first == 100 && (second & 0b1100_0000) == 0b0100_0000;
+
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || is_shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ip(IpAddr::V4(mapped)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Validate the locale code against the supported languages
+fn parse_webhook_locale(locale: &str) -> Result<String, GQLError> {
+    showtimes_i18n::Language::from_code(locale)
+        .map(|lang| lang.code().to_string())
+        .ok_or_else(|| {
+            GQLError::new("Unsupported locale", GQLErrorCode::WebhookInvalidLocale)
+                .extend(|f| f.set("locale", locale))
+        })
+}
+
+/// Convert the actions into deduplicated database actions
+fn parse_webhook_actions(
+    actions: &[WebhookActionGQL],
+) -> Result<Vec<showtimes_db::m::WebhookAction>, GQLError> {
+    if actions.is_empty() {
+        return Err(GQLError::new(
+            "Webhook actions cannot be empty",
+            GQLErrorCode::WebhookInvalidActions,
+        ));
+    }
+
+    let mut parsed: Vec<showtimes_db::m::WebhookAction> = vec![];
+    for action in actions {
+        let action: showtimes_db::m::WebhookAction = (*action).into();
+        if !parsed.contains(&action) {
+            parsed.push(action);
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Validate and apply the filter input into the existing filter
+///
+/// Projects must exist and belong to the same server, role keys will be normalized to uppercase.
+async fn parse_webhook_filter<F>(
+    ctx: &async_graphql::Context<'_>,
+    server_id: showtimes_shared::ulid::Ulid,
+    input: &WebhookFilterInputGQL,
+    mut filter: showtimes_db::m::WebhookFilter,
+    dump_query: F,
+) -> async_graphql::Result<showtimes_db::m::WebhookFilter>
+where
+    F: Fn(&mut async_graphql::ErrorExtensionValues),
+{
+    if let Some(projects) = &input.projects {
+        let mut project_ids: Vec<showtimes_shared::ulid::Ulid> = vec![];
+        for project in projects {
+            if !project_ids.contains(&**project) {
+                project_ids.push(**project);
+            }
+        }
+
+        let loader = ctx.data_unchecked::<DataLoader<ProjectDataLoader>>();
+        let loaded = loader.load_many(project_ids.clone()).await?;
+
+        for project_id in &project_ids {
+            match loaded.get(project_id) {
+                Some(project) if project.creator == server_id => {}
+                _ => {
+                    return GQLError::new(
+                        "Project not found in the server",
+                        GQLErrorCode::WebhookInvalidFilter,
+                    )
+                    .extend(|f| {
+                        f.set("project", project_id.to_string());
+                        f.set("server", server_id.to_string());
+                        dump_query(f);
+                    })
+                    .into();
+                }
+            }
+        }
+
+        filter.projects = project_ids;
+    }
+
+    if let Some(kinds) = &input.kinds {
+        let mut parsed: Vec<showtimes_db::m::ProjectType> = vec![];
+        for kind in kinds {
+            let kind: showtimes_db::m::ProjectType = (*kind).into();
+            if !parsed.contains(&kind) {
+                parsed.push(kind);
+            }
+        }
+        filter.kinds = parsed;
+    }
+
+    if let Some(roles) = &input.roles {
+        let mut parsed: Vec<String> = vec![];
+        for role in roles {
+            let role = role.trim();
+            if role.is_empty() || !role.is_ascii() || role.contains(' ') {
+                return GQLError::new(
+                    "Role key must be a non-empty ASCII string without spaces",
+                    GQLErrorCode::WebhookInvalidFilter,
+                )
+                .extend(|f| {
+                    f.set("role", role);
+                    dump_query(f);
+                })
+                .into();
+            }
+
+            let role = role.to_ascii_uppercase();
+            if !parsed.contains(&role) {
+                parsed.push(role);
+            }
+        }
+        filter.roles = parsed;
+    }
+
+    if let Some(skip_paused) = input.skip_paused {
+        filter.skip_paused = skip_paused;
+    }
+
+    Ok(filter)
+}
+
+pub async fn mutate_webhook_create(
+    ctx: &async_graphql::Context<'_>,
+    input: WebhookCreateInputGQL,
+) -> async_graphql::Result<WebhookGQL> {
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let webhook_loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+    let srv = check_permissions(ctx, *input.server, user).await?;
+
+    // Guarantee that the URL is valid
+    let url_parsed = parse_webhook_url(&input.url).await.map_err(|e| {
+        e.extend(|f| {
+            f.set("server", srv.id.to_string());
+            input.dump_query(f);
+        })
+        .build()
+    })?;
+
+    // Check if URL already exists
+    let already_exist = webhook_loader
+        .loader()
+        .get_inner()
+        .find_by(doc! {
+            "creator": srv.id.to_string(),
+            "url": url_parsed.as_str(),
+        })
+        .await
+        .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+            e.set("server", srv.id.to_string());
+            e.set("at", "verify_query");
+            input.dump_query(e);
+        })?;
+
+    if already_exist.is_some() {
+        return GQLError::new("Webhook already exists", GQLErrorCode::WebhookAlreadyExists)
+            .extend(|f| {
+                f.set("server", srv.id.to_string());
+                input.dump_query(f);
+            })
+            .into();
+    }
+
+    let mut webhook = showtimes_db::m::Webhook::new(url_parsed.as_str(), input.kind.into(), srv.id);
+
+    if let Some(name) = &input.name {
+        webhook = webhook.with_name(name.trim());
+    }
+    if let Some(avatar) = &input.avatar {
+        webhook = webhook.with_avatar(avatar);
+    }
+    if let Some(actions) = &input.actions {
+        let actions = parse_webhook_actions(actions)
+            .map_err(|e| e.extend(|f| input.dump_query(f)).build())?;
+        webhook = webhook.with_actions(actions);
+    }
+    if let Some(locale) = &input.locale {
+        let locale =
+            parse_webhook_locale(locale).map_err(|e| e.extend(|f| input.dump_query(f)).build())?;
+        webhook = webhook.with_locale(locale);
+    }
+    if let Some(filter) = &input.filter {
+        let filter = parse_webhook_filter(ctx, srv.id, filter, Default::default(), |f| {
+            input.dump_query(f);
+        })
+        .await?;
+        webhook = webhook.with_filter(filter);
+    }
+
+    webhook_loader
+        .loader()
+        .get_inner()
+        .save_direct(&mut webhook, None)
+        .await
+        .extend_error(GQLErrorCode::WebhookCreateError, |f| {
+            f.set("id", webhook.id.to_string());
+            f.set("actor", user.id.to_string());
+            input.dump_query(f);
+        })?;
+
+    // The secret is only shown in full on creation
+    Ok(WebhookGQL::from(&webhook).with_secret())
+}
+
+pub async fn mutate_webhook_update(
+    ctx: &async_graphql::Context<'_>,
+    id: UlidGQL,
+    input: WebhookUpdateInputGQL,
+) -> async_graphql::Result<WebhookGQL> {
+    if !input.is_any_set() {
+        return GQLError::new("No fields to update", GQLErrorCode::MissingModification).into();
+    }
+
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let webhook_loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+    // Fetch webhook
+    let mut webhook = webhook_loader.load_one(*id).await?.ok_or_else(|| {
+        GQLError::new("Webhook not found", GQLErrorCode::WebhookNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    check_permissions(ctx, webhook.creator, user).await?;
+    let original_secret = webhook.secret.clone();
+
+    if let Some(url) = &input.url {
+        let url_parsed = parse_webhook_url(url).await.map_err(|e| {
+            e.extend(|f| {
+                f.set("id", id.to_string());
+                input.dump_query(f);
+            })
+            .build()
+        })?;
+
+        // Check if URL already used by other webhook
+        let already_exist = webhook_loader
+            .loader()
+            .get_inner()
+            .find_by(doc! {
+                "creator": webhook.creator.to_string(),
+                "url": url_parsed.as_str(),
+                "id": { "$ne": webhook.id.to_string() },
+            })
+            .await
+            .extend_error(GQLErrorCode::WebhookRequestFails, |e| {
+                e.set("id", id.to_string());
+                e.set("at", "verify_query");
+                input.dump_query(e);
+            })?;
+
+        if already_exist.is_some() {
+            return GQLError::new("Webhook already exists", GQLErrorCode::WebhookAlreadyExists)
+                .extend(|f| {
+                    f.set("id", id.to_string());
+                    input.dump_query(f);
+                })
+                .into();
+        }
+
+        webhook.url = url_parsed.to_string();
+    }
+
+    if let Some(kind) = input.kind {
+        webhook.set_kind(kind.into());
+    }
+
+    if let Some(true) = input.regenerate_secret {
+        if webhook.kind != showtimes_db::m::WebhookTarget::Generic {
+            return GQLError::new(
+                "Only generic webhook has a secret",
+                GQLErrorCode::WebhookInvalidTarget,
+            )
+            .extend(|f| {
+                f.set("id", id.to_string());
+                f.set("kind", WebhookTargetGQL::from(webhook.kind).to_name());
+                input.dump_query(f);
+            })
+            .into();
+        }
+
+        webhook.secret = Some(showtimes_db::m::Webhook::generate_secret());
+    }
+
+    if let Some(name) = &input.name {
+        webhook.name = name.trim().to_string();
+    }
+
+    if let Some(true) = input.unset_avatar {
+        webhook.avatar = None;
+    } else if let Some(avatar) = &input.avatar {
+        webhook.avatar = Some(avatar.clone());
+    }
+
+    if let Some(actions) = &input.actions {
+        webhook.actions = parse_webhook_actions(actions).map_err(|e| {
+            e.extend(|f| {
+                f.set("id", id.to_string());
+                input.dump_query(f);
+            })
+            .build()
+        })?;
+    }
+
+    if let Some(true) = input.unset_locale {
+        webhook.locale = None;
+    } else if let Some(locale) = &input.locale {
+        let locale = parse_webhook_locale(locale).map_err(|e| {
+            e.extend(|f| {
+                f.set("id", id.to_string());
+                input.dump_query(f);
+            })
+            .build()
+        })?;
+        webhook.locale = Some(locale);
+    }
+
+    if let Some(filter) = &input.filter {
+        webhook.filter =
+            parse_webhook_filter(ctx, webhook.creator, filter, webhook.filter.clone(), |f| {
+                f.set("id", id.to_string());
+                input.dump_query(f);
+            })
+            .await?;
+    }
+
+    if let Some(enabled) = input.enabled {
+        webhook.set_enabled(enabled);
+    }
+
+    webhook_loader
+        .loader()
+        .get_inner()
+        .save(&mut webhook, None)
+        .await
+        .extend_error(GQLErrorCode::WebhookUpdateError, |f| {
+            f.set("id", webhook.id.to_string());
+            f.set("actor", user.id.to_string());
+            input.dump_query(f);
+        })?;
+
+    // Show the secret in full when it's regenerated or generated by the kind change
+    if webhook.secret.is_some() && webhook.secret != original_secret {
+        Ok(WebhookGQL::from(&webhook).with_secret())
+    } else {
+        Ok(WebhookGQL::from(&webhook))
+    }
+}
+
+pub async fn mutate_webhook_delete(
+    ctx: &async_graphql::Context<'_>,
+    id: UlidGQL,
+) -> async_graphql::Result<OkResponse> {
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let webhook_loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+    // Fetch webhook
+    let webhook = webhook_loader.load_one(*id).await?.ok_or_else(|| {
+        GQLError::new("Webhook not found", GQLErrorCode::WebhookNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    check_permissions(ctx, webhook.creator, user).await?;
+
+    webhook_loader
+        .loader()
+        .get_inner()
+        .delete(&webhook)
+        .await
+        .extend_error(GQLErrorCode::WebhookDeleteError, |f| {
+            f.set("id", webhook.id.to_string());
+            f.set("actor", user.id.to_string());
+        })?;
+
+    Ok(OkResponse::ok("Webhook deleted"))
+}
+
+pub async fn mutate_webhook_test(
+    ctx: &async_graphql::Context<'_>,
+    id: UlidGQL,
+    action: Option<WebhookActionGQL>,
+) -> async_graphql::Result<OkResponse> {
+    let user = ctx.data_unchecked::<showtimes_db::m::User>();
+    let webhook_loader = ctx.data_unchecked::<DataLoader<WebhookLoader>>();
+
+    // Fetch webhook
+    let webhook = webhook_loader.load_one(*id).await?.ok_or_else(|| {
+        GQLError::new("Webhook not found", GQLErrorCode::WebhookNotFound)
+            .extend(|e| e.set("id", id.to_string()))
+    })?;
+
+    check_permissions(ctx, webhook.creator, user).await?;
+
+    // The webhook might be created before the host is checked, or the domain now
+    // resolves into a different address.
+    parse_webhook_url(&webhook.url).await.map_err(|e| {
+        e.extend(|f| {
+            f.set("id", webhook.id.to_string());
+        })
+        .build()
+    })?;
+
+    // Use the first listened action when not provided
+    let action: showtimes_db::m::WebhookAction = match action {
+        Some(action) => action.into(),
+        None => webhook
+            .actions
+            .first()
+            .copied()
+            .unwrap_or(showtimes_db::m::WebhookAction::ProjectRelease),
+    };
+
+    let dispatcher = ctx.data_unchecked::<Arc<showtimes_webhooks::WebhookDispatcher>>();
+    let event = showtimes_webhooks::WebhookEvent::sample(action, webhook.creator);
+
+    let actor = if user.kind == showtimes_db::m::UserKind::Owner {
+        None
+    } else {
+        Some(user.id.to_string())
+    };
+    let delivery = dispatcher.deliver(&webhook, &event, actor.as_deref()).await;
+
+    // Record the attempts, but test delivery does not count toward failures
+    if !delivery.attempts.is_empty() {
+        if let Err(err) = ctx
+            .data_unchecked::<showtimes_events::SharedSHClickHouse>()
+            .create_webhook_deliveries(delivery.attempts)
+            .await
+        {
+            tracing::error!("Failed to record webhook test deliveries: {}", err);
+        }
+    }
+
+    let status = delivery.result.map_err(|e| {
+        GQLError::new(e.to_string(), GQLErrorCode::WebhookDeliveryError).extend(|f| {
+            f.set("id", webhook.id.to_string());
+            f.set("action", WebhookActionGQL::from(action).to_name());
+            match e {
+                showtimes_webhooks::WebhookError::Payload(_) => {
+                    f.set("kind", "payload");
+                }
+                showtimes_webhooks::WebhookError::Request(_) => {
+                    f.set("kind", "http_request");
+                }
+                showtimes_webhooks::WebhookError::Status(status) => {
+                    f.set("kind", "http_status");
+                    f.set("status", status.as_u16());
+                }
+            }
+        })
+    })?;
+
+    Ok(OkResponse::ok(format!(
+        "Webhook delivered with status {}",
+        status.as_u16()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_ip() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip} should be public");
+        }
+    }
+
+    #[test]
+    fn test_non_public_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(
+                !is_public_ip(ip.parse().unwrap()),
+                "{ip} should not be public"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_webhook_url_public() {
+        let url = parse_webhook_url("https://1.1.1.1/webhook").await;
+
+        assert!(url.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_parse_webhook_url_rejects_internal() {
+        for url in [
+            "http://127.0.0.1:8080/webhook",
+            "http://[::1]/webhook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://192.168.1.1/webhook",
+            "http://localhost/webhook",
+        ] {
+            assert!(
+                parse_webhook_url(url).await.is_err(),
+                "{url} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_webhook_url_rejects_scheme() {
+        assert!(parse_webhook_url("ftp://1.1.1.1/webhook").await.is_err());
+    }
+}
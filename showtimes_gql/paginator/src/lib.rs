@@ -6,6 +6,8 @@ use std::fmt::Debug;
 use showtimes_gql_common::PageInfoGQL;
 
 pub mod projects;
+pub mod schedule;
+pub mod search;
 pub mod servers;
 pub mod users;
 
@@ -5,7 +5,7 @@ use futures_util::TryStreamExt;
 use showtimes_db::{
     DatabaseShared,
     m::UserPrivilege,
-    mongodb::bson::{Document, doc},
+    mongodb::bson::{Bson, DateTime, Document, doc, to_bson},
 };
 use showtimes_gql_common::{
     GQLDataLoaderWhere, GQLErrorCode, GQLErrorExt, PageInfoGQL, SortOrderGQL,
@@ -25,10 +25,22 @@ pub struct ProjectQuery {
     per_page: Option<u32>,
     /// The cursor to start from
     cursor: Option<Ulid>,
+    /// The keyset cursor to start from, takes priority over `cursor`
+    after: Option<String>,
     /// Sort order
     sort: SortOrderGQL,
     /// The server that created the project
     creators: Option<Vec<Ulid>>,
+    /// The project statuses to filter
+    statuses: Option<Vec<showtimes_db::m::ProjectStatus>>,
+    /// The project types to filter
+    kinds: Option<Vec<showtimes_db::m::ProjectType>>,
+    /// The title prefix to filter, case-insensitive
+    title_prefix: Option<String>,
+    /// Filter projects with (or without) any unfinished episodes
+    has_unfinished: Option<bool>,
+    /// Filter projects with (or without) any aired but unreleased episodes
+    has_aired_unreleased: Option<bool>,
     /// Allowed servers to query
     servers_users: Option<Vec<MinimalServerUsers>>,
     current_user: Option<ServerQueryUser>,
@@ -75,6 +87,17 @@ impl ProjectQuery {
         self.cursor = Some(cursor);
     }
 
+    /// Set the keyset cursor to start from
+    pub fn with_after(mut self, after: impl Into<String>) -> Self {
+        self.after = Some(after.into());
+        self
+    }
+
+    /// Set the keyset cursor to start from
+    pub fn set_after(&mut self, after: impl Into<String>) {
+        self.after = Some(after.into());
+    }
+
     /// Set the sort order
     pub fn set_sort(&mut self, sort: SortOrderGQL) {
         self.sort = sort;
@@ -91,6 +114,61 @@ impl ProjectQuery {
         self.creators = Some(user.to_vec());
     }
 
+    /// Set the project statuses to filter
+    pub fn with_statuses(mut self, statuses: Vec<showtimes_db::m::ProjectStatus>) -> Self {
+        self.statuses = Some(statuses);
+        self
+    }
+
+    /// Set the project statuses to filter
+    pub fn set_statuses(&mut self, statuses: Vec<showtimes_db::m::ProjectStatus>) {
+        self.statuses = Some(statuses);
+    }
+
+    /// Set the project types to filter
+    pub fn with_kinds(mut self, kinds: Vec<showtimes_db::m::ProjectType>) -> Self {
+        self.kinds = Some(kinds);
+        self
+    }
+
+    /// Set the project types to filter
+    pub fn set_kinds(&mut self, kinds: Vec<showtimes_db::m::ProjectType>) {
+        self.kinds = Some(kinds);
+    }
+
+    /// Set the title prefix to filter
+    pub fn with_title_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.title_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the title prefix to filter
+    pub fn set_title_prefix(&mut self, prefix: impl Into<String>) {
+        self.title_prefix = Some(prefix.into());
+    }
+
+    /// Only return projects with (or without) any unfinished episodes
+    pub fn with_unfinished(mut self, unfinished: bool) -> Self {
+        self.has_unfinished = Some(unfinished);
+        self
+    }
+
+    /// Only return projects with (or without) any unfinished episodes
+    pub fn set_unfinished(&mut self, unfinished: bool) {
+        self.has_unfinished = Some(unfinished);
+    }
+
+    /// Only return projects with (or without) any episodes that already aired but not released
+    pub fn with_aired_unreleased(mut self, aired_unreleased: bool) -> Self {
+        self.has_aired_unreleased = Some(aired_unreleased);
+        self
+    }
+
+    /// Only return projects with (or without) any episodes that already aired but not released
+    pub fn set_aired_unreleased(&mut self, aired_unreleased: bool) {
+        self.has_aired_unreleased = Some(aired_unreleased);
+    }
+
     /// Set the allowed servers to query
     pub fn with_allowed_servers(mut self, servers: Vec<showtimes_db::m::Server>) -> Self {
         self.servers_users = Some(servers.into_iter().map(|s| s.into()).collect());
@@ -148,6 +226,9 @@ impl ProjectQuery {
         if let Some(cursor) = &self.cursor {
             ctx.set("cursor", cursor.to_string());
         }
+        if let Some(after) = &self.after {
+            ctx.set("after", after);
+        }
         ctx.set("sort", self.sort.to_name());
         if let Some(creators) = &self.creators {
             ctx.set(
@@ -158,6 +239,21 @@ impl ProjectQuery {
                     .collect::<Vec<String>>(),
             );
         }
+        if let Some(statuses) = &self.statuses {
+            ctx.set("statuses", enum_names(statuses));
+        }
+        if let Some(kinds) = &self.kinds {
+            ctx.set("kinds", enum_names(kinds));
+        }
+        if let Some(prefix) = &self.title_prefix {
+            ctx.set("title_prefix", prefix);
+        }
+        if let Some(unfinished) = self.has_unfinished {
+            ctx.set("has_unfinished", unfinished);
+        }
+        if let Some(aired_unreleased) = self.has_aired_unreleased {
+            ctx.set("has_aired_unreleased", aired_unreleased);
+        }
         if let Some(servers) = &self.servers_users {
             let mapped_data = servers
                 .iter()
@@ -171,6 +267,264 @@ impl ProjectQuery {
         }
         ctx.set("unpaged", self.unpaged);
     }
+
+    /// Build the extra filters, each document should be combined with `$and`
+    fn filter_docs(&self) -> Result<Vec<Document>, showtimes_db::mongodb::bson::ser::Error> {
+        let mut filters = vec![];
+
+        if let Some(statuses) = &self.statuses {
+            let statuses = statuses
+                .iter()
+                .map(to_bson)
+                .collect::<Result<Vec<Bson>, _>>()?;
+            filters.push(doc! { "status": { "$in": statuses } });
+        }
+        if let Some(kinds) = &self.kinds {
+            let kinds = kinds
+                .iter()
+                .map(to_bson)
+                .collect::<Result<Vec<Bson>, _>>()?;
+            filters.push(doc! { "kind": { "$in": kinds } });
+        }
+        if let Some(prefix) = self.title_prefix.as_deref().map(str::trim)
+            && !prefix.is_empty()
+        {
+            filters.push(doc! {
+                "title": {
+                    "$regex": format!("^{}", escape_regex(prefix)),
+                    "$options": "i",
+                }
+            });
+        }
+        if let Some(unfinished) = self.has_unfinished {
+            let matcher = doc! { "$elemMatch": { "finished": false } };
+            filters.push(progress_filter(matcher, unfinished));
+        }
+        if let Some(aired_unreleased) = self.has_aired_unreleased {
+            let now = DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+            let matcher = doc! {
+                "$elemMatch": {
+                    "finished": false,
+                    "aired": { "$lte": now },
+                }
+            };
+            filters.push(progress_filter(matcher, aired_unreleased));
+        }
+
+        Ok(filters)
+    }
+}
+
+/// Get the serialized names of the enums that are stored in the database
+fn enum_names<T: serde::Serialize>(values: &[T]) -> Vec<String> {
+    values
+        .iter()
+        .filter_map(|v| match to_bson(v) {
+            Ok(Bson::String(name)) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Create the progress filter, negating the matcher when `matches` is false
+fn progress_filter(matcher: Document, matches: bool) -> Document {
+    if matches {
+        doc! { "progress": matcher }
+    } else {
+        doc! { "progress": { "$not": matcher } }
+    }
+}
+
+/// Escape the regex special characters so the value is matched literally
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(
+            c,
+            '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The computed field used when sorting by the next unreleased episode
+const NEXT_AIRED_FIELD: &str = "_next_aired";
+
+/// The field used to sort the projects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProjectSortField {
+    Id,
+    Title,
+    Created,
+    Updated,
+    NextAired,
+}
+
+impl ProjectSortField {
+    /// Get the sort field and the direction (`1` or `-1`) from the sort order
+    fn from_sort(sort: SortOrderGQL) -> (Self, i32) {
+        match sort {
+            SortOrderGQL::IdAsc => (Self::Id, 1),
+            SortOrderGQL::IdDesc => (Self::Id, -1),
+            SortOrderGQL::NameAsc => (Self::Title, 1),
+            SortOrderGQL::NameDesc => (Self::Title, -1),
+            SortOrderGQL::CreatedAtAsc => (Self::Created, 1),
+            SortOrderGQL::CreatedAtDesc => (Self::Created, -1),
+            SortOrderGQL::UpdatedAtAsc => (Self::Updated, 1),
+            SortOrderGQL::UpdatedAtDesc => (Self::Updated, -1),
+            SortOrderGQL::NextAiredAsc => (Self::NextAired, 1),
+            SortOrderGQL::NextAiredDesc => (Self::NextAired, -1),
+        }
+    }
+
+    /// The document key used for sorting
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Title => "title",
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::NextAired => NEXT_AIRED_FIELD,
+        }
+    }
+
+    /// Get the sort key value of the project, this should match what is stored in the database
+    fn value_of(&self, project: &showtimes_db::m::Project, direction: i32) -> Bson {
+        match self {
+            Self::Id => Bson::String(project.id.to_string()),
+            Self::Title => Bson::String(project.title.clone()),
+            Self::Created => {
+                Bson::DateTime(DateTime::from_millis(project.created.as_millisecond()))
+            }
+            Self::Updated => {
+                Bson::DateTime(DateTime::from_millis(project.updated.as_millisecond()))
+            }
+            Self::NextAired => {
+                let next_aired = project
+                    .progress
+                    .iter()
+                    .filter(|p| !p.finished)
+                    .filter_map(|p| p.aired)
+                    .min()
+                    .map(|aired| DateTime::from_millis(aired.as_millisecond()))
+                    .unwrap_or_else(|| next_aired_fallback(direction));
+                Bson::DateTime(next_aired)
+            }
+        }
+    }
+}
+
+/// The value used for projects without any upcoming episode,
+/// so they are always at the end of the list regardless of the direction.
+fn next_aired_fallback(direction: i32) -> DateTime {
+    if direction > 0 {
+        DateTime::MAX
+    } else {
+        DateTime::MIN
+    }
+}
+
+/// The sort key value stored in the keyset cursor
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "t", content = "v", rename_all = "lowercase")]
+enum ProjectKeysetValue {
+    Text(String),
+    Time(i64),
+}
+
+/// The keyset cursor, encoded as hex JSON and handed out as `nextKeyset`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ProjectKeyset {
+    /// The sort order this cursor was created with
+    sort: String,
+    /// The sort key value of the first item in the page
+    key: ProjectKeysetValue,
+    /// The ID of the first item in the page, used as tie-breaker
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    id: Ulid,
+}
+
+impl ProjectKeyset {
+    fn new(sort: SortOrderGQL, key: Bson, id: Ulid) -> Option<Self> {
+        let key = match key {
+            Bson::String(text) => ProjectKeysetValue::Text(text),
+            Bson::DateTime(time) => ProjectKeysetValue::Time(time.timestamp_millis()),
+            _ => return None,
+        };
+
+        Some(Self {
+            sort: sort.to_name().to_string(),
+            key,
+            id,
+        })
+    }
+
+    fn encode(&self) -> Option<String> {
+        serde_json::to_vec(self).ok().map(hex::encode)
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let bytes = hex::decode(value.trim()).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn key_bson(&self) -> Bson {
+        match &self.key {
+            ProjectKeysetValue::Text(text) => Bson::String(text.clone()),
+            ProjectKeysetValue::Time(millis) => Bson::DateTime(DateTime::from_millis(*millis)),
+        }
+    }
+}
+
+/// Create the filter to start the page from the provided key, inclusive
+fn keyset_filter(field: ProjectSortField, direction: i32, key: Bson, id: Ulid) -> Document {
+    let (key_op, id_op) = if direction > 0 {
+        ("$gt", "$gte")
+    } else {
+        ("$lt", "$lte")
+    };
+
+    if field == ProjectSortField::Id {
+        return doc! { "id": { id_op: id.to_string() } };
+    }
+
+    doc! {
+        "$or": [
+            { field.key(): { key_op: key.clone() } },
+            { field.key(): key, "id": { id_op: id.to_string() } },
+        ]
+    }
+}
+
+/// Compute the earliest air date of the unfinished episodes
+fn next_aired_stage(direction: i32) -> Document {
+    doc! {
+        "$addFields": {
+            NEXT_AIRED_FIELD: {
+                "$ifNull": [
+                    {
+                        "$min": {
+                            "$map": {
+                                "input": {
+                                    "$filter": {
+                                        "input": "$progress",
+                                        "as": "ep",
+                                        "cond": { "$eq": ["$$ep.finished", false] },
+                                    }
+                                },
+                                "as": "ep",
+                                "in": "$$ep.aired",
+                            }
+                        }
+                    },
+                    next_aired_fallback(direction),
+                ]
+            }
+        }
+    }
 }
 
 /// Query the projects database and return the paginated data.
@@ -303,71 +657,108 @@ pub async fn query_projects_paginated(
         }
     };
 
-    let (query_docs_fetch, query_count_fetch) = if fetch_docs.len() > 1 {
-        match queries.cursor {
-            Some(cursor) => (
-                doc! {
-                    "$or": fetch_docs.clone(),
-                    "id": { "$gte": cursor.to_string() }
-                },
-                doc! {
-                    "$or": fetch_docs,
-                },
-            ),
-            None => (
-                doc! {
-                    "$or": fetch_docs.clone()
-                },
-                doc! {
-                    "$or": fetch_docs
-                },
-            ),
-        }
+    let filter_docs = queries
+        .filter_docs()
+        .extend_error(GQLErrorCode::ParseError, |f_ctx| {
+            queries.dump_query(f_ctx);
+            f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
+        })?;
+
+    let (mut query_docs_fetch, mut query_count_fetch) = if fetch_docs.len() > 1 {
+        (
+            doc! {
+                "$or": fetch_docs.clone()
+            },
+            doc! {
+                "$or": fetch_docs
+            },
+        )
     } else {
         // Guaranteed to have at least one document
         let count_query = fetch_docs.first().unwrap().clone();
-        let mut base_query = count_query.clone();
-        match queries.cursor {
-            Some(cursor) => {
-                if queries.unpaged {
-                    (base_query, count_query)
-                } else {
-                    let cursor = cursor.to_string();
-
-                    // Extend $id query to include $gte
-                    let entry = base_query.entry("id".to_string()).or_insert_with(|| {
-                        showtimes_db::mongodb::bson::Bson::Document({
-                            // empty doc
-                            showtimes_db::mongodb::bson::Document::new()
-                        })
-                    });
-
-                    if let showtimes_db::mongodb::bson::Bson::Document(doc) = entry {
-                        doc.insert("$gte".to_string(), cursor);
-                    }
+        (count_query.clone(), count_query)
+    };
 
-                    (base_query, count_query)
-                }
-            }
-            None => (base_query, count_query),
+    if !filter_docs.is_empty() {
+        query_docs_fetch.insert("$and", filter_docs.clone());
+        query_count_fetch.insert("$and", filter_docs);
+    }
+
+    let (sort_field, direction) = ProjectSortField::from_sort(queries.sort);
+
+    // Resolve where the page should start from, cursor is ignored on unpaged query
+    let start_key = match (&queries.after, queries.cursor) {
+        _ if queries.unpaged => None,
+        (Some(after), _) => {
+            let keyset = ProjectKeyset::decode(after)
+                .filter(|k| k.sort == queries.sort.to_name())
+                .ok_or_else(|| {
+                    GQLError::new(
+                        "Invalid keyset cursor for the requested sort order",
+                        GQLErrorCode::InvalidRequest,
+                    )
+                    .extend(|e| {
+                        queries.dump_query(e);
+                        e.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
+                    })
+                })?;
+
+            Some((keyset.key_bson(), keyset.id))
+        }
+        (None, Some(cursor)) if sort_field == ProjectSortField::Id => {
+            Some((Bson::String(cursor.to_string()), cursor))
+        }
+        (None, Some(cursor)) => {
+            // Legacy ID cursor, find the sort key from the project itself
+            let project = prj_handler
+                .find_by_id(&cursor.to_string())
+                .await
+                .extend_error(GQLErrorCode::ProjectRequestFails, |f_ctx| {
+                    queries.dump_query(f_ctx);
+                    f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
+                })?
+                .ok_or_else(|| {
+                    GQLError::new("Cursor project not found", GQLErrorCode::InvalidRequest).extend(
+                        |e| {
+                            queries.dump_query(e);
+                            e.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
+                        },
+                    )
+                })?;
+
+            Some((sort_field.value_of(&project, direction), cursor))
         }
+        (None, None) => None,
     };
 
-    let col = prj_handler.get_collection();
-    let base_cursor = col
-        .find(query_docs_fetch)
-        .sort(queries.sort.into_sort_doc(Some("title".to_string())));
-
-    let cursor = if queries.unpaged {
-        base_cursor
+    let mut pipeline = vec![doc! { "$match": query_docs_fetch }];
+    if sort_field == ProjectSortField::NextAired {
+        pipeline.push(next_aired_stage(direction));
+    }
+    if let Some((key, id)) = start_key {
+        pipeline.push(doc! { "$match": keyset_filter(sort_field, direction, key, id) });
+    }
+    if sort_field == ProjectSortField::Id {
+        pipeline.push(doc! { "$sort": { "id": direction } });
     } else {
-        base_cursor.limit((per_page + 1) as i64)
+        // Always tie-break with the ID so the order is stable
+        pipeline.push(doc! { "$sort": { sort_field.key(): direction, "id": direction } });
+    }
+    if !queries.unpaged {
+        pipeline.push(doc! { "$limit": (per_page + 1) as i64 });
     }
-    .await
-    .extend_error(GQLErrorCode::ProjectRequestFails, |f_ctx| {
-        queries.dump_query(f_ctx);
-        f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
-    })?;
+    if sort_field == ProjectSortField::NextAired {
+        pipeline.push(doc! { "$unset": NEXT_AIRED_FIELD });
+    }
+
+    let col = prj_handler.get_collection();
+    let cursor =
+        col.aggregate(pipeline)
+            .await
+            .extend_error(GQLErrorCode::ProjectRequestFails, |f_ctx| {
+                queries.dump_query(f_ctx);
+                f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderPaginated);
+            })?;
     let count = prj_handler
         .get_collection()
         .count_documents(query_count_fetch)
@@ -377,7 +768,7 @@ pub async fn query_projects_paginated(
             f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderPaginatedCount);
         })?;
 
-    let mut all_servers: Vec<showtimes_db::m::Project> =
+    let documents: Vec<Document> =
         cursor
             .try_collect()
             .await
@@ -387,6 +778,18 @@ pub async fn query_projects_paginated(
                 f_ctx.set("where_req", GQLDataLoaderWhere::ProjectLoaderPaginatedCount);
             })?;
 
+    let mut all_servers = Vec::with_capacity(documents.len());
+    for document in documents {
+        let project: showtimes_db::m::Project = showtimes_db::mongodb::bson::from_document(
+            document,
+        )
+        .extend_error(GQLErrorCode::ParseError, |f_ctx| {
+            queries.dump_query(f_ctx);
+            f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderCollect);
+        })?;
+        all_servers.push(project);
+    }
+
     if queries.unpaged {
         let page_info = PageInfoGQL::new(count, per_page, None);
         return Ok(PaginatedResult::new(all_servers, page_info));
@@ -403,7 +806,12 @@ pub async fn query_projects_paginated(
         None
     };
 
-    let page_info = PageInfoGQL::new(count, per_page, last_srv.map(|p| p.id.into()));
+    let next_keyset = last_srv.as_ref().and_then(|p| {
+        ProjectKeyset::new(queries.sort, sort_field.value_of(p, direction), p.id)
+            .and_then(|k| k.encode())
+    });
+    let page_info = PageInfoGQL::new(count, per_page, last_srv.map(|p| p.id.into()))
+        .with_next_keyset(next_keyset);
 
     Ok(PaginatedResult::new(all_servers, page_info))
 }
@@ -0,0 +1,261 @@
+//! Episode schedule related queries
+
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use showtimes_db::{
+    DatabaseShared,
+    m::UserPrivilege,
+    mongodb::bson::{DateTime, Document, doc},
+};
+use showtimes_gql_common::{
+    GQLDataLoaderWhere, GQLErrorCode, GQLErrorExt,
+    errors::GQLError,
+    queries::{MinimalServerUsers, ServerQueryUser},
+};
+use showtimes_shared::ulid::Ulid;
+
+/// The maximum range of a single schedule query
+pub const MAX_SCHEDULE_RANGE_DAYS: i64 = 31;
+
+/// The maximum episodes returned in a single schedule query
+pub const MAX_SCHEDULE_EPISODES: i64 = 500;
+
+/// A single episode airing, along with the minimal project information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleEpisode {
+    /// The project ID
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    pub id: Ulid,
+    /// The project title
+    pub title: String,
+    /// The project poster
+    pub poster: showtimes_db::m::Poster,
+    /// The project roles
+    pub roles: Vec<showtimes_db::m::Role>,
+    /// The server ID creator of the project
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    pub creator: Ulid,
+    /// The project status
+    pub status: showtimes_db::m::ProjectStatus,
+    /// The project type
+    pub kind: showtimes_db::m::ProjectType,
+    /// The airing episode
+    pub progress: showtimes_db::m::EpisodeProgress,
+}
+
+/// The config for querying the episode schedule
+#[derive(Debug, Clone)]
+pub struct ScheduleQuery {
+    /// The start of the range, inclusive
+    from: jiff::Timestamp,
+    /// The end of the range, exclusive
+    to: jiff::Timestamp,
+    /// The server that created the project
+    creators: Option<Vec<Ulid>>,
+    /// Allowed servers to query
+    servers_users: Option<Vec<MinimalServerUsers>>,
+    current_user: Option<ServerQueryUser>,
+}
+
+impl ScheduleQuery {
+    /// Create a new schedule query for the provided range
+    pub fn new(from: jiff::Timestamp, to: jiff::Timestamp) -> Self {
+        ScheduleQuery {
+            from,
+            to,
+            creators: None,
+            servers_users: None,
+            current_user: None,
+        }
+    }
+
+    /// Set the servers fetching this data
+    pub fn set_creators(&mut self, creators: &[Ulid]) {
+        self.creators = Some(creators.to_vec());
+    }
+
+    /// Set the allowed servers to query
+    pub fn set_allowed_servers(&mut self, servers: Vec<showtimes_db::m::Server>) {
+        self.servers_users = Some(servers.into_iter().map(|s| s.into()).collect());
+    }
+
+    /// Set the current user fetching this data
+    pub fn with_current_user(mut self, user: ServerQueryUser) -> Self {
+        self.current_user = Some(user);
+        self
+    }
+
+    fn dump_query(&self, ctx: &mut async_graphql::ErrorExtensionValues) {
+        ctx.set("from", self.from.to_string());
+        ctx.set("to", self.to.to_string());
+        if let Some(creators) = &self.creators {
+            ctx.set(
+                "creators",
+                creators
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        if let Some(servers) = &self.servers_users {
+            let mapped_data = servers
+                .iter()
+                .map(|s| s.as_graphql_value())
+                .collect::<Vec<_>>();
+
+            ctx.set("servers", mapped_data);
+        }
+        if let Some(user) = &self.current_user {
+            ctx.set("current_user", user.as_graphql_value());
+        }
+    }
+
+    /// The access filter for the projects
+    fn access_docs(&self) -> async_graphql::Result<Vec<Document>> {
+        match (&self.servers_users, self.current_user) {
+            (Some(servers), Some(user_info)) => {
+                let user_methods: Vec<(Ulid, &showtimes_db::m::ServerUser)> = servers
+                    .iter()
+                    .filter(|s| match &self.creators {
+                        Some(creators) => creators.contains(&s.id()),
+                        None => true,
+                    })
+                    .filter_map(|s| {
+                        s.owners()
+                            .iter()
+                            .find(|&o| o.id == user_info.id())
+                            .map(|o| (s.id(), o))
+                    })
+                    .collect();
+
+                if user_methods.is_empty() {
+                    return GQLError::new(
+                        "User does not have access to any of the allowed servers",
+                        GQLErrorCode::UserInsufficientPrivilege,
+                    )
+                    .extend(|e| {
+                        e.set("user_id", user_info.id().to_string());
+                        self.dump_query(e);
+                    })
+                    .into();
+                }
+
+                Ok(user_methods
+                    .iter()
+                    .map(|(s, m)| {
+                        if m.privilege == UserPrivilege::ProjectManager {
+                            doc! {
+                                "creator": s.to_string(),
+                                "id": { "$in": m.extras.clone() }
+                            }
+                        } else {
+                            doc! {
+                                "creator": s.to_string()
+                            }
+                        }
+                    })
+                    .collect())
+            }
+            _ => match &self.creators {
+                Some(creators) => {
+                    let creators: Vec<String> = creators.iter().map(|id| id.to_string()).collect();
+                    Ok(vec![doc! { "creator": { "$in": creators } }])
+                }
+                None => Ok(vec![Document::new()]),
+            },
+        }
+    }
+}
+
+/// Query the episodes airing in the provided range, sorted by the air time.
+///
+/// This unwinds the progress in the database, so only the matching episodes are returned.
+pub async fn query_schedule(
+    ctx: &async_graphql::Context<'_>,
+    queries: ScheduleQuery,
+) -> async_graphql::Result<Vec<ScheduleEpisode>> {
+    if queries.to <= queries.from {
+        return GQLError::new(
+            "The end of the range must be after the start",
+            GQLErrorCode::InvalidRequest,
+        )
+        .extend(|e| queries.dump_query(e))
+        .into();
+    }
+
+    let max_range = jiff::SignedDuration::from_hours(24 * MAX_SCHEDULE_RANGE_DAYS);
+    if queries.from.duration_until(queries.to) > max_range {
+        return GQLError::new(
+            format!("The range cannot be longer than {MAX_SCHEDULE_RANGE_DAYS} days"),
+            GQLErrorCode::InvalidRequest,
+        )
+        .extend(|e| queries.dump_query(e))
+        .into();
+    }
+
+    let db = ctx.data_unchecked::<DatabaseShared>();
+    let prj_handler = showtimes_db::ProjectHandler::new(db);
+
+    let aired_range = doc! {
+        "$gte": DateTime::from_millis(queries.from.as_millisecond()),
+        "$lt": DateTime::from_millis(queries.to.as_millisecond()),
+    };
+
+    let pipeline = vec![
+        // $elemMatch allow the multikey index on `progress.aired` to be used
+        doc! {
+            "$match": {
+                "$or": queries.access_docs()?,
+                "progress": { "$elemMatch": { "aired": aired_range.clone() } },
+            }
+        },
+        doc! { "$unwind": "$progress" },
+        doc! { "$match": { "progress.aired": aired_range } },
+        doc! { "$sort": { "progress.aired": 1, "id": 1, "progress.number": 1 } },
+        doc! { "$limit": MAX_SCHEDULE_EPISODES },
+        doc! {
+            "$project": {
+                "_id": 0,
+                "id": 1,
+                "title": 1,
+                "poster": 1,
+                "roles": 1,
+                "creator": 1,
+                "status": 1,
+                "kind": 1,
+                "progress": 1,
+            }
+        },
+    ];
+
+    let cursor = prj_handler
+        .get_collection()
+        .aggregate(pipeline)
+        .await
+        .extend_error(GQLErrorCode::ProjectRequestFails, |f_ctx| {
+            queries.dump_query(f_ctx);
+            f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderSchedule);
+        })?;
+
+    let documents: Vec<Document> =
+        cursor
+            .try_collect()
+            .await
+            .extend_error(GQLErrorCode::ProjectRequestFails, |f_ctx| {
+                queries.dump_query(f_ctx);
+                f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderCollect);
+                f_ctx.set("where_req", GQLDataLoaderWhere::ProjectLoaderSchedule);
+            })?;
+
+    let mut episodes = Vec::with_capacity(documents.len());
+    for document in documents {
+        let episode: ScheduleEpisode = showtimes_db::mongodb::bson::from_document(document)
+            .extend_error(GQLErrorCode::ParseError, |f_ctx| {
+                queries.dump_query(f_ctx);
+                f_ctx.set("where", GQLDataLoaderWhere::ProjectLoaderSchedule);
+            })?;
+        episodes.push(episode);
+    }
+
+    Ok(episodes)
+}
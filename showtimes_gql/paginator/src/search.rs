@@ -0,0 +1,312 @@
+//! Full-text search queries backed by Meilisearch
+
+use showtimes_db::m::UserPrivilege;
+use showtimes_gql_common::{
+    GQLErrorCode, GQLErrorExt,
+    errors::GQLError,
+    queries::{MinimalServerUsers, ServerQueryUser},
+};
+use showtimes_search::SearchClientShared;
+use showtimes_shared::ulid::Ulid;
+
+/// The default amount of search hits returned
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// The maximum amount of search hits returned
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Quote a string value to be used in Meilisearch filter
+fn quote_filter(value: impl AsRef<str>) -> String {
+    let escaped = value.as_ref().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{escaped}\"")
+}
+
+/// Get the serialized enum name that is stored in the search index
+fn serde_name<T: serde::Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+fn filter_in(field: &str, values: impl IntoIterator<Item = impl AsRef<str>>) -> String {
+    let values: Vec<String> = values.into_iter().map(quote_filter).collect();
+    format!("{field} IN [{}]", values.join(", "))
+}
+
+fn clamp_limit(limit: Option<usize>) -> usize {
+    limit
+        .filter(|&l| (1..=MAX_SEARCH_LIMIT).contains(&l))
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+}
+
+/// The config for searching projects
+#[derive(Debug, Clone)]
+pub struct ProjectSearchQuery {
+    /// The full-text query
+    query: String,
+    /// The server that created the project
+    creators: Option<Vec<Ulid>>,
+    /// The project type
+    kind: Option<showtimes_db::m::ProjectType>,
+    /// The project status
+    status: Option<showtimes_db::m::ProjectStatus>,
+    /// The amount of hits to return
+    limit: Option<usize>,
+    /// Allowed servers to query
+    servers_users: Option<Vec<MinimalServerUsers>>,
+    current_user: Option<ServerQueryUser>,
+}
+
+impl ProjectSearchQuery {
+    /// Create a new project search query
+    pub fn new(query: impl Into<String>) -> Self {
+        ProjectSearchQuery {
+            query: query.into(),
+            creators: None,
+            kind: None,
+            status: None,
+            limit: None,
+            servers_users: None,
+            current_user: None,
+        }
+    }
+
+    /// Set the servers fetching this data
+    pub fn set_creators(&mut self, creators: &[Ulid]) {
+        self.creators = Some(creators.to_vec());
+    }
+
+    /// Set the project type to filter
+    pub fn set_kind(&mut self, kind: showtimes_db::m::ProjectType) {
+        self.kind = Some(kind);
+    }
+
+    /// Set the project status to filter
+    pub fn set_status(&mut self, status: showtimes_db::m::ProjectStatus) {
+        self.status = Some(status);
+    }
+
+    /// Set the amount of hits to return
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+
+    /// Set the allowed servers to query
+    pub fn set_allowed_servers(&mut self, servers: Vec<showtimes_db::m::Server>) {
+        self.servers_users = Some(servers.into_iter().map(|s| s.into()).collect());
+    }
+
+    /// Set the current user fetching this data
+    pub fn with_current_user(mut self, user: ServerQueryUser) -> Self {
+        self.current_user = Some(user);
+        self
+    }
+
+    fn dump_query(&self, ctx: &mut async_graphql::ErrorExtensionValues) {
+        ctx.set("query", &self.query);
+        if let Some(creators) = &self.creators {
+            ctx.set(
+                "creators",
+                creators
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+        if let Some(kind) = self.kind.as_ref().and_then(serde_name) {
+            ctx.set("kind", kind);
+        }
+        if let Some(status) = self.status.as_ref().and_then(serde_name) {
+            ctx.set("status", status);
+        }
+        if let Some(servers) = &self.servers_users {
+            let mapped_data = servers
+                .iter()
+                .map(|s| s.as_graphql_value())
+                .collect::<Vec<_>>();
+
+            ctx.set("servers", mapped_data);
+        }
+        if let Some(user) = &self.current_user {
+            ctx.set("current_user", user.as_graphql_value());
+        }
+    }
+
+    /// Build the access filter clauses, `None` means there is no restriction.
+    ///
+    /// Each clause should be joined with `OR`, an empty list means no project is accessible.
+    fn access_filter(&self) -> async_graphql::Result<Option<Vec<String>>> {
+        match (&self.servers_users, self.current_user) {
+            (Some(servers), Some(user_info)) => {
+                let user_methods: Vec<(Ulid, &showtimes_db::m::ServerUser)> = servers
+                    .iter()
+                    .filter(|s| match &self.creators {
+                        Some(creators) => creators.contains(&s.id()),
+                        None => true,
+                    })
+                    .filter_map(|s| {
+                        s.owners()
+                            .iter()
+                            .find(|&o| o.id == user_info.id())
+                            .map(|o| (s.id(), o))
+                    })
+                    .collect();
+
+                if user_methods.is_empty() {
+                    return GQLError::new(
+                        "User does not have access to any of the allowed servers",
+                        GQLErrorCode::UserInsufficientPrivilege,
+                    )
+                    .extend(|e| {
+                        e.set("user_id", user_info.id().to_string());
+                        self.dump_query(e);
+                    })
+                    .into();
+                }
+
+                let clauses = user_methods
+                    .iter()
+                    .filter_map(|(s, m)| {
+                        let parent = format!("parent = {}", quote_filter(s.to_string()));
+                        if m.privilege == UserPrivilege::ProjectManager {
+                            // Project manager without any project can't see anything
+                            (!m.extras.is_empty())
+                                .then(|| format!("({parent} AND {})", filter_in("id", &m.extras)))
+                        } else {
+                            Some(parent)
+                        }
+                    })
+                    .collect();
+
+                Ok(Some(clauses))
+            }
+            _ => Ok(self.creators.as_ref().map(|creators| {
+                vec![filter_in(
+                    "parent",
+                    creators.iter().map(|id| id.to_string()),
+                )]
+            })),
+        }
+    }
+}
+
+/// Search the projects index, returning the project IDs ordered by relevancy.
+pub async fn search_projects(
+    ctx: &async_graphql::Context<'_>,
+    queries: ProjectSearchQuery,
+) -> async_graphql::Result<Vec<Ulid>> {
+    let meili = ctx.data_unchecked::<SearchClientShared>();
+
+    let mut filters = vec![];
+    if let Some(clauses) = queries.access_filter()? {
+        if clauses.is_empty() {
+            return Ok(vec![]);
+        }
+        filters.push(format!("({})", clauses.join(" OR ")));
+    }
+    if let Some(kind) = queries.kind.as_ref().and_then(serde_name) {
+        filters.push(format!("kind = {}", quote_filter(kind)));
+    }
+    if let Some(status) = queries.status.as_ref().and_then(serde_name) {
+        filters.push(format!("status = {}", quote_filter(status)));
+    }
+    let filter = filters.join(" AND ");
+
+    let index = meili.index(showtimes_search::models::Project::index_name());
+    let mut search = index.search();
+    search
+        .with_query(&queries.query)
+        .with_limit(clamp_limit(queries.limit));
+    if !filter.is_empty() {
+        search.with_filter(&filter);
+    }
+
+    let results = search
+        .execute::<showtimes_search::models::Project>()
+        .await
+        .extend_error(GQLErrorCode::ProjectQuerySearchError, |f_ctx| {
+            queries.dump_query(f_ctx);
+            f_ctx.set("filter", &filter);
+        })?;
+
+    Ok(results.hits.into_iter().map(|hit| hit.result.id).collect())
+}
+
+/// The config for searching servers
+#[derive(Debug, Clone)]
+pub struct ServerSearchQuery {
+    /// The full-text query
+    query: String,
+    /// The amount of hits to return
+    limit: Option<usize>,
+    /// Allowed servers to query
+    allowed_servers: Option<Vec<Ulid>>,
+}
+
+impl ServerSearchQuery {
+    /// Create a new server search query
+    pub fn new(query: impl Into<String>) -> Self {
+        ServerSearchQuery {
+            query: query.into(),
+            limit: None,
+            allowed_servers: None,
+        }
+    }
+
+    /// Set the amount of hits to return
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+
+    /// Set the allowed servers to query
+    pub fn set_allowed_servers(&mut self, servers: Vec<showtimes_db::m::Server>) {
+        self.allowed_servers = Some(servers.into_iter().map(|s| s.id).collect());
+    }
+
+    fn dump_query(&self, ctx: &mut async_graphql::ErrorExtensionValues) {
+        ctx.set("query", &self.query);
+        if let Some(servers) = &self.allowed_servers {
+            ctx.set(
+                "servers",
+                servers
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>(),
+            );
+        }
+    }
+}
+
+/// Search the servers index, returning the server IDs ordered by relevancy.
+pub async fn search_servers(
+    ctx: &async_graphql::Context<'_>,
+    queries: ServerSearchQuery,
+) -> async_graphql::Result<Vec<Ulid>> {
+    let meili = ctx.data_unchecked::<SearchClientShared>();
+
+    let filter = match &queries.allowed_servers {
+        Some(servers) if servers.is_empty() => return Ok(vec![]),
+        Some(servers) => filter_in("id", servers.iter().map(|id| id.to_string())),
+        None => String::new(),
+    };
+
+    let index = meili.index(showtimes_search::models::Server::index_name());
+    let mut search = index.search();
+    search
+        .with_query(&queries.query)
+        .with_limit(clamp_limit(queries.limit));
+    if !filter.is_empty() {
+        search.with_filter(&filter);
+    }
+
+    let results = search
+        .execute::<showtimes_search::models::Server>()
+        .await
+        .extend_error(GQLErrorCode::ServerQuerySearchError, |f_ctx| {
+            queries.dump_query(f_ctx);
+            f_ctx.set("filter", &filter);
+        })?;
+
+    Ok(results.hits.into_iter().map(|hit| hit.result.id).collect())
+}
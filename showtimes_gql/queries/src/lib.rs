@@ -13,7 +13,8 @@ use showtimes_gql_common::{
 use showtimes_gql_events::QueryEventsRoot;
 use showtimes_gql_models::{
     common::PaginatedGQL,
-    projects::ProjectGQL,
+    projects::{ProjectGQL, ProjectStatusGQL, ProjectTypeGQL},
+    schedule::ScheduleEpisodeGQL,
     search::QuerySearchRoot,
     servers::ServerGQL,
     stats::StatsGQL,
@@ -24,6 +25,29 @@ use showtimes_session::{ShowtimesRefreshSession, ShowtimesUserSession};
 /// The main Query Root type for the GraphQL schema. This is where all the queries are defined.
 pub struct QueryRoot;
 
+/// Limit the requested server IDs to the servers allowed by the API key of the request
+fn scoped_server_ids(
+    ctx: &Context<'_>,
+    ids: Option<Vec<showtimes_gql_common::UlidGQL>>,
+) -> Option<Vec<showtimes_shared::ulid::Ulid>> {
+    let ids: Option<Vec<showtimes_shared::ulid::Ulid>> =
+        ids.map(|ids| ids.into_iter().map(|id| *id).collect());
+
+    match ctx
+        .data_opt::<showtimes_db::m::APIKey>()
+        .filter(|api_key| api_key.is_server_scoped())
+    {
+        Some(api_key) => Some(match ids {
+            Some(ids) => ids
+                .into_iter()
+                .filter(|&id| api_key.can_access_server(id))
+                .collect(),
+            None => api_key.servers.clone(),
+        }),
+        None => ids,
+    }
+}
+
 /// The main Query Root type for the GraphQL schema. This is where all the queries are defined.
 #[Object]
 impl QueryRoot {
@@ -65,8 +89,8 @@ impl QueryRoot {
 
         let mut queries = showtimes_gql_paginator::servers::ServerQuery::new()
             .with_current_user(showtimes_gql_common::queries::ServerQueryUser::from(user));
-        if let Some(ids) = ids {
-            queries.set_ids(ids.into_iter().map(|id| *id).collect());
+        if let Some(ids) = scoped_server_ids(ctx, ids) {
+            queries.set_ids(ids);
         };
         if let Some(per_page) = per_page {
             queries.set_per_page(per_page);
@@ -91,7 +115,7 @@ impl QueryRoot {
             })
             .collect();
 
-        Ok(PaginatedGQL::new(mapped_nodes, *results.page_info()))
+        Ok(PaginatedGQL::new(mapped_nodes, results.page_info().clone()))
     }
 
     /// Get authenticated user associated projects
@@ -114,11 +138,35 @@ impl QueryRoot {
         )]
         per_page: Option<u32>,
         #[graphql(desc = "The cursor to start from")] cursor: Option<showtimes_gql_common::UlidGQL>,
-        #[graphql(desc = "Sort order, default to ID_ASC")] sort: Option<
-            showtimes_gql_common::SortOrderGQL,
-        >,
+        #[graphql(
+            desc = "The keyset cursor to start from, taken from `pageInfo.nextKeyset`. Takes priority over `cursor` and must be used with the same sort order"
+        )]
+        after: Option<String>,
+        #[graphql(
+            desc = "Sort order, default to ID_ASC. Sorting by name, dates, and next aired episode is stable across pages when using `after`"
+        )]
+        sort: Option<showtimes_gql_common::SortOrderGQL>,
         #[graphql(desc = "Remove pagination limit, this only works if you're an Admin")]
         unpaged: bool,
+        #[graphql(desc = "Limit projects to specific statuses")] statuses: Option<
+            Vec<ProjectStatusGQL>,
+        >,
+        #[graphql(desc = "Limit projects to specific types")] kinds: Option<Vec<ProjectTypeGQL>>,
+        #[graphql(
+            name = "titlePrefix",
+            desc = "Limit projects to title starting with this value, case-insensitive"
+        )]
+        title_prefix: Option<String>,
+        #[graphql(
+            name = "hasUnfinished",
+            desc = "Limit projects with (or without) any unfinished episodes"
+        )]
+        has_unfinished: Option<bool>,
+        #[graphql(
+            name = "hasAiredUnreleased",
+            desc = "Limit projects with (or without) any episodes that already aired but not released"
+        )]
+        has_aired_unreleased: Option<bool>,
     ) -> async_graphql::Result<PaginatedGQL<ProjectGQL>> {
         let user = ctx.data_unchecked::<showtimes_db::m::User>();
 
@@ -136,9 +184,7 @@ impl QueryRoot {
         if let Some(ids) = ids {
             queries.set_ids(ids.into_iter().map(|id| *id).collect());
         };
-        if let Some(server_ids) = server_ids {
-            let server_ids: Vec<showtimes_shared::ulid::Ulid> =
-                server_ids.into_iter().map(|id| *id).collect();
+        if let Some(server_ids) = scoped_server_ids(ctx, server_ids) {
             queries.set_creators(&server_ids);
         };
         if let Some(per_page) = per_page {
@@ -147,6 +193,9 @@ impl QueryRoot {
         if let Some(cursor) = cursor {
             queries.set_cursor(*cursor);
         }
+        if let Some(after) = after {
+            queries.set_after(after);
+        }
         if let Some(sort) = sort {
             queries.set_sort(sort);
         }
@@ -156,13 +205,73 @@ impl QueryRoot {
         if unpaged && user.kind != showtimes_db::m::UserKind::User {
             queries.set_unpaged();
         }
+        if let Some(statuses) = statuses {
+            queries.set_statuses(statuses.into_iter().map(|s| s.into()).collect());
+        }
+        if let Some(kinds) = kinds {
+            queries.set_kinds(kinds.into_iter().map(|k| k.into()).collect());
+        }
+        if let Some(title_prefix) = title_prefix {
+            queries.set_title_prefix(title_prefix);
+        }
+        if let Some(has_unfinished) = has_unfinished {
+            queries.set_unfinished(has_unfinished);
+        }
+        if let Some(has_aired_unreleased) = has_aired_unreleased {
+            queries.set_aired_unreleased(has_aired_unreleased);
+        }
 
         let results =
             showtimes_gql_paginator::projects::query_projects_paginated(ctx, queries).await?;
 
         let mapped_nodes: Vec<ProjectGQL> = results.nodes().iter().map(ProjectGQL::from).collect();
 
-        Ok(PaginatedGQL::new(mapped_nodes, *results.page_info()))
+        Ok(PaginatedGQL::new(mapped_nodes, results.page_info().clone()))
+    }
+
+    /// Get the episodes airing in the provided range, sorted by the air time.
+    ///
+    /// The range is limited to 31 days and 500 episodes.
+    #[graphql(
+        guard = "AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::QueryProjects))"
+    )]
+    async fn schedule(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The start of the range, inclusive")]
+        from: showtimes_gql_common::DateTimeGQL,
+        #[graphql(desc = "The end of the range, exclusive")] to: showtimes_gql_common::DateTimeGQL,
+        #[graphql(name = "serverIds", desc = "Limit episodes to specific server IDs")]
+        server_ids: Option<Vec<showtimes_gql_common::UlidGQL>>,
+    ) -> async_graphql::Result<Vec<ScheduleEpisodeGQL>> {
+        let user = ctx.data_unchecked::<showtimes_db::m::User>();
+
+        let allowed_servers = match user.kind {
+            showtimes_db::m::UserKind::User => {
+                let projector = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+                Some(
+                    projector
+                        .load_one(ServerOwnerId::new(user.id))
+                        .await?
+                        .unwrap_or_default(),
+                )
+            }
+            _ => None,
+        };
+
+        let mut queries = showtimes_gql_paginator::schedule::ScheduleQuery::new(*from, *to)
+            .with_current_user(user.clone().into());
+        if let Some(server_ids) = scoped_server_ids(ctx, server_ids) {
+            queries.set_creators(&server_ids);
+        };
+        if let Some(allowed_servers) = allowed_servers {
+            queries.set_allowed_servers(allowed_servers);
+        }
+
+        let results = showtimes_gql_paginator::schedule::query_schedule(ctx, queries).await?;
+
+        Ok(results.into_iter().map(ScheduleEpisodeGQL::from).collect())
     }
 
     /// Get all available users, you need a minimum of admin role to access this
@@ -207,7 +316,7 @@ impl QueryRoot {
 
         let mapped_nodes: Vec<UserGQL> = results.nodes().iter().map(UserGQL::from).collect();
 
-        Ok(PaginatedGQL::new(mapped_nodes, *results.page_info()))
+        Ok(PaginatedGQL::new(mapped_nodes, results.page_info().clone()))
     }
 
     /// Get server statistics
@@ -1,5 +1,6 @@
-use std::{fmt::Debug, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
 
+use ahash::{HashSet, HashSetExt};
 use async_graphql::OutputType;
 use futures_util::{Stream, StreamExt};
 use serde::{Serialize, de::DeserializeOwned};
@@ -8,6 +9,20 @@ use showtimes_gql_events_models::{
     prelude::{EventGQL, QueryNew},
     rss::RSSEventGQL,
 };
+use showtimes_shared::ulid::Ulid;
+
+use crate::scope::{ProjectScoped, ServerScope};
+
+/// The scope of the watcher with the function to get the project and server of the event
+type WatcherScope<O> = (Arc<ServerScope>, fn(&O) -> (Ulid, Option<Ulid>));
+
+/// Check if the event is allowed by the scope, unscoped watcher allow everything.
+async fn in_scope<O>(scope: &Option<WatcherScope<O>>, data: &O) -> bool {
+    match scope {
+        Some((scope, target)) => scope.contains(target(data)).await,
+        None => true,
+    }
+}
 
 pub(crate) struct EventWatcher<
     O: Serialize + DeserializeOwned + Send + Sync + Clone + Unpin + Debug + 'static,
@@ -24,6 +39,7 @@ pub(crate) struct EventWatcherWithUser<
 > {
     kind: showtimes_events::m::EventKind,
     user: ServerQueryUser,
+    scope: Option<WatcherScope<O>>,
     _pin_o: PhantomData<O>,
     _pin_t: PhantomData<T>,
 }
@@ -46,81 +62,7 @@ impl<
         ctx: &async_graphql::Context<'_>,
         id: Option<UlidGQL>,
     ) -> impl Stream<Item = EventGQL<T>> + use<O, T> {
-        let mut stream_map = tokio_stream::StreamMap::new();
-        let (tx_mem, rx_mem) = tokio::sync::mpsc::channel(100);
-
-        tokio::spawn(async move {
-            let mut subscribers = showtimes_events::MemoryBroker::<O>::subscribe();
-            while let Some(event) = subscribers.next().await {
-                let inner = T::from(event.data());
-                let parsed_data = EventGQL::new(
-                    event.id(),
-                    inner,
-                    event.kind().into(),
-                    event.actor().map(|a| a.to_string()),
-                    event.timestamp(),
-                );
-                if let Err(e) = tx_mem.send(parsed_data).await {
-                    // Stop process
-                    tracing::warn!("Channel is closed on memory broker, stopping: {e}");
-                    break;
-                }
-            }
-
-            // Close the channel
-            drop(tx_mem)
-        });
-
-        stream_map.insert(
-            "memory_stream",
-            tokio_stream::wrappers::ReceiverStream::new(rx_mem),
-        );
-
-        if let Some(id) = id {
-            let query_stream = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
-            let mut stream = query_stream.query::<O>(self.kind).start_after(*id);
-
-            let (tx_query, rx_query) = tokio::sync::mpsc::channel(100);
-            tokio::spawn(async move {
-                while !stream.is_exhausted() {
-                    let event_batch = stream.advance().await;
-                    match event_batch {
-                        Ok(event_batch) => {
-                            for event in event_batch.iter() {
-                                let inner = T::from(event.data());
-                                let parsed_data = EventGQL::new(
-                                    event.id(),
-                                    inner,
-                                    event.kind().into(),
-                                    event.actor().map(|a| a.to_string()),
-                                    event.timestamp(),
-                                );
-
-                                if let Err(e) = tx_query.send(parsed_data).await {
-                                    // Stop process
-                                    tracing::warn!(
-                                        "Channel is closed on query stream, stopping: {e}"
-                                    );
-                                    break;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            // Stop process
-                            tracing::warn!("Failed querying data from query stream: {e}");
-                            break;
-                        }
-                    }
-                }
-            });
-
-            stream_map.insert(
-                "query_streams",
-                tokio_stream::wrappers::ReceiverStream::new(rx_query),
-            );
-        };
-
-        stream_map.map(|(_, item)| item)
+        stream_resumable(ctx, self.kind, id, None, |data| T::from(data))
     }
 }
 
@@ -133,158 +75,406 @@ impl<
         Self {
             kind,
             user,
+            scope: None,
             _pin_o: PhantomData,
             _pin_t: PhantomData,
         }
     }
 
+    /// Only emit the events of projects that belong to the provided scope
+    pub(crate) fn with_scope(mut self, scope: Option<ServerScope>) -> Self
+    where
+        O: ProjectScoped,
+    {
+        self.scope = scope.map(|scope| (Arc::new(scope), O::scope_target as fn(&O) -> _));
+        self
+    }
+
     pub(crate) fn stream(
         self,
         ctx: &async_graphql::Context<'_>,
         id: Option<UlidGQL>,
     ) -> impl Stream<Item = EventGQL<T>> + use<O, T> {
-        let mut stream_map = tokio_stream::StreamMap::new();
-        let (tx_mem, rx_mem) = tokio::sync::mpsc::channel(100);
-
-        tokio::spawn(async move {
-            let mut subscribers = showtimes_events::MemoryBroker::<O>::subscribe();
-            while let Some(event) = subscribers.next().await {
-                let inner = T::new(event.data(), self.user);
-                let parsed_data = EventGQL::new(
-                    event.id(),
-                    inner,
-                    event.kind().into(),
-                    event.actor().map(|a| a.to_string()),
-                    event.timestamp(),
-                );
-                if let Err(e) = tx_mem.send(parsed_data).await {
-                    // Stop process
-                    tracing::warn!("Channel is closed on memory broker, stopping: {e}");
-                    break;
-                }
-            }
+        let user = self.user;
+        stream_resumable(ctx, self.kind, id, self.scope, move |data| {
+            T::new(data, user)
+        })
+    }
+}
 
-            // Close the channel
-            drop(tx_mem)
-        });
+/// Convert the event to the GQL event if it's in scope
+async fn scoped_event<O, T>(
+    event: showtimes_events::m::SHEvent<O>,
+    scope: Option<WatcherScope<O>>,
+    convert: &impl Fn(&O) -> T,
+) -> Option<EventGQL<T>>
+where
+    O: Serialize + Send + Sync + Clone + 'static,
+    T: OutputType,
+{
+    if !in_scope(&scope, event.data()).await {
+        return None;
+    }
 
-        stream_map.insert(
-            "memory_stream",
-            tokio_stream::wrappers::ReceiverStream::new(rx_mem),
-        );
+    Some(EventGQL::new(
+        event.id(),
+        convert(event.data()),
+        event.kind().into(),
+        event.actor().map(|a| a.to_string()),
+        event.timestamp(),
+    ))
+}
 
-        if let Some(id) = id {
-            let query_stream = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
-            let mut stream = query_stream.query::<O>(self.kind).start_after(*id);
-
-            let (tx_query, rx_query) = tokio::sync::mpsc::channel(100);
-            tokio::spawn(async move {
-                while !stream.is_exhausted() {
-                    let event_batch = stream.advance().await;
-                    match event_batch {
-                        Ok(event_batch) => {
-                            for event in event_batch.iter() {
-                                let inner = T::new(event.data(), self.user);
-                                let parsed_data = EventGQL::new(
-                                    event.id(),
-                                    inner,
-                                    event.kind().into(),
-                                    event.actor().map(|a| a.to_string()),
-                                    event.timestamp(),
-                                );
-
-                                if let Err(e) = tx_query.send(parsed_data).await {
-                                    // Stop process
-                                    tracing::warn!(
-                                        "Channel is closed on query stream, stopping: {e}"
-                                    );
-                                    break;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            // Stop process
-                            tracing::warn!("Failed querying data from query stream: {e}");
-                            break;
-                        }
+/// Stream the live events, resuming from the event after `id` if provided.
+fn stream_resumable<O, T, F>(
+    ctx: &async_graphql::Context<'_>,
+    kind: showtimes_events::m::EventKind,
+    id: Option<UlidGQL>,
+    scope: Option<WatcherScope<O>>,
+    convert: F,
+) -> tokio_stream::wrappers::ReceiverStream<EventGQL<T>>
+where
+    O: Serialize + DeserializeOwned + Send + Sync + Clone + Unpin + Debug + 'static,
+    T: OutputType + 'static,
+    F: Fn(&O) -> T + Send + Sync + 'static,
+{
+    let events = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
+
+    // Subscribe before the backfill starts so nothing is missed in between
+    let live = events.subscribe::<O>(kind);
+    let gap_events = Arc::clone(events);
+    let query_events = Arc::clone(events);
+    let convert = Arc::new(convert);
+
+    spawn_resumable(
+        id.map(|cursor| *cursor),
+        live,
+        move |cursor| async move { gap_events.has_gap(kind, cursor).await },
+        move |cursor| {
+            futures_util::stream::unfold(
+                query_events.query::<O>(kind).start_after(cursor),
+                |mut stream| async move {
+                    if stream.is_exhausted() {
+                        return None;
                     }
-                }
-            });
-
-            stream_map.insert(
-                "query_streams",
-                tokio_stream::wrappers::ReceiverStream::new(rx_query),
-            );
-        };
-
-        stream_map.map(|(_, item)| item)
-    }
+                    let batch = stream.advance().await;
+                    Some((batch, stream))
+                },
+            )
+        },
+        |event: &showtimes_events::m::SHEvent<O>| event.id(),
+        move |event| {
+            let scope = scope.clone();
+            let convert = Arc::clone(&convert);
+            async move { scoped_event(event, scope, convert.as_ref()).await }
+        },
+        move |cursor| EventGQL::gap(cursor, kind.into()),
+    )
 }
 
+/// Stream the live RSS events of the feed, resuming from the event after `id` if provided.
 pub(crate) fn stream_rss_events(
     ctx: &async_graphql::Context<'_>,
-    feed_id: UlidGQL,
+    feed: &showtimes_db::m::RSSFeed,
     id: Option<UlidGQL>,
 ) -> impl Stream<Item = RSSEventGQL> + use<> {
-    let mut stream_map = tokio_stream::StreamMap::new();
-    let (tx_mem, rx_mem) = tokio::sync::mpsc::channel(100);
-
-    tokio::spawn(async move {
-        let mut subscribers = showtimes_events::RSSBroker::subscribe(*feed_id);
-        while let Some(event) = subscribers.next().await {
-            let parsed_data = RSSEventGQL::from(event);
-            if let Err(e) = tx_mem.send(parsed_data).await {
-                // Stop process
-                tracing::warn!("Channel is closed on RSS broker, stopping: {e}");
-                break;
-            }
-        }
+    let events = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
+    let feed_id = feed.id;
+    let server_id = feed.creator;
+
+    // Subscribe before the backfill starts so nothing is missed in between
+    let live = events.subscribe_rss(feed_id);
+    let gap_events = Arc::clone(events);
+    let query_events = Arc::clone(events);
+
+    spawn_resumable(
+        id.map(|cursor| *cursor),
+        live,
+        move |cursor| async move { gap_events.has_rss_gap(feed_id, cursor).await },
+        move |cursor| {
+            futures_util::stream::unfold(
+                query_events.query_rss(feed_id).start_after(cursor),
+                |mut stream| async move {
+                    if stream.is_exhausted() {
+                        return None;
+                    }
+                    let batch = stream.advance().await;
+                    Some((batch, stream))
+                },
+            )
+        },
+        |event: &showtimes_events::m::RSSEvent| event.id(),
+        |event| std::future::ready(Some(RSSEventGQL::from(event))),
+        move |cursor| RSSEventGQL::gap(cursor, feed_id, server_id),
+    )
+}
 
-        // Close the channel
-        drop(tx_mem)
-    });
+/// Forward the live events to the client, resuming from the event after `cursor` if provided.
+///
+/// The live events are buffered by the broker while the backfill from ClickHouse is
+/// running, then the events already sent by the backfill are dropped so each event is
+/// only sent once. The backfilled IDs are forgotten once a live event newer than the
+/// backfill arrives. A gap marker is sent first when the events after `cursor` are no longer
+/// fully retained or the backfill fails.
+///
+/// * `live`: The live events, this must be subscribed before calling this function
+/// * `has_gap`: Check if some events after the cursor are no longer retained
+/// * `backfill`: The stored events after the cursor, in batches
+/// * `event_id`: Get the ID of the event, used to drop the duplicated events
+/// * `convert`: Convert the event to the output, `None` will skip the event
+/// * `gap_marker`: Create the gap marker for the cursor
+fn spawn_resumable<E, T, L, G, GF, B, BF, C, CF>(
+    cursor: Option<Ulid>,
+    live: L,
+    has_gap: GF,
+    backfill: BF,
+    event_id: fn(&E) -> Ulid,
+    convert: C,
+    gap_marker: impl Fn(Ulid) -> T + Send + 'static,
+) -> tokio_stream::wrappers::ReceiverStream<T>
+where
+    E: Send + 'static,
+    T: Send + 'static,
+    L: Stream<Item = E> + Send + 'static,
+    G: Future<Output = Result<bool, showtimes_events::ClickHouseError>> + Send,
+    GF: FnOnce(Ulid) -> G + Send + 'static,
+    B: Stream<Item = Result<Vec<E>, showtimes_events::ClickHouseError>> + Send,
+    BF: FnOnce(Ulid) -> B + Send + 'static,
+    C: Fn(E) -> CF + Send + 'static,
+    CF: Future<Output = Option<T>> + Send,
+{
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
 
-    stream_map.insert(
-        "memory_stream",
-        tokio_stream::wrappers::ReceiverStream::new(rx_mem),
-    );
+    tokio::spawn(async move {
+        let mut live = std::pin::pin!(live);
+        let mut seen: HashSet<Ulid> = HashSet::new();
+        let mut backfill_until: Option<Ulid> = None;
+
+        if let Some(cursor) = cursor {
+            let gap = match has_gap(cursor).await {
+                Ok(gap) => gap,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed checking the retention after {cursor}, assuming gap: {e}"
+                    );
+                    true
+                }
+            };
 
-    if let Some(id) = id {
-        let query_stream = ctx.data_unchecked::<showtimes_events::SharedSHClickHouse>();
-        let mut stream = query_stream.query_rss(*feed_id).start_after(*id);
+            if gap && tx.send(gap_marker(cursor)).await.is_err() {
+                return;
+            }
 
-        let (tx_query, rx_query) = tokio::sync::mpsc::channel(100);
-        tokio::spawn(async move {
-            while !stream.is_exhausted() {
-                let event_batch = stream.advance().await;
+            let mut batches = std::pin::pin!(backfill(cursor));
+            while let Some(event_batch) = batches.next().await {
                 match event_batch {
                     Ok(event_batch) => {
-                        for event in event_batch.iter() {
-                            let parsed_data = RSSEventGQL::from(event);
-
-                            if let Err(e) = tx_query.send(parsed_data).await {
-                                // Stop process
-                                tracing::warn!(
-                                    "Channel is closed on RSS query stream, stopping: {e}"
-                                );
-                                break;
+                        for event in event_batch {
+                            let id = event_id(&event);
+                            backfill_until = backfill_until.max(Some(id));
+                            seen.insert(id);
+                            if let Some(data) = convert(event).await
+                                && tx.send(data).await.is_err()
+                            {
+                                tracing::warn!("Channel is closed on query stream, stopping");
+                                return;
                             }
                         }
                     }
                     Err(e) => {
-                        // Stop process
-                        tracing::warn!("Failed querying data from RSS query stream: {e}");
+                        tracing::warn!("Failed querying data from query stream: {e}");
+                        // Let the client knows that some events might be missing
+                        if !gap && tx.send(gap_marker(cursor)).await.is_err() {
+                            return;
+                        }
                         break;
                     }
                 }
             }
-        });
+        }
+
+        while let Some(event) = live.next().await {
+            let id = event_id(&event);
+            match backfill_until {
+                // Already sent by the backfill, each event can only be duplicated once.
+                Some(until) if id <= until => {
+                    if seen.remove(&id) {
+                        continue;
+                    }
+                }
+                // Newer than the backfill, the buffered duplicates are already consumed
+                Some(_) => {
+                    seen = HashSet::new();
+                    backfill_until = None;
+                }
+                None => {}
+            }
+
+            if let Some(data) = convert(event).await
+                && tx.send(data).await.is_err()
+            {
+                tracing::warn!("Channel is closed on live stream, stopping");
+                break;
+            }
+        }
+    });
+
+    tokio_stream::wrappers::ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Output {
+        Event(Ulid),
+        Gap(Ulid),
+    }
+
+    fn ulid(ms: u64) -> Ulid {
+        Ulid::from_parts(ms, 0)
+    }
+
+    fn event_id(event: &Ulid) -> Ulid {
+        *event
+    }
+
+    async fn collect(
+        cursor: Option<Ulid>,
+        live: Vec<Ulid>,
+        gap: bool,
+        backfill: Vec<Result<Vec<Ulid>, showtimes_events::ClickHouseError>>,
+    ) -> Vec<Output> {
+        let stream = spawn_resumable(
+            cursor,
+            tokio_stream::iter(live),
+            move |_| std::future::ready(Ok(gap)),
+            move |_| tokio_stream::iter(backfill),
+            event_id,
+            |event| std::future::ready(Some(Output::Event(event))),
+            Output::Gap,
+        );
+
+        stream.collect().await
+    }
+
+    #[tokio::test]
+    async fn test_resumable_without_cursor() {
+        let output = collect(None, vec![ulid(1), ulid(2)], false, vec![]).await;
+
+        assert_eq!(output, vec![Output::Event(ulid(1)), Output::Event(ulid(2))]);
+    }
 
-        stream_map.insert(
-            "query_streams",
-            tokio_stream::wrappers::ReceiverStream::new(rx_query),
+    #[tokio::test]
+    async fn test_resumable_dedupe() {
+        let output = collect(
+            Some(ulid(1)),
+            vec![ulid(3), ulid(4), ulid(5)],
+            false,
+            vec![Ok(vec![ulid(2), ulid(3)]), Ok(vec![ulid(4)])],
+        )
+        .await;
+
+        assert_eq!(
+            output,
+            vec![
+                Output::Event(ulid(2)),
+                Output::Event(ulid(3)),
+                Output::Event(ulid(4)),
+                Output::Event(ulid(5)),
+            ]
         );
-    };
+    }
 
-    stream_map.map(|(_, item)| item)
+    #[tokio::test]
+    async fn test_resumable_keeps_unseen_older_event() {
+        // Not stored yet when the backfill ran, this should still be sent
+        let output = collect(
+            Some(ulid(1)),
+            vec![ulid(2), ulid(3)],
+            false,
+            vec![Ok(vec![ulid(3)])],
+        )
+        .await;
+
+        assert_eq!(output, vec![Output::Event(ulid(3)), Output::Event(ulid(2))]);
+    }
+
+    #[tokio::test]
+    async fn test_resumable_forgets_backfill() {
+        // The duplicate after a newer live event is no longer dropped
+        let output = collect(
+            Some(ulid(1)),
+            vec![ulid(5), ulid(2)],
+            false,
+            vec![Ok(vec![ulid(2)])],
+        )
+        .await;
+
+        assert_eq!(
+            output,
+            vec![
+                Output::Event(ulid(2)),
+                Output::Event(ulid(5)),
+                Output::Event(ulid(2)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_gap_marker() {
+        let output = collect(Some(ulid(1)), vec![ulid(3)], true, vec![Ok(vec![ulid(2)])]).await;
+
+        assert_eq!(
+            output,
+            vec![
+                Output::Gap(ulid(1)),
+                Output::Event(ulid(2)),
+                Output::Event(ulid(3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_backfill_failure() {
+        let output = collect(
+            Some(ulid(1)),
+            vec![ulid(3)],
+            false,
+            vec![
+                Ok(vec![ulid(2)]),
+                Err(showtimes_events::ClickHouseError::Custom(
+                    "connection reset".to_string(),
+                )),
+                Ok(vec![ulid(4)]),
+            ],
+        )
+        .await;
+
+        // The backfill stops on failure and the gap marker is sent once
+        assert_eq!(
+            output,
+            vec![
+                Output::Event(ulid(2)),
+                Output::Gap(ulid(1)),
+                Output::Event(ulid(3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_backfill_failure_after_gap() {
+        let output = collect(
+            Some(ulid(1)),
+            vec![],
+            true,
+            vec![Err(showtimes_events::ClickHouseError::Custom(
+                "connection reset".to_string(),
+            ))],
+        )
+        .await;
+
+        assert_eq!(output, vec![Output::Gap(ulid(1))]);
+    }
 }
@@ -8,7 +8,15 @@ use futures_util::Stream;
 
 use async_graphql::{Context, Subscription};
 
-use showtimes_gql_common::{UserKindGQL, guard, queries::ServerQueryUser};
+use scope::ServerScope;
+use showtimes_db::m::APIKeyCapability;
+use showtimes_gql_common::{
+    DataLoader, GQLErrorCode, UserKindGQL,
+    data_loader::RSSFeedLoader,
+    errors::GQLError,
+    guard::{self, APIKeyVerify},
+    queries::ServerQueryUser,
+};
 use showtimes_gql_events_models::collaborations::{
     CollabAcceptedEventDataGQL, CollabCreatedEventDataGQL, CollabDeletedEventDataGQL,
     CollabRejectedEventDataGQL, CollabRetractedEventDataGQL,
@@ -27,6 +35,7 @@ use showtimes_gql_events_models::users::{
 };
 
 mod executor;
+mod scope;
 
 static STUBBED_ADMIN: LazyLock<ServerQueryUser> = LazyLock::new(|| {
     ServerQueryUser::new(
@@ -146,72 +155,124 @@ impl SubscriptionRoot {
     }
 
     /// Watch for project created events
+    ///
+    /// Only events of projects owned by the servers the user belongs to are emitted,
+    /// use `serverId` to limit it to a single server. Admins get every events unless
+    /// `serverId` is provided.
     #[graphql(
         name = "watchProjectCreated",
-        guard = "guard::AuthUserMinimumGuard::new(UserKindGQL::Admin)",
-        visible = "guard::visible_minimum_admin"
+        guard = "guard::AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::QueryEvents))"
     )]
     async fn watch_project_created(
         &self,
         ctx: &Context<'_>,
+        #[graphql(desc = "The server to limit the events to")] server_id: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
         #[graphql(desc = "The starting ID to query")] id: Option<showtimes_gql_common::UlidGQL>,
-    ) -> impl Stream<Item = EventGQL<ProjectCreatedEventDataGQL>> + use<> {
-        EventWatcher::<showtimes_events::m::ProjectCreatedEvent, ProjectCreatedEventDataGQL>::new(
-            showtimes_events::m::EventKind::ProjectCreated,
-        )
-        .stream(ctx, id)
+    ) -> async_graphql::Result<impl Stream<Item = EventGQL<ProjectCreatedEventDataGQL>> + use<>>
+    {
+        let user = ServerQueryUser::from(ctx.data_unchecked::<showtimes_db::m::User>());
+        let scope = ServerScope::resolve(ctx, user, server_id).await?;
+
+        let watcher = EventWatcherWithUser::<
+            showtimes_events::m::ProjectCreatedEvent,
+            ProjectCreatedEventDataGQL,
+        >::new(showtimes_events::m::EventKind::ProjectCreated, user)
+        .with_scope(scope);
+
+        Ok(watcher.stream(ctx, id))
     }
 
     /// Watch for project updates events
+    ///
+    /// Only events of projects owned by the servers the user belongs to are emitted,
+    /// use `serverId` to limit it to a single server. Admins get every events unless
+    /// `serverId` is provided.
     #[graphql(
         name = "watchProjectUpdated",
-        guard = "guard::AuthUserMinimumGuard::new(UserKindGQL::Admin)",
-        visible = "guard::visible_minimum_admin"
+        guard = "guard::AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::QueryEvents))"
     )]
     async fn watch_project_updated(
         &self,
         ctx: &Context<'_>,
+        #[graphql(desc = "The server to limit the events to")] server_id: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
         #[graphql(desc = "The starting ID to query")] id: Option<showtimes_gql_common::UlidGQL>,
-    ) -> impl Stream<Item = EventGQL<ProjectUpdatedEventDataGQL>> + use<> {
-        EventWatcher::<showtimes_events::m::ProjectUpdatedEvent, ProjectUpdatedEventDataGQL>::new(
-            showtimes_events::m::EventKind::ProjectUpdated,
-        )
-        .stream(ctx, id)
+    ) -> async_graphql::Result<impl Stream<Item = EventGQL<ProjectUpdatedEventDataGQL>> + use<>>
+    {
+        let user = ServerQueryUser::from(ctx.data_unchecked::<showtimes_db::m::User>());
+        let scope = ServerScope::resolve(ctx, user, server_id).await?;
+
+        let watcher = EventWatcherWithUser::<
+            showtimes_events::m::ProjectUpdatedEvent,
+            ProjectUpdatedEventDataGQL,
+        >::new(showtimes_events::m::EventKind::ProjectUpdated, user)
+        .with_scope(scope);
+
+        Ok(watcher.stream(ctx, id))
     }
 
     /// Watch for project episodes update events
+    ///
+    /// Only events of projects owned by the servers the user belongs to are emitted,
+    /// use `serverId` to limit it to a single server. Admins get every events unless
+    /// `serverId` is provided.
     #[graphql(
         name = "watchProjectEpisodeUpdated",
-        guard = "guard::AuthUserMinimumGuard::new(UserKindGQL::Admin)",
-        visible = "guard::visible_minimum_admin"
+        guard = "guard::AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::QueryEvents))"
     )]
     async fn watch_project_episode_updated(
         &self,
         ctx: &Context<'_>,
+        #[graphql(desc = "The server to limit the events to")] server_id: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
         #[graphql(desc = "The starting ID to query")] id: Option<showtimes_gql_common::UlidGQL>,
-    ) -> impl Stream<Item = EventGQL<ProjectEpisodeUpdatedEventDataGQL>> + use<> {
-        EventWatcher::<
+    ) -> async_graphql::Result<
+        impl Stream<Item = EventGQL<ProjectEpisodeUpdatedEventDataGQL>> + use<>,
+    > {
+        let user = ServerQueryUser::from(ctx.data_unchecked::<showtimes_db::m::User>());
+        let scope = ServerScope::resolve(ctx, user, server_id).await?;
+
+        let watcher = EventWatcherWithUser::<
             showtimes_events::m::ProjectEpisodeUpdatedEvent,
             ProjectEpisodeUpdatedEventDataGQL,
-        >::new(showtimes_events::m::EventKind::ProjectEpisodes)
-        .stream(ctx, id)
+        >::new(showtimes_events::m::EventKind::ProjectEpisodes, user)
+        .with_scope(scope);
+
+        Ok(watcher.stream(ctx, id))
     }
 
     /// Watch for project deleted events
+    ///
+    /// Only events of projects owned by the servers the user belongs to are emitted,
+    /// use `serverId` to limit it to a single server. Admins get every events unless
+    /// `serverId` is provided.
     #[graphql(
         name = "watchProjectDeleted",
-        guard = "guard::AuthUserMinimumGuard::new(UserKindGQL::Admin)",
-        visible = "guard::visible_minimum_admin"
+        guard = "guard::AuthUserAndAPIKeyGuard::new(UserKindGQL::User, APIKeyVerify::Specific(APIKeyCapability::QueryEvents))"
     )]
     async fn watch_project_deleted(
         &self,
         ctx: &Context<'_>,
+        #[graphql(desc = "The server to limit the events to")] server_id: Option<
+            showtimes_gql_common::UlidGQL,
+        >,
         #[graphql(desc = "The starting ID to query")] id: Option<showtimes_gql_common::UlidGQL>,
-    ) -> impl Stream<Item = EventGQL<ProjectDeletedEventDataGQL>> + use<> {
-        EventWatcher::<showtimes_events::m::ProjectDeletedEvent, ProjectDeletedEventDataGQL>::new(
-            showtimes_events::m::EventKind::ProjectDeleted,
-        )
-        .stream(ctx, id)
+    ) -> async_graphql::Result<impl Stream<Item = EventGQL<ProjectDeletedEventDataGQL>> + use<>>
+    {
+        let user = ServerQueryUser::from(ctx.data_unchecked::<showtimes_db::m::User>());
+        let scope = ServerScope::resolve(ctx, user, server_id).await?;
+
+        let watcher = EventWatcherWithUser::<
+            showtimes_events::m::ProjectDeletedEvent,
+            ProjectDeletedEventDataGQL,
+        >::new(showtimes_events::m::EventKind::ProjectDeleted, user)
+        .with_scope(scope);
+
+        Ok(watcher.stream(ctx, id))
     }
 
     /// Watch for collaboration created events
@@ -309,7 +370,13 @@ impl SubscriptionRoot {
         ctx: &Context<'_>,
         #[graphql(desc = "The RSS feed to query")] feed_id: showtimes_gql_common::UlidGQL,
         #[graphql(desc = "The starting ID to query")] id: Option<showtimes_gql_common::UlidGQL>,
-    ) -> impl Stream<Item = RSSEventGQL> + use<> {
-        stream_rss_events(ctx, feed_id, id)
+    ) -> async_graphql::Result<impl Stream<Item = RSSEventGQL> + use<>> {
+        let loader = ctx.data_unchecked::<DataLoader<RSSFeedLoader>>();
+        let feed = loader.load_one(*feed_id).await?.ok_or_else(|| {
+            GQLError::new("RSS feed not found", GQLErrorCode::RSSFeedNotFound)
+                .extend(|e| e.set("id", feed_id.to_string()))
+        })?;
+
+        Ok(stream_rss_events(ctx, &feed, id))
     }
 }
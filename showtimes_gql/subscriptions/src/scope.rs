@@ -0,0 +1,155 @@
+use ahash::{HashMap, HashMapExt, HashSet};
+use async_graphql::dataloader::DataLoader;
+use showtimes_db::DatabaseShared;
+use showtimes_gql_common::{
+    GQLErrorCode, UlidGQL,
+    data_loader::{ServerAndOwnerId, ServerDataLoader, ServerOwnerId},
+    errors::GQLError,
+    queries::ServerQueryUser,
+};
+use showtimes_shared::ulid::Ulid;
+use tokio::sync::RwLock;
+
+/// An event that belongs to a single project
+pub(crate) trait ProjectScoped {
+    /// The project ID of the event
+    fn project_id(&self) -> Ulid;
+
+    /// The server that owns the project, if the event carries it
+    fn server_id(&self) -> Option<Ulid> {
+        None
+    }
+
+    /// The project and the server of the event for [`ServerScope::contains`]
+    fn scope_target(&self) -> (Ulid, Option<Ulid>) {
+        (self.project_id(), self.server_id())
+    }
+}
+
+impl ProjectScoped for showtimes_events::m::ProjectCreatedEvent {
+    fn project_id(&self) -> Ulid {
+        self.id()
+    }
+}
+
+impl ProjectScoped for showtimes_events::m::ProjectUpdatedEvent {
+    fn project_id(&self) -> Ulid {
+        self.id()
+    }
+}
+
+impl ProjectScoped for showtimes_events::m::ProjectEpisodeUpdatedEvent {
+    fn project_id(&self) -> Ulid {
+        self.id()
+    }
+}
+
+impl ProjectScoped for showtimes_events::m::ProjectDeletedEvent {
+    fn project_id(&self) -> Ulid {
+        self.id()
+    }
+
+    fn server_id(&self) -> Option<Ulid> {
+        self.server()
+    }
+}
+
+/// The set of servers a subscription is limited to.
+///
+/// The servers are resolved once when the subscription starts, the project
+/// ownership is looked up and cached as the events come in.
+pub(crate) struct ServerScope {
+    servers: HashSet<Ulid>,
+    projects: RwLock<HashMap<Ulid, Ulid>>,
+    handler: showtimes_db::ProjectHandler,
+}
+
+impl ServerScope {
+    /// Resolve the servers the user belongs to, or only `server_id` if provided.
+    ///
+    /// Admins without `server_id` are not limited and get `None`, unless the
+    /// API key of the request is limited to some servers.
+    pub(crate) async fn resolve(
+        ctx: &async_graphql::Context<'_>,
+        user: ServerQueryUser,
+        server_id: Option<UlidGQL>,
+    ) -> async_graphql::Result<Option<Self>> {
+        let srv_loader = ctx.data_unchecked::<DataLoader<ServerDataLoader>>();
+
+        let servers: HashSet<Ulid> = match server_id {
+            Some(server_id) => {
+                let server = if user.kind() >= showtimes_db::m::UserKind::Admin {
+                    srv_loader.load_one(*server_id).await?
+                } else {
+                    srv_loader
+                        .load_one(ServerAndOwnerId::new(*server_id, user.id()))
+                        .await?
+                };
+
+                let server = server.ok_or_else(|| {
+                    GQLError::new("Server not found", GQLErrorCode::ServerNotFound).extend(|e| {
+                        e.set("id", server_id.to_string());
+                        e.set("user", user.id().to_string());
+                    })
+                })?;
+
+                HashSet::from_iter([server.id])
+            }
+            None if user.kind() >= showtimes_db::m::UserKind::Admin => {
+                // Admins are only limited by the servers of their API key
+                match ctx
+                    .data_opt::<showtimes_db::m::APIKey>()
+                    .filter(|api_key| api_key.is_server_scoped())
+                {
+                    Some(api_key) => api_key.servers.iter().copied().collect(),
+                    None => return Ok(None),
+                }
+            }
+            None => srv_loader
+                .load_one(ServerOwnerId::new(user.id()))
+                .await?
+                .unwrap_or_default()
+                .iter()
+                .map(|s| s.id)
+                .collect(),
+        };
+
+        let db = ctx.data_unchecked::<DatabaseShared>();
+
+        Ok(Some(Self {
+            servers,
+            projects: RwLock::new(HashMap::new()),
+            handler: showtimes_db::ProjectHandler::new(db),
+        }))
+    }
+
+    /// Check if the event project belongs to one of the servers in the scope
+    pub(crate) async fn contains(&self, (project, server): (Ulid, Option<Ulid>)) -> bool {
+        match server {
+            Some(server) => self.servers.contains(&server),
+            None => self.contains_project(project).await,
+        }
+    }
+
+    /// Check if the project belongs to one of the servers in the scope
+    async fn contains_project(&self, id: Ulid) -> bool {
+        if let Some(creator) = self.projects.read().await.get(&id) {
+            return self.servers.contains(creator);
+        }
+
+        match self.handler.find_by_id(&id.to_string()).await {
+            Ok(Some(project)) => {
+                self.projects
+                    .write()
+                    .await
+                    .insert(project.id, project.creator);
+                self.servers.contains(&project.creator)
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!("Failed to load project {id} for subscription scope: {e}");
+                false
+            }
+        }
+    }
+}
@@ -44,6 +44,20 @@ impl Language {
             Language::Su => "su-ID",
         }
     }
+
+    /// Get the language from the string code
+    ///
+    /// This accept both the full code (`en-US`) and the short code (`en`)
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "en-us" | "en" => Some(Language::En),
+            "id-id" | "id" => Some(Language::Id),
+            "ja-jp" | "ja" => Some(Language::Ja),
+            "jv-id" | "jv" => Some(Language::Jv),
+            "su-id" | "su" => Some(Language::Su),
+            _ => None,
+        }
+    }
 }
 
 static_loader! {
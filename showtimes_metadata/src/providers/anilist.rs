@@ -264,6 +264,51 @@ impl AnilistProvider {
         Ok(res.data.media.clone())
     }
 
+    /// Get specific anime information from the MyAnimeList ID
+    ///
+    /// * `mal_id` - The MyAnimeList ID of the anime
+    pub async fn get_media_by_mal(&mut self, mal_id: i32) -> MetadataResult<AnilistMedia> {
+        let queries = r#"query mediaInfoMal($id:Int) {
+            Media(idMal:$id,type:ANIME) {
+                id
+                idMal
+                format
+                type
+                season
+                seasonYear
+                episodes
+                chapters
+                volumes
+                isAdult
+                startDate {
+                    year
+                    month
+                    day
+                }
+                title {
+                    romaji
+                    native
+                    english
+                }
+                coverImage {
+                    medium
+                    large
+                    extraLarge
+                }
+            }
+        }"#;
+
+        let variables = json!({
+            "id": mal_id,
+        });
+
+        let res = self
+            .query::<AnilistSingleMedia>(queries, &variables)
+            .await?;
+
+        Ok(res.data.media.clone())
+    }
+
     /// Get the airing schedules for a media
     ///
     /// * `id` - The ID of the media
@@ -62,6 +62,12 @@ pub(crate) enum MigrationMeiliCommands {
     Fix,
     /// Reindex all Meilisearch indexes, this will also fix the schema
     Reindex,
+    /// Verify that Meilisearch indexes match the database
+    Verify {
+        /// Queue and apply the missing, orphaned, and outdated documents
+        #[arg(short, long)]
+        fix: bool,
+    },
 }
 
 fn cli_styles() -> Styles {
@@ -120,6 +120,9 @@ async fn main() -> anyhow::Result<()> {
             cli::MigrationMeiliCommands::Reindex => {
                 runner::run_meilisearch_reindex(&connection).await?;
             }
+            cli::MigrationMeiliCommands::Verify { fix } => {
+                runner::run_meilisearch_verify(&connection, fix).await?;
+            }
         },
         MigrationCommands::Indexes => {
             runner::run_database_create_indexes(&connection).await?;
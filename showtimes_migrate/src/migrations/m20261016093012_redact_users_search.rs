@@ -0,0 +1,84 @@
+use showtimes_db::{ClientShared, DatabaseShared};
+
+use crate::common::env_or_exit;
+
+use super::Migration;
+
+pub struct M20261016093012RedactUsersSearch {
+    client: ClientShared,
+    db: DatabaseShared,
+}
+
+#[async_trait::async_trait]
+impl Migration for M20261016093012RedactUsersSearch {
+    fn init(client: &ClientShared, db: &DatabaseShared) -> Self {
+        Self {
+            client: client.clone(),
+            db: db.clone(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "M20261016093012RedactUsersSearch"
+    }
+
+    fn timestamp(&self) -> jiff::Timestamp {
+        jiff::civil::datetime(2026, 10, 16, 9, 30, 12, 0)
+            .to_zoned(jiff::tz::TimeZone::UTC)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn clone_box(&self) -> Box<dyn Migration> {
+        Box::new(Self {
+            client: self.client.clone(),
+            db: self.db.clone(),
+        })
+    }
+
+    async fn up(&self) -> anyhow::Result<()> {
+        let meili_url = env_or_exit("MEILI_URL");
+        let meili_key = env_or_exit("MEILI_KEY");
+
+        tracing::info!("Creating Meilisearch client instances...");
+        let meilisearch = showtimes_search::create_connection(&meili_url, &meili_key).await?;
+        let s_user_index = meilisearch.index(showtimes_search::models::User::index_name());
+        let s_user_pk = showtimes_search::models::User::primary_key();
+
+        // Drop the API key and Discord ID from the filterable and searchable attributes
+        tracing::info!("Updating users index schema...");
+        showtimes_search::models::User::update_schema(&meilisearch).await?;
+
+        tracing::info!("Getting all users to reindex...");
+        let users_db = showtimes_db::UserHandler::new(&self.db);
+        let users = users_db.find_all().await?;
+
+        let queued_search: Vec<showtimes_search::models::User> = users
+            .into_iter()
+            .map(showtimes_search::models::User::from)
+            .collect();
+
+        tracing::info!(
+            "Reindexing {} users without secrets (skipped: {:?})...",
+            queued_search.len(),
+            showtimes_search::models::User::search_skipped()
+        );
+        if !queued_search.is_empty() {
+            // Replace the whole document, a partial update would keep the old secrets
+            let task = s_user_index
+                .add_or_replace(&queued_search, Some(s_user_pk))
+                .await?;
+
+            tracing::info!(" Waiting for users index update to complete...");
+            task.wait_for_completion(&*meilisearch, None, None).await?;
+        }
+        tracing::info!("Migration completed successfully");
+
+        Ok(())
+    }
+
+    async fn down(&self) -> anyhow::Result<()> {
+        // The secrets should never be indexed again, ignore this migration
+        Ok(())
+    }
+}
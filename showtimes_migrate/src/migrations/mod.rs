@@ -6,6 +6,7 @@ pub(crate) mod m20240821113204_fallback_images_invalids;
 pub(crate) mod m20240916075925_clickhouse_init;
 pub(crate) mod m20241026154029_project_status_field;
 pub(crate) mod m20250125075556_update_users_api_key;
+pub(crate) mod m20261016093012_redact_users_search;
 
 #[async_trait::async_trait]
 pub trait Migration {
@@ -39,5 +40,8 @@ pub fn get_migrations(client: &ClientShared, db: &DatabaseShared) -> Vec<Box<dyn
                 client, db,
             ),
         ),
+        Box::new(
+            m20261016093012_redact_users_search::M20261016093012RedactUsersSearch::init(client, db),
+        ),
     ]
 }
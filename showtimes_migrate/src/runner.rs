@@ -293,6 +293,265 @@ pub async fn run_meilisearch_reindex(conn: &showtimes_db::Connection) -> anyhow:
     Ok(())
 }
 
+/// The minimal document fetched from Meilisearch when verifying
+#[derive(serde::Deserialize)]
+struct MeiliVerifyDocument {
+    id: String,
+    updated: Option<i64>,
+}
+
+/// The difference between the database and an index
+#[derive(Default)]
+struct MeiliVerifyReport {
+    /// Exists in the database but missing in the index
+    missing: Vec<String>,
+    /// Exists in the index but missing in the database
+    orphaned: Vec<String>,
+    /// The indexed document is older than the database
+    outdated: Vec<String>,
+}
+
+impl MeiliVerifyReport {
+    fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty() && self.outdated.is_empty()
+    }
+}
+
+async fn meili_fetch_documents(
+    client: &showtimes_search::SearchClientShared,
+    index_name: &str,
+) -> anyhow::Result<ahash::HashMap<String, Option<i64>>> {
+    const PAGE_SIZE: usize = 1000;
+
+    let index = client.index(index_name);
+    let mut documents = ahash::HashMap::default();
+    let mut offset = 0usize;
+    loop {
+        let mut query = meilisearch_sdk::documents::DocumentsQuery::new(&index);
+        query
+            .with_fields(["id", "updated"])
+            .with_limit(PAGE_SIZE)
+            .with_offset(offset);
+
+        let results = index
+            .get_documents_with::<MeiliVerifyDocument>(&query)
+            .await?;
+        let fetched = results.results.len();
+        for doc in results.results {
+            documents.insert(doc.id, doc.updated);
+        }
+
+        offset += fetched;
+        if fetched < PAGE_SIZE || offset >= results.total as usize {
+            break;
+        }
+    }
+
+    Ok(documents)
+}
+
+async fn meili_verify_index(
+    client: &showtimes_search::SearchClientShared,
+    index_name: &str,
+    database: impl IntoIterator<Item = (String, i64)>,
+) -> anyhow::Result<MeiliVerifyReport> {
+    let mut indexed = meili_fetch_documents(client, index_name).await?;
+    let mut report = MeiliVerifyReport::default();
+
+    for (id, updated) in database {
+        match indexed.remove(&id) {
+            None => report.missing.push(id),
+            Some(indexed_updated) => {
+                if indexed_updated.is_none_or(|at| at < updated) {
+                    report.outdated.push(id);
+                }
+            }
+        }
+    }
+    report.orphaned.extend(indexed.into_keys());
+
+    report.missing.sort();
+    report.orphaned.sort();
+    report.outdated.sort();
+    Ok(report)
+}
+
+fn meili_report_entries(
+    kind: showtimes_db::m::SearchOutboxKind,
+    report: &MeiliVerifyReport,
+) -> Vec<showtimes_db::m::SearchOutbox> {
+    let parse = |id: &String| match showtimes_shared::ulid::Ulid::from_string(id) {
+        Ok(ulid) => Some(ulid),
+        Err(_) => {
+            tracing::warn!("  Skipping invalid document ID: {}", id);
+            None
+        }
+    };
+
+    let upserts = report
+        .missing
+        .iter()
+        .chain(report.outdated.iter())
+        .filter_map(parse)
+        .map(|id| showtimes_db::m::SearchOutbox::upsert(kind, id));
+    let deletes = report
+        .orphaned
+        .iter()
+        .filter_map(parse)
+        .map(|id| showtimes_db::m::SearchOutbox::delete(kind, id));
+
+    upserts.chain(deletes).collect()
+}
+
+/// Compare the database with the Meilisearch indexes.
+///
+/// When `fix` is enabled, the differences are queued into the search outbox
+/// and applied directly, anything that fails is left for the outbox worker.
+pub async fn run_meilisearch_verify(
+    conn: &showtimes_db::Connection,
+    fix: bool,
+) -> anyhow::Result<()> {
+    use showtimes_db::m::SearchOutboxKind;
+
+    let meili_url = env_or_exit("MEILI_URL");
+    let meili_key = env_or_exit("MEILI_KEY");
+
+    tracing::info!("Creating Meilisearch client instances...");
+    let client = showtimes_search::create_connection(&meili_url, &meili_key).await?;
+
+    meili_create_index(&client).await?;
+
+    let mut reports = vec![];
+
+    tracing::info!("Verifying users...");
+    let users = showtimes_db::UserHandler::new(&conn.db).find_all().await?;
+    reports.push((
+        SearchOutboxKind::User,
+        meili_verify_index(
+            &client,
+            showtimes_search::models::User::index_name(),
+            users
+                .iter()
+                .map(|u| (u.id.to_string(), u.updated.as_second())),
+        )
+        .await?,
+    ));
+
+    tracing::info!("Verifying servers...");
+    let servers = showtimes_db::ServerHandler::new(&conn.db)
+        .find_all()
+        .await?;
+    reports.push((
+        SearchOutboxKind::Server,
+        meili_verify_index(
+            &client,
+            showtimes_search::models::Server::index_name(),
+            servers
+                .iter()
+                .map(|s| (s.id.to_string(), s.updated.as_second())),
+        )
+        .await?,
+    ));
+
+    tracing::info!("Verifying projects...");
+    let projects = showtimes_db::ProjectHandler::new(&conn.db)
+        .find_all()
+        .await?;
+    reports.push((
+        SearchOutboxKind::Project,
+        meili_verify_index(
+            &client,
+            showtimes_search::models::Project::index_name(),
+            projects
+                .iter()
+                .map(|p| (p.id.to_string(), p.updated.as_second())),
+        )
+        .await?,
+    ));
+
+    tracing::info!("Verifying server collab syncs...");
+    let collab_syncs = showtimes_db::CollaborationSyncHandler::new(&conn.db)
+        .find_all()
+        .await?;
+    reports.push((
+        SearchOutboxKind::CollaborationSync,
+        meili_verify_index(
+            &client,
+            showtimes_search::models::ServerCollabSync::index_name(),
+            collab_syncs
+                .iter()
+                .map(|c| (c.id.to_string(), c.updated.as_second())),
+        )
+        .await?,
+    ));
+
+    tracing::info!("Verifying server collab invites...");
+    let collab_invites = showtimes_db::CollaborationInviteHandler::new(&conn.db)
+        .find_all()
+        .await?;
+    reports.push((
+        SearchOutboxKind::CollaborationInvite,
+        meili_verify_index(
+            &client,
+            showtimes_search::models::ServerCollabInvite::index_name(),
+            collab_invites
+                .iter()
+                .map(|c| (c.id.to_string(), c.updated.as_second())),
+        )
+        .await?,
+    ));
+
+    let mut entries = vec![];
+    for (kind, report) in &reports {
+        if report.is_clean() {
+            tracing::info!("[{}] Index is in sync", kind.to_name());
+            continue;
+        }
+
+        tracing::warn!(
+            "[{}] {} missing, {} orphaned, {} outdated",
+            kind.to_name(),
+            report.missing.len(),
+            report.orphaned.len(),
+            report.outdated.len()
+        );
+        for id in &report.missing {
+            tracing::warn!("  Missing: {}", id);
+        }
+        for id in &report.orphaned {
+            tracing::warn!("  Orphaned: {}", id);
+        }
+        for id in &report.outdated {
+            tracing::warn!("  Outdated: {}", id);
+        }
+
+        if fix {
+            entries.extend(meili_report_entries(*kind, report));
+        }
+    }
+
+    if entries.is_empty() {
+        if fix {
+            tracing::info!("Nothing to fix");
+        }
+        return Ok(());
+    }
+
+    tracing::info!("Queueing {} fixes into the search outbox...", entries.len());
+    showtimes_db::SearchOutboxHandler::new(&conn.db)
+        .insert(&mut entries)
+        .await?;
+
+    let stats = showtimes_search::outbox::apply_entries(&conn.db, &client, entries).await?;
+    tracing::info!(
+        "Search outbox applied, {} fixed and {} left for retry",
+        stats.applied,
+        stats.failed
+    );
+
+    Ok(())
+}
+
 pub async fn run_database_create_indexes(conn: &showtimes_db::Connection) -> anyhow::Result<()> {
     let user_db = showtimes_db::UserHandler::new(&conn.db);
     let project_db = showtimes_db::ProjectHandler::new(&conn.db);
@@ -408,6 +667,54 @@ pub async fn run_database_create_indexes(conn: &showtimes_db::Connection) -> any
                     .build(),
             ))
             .build(),
+        IndexModel::builder()
+            .keys(doc! { "progress.aired": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Episode Aired".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "creator": 1, "progress.aired": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Creator + Episode Aired".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "creator": 1, "status": 1, "kind": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Creator + Status + Kind".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "title": 1, "id": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Title + ID".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "created": 1, "id": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Created + ID".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "updated": 1, "id": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Updated + ID".to_string()))
+                    .build(),
+            ))
+            .build(),
     ];
 
     tracing::info!("Creating project specific indexes...");
@@ -518,5 +825,32 @@ pub async fn run_database_create_indexes(conn: &showtimes_db::Connection) -> any
         .create_indexes(collab_invite_indexes)
         .await?;
 
+    // Search outbox indexes
+    let search_outbox_db = showtimes_db::SearchOutboxHandler::new(&conn.db);
+    let search_outbox_indexes = vec![
+        IndexModel::builder()
+            .keys(doc! { "next_attempt": 1, "created": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Next Attempt + Created".to_string()))
+                    .build(),
+            ))
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "kind": 1, "target": 1 })
+            .options(Some(
+                IndexOptions::builder()
+                    .name(Some("Kind + Target".to_string()))
+                    .build(),
+            ))
+            .build(),
+    ];
+
+    tracing::info!("Creating search outbox specific indexes...");
+    search_outbox_db
+        .get_collection()
+        .create_indexes(search_outbox_indexes)
+        .await?;
+
     Ok(())
 }
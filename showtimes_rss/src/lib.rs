@@ -64,6 +64,17 @@ pub enum FeedValue {
     Timestamp(Timestamp),
 }
 
+impl FeedValue {
+    /// Get the text values, a collection will return each of the items
+    pub fn to_texts(&self) -> Vec<String> {
+        match self {
+            FeedValue::String(s) => vec![s.clone()],
+            FeedValue::Collection(s) => s.as_slice().to_vec(),
+            FeedValue::Timestamp(_) => vec![self.to_string()],
+        }
+    }
+}
+
 impl From<String> for FeedValue {
     fn from(value: String) -> Self {
         Self::String(value)
@@ -200,21 +211,103 @@ pub struct FeedParsed<'a> {
     pub last_modified: Option<String>,
 }
 
+/// The cache validators from the previous request of a feed
+///
+/// These are sent as `If-None-Match` and `If-Modified-Since` so the server
+/// can respond with `304 Not Modified` when the feed has not changed.
+#[derive(Debug, Clone, Default)]
+pub struct FeedCacheHeaders {
+    /// The `ETag` header from the previous response
+    pub etag: Option<String>,
+    /// The `Last-Modified` header from the previous response
+    pub last_modified: Option<String>,
+}
+
+impl FeedCacheHeaders {
+    /// Create a new cache validators
+    pub fn new(etag: Option<String>, last_modified: Option<String>) -> Self {
+        Self {
+            etag,
+            last_modified,
+        }
+    }
+
+    fn to_header_map(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        // Invalid stored values are skipped, the feed will be fully fetched instead
+        if let Some(etag) = &self.etag
+            && let Ok(value) = reqwest::header::HeaderValue::from_str(etag)
+        {
+            headers.insert(reqwest::header::IF_NONE_MATCH, value);
+        }
+        if let Some(last_modified) = &self.last_modified
+            && let Ok(value) = reqwest::header::HeaderValue::from_str(last_modified)
+        {
+            headers.insert(reqwest::header::IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+/// The statistics of a single feed request
+#[derive(Debug, Clone, Copy)]
+pub struct FeedFetchStats {
+    /// The HTTP status code of the response
+    pub status: u16,
+    /// The amount of bytes received in the response body
+    pub bytes: u64,
+    /// How long the request took, including reading the body
+    pub duration: std::time::Duration,
+}
+
+/// The result of requesting a feed
+pub enum FeedFetchResult<'a> {
+    /// The feed has changed and has been parsed
+    Modified(FeedParsed<'a>),
+    /// The server responded with `304 Not Modified`
+    NotModified,
+}
+
+/// A requested feed along with the request statistics
+pub struct FeedFetched<'a> {
+    /// The result of the request
+    pub result: FeedFetchResult<'a>,
+    /// The request statistics
+    pub stats: FeedFetchStats,
+}
+
 /// Request the given feed and return the parsed feed
+///
+/// When the cache validators are provided, the request is made conditional and
+/// [`FeedFetchResult::NotModified`] is returned if the feed has not changed.
 pub async fn parse_feed<'a>(
     feed_url: impl AsRef<str>,
-    headers: Option<reqwest::header::HeaderMap>,
-) -> Result<FeedParsed<'a>, RSSError> {
+    cache: Option<&FeedCacheHeaders>,
+) -> Result<FeedFetched<'a>, RSSError> {
     let url = feed_url.as_ref();
     let parsed_url = reqwest::Url::parse(url)?;
     let client = create_client()?;
 
+    let started = std::time::Instant::now();
     let data = client
         .get(parsed_url.clone())
-        .headers(headers.unwrap_or_default())
+        .headers(cache.map(|c| c.to_header_map()).unwrap_or_default())
         .send()
-        .await?
-        .error_for_status()?;
+        .await?;
+
+    if data.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FeedFetched {
+            result: FeedFetchResult::NotModified,
+            stats: FeedFetchStats {
+                status: data.status().as_u16(),
+                bytes: 0,
+                duration: started.elapsed(),
+            },
+        });
+    }
+
+    let data = data.error_for_status()?;
+    let status = data.status().as_u16();
 
     let etags = data
         .headers()
@@ -227,10 +320,15 @@ pub async fn parse_feed<'a>(
         .and_then(|v| v.to_str().ok())
         .map(|v| v.to_string());
 
-    let text = data.text().await?;
+    let body = data.bytes().await?;
+    let stats = FeedFetchStats {
+        status,
+        bytes: body.len() as u64,
+        duration: started.elapsed(),
+    };
 
     let parser = create_parser(parsed_url.as_str());
-    let parsed = parser.parse(text.as_bytes())?;
+    let parsed = parser.parse(body.as_ref())?;
 
     let real_url = parsed
         .links
@@ -440,12 +538,15 @@ pub async fn parse_feed<'a>(
         })
         .collect();
 
-    Ok(FeedParsed {
-        title: parsed.title.map(|a| a.content),
-        entries: reparsed_values,
-        url: parsed_url,
-        etag: etags,
-        last_modified,
+    Ok(FeedFetched {
+        result: FeedFetchResult::Modified(FeedParsed {
+            title: parsed.title.map(|a| a.content),
+            entries: reparsed_values,
+            url: parsed_url,
+            etag: etags,
+            last_modified,
+        }),
+        stats,
     })
 }
 
@@ -4,57 +4,66 @@
 
 use std::collections::BTreeMap;
 
+use jiff::{Timestamp, fmt::rfc2822, tz::TimeZone};
 use nom::{
     IResult, Parser,
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{alphanumeric1, digit1},
-    combinator::{map, map_res, value},
-    multi::many0,
-    sequence::delimited,
+    bytes::complete::{tag, take_while1},
+    character::complete::{digit1, multispace0, multispace1},
 };
 use serde::{Deserialize, Serialize, ser::SerializeSeq};
 
 /// A simple tempate error
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TemplateError {
-    /// Error related to nom
-    NomError(String),
+    /// Invalid template syntax at a specific position
+    Syntax {
+        /// The error message
+        message: String,
+        /// The byte offset in the template
+        offset: usize,
+        /// The line number, starting from 1
+        line: usize,
+        /// The column number in characters, starting from 1
+        column: usize,
+    },
 }
 
-impl std::error::Error for TemplateError {}
+impl TemplateError {
+    /// Create a new syntax error, `at` must be the remaining part of the `source`
+    fn syntax(source: &str, at: &str, message: impl Into<String>) -> Self {
+        let offset = source.len().saturating_sub(at.len());
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+
+        TemplateError::Syntax {
+            message: message.into(),
+            offset,
+            line,
+            column,
+        }
+    }
 
-impl std::fmt::Display for TemplateError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    /// The line and column of the error
+    pub fn position(&self) -> (usize, usize) {
         match self {
-            TemplateError::NomError(e) => write!(f, "Nom error: {e}"),
+            TemplateError::Syntax { line, column, .. } => (*line, *column),
         }
     }
 }
 
-fn throw_nom_error(error: nom::Err<nom::error::Error<&str>>) -> TemplateError {
-    // clone the error to avoid lifetime issues
-    match &error {
-        nom::Err::Error(e) | nom::Err::Failure(e) => {
-            // get like 64 bytes of data to show in the error message
-            let str_data = if let nom::Err::Error(_) = &error {
-                "Error"
-            } else {
-                "Failure"
-            };
-            let data = e.input;
-            let data = if data.len() > 64 { &data[..64] } else { data };
-
-            TemplateError::NomError(format!("{}: {:?}, data: {:?}", str_data, e.code, data))
-        }
-        nom::Err::Incomplete(e) => {
-            let need_amount = if let nom::Needed::Size(amount) = e {
-                format!("{amount} bytes")
-            } else {
-                "unknown amount".to_string()
-            };
+impl std::error::Error for TemplateError {}
 
-            TemplateError::NomError(format!("Incomplete data, need: {need_amount}"))
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TemplateError::Syntax {
+                message,
+                line,
+                column,
+                ..
+            } => write!(f, "{message} at line {line}, column {column}"),
         }
     }
 }
@@ -70,6 +79,26 @@ pub enum TemplateToken<'a> {
     Template(Template<'a>),
     /// An escaped `{` or `}`.
     Escaped(char),
+    /// A template format with one or more filters, something like `{key|truncate:80}`.
+    Filtered {
+        /// The template format
+        template: Template<'a>,
+        /// The filters, applied in order
+        filters: Vec<TemplateFilter>,
+        /// The original text, used when the value is missing
+        raw: &'a str,
+    },
+    /// A conditional block, something like `{#if key}...{:else}...{/if}`.
+    ///
+    /// The condition pass when the value exist and is not empty.
+    Conditional {
+        /// The template format that is checked
+        condition: Template<'a>,
+        /// The tokens used when the condition pass
+        then: Vec<TemplateToken<'a>>,
+        /// The tokens used when the condition fails, from the `{:else}` part
+        otherwise: Vec<TemplateToken<'a>>,
+    },
 }
 
 /// A template format
@@ -94,150 +123,473 @@ pub enum Template<'a> {
     Named(&'a str),
 }
 
-fn take_until0_multiple<'a>(
-    delimiters: &'a [&'a str],
-) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
-    move |input: &'a str| {
-        if input.is_empty() {
-            return Err(nom::Err::Error(nom::error::Error::new(
-                input,
-                nom::error::ErrorKind::Eof,
-            )));
+/// A filter that is applied to a template value
+#[derive(PartialEq, Clone, Debug)]
+pub enum TemplateFilter {
+    /// Limit the value into the maximum amount of characters, something like `|truncate:80`
+    ///
+    /// The ellipsis is included in the limit.
+    Truncate(usize),
+    /// Format a date value with the strftime format, something like `|date:"%Y-%m-%d"`
+    ///
+    /// The date is formatted in UTC, value that is not a date is kept as is.
+    Date(String),
+    /// Fallback value when the value is missing or empty, something like `|default:"None"`
+    Default(String),
+}
+
+impl TemplateFilter {
+    /// Apply the filter into the value, `None` means the value is missing
+    fn apply(&self, value: Option<String>) -> Option<String> {
+        match self {
+            TemplateFilter::Truncate(max) => value.map(|v| {
+                if v.chars().count() <= *max {
+                    v
+                } else {
+                    let mut truncated: String = v.chars().take(max.saturating_sub(1)).collect();
+                    truncated.push('…');
+                    truncated
+                }
+            }),
+            TemplateFilter::Date(format) => value.map(|v| {
+                let zoned = match v.trim().parse::<Timestamp>() {
+                    Ok(ts) => Some(ts.to_zoned(TimeZone::UTC)),
+                    Err(_) => rfc2822::parse(v.trim()).ok(),
+                };
+
+                zoned
+                    .and_then(|z| jiff::fmt::strtime::format(format, &z).ok())
+                    .unwrap_or(v)
+            }),
+            TemplateFilter::Default(fallback) => match value {
+                Some(v) if !v.trim().is_empty() => Some(v),
+                _ => Some(fallback.clone()),
+            },
         }
+    }
+}
 
-        let mut end_index = input.len();
-        for delimiter in delimiters {
-            if let Some(index) = input.find(delimiter) {
-                end_index = end_index.min(index);
-            }
+/// A flat template segment, before the conditional blocks are nested
+enum Segment<'a> {
+    Token(TemplateToken<'a>),
+    If(Template<'a>),
+    Else,
+    EndIf,
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn parse_key(input: &str) -> IResult<&str, Template<'_>> {
+    let (rest, key) = take_while1(is_key_char).parse(input)?;
+
+    let template = if key.bytes().all(|b| b.is_ascii_digit()) {
+        match key.parse() {
+            Ok(index) => Template::Indexed(index),
+            Err(_) => Template::Named(key),
         }
+    } else {
+        Template::Named(key)
+    };
 
-        Ok((&input[end_index..], &input[..end_index]))
-    }
+    Ok((rest, template))
 }
 
-/// Parse a template string into a vector of template tokens.
-///
-/// We use [nom](https://github.com/rust-bakery/nom) for parsing
-///
-/// The following "template" format is supported:
-/// - `{key}` for a named template
-/// - `{0}`, `{1}` for an indexed template
-/// - `{}` for an empty template, this depends on the internal index counter, separate from the Indexed.
-///
-/// To escape use `{{` and `}}`
-pub fn parse_template(text_template: &str) -> Result<Vec<TemplateToken<'_>>, TemplateError> {
-    fn parse_template_token_other(input: &str) -> IResult<&str, Template> {
-        alt((
-            map_res(digit1, |s: &str| s.parse().map(Template::Indexed)),
-            map(alphanumeric1, Template::Named),
-        ))
-        .parse(input)
+/// Parse a quoted string argument, `input` must start with `"`
+fn parse_quoted<'a>(source: &'a str, input: &'a str) -> Result<(&'a str, String), TemplateError> {
+    let mut output = String::new();
+    let mut chars = input.char_indices().skip(1);
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((&input[idx + 1..], output)),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => output.push(escaped),
+                Some((esc_idx, _)) => {
+                    return Err(TemplateError::syntax(
+                        source,
+                        &input[esc_idx - 1..],
+                        "invalid escape, only `\\\"` and `\\\\` are supported",
+                    ));
+                }
+                None => break,
+            },
+            _ => output.push(c),
+        }
     }
 
-    fn parse_template_token(input: &str) -> IResult<&str, Template> {
-        let (rest, token) =
-            alt((tag("{}"), delimited(tag("{"), alphanumeric1, tag("}")))).parse(input)?;
+    Err(TemplateError::syntax(source, input, "unterminated string"))
+}
 
-        if token == "{}" {
-            Ok((rest, Template::Empty))
-        } else {
-            let (_, key) = parse_template_token_other(token)?;
+/// Parse a single filter, something like `truncate:80`
+fn parse_filter<'a>(
+    source: &'a str,
+    input: &'a str,
+) -> Result<(&'a str, TemplateFilter), TemplateError> {
+    let (rest, name) = take_while1::<_, _, nom::error::Error<&str>>(is_key_char)
+        .parse(input)
+        .map_err(|_| TemplateError::syntax(source, input, "expected a filter name"))?;
 
-            Ok((rest, key))
+    let (rest, argument) = match rest.strip_prefix(':') {
+        Some(arg_input) => {
+            if arg_input.starts_with('"') {
+                let (rest, argument) = parse_quoted(source, arg_input)?;
+                (rest, Some((arg_input, argument)))
+            } else {
+                let (rest, argument) =
+                    digit1::<_, nom::error::Error<&str>>(arg_input).map_err(|_| {
+                        TemplateError::syntax(
+                            source,
+                            arg_input,
+                            "expected a number or a quoted string",
+                        )
+                    })?;
+                (rest, Some((arg_input, argument.to_string())))
+            }
+        }
+        None => (rest, None),
+    };
+
+    let filter = match (name, argument) {
+        ("truncate", Some((arg_input, argument))) => match argument.parse::<usize>() {
+            Ok(max) if max > 0 => TemplateFilter::Truncate(max),
+            _ => {
+                return Err(TemplateError::syntax(
+                    source,
+                    arg_input,
+                    "`truncate` expects a positive number",
+                ));
+            }
+        },
+        ("date", Some((arg_input, argument))) => {
+            // Make sure the format is valid before it's used
+            let sample = Timestamp::UNIX_EPOCH.to_zoned(TimeZone::UTC);
+            if let Err(err) = jiff::fmt::strtime::format(&argument, &sample) {
+                return Err(TemplateError::syntax(
+                    source,
+                    arg_input,
+                    format!("invalid date format: {err}"),
+                ));
+            }
+            TemplateFilter::Date(argument)
+        }
+        ("default", Some((_, argument))) => TemplateFilter::Default(argument),
+        ("truncate" | "date" | "default", None) => {
+            return Err(TemplateError::syntax(
+                source,
+                rest,
+                format!("`{name}` expects an argument, something like `{name}:<value>`"),
+            ));
         }
+        _ => {
+            return Err(TemplateError::syntax(
+                source,
+                input,
+                format!("unknown filter `{name}`"),
+            ));
+        }
+    };
+
+    Ok((rest, filter))
+}
+
+/// Expect the closing `}` of a placeholder, returning the rest after it
+fn expect_close<'a>(source: &'a str, input: &'a str) -> Result<&'a str, TemplateError> {
+    let (rest, _) =
+        multispace0::<_, nom::error::Error<&str>>(input).expect("multispace0 should never fail");
+
+    match rest.strip_prefix('}') {
+        Some(rest) => Ok(rest),
+        None if rest.is_empty() => Err(TemplateError::syntax(
+            source,
+            rest,
+            "unclosed placeholder, expected `}`",
+        )),
+        None => Err(TemplateError::syntax(
+            source,
+            rest,
+            "unexpected character, expected `}` or `|`",
+        )),
     }
+}
 
-    fn map_template(input: &str) -> IResult<&str, TemplateToken> {
-        map(parse_template_token, TemplateToken::Template).parse(input)
+/// Parse a placeholder, `input` must start with `{`
+fn parse_placeholder<'a>(
+    source: &'a str,
+    input: &'a str,
+) -> Result<(&'a str, Segment<'a>), TemplateError> {
+    let body = &input[1..];
+
+    if let Some(rest) = body.strip_prefix('}') {
+        return Ok((
+            rest,
+            Segment::Token(TemplateToken::Template(Template::Empty)),
+        ));
     }
 
-    fn map_escaped_open(input: &str) -> IResult<&str, TemplateToken> {
-        value(TemplateToken::Escaped('{'), tag("{{")).parse(input)
+    if let Some(rest) = body.strip_prefix(":else") {
+        return Ok((expect_close(source, rest)?, Segment::Else));
     }
 
-    fn map_escaped_close(input: &str) -> IResult<&str, TemplateToken> {
-        value(TemplateToken::Escaped('}'), tag("}}")).parse(input)
+    if let Some(rest) = body.strip_prefix("/if") {
+        return Ok((expect_close(source, rest)?, Segment::EndIf));
     }
 
-    fn map_standard(input: &str) -> IResult<&str, TemplateToken> {
-        map(
-            take_until0_multiple(&["{", "}", "{{", "}}"]),
-            TemplateToken::Standard,
-        )
-        .parse(input)
+    if let Some(rest) = body.strip_prefix("#if") {
+        let (rest, _) = multispace1::<_, nom::error::Error<&str>>(rest).map_err(|_| {
+            TemplateError::syntax(source, rest, "expected a condition, like `{#if key}`")
+        })?;
+        let (rest, condition) = parse_key(rest).map_err(|_| {
+            TemplateError::syntax(source, rest, "expected a key or an index for the condition")
+        })?;
+        return Ok((expect_close(source, rest)?, Segment::If(condition)));
     }
 
-    fn map_template_token(input: &str) -> IResult<&str, TemplateToken> {
-        if input.is_empty() {
-            // Do this so many0 will stop
-            return Err(nom::Err::Error(nom::error::Error::new(
-                input,
-                nom::error::ErrorKind::Eof,
-            )));
+    if body.starts_with('#') {
+        return Err(TemplateError::syntax(
+            source,
+            body,
+            "unknown block, only `{#if key}` is supported",
+        ));
+    }
+
+    let (rest, _) =
+        multispace0::<_, nom::error::Error<&str>>(body).expect("multispace0 should never fail");
+    let (mut rest, template) = parse_key(rest).map_err(|_| {
+        if rest.is_empty() {
+            TemplateError::syntax(source, rest, "unclosed placeholder, expected `}`")
+        } else {
+            TemplateError::syntax(source, rest, "expected a key or an index")
         }
+    })?;
+
+    let mut filters = vec![];
+    loop {
+        let (after_space, _) =
+            multispace0::<_, nom::error::Error<&str>>(rest).expect("multispace0 should never fail");
+        match tag::<_, _, nom::error::Error<&str>>("|").parse(after_space) {
+            Ok((after_pipe, _)) => {
+                let (after_pipe, _) = multispace0::<_, nom::error::Error<&str>>(after_pipe)
+                    .expect("multispace0 should never fail");
+                let (after_filter, filter) = parse_filter(source, after_pipe)?;
+                filters.push(filter);
+                rest = after_filter;
+            }
+            Err(_) => break,
+        }
+    }
 
-        let (rest, token) = alt((
-            map_escaped_open,
-            map_escaped_close,
-            map_template,
-            map_standard,
-        ))
-        .parse(input)?;
+    let after = expect_close(source, rest)?;
+    let raw = &input[..input.len() - after.len()];
 
-        Ok((rest, token))
+    let token = if filters.is_empty() {
+        TemplateToken::Template(template)
+    } else {
+        TemplateToken::Filtered {
+            template,
+            filters,
+            raw,
+        }
+    };
+
+    Ok((after, Segment::Token(token)))
+}
+
+/// Parse the next segment of the template
+fn parse_segment<'a>(
+    source: &'a str,
+    input: &'a str,
+) -> Result<(&'a str, Segment<'a>), TemplateError> {
+    if let Some(rest) = input.strip_prefix("{{") {
+        return Ok((rest, Segment::Token(TemplateToken::Escaped('{'))));
+    }
+    if let Some(rest) = input.strip_prefix("}}") {
+        return Ok((rest, Segment::Token(TemplateToken::Escaped('}'))));
+    }
+    if input.starts_with('{') {
+        return parse_placeholder(source, input);
+    }
+    if input.starts_with('}') {
+        return Err(TemplateError::syntax(
+            source,
+            input,
+            "unmatched `}`, use `}}` to escape it",
+        ));
     }
 
-    fn internal_parser(input: &str) -> IResult<&str, Vec<TemplateToken>> {
-        many0(map_template_token).parse(input)
+    let end = input.find(['{', '}']).unwrap_or(input.len());
+    Ok((
+        &input[end..],
+        Segment::Token(TemplateToken::Standard(&input[..end])),
+    ))
+}
+
+/// Parse a template string into a vector of template tokens.
+///
+/// We use [nom](https://github.com/rust-bakery/nom) for parsing
+///
+/// The following "template" format is supported:
+/// - `{key}` for a named template
+/// - `{0}`, `{1}` for an indexed template
+/// - `{}` for an empty template, this depends on the internal index counter, separate from the Indexed.
+/// - `{key|filter:arg}` for a template with filters, see [`TemplateFilter`]
+/// - `{#if key}...{:else}...{/if}` for a conditional block, the `{:else}` part is optional
+///
+/// To escape use `{{` and `}}`
+pub fn parse_template(text_template: &str) -> Result<Vec<TemplateToken<'_>>, TemplateError> {
+    struct Block<'a> {
+        /// The remaining input at the `{#if}` tag, used for the error position
+        at: &'a str,
+        condition: Template<'a>,
+        /// The tokens before the block
+        outer: Vec<TemplateToken<'a>>,
+        /// The tokens of the if part, only set after the `{:else}` tag
+        then: Option<Vec<TemplateToken<'a>>>,
     }
 
-    let (rest, mut tokens) = internal_parser(text_template).map_err(throw_nom_error)?;
+    let mut stack: Vec<Block<'_>> = vec![];
+    let mut tokens: Vec<TemplateToken<'_>> = vec![];
+    let mut input = text_template;
+
+    while !input.is_empty() {
+        let (rest, segment) = parse_segment(text_template, input)?;
+        match segment {
+            Segment::Token(token) => tokens.push(token),
+            Segment::If(condition) => stack.push(Block {
+                at: input,
+                condition,
+                outer: std::mem::take(&mut tokens),
+                then: None,
+            }),
+            Segment::Else => match stack.last_mut() {
+                Some(block) if block.then.is_none() => {
+                    block.then = Some(std::mem::take(&mut tokens));
+                }
+                Some(_) => {
+                    return Err(TemplateError::syntax(
+                        text_template,
+                        input,
+                        "duplicate `{:else}` in the same `{#if}` block",
+                    ));
+                }
+                None => {
+                    return Err(TemplateError::syntax(
+                        text_template,
+                        input,
+                        "`{:else}` without a matching `{#if}`",
+                    ));
+                }
+            },
+            Segment::EndIf => {
+                let block = stack.pop().ok_or_else(|| {
+                    TemplateError::syntax(
+                        text_template,
+                        input,
+                        "`{/if}` without a matching `{#if}`",
+                    )
+                })?;
+
+                let current = std::mem::replace(&mut tokens, block.outer);
+                let (then, otherwise) = match block.then {
+                    Some(then) => (then, current),
+                    None => (current, vec![]),
+                };
+                tokens.push(TemplateToken::Conditional {
+                    condition: block.condition,
+                    then,
+                    otherwise,
+                });
+            }
+        }
+        input = rest;
+    }
 
-    if !rest.is_empty() {
-        tokens.push(TemplateToken::Standard(rest));
+    if let Some(block) = stack.last() {
+        return Err(TemplateError::syntax(
+            text_template,
+            block.at,
+            "unclosed `{#if}` block, expected `{/if}`",
+        ));
     }
 
     Ok(tokens)
 }
 
-pub(crate) fn format_template<T: ToString>(
-    tokens: &[TemplateToken<'_>],
+fn resolve_template<T: ToString>(
+    template: &Template<'_>,
+    index: &mut usize,
     args: &[T],
     kwargs: &BTreeMap<String, T>,
-) -> String {
-    let mut index = 0;
-    let mut result = String::new();
+) -> Option<String> {
+    match template {
+        Template::Indexed(i) => args.get(*i as usize).map(ToString::to_string),
+        Template::Empty => {
+            let res = args.get(*index).map(ToString::to_string);
+            *index += 1;
+            res
+        }
+        Template::Named(key) => kwargs.get(*key).map(ToString::to_string),
+    }
+}
 
+fn format_template_inner<T: ToString>(
+    tokens: &[TemplateToken<'_>],
+    index: &mut usize,
+    args: &[T],
+    kwargs: &BTreeMap<String, T>,
+    result: &mut String,
+) {
     for token in tokens {
         match token {
             TemplateToken::Standard(s) => result.push_str(s),
             TemplateToken::Escaped(c) => result.push(*c),
             TemplateToken::Template(t) => {
-                let value = match t {
-                    Template::Indexed(i) => {
-                        let res = args.get(*i as usize).map(ToString::to_string);
-                        res.unwrap_or(format!("{{{i}}}"))
-                    }
-                    Template::Empty => {
-                        let res = args
-                            .get(index)
-                            .map(ToString::to_string)
-                            .unwrap_or("{}".to_string());
-                        index += 1;
-                        res
-                    }
-                    Template::Named(key) => {
-                        let k_str = key.to_string();
-                        let res = kwargs.get(k_str.as_str()).map(ToString::to_string);
-                        res.unwrap_or(format!("{{{key}}}"))
-                    }
-                };
+                let value = resolve_template(t, index, args, kwargs);
+                let value = value.unwrap_or_else(|| match t {
+                    Template::Indexed(i) => format!("{{{i}}}"),
+                    Template::Empty => "{}".to_string(),
+                    Template::Named(key) => format!("{{{key}}}"),
+                });
 
                 result.push_str(&value);
             }
+            TemplateToken::Filtered {
+                template,
+                filters,
+                raw,
+            } => {
+                let value = filters.iter().fold(
+                    resolve_template(template, index, args, kwargs),
+                    |value, filter| filter.apply(value),
+                );
+
+                result.push_str(value.as_deref().unwrap_or(raw));
+            }
+            TemplateToken::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let passed = resolve_template(condition, index, args, kwargs)
+                    .is_some_and(|v| !v.trim().is_empty());
+
+                let branch = if passed { then } else { otherwise };
+                format_template_inner(branch, index, args, kwargs, result);
+            }
         }
     }
+}
+
+pub(crate) fn format_template<T: ToString>(
+    tokens: &[TemplateToken<'_>],
+    args: &[T],
+    kwargs: &BTreeMap<String, T>,
+) -> String {
+    let mut index = 0;
+    let mut result = String::new();
+
+    format_template_inner(tokens, &mut index, args, kwargs, &mut result);
 
     result
 }
@@ -248,6 +600,8 @@ pub(crate) fn format_template<T: ToString>(
 /// - `{key}` for a named template
 /// - `{0}`, `{1}` for an indexed template
 /// - `{}` for an empty template, this depends on the internal index counter, separate from the Indexed.
+/// - `{key|truncate:80}`, `{key|date:"%Y-%m-%d"}` and `{key|default:"None"}` filters, which can be chained.
+/// - `{#if key}...{:else}...{/if}` for a conditional block, passed when the value exist and is not empty.
 ///
 /// To escape use `{{` and `}}`, this will convert `{{` to `{` and `}}` to `}`
 ///
@@ -281,6 +635,11 @@ impl VecString {
         self.internal.push(s.into());
     }
 
+    /// Get the internal vector as a slice
+    pub fn as_slice(&self) -> &[String] {
+        &self.internal
+    }
+
     /// Get the internal vector
     pub fn into_inner(self) -> Vec<String> {
         self.internal
@@ -409,4 +768,98 @@ mod tests {
             "This is a nice and {escaped} other nice guy empty then nice guy indexed xdd final key"
         );
     }
+
+    fn make_kwargs() -> BTreeMap<String, String> {
+        let mut tree_map = BTreeMap::new();
+        tree_map.insert("title".to_string(), "A very long release title".to_string());
+        tree_map.insert(
+            "published".to_string(),
+            "Wed, 1 May 2024 12:30:00 +0000".to_string(),
+        );
+        tree_map.insert("summary".to_string(), "   ".to_string());
+        tree_map.insert("author".to_string(), "naoTimes".to_string());
+        tree_map
+    }
+
+    #[test]
+    fn test_format_filters() {
+        let kwargs = make_kwargs();
+        let args: Vec<String> = vec![];
+
+        let result = format_text(
+            "{title|truncate:10} on {published|date:\"%Y-%m-%d\"}: {summary|default:\"No \\\"summary\\\"\"}",
+            &args,
+            &kwargs,
+        )
+        .unwrap();
+
+        assert_eq!(result, "A very lo… on 2024-05-01: No \"summary\"");
+    }
+
+    #[test]
+    fn test_format_filters_missing() {
+        let kwargs = make_kwargs();
+        let args: Vec<String> = vec![];
+
+        let result = format_text(
+            "{missing|default:\"none\"} {missing | truncate:5} {missing}",
+            &args,
+            &kwargs,
+        )
+        .unwrap();
+
+        assert_eq!(result, "none {missing | truncate:5} {missing}");
+    }
+
+    #[test]
+    fn test_format_conditional() {
+        let kwargs = make_kwargs();
+        let args: Vec<String> = vec![];
+
+        let template = "{#if author}By {author}{/if}{#if summary}{summary}{:else}-{/if}";
+        let result = format_text(template, &args, &kwargs).unwrap();
+        assert_eq!(result, "By naoTimes-");
+
+        let nested = "{#if title}[{#if missing}x{:else}{title|truncate:3}{/if}]{/if}";
+        let result = format_text(nested, &args, &kwargs).unwrap();
+        assert_eq!(result, "[A …]");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("Hello {title", (1, 13)),
+            ("Hello\n{title|upper}", (2, 8)),
+            ("{title|truncate:abc}", (1, 17)),
+            ("{title|default}", (1, 15)),
+            ("{title|default:\"none}", (1, 16)),
+            ("{#if title}yes", (1, 1)),
+            ("yes{/if}", (1, 4)),
+            ("{#if a}{:else}{:else}{/if}", (1, 15)),
+            ("unmatched }", (1, 11)),
+            ("{title name}", (1, 8)),
+        ];
+
+        for (template, position) in cases {
+            let err = parse_template(template).unwrap_err();
+            assert_eq!(err.position(), position, "template: {template:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let parsed = parse_template("{title|truncate:80|default:\"x\"}").unwrap();
+
+        assert_eq!(
+            parsed,
+            vec![TemplateToken::Filtered {
+                template: Template::Named("title"),
+                filters: vec![
+                    TemplateFilter::Truncate(80),
+                    TemplateFilter::Default("x".to_string()),
+                ],
+                raw: "{title|truncate:80|default:\"x\"}",
+            }]
+        );
+    }
 }
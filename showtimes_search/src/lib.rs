@@ -7,6 +7,7 @@ use meilisearch_sdk::client::Client;
 pub use meilisearch_sdk::errors::Error as MeiliError;
 
 pub mod models;
+pub mod outbox;
 
 /// The shared MeiliSearch client
 pub type SearchClientShared = Arc<Client>;
@@ -165,11 +165,12 @@ impl From<showtimes_db::m::Server> for Server {
 /// The user model for Showtimes Search data.
 ///
 /// This is mapped from [`showtimes_db::m::User`] and use `nt-users` as the index name.
+/// Secrets and Discord identifiers are marked with `#[search(skip)]` and never indexed.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, SearchModel)]
 #[search(
     name = "nt-users",
-    filterable = ["id", "created", "username", "discord_id", "discord_username", "api_key.key", "api_key.capabilities", "kind", "registered"],
-    searchable = ["id", "username", "discord_id", "discord_username", "api_key"],
+    filterable = ["id", "created", "username", "discord_username", "kind", "registered"],
+    searchable = ["id", "username", "discord_username"],
     sortable = ["id", "created", "updated"],
     distinct = "id",
 )]
@@ -183,10 +184,18 @@ pub struct User {
     /// The avatar URL of the user
     pub avatar_url: Option<String>,
     /// Their ID on Discord
+    ///
+    /// This is never sent to the search index.
+    #[search(skip)]
+    #[serde(skip_serializing, default)]
     pub discord_id: String,
     /// Their username on Discord
     pub discord_username: String,
     /// Their API key
+    ///
+    /// This is never sent to the search index.
+    #[search(skip)]
+    #[serde(skip_serializing, default)]
     pub api_key: Vec<showtimes_db::m::APIKey>,
     /// Their user kind
     pub kind: showtimes_db::m::UserKind,
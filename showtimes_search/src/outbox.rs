@@ -0,0 +1,345 @@
+//! Apply the pending search index operations from the database outbox.
+//!
+//! Each entry only stores the model kind and ID, the latest data is always
+//! read from the database so applying the same entry twice is harmless.
+
+use std::time::Duration;
+
+use futures_util::TryStreamExt;
+use serde::Serialize;
+use showtimes_db::{
+    DatabaseShared, SearchOutboxHandler,
+    m::{SearchOutbox, SearchOutboxAction, SearchOutboxKind},
+    mongodb::bson::{DateTime, Document, doc},
+};
+use showtimes_shared::ulid::Ulid;
+
+use crate::SearchClientShared;
+
+/// The default amount of entries processed in a single run
+pub const DEFAULT_OUTBOX_BATCH: i64 = 100;
+
+/// The base delay for the exponential backoff
+const RETRY_BASE: Duration = Duration::from_secs(5);
+
+/// The maximum delay between each retry
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 30);
+
+/// Pending entries are applied directly by the writer once the database write landed.
+///
+/// An entry that is never confirmed means the write failed or the writer died,
+/// the worker picks it up after this timeout since applying it is harmless.
+const PENDING_TIMEOUT: jiff::SignedDuration = jiff::SignedDuration::from_secs(60 * 10);
+
+/// Error that happens when applying an outbox entry
+#[derive(Debug)]
+pub enum OutboxError {
+    /// Failed to read or write the database
+    Database(showtimes_db::mongodb::error::Error),
+    /// Failed to apply the operation into Meilisearch
+    Search(crate::MeiliError),
+}
+
+impl std::fmt::Display for OutboxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboxError::Database(e) => write!(f, "database error: {e}"),
+            OutboxError::Search(e) => write!(f, "search error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for OutboxError {}
+
+impl From<showtimes_db::mongodb::error::Error> for OutboxError {
+    fn from(value: showtimes_db::mongodb::error::Error) -> Self {
+        OutboxError::Database(value)
+    }
+}
+
+impl From<crate::MeiliError> for OutboxError {
+    fn from(value: crate::MeiliError) -> Self {
+        OutboxError::Search(value)
+    }
+}
+
+/// The result of processing the outbox
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutboxStats {
+    /// The amount of entries applied
+    pub applied: usize,
+    /// The amount of entries that failed and will be retried
+    pub failed: usize,
+}
+
+/// The exponential backoff delay for the provided attempt
+fn backoff(attempts: u32) -> Duration {
+    let factor = 1u32 << attempts.saturating_sub(1).min(16);
+    RETRY_BASE.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+fn index_info(kind: SearchOutboxKind) -> (&'static str, &'static str) {
+    match kind {
+        SearchOutboxKind::Project => (
+            crate::models::Project::index_name(),
+            crate::models::Project::primary_key(),
+        ),
+        SearchOutboxKind::Server => (
+            crate::models::Server::index_name(),
+            crate::models::Server::primary_key(),
+        ),
+        SearchOutboxKind::User => (
+            crate::models::User::index_name(),
+            crate::models::User::primary_key(),
+        ),
+        SearchOutboxKind::CollaborationSync => (
+            crate::models::ServerCollabSync::index_name(),
+            crate::models::ServerCollabSync::primary_key(),
+        ),
+        SearchOutboxKind::CollaborationInvite => (
+            crate::models::ServerCollabInvite::index_name(),
+            crate::models::ServerCollabInvite::primary_key(),
+        ),
+    }
+}
+
+async fn delete_document(
+    client: &SearchClientShared,
+    kind: SearchOutboxKind,
+    target: Ulid,
+) -> Result<(), crate::MeiliError> {
+    let (index_name, _) = index_info(kind);
+    let task = client
+        .index(index_name)
+        .delete_document(target.to_string())
+        .await?;
+    task.wait_for_completion(&**client, None, None).await?;
+    Ok(())
+}
+
+async fn replace_document<T: Serialize + Send + Sync>(
+    client: &SearchClientShared,
+    kind: SearchOutboxKind,
+    document: T,
+) -> Result<(), crate::MeiliError> {
+    let (index_name, primary_key) = index_info(kind);
+    // Replace the whole document, so removed fields are not kept in the index
+    let task = client
+        .index(index_name)
+        .add_or_replace(&[document], Some(primary_key))
+        .await?;
+    task.wait_for_completion(&**client, None, None).await?;
+    Ok(())
+}
+
+async fn upsert_document(
+    db: &DatabaseShared,
+    client: &SearchClientShared,
+    kind: SearchOutboxKind,
+    target: Ulid,
+) -> Result<(), OutboxError> {
+    let target_id = target.to_string();
+    let document_exists = match kind {
+        SearchOutboxKind::Project => {
+            match showtimes_db::ProjectHandler::new(db)
+                .find_by_id(&target_id)
+                .await?
+            {
+                Some(data) => {
+                    replace_document(client, kind, crate::models::Project::from(data)).await?;
+                    true
+                }
+                None => false,
+            }
+        }
+        SearchOutboxKind::Server => {
+            match showtimes_db::ServerHandler::new(db)
+                .find_by_id(&target_id)
+                .await?
+            {
+                Some(data) => {
+                    replace_document(client, kind, crate::models::Server::from(data)).await?;
+                    true
+                }
+                None => false,
+            }
+        }
+        SearchOutboxKind::User => {
+            match showtimes_db::UserHandler::new(db)
+                .find_by_id(&target_id)
+                .await?
+            {
+                Some(data) => {
+                    replace_document(client, kind, crate::models::User::from(data)).await?;
+                    true
+                }
+                None => false,
+            }
+        }
+        SearchOutboxKind::CollaborationSync => {
+            match showtimes_db::CollaborationSyncHandler::new(db)
+                .find_by_id(&target_id)
+                .await?
+            {
+                Some(data) => {
+                    let document = crate::models::ServerCollabSync::from(data);
+                    replace_document(client, kind, document).await?;
+                    true
+                }
+                None => false,
+            }
+        }
+        SearchOutboxKind::CollaborationInvite => {
+            match showtimes_db::CollaborationInviteHandler::new(db)
+                .find_by_id(&target_id)
+                .await?
+            {
+                Some(data) => {
+                    let document = crate::models::ServerCollabInvite::from(data);
+                    replace_document(client, kind, document).await?;
+                    true
+                }
+                None => false,
+            }
+        }
+    };
+
+    if !document_exists {
+        // Already removed from the database, make sure it's gone from the index too
+        delete_document(client, kind, target).await?;
+    }
+
+    Ok(())
+}
+
+/// Apply a single outbox entry into Meilisearch
+pub async fn apply_entry(
+    db: &DatabaseShared,
+    client: &SearchClientShared,
+    entry: &SearchOutbox,
+) -> Result<(), OutboxError> {
+    match entry.action {
+        SearchOutboxAction::Upsert => upsert_document(db, client, entry.kind, entry.target).await,
+        // The entry is recorded before the database write, re-check the record
+        // so a failed delete does not remove the document from the index.
+        SearchOutboxAction::Delete => upsert_document(db, client, entry.kind, entry.target).await,
+    }
+}
+
+/// Apply the provided entries and remove them from the outbox.
+///
+/// Failed entries are kept and rescheduled with exponential backoff.
+pub async fn apply_entries(
+    db: &DatabaseShared,
+    client: &SearchClientShared,
+    entries: Vec<SearchOutbox>,
+) -> Result<OutboxStats, showtimes_db::mongodb::error::Error> {
+    let handler = SearchOutboxHandler::new(db);
+    let mut stats = OutboxStats::default();
+
+    for mut entry in entries {
+        match apply_entry(db, client, &entry).await {
+            Ok(()) => {
+                handler.delete(&entry).await?;
+                stats.applied += 1;
+            }
+            Err(err) => {
+                let delay = backoff(entry.attempts.saturating_add(1));
+                tracing::warn!(
+                    "Failed to apply search outbox {} ({} {} {}), retrying in {:?}: {}",
+                    entry.id,
+                    entry.action.to_name(),
+                    entry.kind.to_name(),
+                    entry.target,
+                    delay,
+                    err
+                );
+                entry.mark_failed(err.to_string(), delay);
+                handler.save(&mut entry, None).await?;
+                stats.failed += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// The filter for the entries that are due and handed over to the worker
+fn due_filter(now: jiff::Timestamp) -> Document {
+    let abandoned = now.saturating_sub(PENDING_TIMEOUT).unwrap_or(now);
+
+    doc! {
+        "next_attempt": { "$lte": DateTime::from_millis(now.as_millisecond()) },
+        "$or": [
+            { "pending": { "$ne": true } },
+            { "created": { "$lte": DateTime::from_millis(abandoned.as_millisecond()) } },
+        ],
+    }
+}
+
+/// Process the entries that are due, oldest first.
+///
+/// Entries that are still pending on the writer are skipped, see [`SearchOutbox::pending`].
+pub async fn process_outbox(
+    db: &DatabaseShared,
+    client: &SearchClientShared,
+    limit: i64,
+) -> Result<OutboxStats, showtimes_db::mongodb::error::Error> {
+    let handler = SearchOutboxHandler::new(db);
+
+    let entries: Vec<SearchOutbox> = handler
+        .get_collection()
+        .find(due_filter(jiff::Timestamp::now()))
+        .sort(doc! { "created": 1 })
+        .limit(limit)
+        .await?
+        .try_collect()
+        .await?;
+
+    if entries.is_empty() {
+        return Ok(OutboxStats::default());
+    }
+
+    apply_entries(db, client, entries).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_filter_skips_pending() {
+        let now = jiff::Timestamp::from_second(1_700_000_000).unwrap();
+        let filter = due_filter(now);
+
+        let next_attempt = filter.get_document("next_attempt").unwrap();
+        assert_eq!(
+            next_attempt.get_datetime("$lte").unwrap(),
+            &DateTime::from_millis(now.as_millisecond())
+        );
+
+        let conditions = filter.get_array("$or").unwrap();
+        assert_eq!(conditions.len(), 2);
+
+        let confirmed = conditions[0].as_document().unwrap();
+        assert_eq!(
+            confirmed.get_document("pending").unwrap(),
+            &doc! { "$ne": true }
+        );
+
+        let abandoned = conditions[1].as_document().unwrap();
+        let abandoned_at = now.saturating_sub(PENDING_TIMEOUT).unwrap();
+        assert_eq!(
+            abandoned.get_document("created").unwrap(),
+            &doc! { "$lte": DateTime::from_millis(abandoned_at.as_millisecond()) }
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), RETRY_BASE);
+        assert_eq!(backoff(2), RETRY_BASE * 2);
+        assert_eq!(backoff(3), RETRY_BASE * 4);
+        assert_eq!(backoff(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
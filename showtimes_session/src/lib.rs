@@ -192,6 +192,11 @@ impl ShowtimesRefreshClaims {
         self.user
     }
 
+    /// Get the expiry time in UNIX timestamp format
+    pub fn get_expires_at(&self) -> i64 {
+        self.exp
+    }
+
     /// Get when the claims is issued
     pub fn get_issued_at(&self) -> jiff::Timestamp {
         self.iat
@@ -7,6 +7,8 @@ use jwt_lc_rs::errors::ValidationError;
 use redis::AsyncCommands;
 use redis::RedisResult;
 use redis::cmd;
+use serde::{Deserialize, Serialize};
+use showtimes_shared::ulid::Ulid;
 
 use super::{
     ShowtimesAudience, ShowtimesRefreshSession, ShowtimesUserClaims, ShowtimesUserSession,
@@ -19,6 +21,9 @@ use super::{
 pub type SharedSessionManager = std::sync::Arc<tokio::sync::Mutex<SessionManager>>;
 const SESSION_MANAGER: &str = "showtimes:session";
 const SESSION_REFRESH_MANAGER: &str = "showtimes:session:refresh";
+const SESSION_INFO_MANAGER: &str = "showtimes:session:info";
+const SESSION_TOKEN_MANAGER: &str = "showtimes:session:token";
+const SESSION_USER_MANAGER: &str = "showtimes:session:user";
 
 /// Redis-managed session state for the showtimes service.
 #[derive(Clone)]
@@ -38,6 +43,111 @@ pub enum SessionKind {
     MasterKey,
 }
 
+/// The client information recorded when a session is created.
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadata {
+    /// The user agent of the client
+    pub user_agent: Option<String>,
+    /// The IP address of the client
+    pub ip: Option<String>,
+}
+
+/// A tracked bearer session of a user.
+///
+/// The session keeps the same ID when the token is refreshed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSessionInfo {
+    /// The session ID
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    id: Ulid,
+    /// The user who owns the session
+    #[serde(with = "showtimes_shared::ulid_serializer")]
+    user: Ulid,
+    /// The current bearer token of the session
+    token: String,
+    /// The refresh token of the session
+    refresh_token: Option<String>,
+    /// When the session is created
+    #[serde(with = "jiff::fmt::serde::timestamp::second::required")]
+    issued_at: jiff::Timestamp,
+    /// When the session token is last refreshed
+    #[serde(with = "jiff::fmt::serde::timestamp::second::optional")]
+    refreshed_at: Option<jiff::Timestamp>,
+    /// When the session can no longer be used or refreshed
+    #[serde(with = "jiff::fmt::serde::timestamp::second::required")]
+    expires_at: jiff::Timestamp,
+    /// The user agent of the client that created the session
+    user_agent: Option<String>,
+    /// The IP address of the client that created the session
+    ip: Option<String>,
+}
+
+impl UserSessionInfo {
+    /// Get the session ID
+    pub fn get_id(&self) -> Ulid {
+        self.id
+    }
+
+    /// Get the user who owns the session
+    pub fn get_user(&self) -> Ulid {
+        self.user
+    }
+
+    /// Check if the session is using the provided bearer token
+    pub fn is_token(&self, token: &str) -> bool {
+        self.token == token
+    }
+
+    /// Get when the session is created
+    pub fn get_issued_at(&self) -> jiff::Timestamp {
+        self.issued_at
+    }
+
+    /// Get when the session token is last refreshed
+    pub fn get_refreshed_at(&self) -> Option<jiff::Timestamp> {
+        self.refreshed_at
+    }
+
+    /// Get when the session can no longer be used or refreshed
+    pub fn get_expires_at(&self) -> jiff::Timestamp {
+        self.expires_at
+    }
+
+    /// Get the user agent of the client that created the session
+    pub fn get_user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Get the IP address of the client that created the session
+    pub fn get_ip(&self) -> Option<&str> {
+        self.ip.as_deref()
+    }
+
+    /// Check if the session can no longer be used or refreshed at the provided time
+    fn is_expired_at(&self, now: jiff::Timestamp) -> bool {
+        self.expires_at < now
+    }
+}
+
+/// The session is alive as long as it can be refreshed, `refresh_exp` is the refresh token expiry
+fn session_expires_at(session_exp: i64, refresh_exp: Option<i64>) -> jiff::Timestamp {
+    let expires_at = refresh_exp.map_or(session_exp, |refresh_exp| refresh_exp.max(session_exp));
+
+    jiff::Timestamp::from_second(expires_at).unwrap_or(jiff::Timestamp::MAX)
+}
+
+fn user_sessions_key(user: Ulid) -> String {
+    format!("{SESSION_USER_MANAGER}:{user}")
+}
+
+fn json_error(e: serde_json::Error) -> redis::RedisError {
+    redis::RedisError::from((
+        redis::ErrorKind::TypeError,
+        "Failed to serialize session info",
+        e.to_string(),
+    ))
+}
+
 /// Error type for session manager.
 #[derive(Debug)]
 pub enum SessionError {
@@ -283,10 +393,306 @@ impl SessionManager {
         if let Some(token_session) = token_session {
             // Remove old session
             self.remove_session(&token_session).await?;
+            // Keep the tracked session pointing to the new token
+            self.refresh_tracked_session(&token_session, &session_token)
+                .await?;
         }
 
         self.connection
             .hset(SESSION_REFRESH_MANAGER, refresh_token, session_token)
             .await
     }
+
+    /// Track a bearer session of a user so it can be listed and revoked.
+    ///
+    /// This should be called after the session and the refresh session is stored.
+    pub async fn track_session(
+        &mut self,
+        user: Ulid,
+        token: impl Into<String>,
+        session: &ShowtimesUserClaims,
+        refresh_token: Option<&str>,
+        metadata: &SessionMetadata,
+    ) -> RedisResult<UserSessionInfo> {
+        let refresh_exp = refresh_token
+            .and_then(|token| verify_refresh_session(token, &self.signer).ok())
+            .map(|claims| claims.get_expires_at());
+
+        let info = UserSessionInfo {
+            id: Ulid::new(),
+            user,
+            token: token.into(),
+            refresh_token: refresh_token.map(|token| token.to_string()),
+            issued_at: session.get_issued_at(),
+            refreshed_at: None,
+            expires_at: session_expires_at(session.exp, refresh_exp),
+            user_agent: metadata.user_agent.clone(),
+            ip: metadata.ip.clone(),
+        };
+        let payload = serde_json::to_string(&info).map_err(json_error)?;
+        let session_id = info.id.to_string();
+
+        redis::pipe()
+            .atomic()
+            .hset(SESSION_INFO_MANAGER, &session_id, payload)
+            .hset(SESSION_TOKEN_MANAGER, &info.token, &session_id)
+            .sadd(user_sessions_key(user), &session_id)
+            .query_async::<()>(&mut self.connection)
+            .await?;
+
+        Ok(info)
+    }
+
+    async fn get_session_info(&mut self, session_id: &str) -> RedisResult<Option<UserSessionInfo>> {
+        let payload: Option<String> = self
+            .connection
+            .hget(SESSION_INFO_MANAGER, session_id)
+            .await?;
+
+        Ok(payload.and_then(|payload| parse_session_info(session_id, &payload)))
+    }
+
+    /// Move the tracked session from the old token to the refreshed token
+    async fn refresh_tracked_session(
+        &mut self,
+        old_token: &str,
+        new_token: &str,
+    ) -> RedisResult<()> {
+        let session_id: Option<String> = self
+            .connection
+            .hget(SESSION_TOKEN_MANAGER, old_token)
+            .await?;
+        let Some(session_id) = session_id else {
+            return Ok(());
+        };
+
+        let Some(mut info) = self.get_session_info(&session_id).await? else {
+            return self.connection.hdel(SESSION_TOKEN_MANAGER, old_token).await;
+        };
+
+        info.token = new_token.to_string();
+        info.refreshed_at = Some(jiff::Timestamp::now());
+        let payload = serde_json::to_string(&info).map_err(json_error)?;
+
+        redis::pipe()
+            .atomic()
+            .hdel(SESSION_TOKEN_MANAGER, old_token)
+            .hset(SESSION_TOKEN_MANAGER, new_token, &session_id)
+            .hset(SESSION_INFO_MANAGER, &session_id, payload)
+            .query_async(&mut self.connection)
+            .await
+    }
+
+    /// Remove the tracked session along with the token and refresh token
+    async fn remove_tracked_session(&mut self, info: &UserSessionInfo) -> RedisResult<()> {
+        let session_id = info.id.to_string();
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .hdel(SESSION_MANAGER, &info.token)
+            .hdel(SESSION_TOKEN_MANAGER, &info.token)
+            .hdel(SESSION_INFO_MANAGER, &session_id)
+            .srem(user_sessions_key(info.user), &session_id);
+        if let Some(refresh_token) = &info.refresh_token {
+            pipe.hdel(SESSION_REFRESH_MANAGER, refresh_token);
+        }
+
+        pipe.query_async(&mut self.connection).await
+    }
+
+    /// Get all the active sessions of a user, newest first.
+    ///
+    /// Expired sessions are removed while listing.
+    pub async fn get_user_sessions(&mut self, user: Ulid) -> RedisResult<Vec<UserSessionInfo>> {
+        let user_key = user_sessions_key(user);
+        let session_ids: Vec<String> = self.connection.smembers(&user_key).await?;
+        if session_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let payloads: Vec<Option<String>> = cmd("HMGET")
+            .arg(SESSION_INFO_MANAGER)
+            .arg(&session_ids)
+            .query_async(&mut self.connection)
+            .await?;
+
+        let now = jiff::Timestamp::now();
+        let mut sessions = vec![];
+        for (session_id, payload) in session_ids.iter().zip(payloads) {
+            match payload.and_then(|payload| parse_session_info(session_id, &payload)) {
+                Some(info) if info.is_expired_at(now) => {
+                    self.remove_tracked_session(&info).await?;
+                }
+                Some(info) => sessions.push(info),
+                None => {
+                    self.connection
+                        .srem::<_, _, ()>(&user_key, session_id)
+                        .await?;
+                }
+            }
+        }
+
+        sessions.sort_by(|a, b| b.issued_at.cmp(&a.issued_at));
+        Ok(sessions)
+    }
+
+    /// Revoke a session of a user.
+    ///
+    /// Returns `false` if the session does not exist or not owned by the user.
+    pub async fn revoke_user_session(&mut self, user: Ulid, session_id: Ulid) -> RedisResult<bool> {
+        match self.get_session_info(&session_id.to_string()).await? {
+            Some(info) if info.user == user => {
+                self.remove_tracked_session(&info).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Revoke all the sessions of a user, except the session using the `keep` token.
+    ///
+    /// Returns the number of revoked sessions.
+    pub async fn revoke_all_user_sessions(
+        &mut self,
+        user: Ulid,
+        keep: Option<&str>,
+    ) -> RedisResult<usize> {
+        let sessions = self.get_user_sessions(user).await?;
+
+        let mut revoked = 0usize;
+        for info in sessions
+            .iter()
+            .filter(|info| keep.is_none_or(|token| !info.is_token(token)))
+        {
+            self.remove_tracked_session(info).await?;
+            revoked += 1;
+        }
+
+        Ok(revoked)
+    }
+
+    /// Revoke the session using the token, including the refresh token when the session is tracked.
+    pub async fn revoke_session(&mut self, token: impl Into<String>) -> RedisResult<()> {
+        let token: String = token.into();
+
+        let session_id: Option<String> =
+            self.connection.hget(SESSION_TOKEN_MANAGER, &token).await?;
+        if let Some(session_id) = session_id
+            && let Some(info) = self.get_session_info(&session_id).await?
+        {
+            return self.remove_tracked_session(&info).await;
+        }
+
+        self.remove_session(token).await
+    }
+}
+
+fn parse_session_info(session_id: &str, payload: &str) -> Option<UserSessionInfo> {
+    match serde_json::from_str(payload) {
+        Ok(info) => Some(info),
+        Err(e) => {
+            tracing::warn!("Failed to parse session info {}: {}", session_id, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_info(expires_at: jiff::Timestamp) -> UserSessionInfo {
+        UserSessionInfo {
+            id: Ulid::new(),
+            user: Ulid::new(),
+            token: "session-token".to_string(),
+            refresh_token: Some("refresh-token".to_string()),
+            issued_at: jiff::Timestamp::from_second(1_700_000_000).unwrap(),
+            refreshed_at: None,
+            expires_at,
+            user_agent: Some("Mozilla/5.0".to_string()),
+            ip: Some("127.0.0.1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_session_info_serde() {
+        let mut info = make_info(jiff::Timestamp::from_second(1_700_086_400).unwrap());
+        info.refreshed_at = Some(jiff::Timestamp::from_second(1_700_003_600).unwrap());
+
+        let payload = serde_json::to_string(&info).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        // Timestamps are stored as seconds
+        assert_eq!(value["issued_at"], 1_700_000_000);
+        assert_eq!(value["refreshed_at"], 1_700_003_600);
+        assert_eq!(value["expires_at"], 1_700_086_400);
+
+        let parsed = parse_session_info("test", &payload).unwrap();
+        assert_eq!(parsed.get_id(), info.get_id());
+        assert_eq!(parsed.get_user(), info.get_user());
+        assert!(parsed.is_token("session-token"));
+        assert_eq!(parsed.refresh_token.as_deref(), Some("refresh-token"));
+        assert_eq!(parsed.get_issued_at(), info.get_issued_at());
+        assert_eq!(parsed.get_refreshed_at(), info.get_refreshed_at());
+        assert_eq!(parsed.get_expires_at(), info.get_expires_at());
+        assert_eq!(parsed.get_user_agent(), Some("Mozilla/5.0"));
+        assert_eq!(parsed.get_ip(), Some("127.0.0.1"));
+    }
+
+    #[test]
+    fn test_session_info_serde_without_optional() {
+        let mut info = make_info(jiff::Timestamp::from_second(1_700_086_400).unwrap());
+        info.refresh_token = None;
+        info.user_agent = None;
+        info.ip = None;
+
+        let payload = serde_json::to_string(&info).unwrap();
+        let parsed = parse_session_info("test", &payload).unwrap();
+
+        assert!(parsed.refresh_token.is_none());
+        assert!(parsed.get_refreshed_at().is_none());
+        assert!(parsed.get_user_agent().is_none());
+        assert!(parsed.get_ip().is_none());
+    }
+
+    #[test]
+    fn test_session_info_invalid() {
+        assert!(parse_session_info("test", "not json").is_none());
+        assert!(parse_session_info("test", "{}").is_none());
+    }
+
+    #[test]
+    fn test_session_info_expiry() {
+        let now = jiff::Timestamp::from_second(1_700_000_000).unwrap();
+
+        let expired = make_info(now - jiff::SignedDuration::from_secs(1));
+        assert!(expired.is_expired_at(now));
+
+        let active = make_info(now + jiff::SignedDuration::from_secs(1));
+        assert!(!active.is_expired_at(now));
+
+        // Still usable at the exact expiry second
+        let boundary = make_info(now);
+        assert!(!boundary.is_expired_at(now));
+    }
+
+    #[test]
+    fn test_session_expires_at() {
+        // Without refresh token, the session expiry is used
+        assert_eq!(
+            session_expires_at(1_700_000_000, None),
+            jiff::Timestamp::from_second(1_700_000_000).unwrap()
+        );
+        // The refresh token usually outlives the session
+        assert_eq!(
+            session_expires_at(1_700_000_000, Some(1_700_600_000)),
+            jiff::Timestamp::from_second(1_700_600_000).unwrap()
+        );
+        // But never shorten the session
+        assert_eq!(
+            session_expires_at(1_700_000_000, Some(1_600_000_000)),
+            jiff::Timestamp::from_second(1_700_000_000).unwrap()
+        );
+        // Out of range expiry never expires
+        assert_eq!(session_expires_at(i64::MAX, None), jiff::Timestamp::MAX);
+    }
 }
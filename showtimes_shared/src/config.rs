@@ -76,6 +76,11 @@ pub struct Database {
     pub mongodb: String,
     /// The URL of the Redis server
     pub redis: String,
+    /// The broker used to share the events to the subscribers
+    ///
+    /// Use `redis` when running multiple instances of the server
+    #[serde(default)]
+    pub event_broker: EventBroker,
 }
 
 /// Meilisearch configuration
@@ -166,6 +171,17 @@ pub struct StorageS3 {
     pub path_style: StorageS3PathStyle,
 }
 
+/// The broker used to share the events to the subscribers
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EventBroker {
+    /// In-memory broker, only the events of the same instance are shared
+    #[default]
+    Memory,
+    /// Redis pub/sub broker, the events are shared between all instances
+    Redis,
+}
+
 /// ClickHouse configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct ClickHouseEvent {
@@ -196,6 +212,35 @@ pub struct RSS {
     /// The limit of the premium feed that can be stored in the database
     #[serde(default)]
     pub premium_limit: Option<u32>,
+    /// The consecutive failures before a feed is disabled
+    #[serde(default)]
+    pub max_failures: Option<u32>,
+}
+
+/// Webhooks delivery configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Webhooks {
+    /// The maximum retries of a single delivery
+    #[serde(default)]
+    pub max_retries: Option<u8>,
+    /// The consecutive failures before a webhook is disabled
+    #[serde(default)]
+    pub max_failures: Option<u32>,
+    /// The public base URL of the server, used to show the project poster
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+/// FansubDB integration configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct FansubDB {
+    /// The email of the contributor account
+    pub email: String,
+    /// The password of the contributor account
+    pub password: String,
+    /// The maximum retries of a failed release sync
+    #[serde(default)]
+    pub max_retries: Option<u8>,
 }
 
 /// The full configuration for Showtimes
@@ -221,6 +266,12 @@ pub struct Config {
     /// The log directory for the server
     #[serde(rename = "log-directory", default)]
     pub log_directory: Option<String>,
+    /// The reverse proxies allowed to set the client address with the
+    /// `X-Forwarded-For` or `X-Real-IP` headers.
+    ///
+    /// Default to empty, which always use the peer address.
+    #[serde(rename = "trusted-proxies", default)]
+    pub trusted_proxies: Vec<std::net::IpAddr>,
     /// The database connection configuration
     pub database: Database,
     /// The Meilisearch configuration
@@ -239,6 +290,12 @@ pub struct Config {
     pub jwt: JwtSession,
     /// RSS configuration
     pub rss: RSS,
+    /// Webhooks delivery configuration
+    #[serde(default)]
+    pub webhooks: Webhooks,
+    /// FansubDB integration configuration, the release sync is disabled when missing
+    #[serde(default)]
+    pub fansubdb: Option<FansubDB>,
 }
 
 /// This macro wraps [`ConfigVerifyError`] and the error item &str into a String
@@ -450,6 +507,13 @@ impl Config {
                     ConfigVerifyError::MinimumAmount("Premium RSS limit".to_string(), 1).into(),
                 );
             }
+            if let Some(max_failures) = self.rss.max_failures
+                && max_failures < 1
+            {
+                return Err(
+                    ConfigVerifyError::MinimumAmount("RSS max failures".to_string(), 1).into(),
+                );
+            }
         }
 
         Ok(())
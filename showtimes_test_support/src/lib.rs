@@ -0,0 +1,204 @@
+#![warn(missing_docs, clippy::empty_docs, rustdoc::broken_intra_doc_links)]
+//! Shared helpers for the Showtimes API tests
+//!
+//! This crate is only used as a dev-dependency.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use showtimes_db::m::{EpisodeProgress, Project, ProjectType, Role};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::UnboundedReceiver,
+};
+
+/// A request received by the stand-in server
+#[derive(Debug, Default)]
+pub struct StandInRequest {
+    /// The request method
+    pub method: String,
+    /// The request path
+    pub path: String,
+    /// The `Authorization` header
+    pub authorization: Option<String>,
+    /// The request body
+    pub body: String,
+}
+
+/// A response sent by the stand-in server
+#[derive(Debug)]
+pub struct StandInResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl StandInResponse {
+    /// Create an empty response with the given status
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    /// Create a JSON response with the given status
+    pub fn json(status: u16, body: impl Into<String>) -> Self {
+        Self::new(status)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+    }
+
+    /// Add a header to the response
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the body of the response
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    fn to_http(&self) -> String {
+        let headers: String = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}\r\n"))
+            .collect();
+
+        format!(
+            "HTTP/1.1 {} Stand-In\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n{}",
+            self.status,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// The function that decide the response of the stand-in server
+pub type StandInResponder = dyn Fn(&StandInRequest) -> StandInResponse + Send + Sync;
+
+/// Spawn a tiny HTTP stand-in server, the `responder` decide the response of every request.
+///
+/// Returns the URL of the server with the `path` appended and a receiver for every request.
+pub async fn spawn_stand_in(
+    path: &str,
+    responder: Arc<StandInResponder>,
+) -> (String, UnboundedReceiver<StandInRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let tx = tx.clone();
+            let responder = Arc::clone(&responder);
+            tokio::spawn(async move {
+                let request = read_request(&mut socket).await;
+                let response = responder(&request);
+                tx.send(request).ok();
+
+                socket.write_all(response.to_http().as_bytes()).await.ok();
+                socket.shutdown().await.ok();
+            });
+        }
+    });
+
+    (format!("http://{addr}{path}"), rx)
+}
+
+/// Spawn a tiny HTTP stand-in server that respond with each `statuses` in order.
+///
+/// The last status will be repeated, a 429 response will include `retry-after: 0`
+pub async fn spawn_stand_in_status(
+    path: &str,
+    statuses: Vec<u16>,
+) -> (String, UnboundedReceiver<StandInRequest>) {
+    let counter = AtomicUsize::new(0);
+
+    spawn_stand_in(
+        path,
+        Arc::new(move |_: &StandInRequest| {
+            let index = counter.fetch_add(1, Ordering::SeqCst);
+            let status = statuses[index.min(statuses.len() - 1)];
+            let response = StandInResponse::new(status);
+            if status == 429 {
+                response.with_header("retry-after", "0")
+            } else {
+                response
+            }
+        }),
+    )
+    .await
+}
+
+/// Read a single HTTP request from the socket
+pub async fn read_request(socket: &mut TcpStream) -> StandInRequest {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        let read = socket.read(&mut chunk).await.unwrap();
+        if read == 0 && buffer.is_empty() {
+            return StandInRequest::default();
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        if let Some(header_end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+            let mut lines = head.lines();
+            let mut request_line = lines.next().unwrap_or_default().split(' ');
+            let method = request_line.next().unwrap_or_default().to_string();
+            let path = request_line.next().unwrap_or_default().to_string();
+
+            let mut length = 0;
+            let mut authorization = None;
+            for line in lines {
+                let (name, value) = line.split_once(':').unwrap_or_default();
+                match name.to_ascii_lowercase().as_str() {
+                    "content-length" => length = value.trim().parse().unwrap(),
+                    "authorization" => authorization = Some(value.trim().to_string()),
+                    _ => {}
+                }
+            }
+
+            let body_start = header_end + 4;
+            if buffer.len() >= body_start + length || read == 0 {
+                let end = (body_start + length).min(buffer.len());
+                return StandInRequest {
+                    method,
+                    path,
+                    authorization,
+                    body: String::from_utf8_lossy(&buffer[body_start..end]).to_string(),
+                };
+            }
+        }
+    }
+}
+
+/// A series project with two episodes and two roles
+pub fn make_project() -> Project {
+    let mut project = Project::new(
+        "Test Project",
+        ProjectType::Series,
+        showtimes_shared::ulid::Ulid::new(),
+    )
+    .unwrap();
+
+    let roles = vec![
+        Role::new("TL", "Translator").unwrap(),
+        Role::new("ED", "Editor").unwrap().with_order(1),
+    ];
+    project.progress = vec![
+        EpisodeProgress::new_with_roles(1, false, &roles),
+        EpisodeProgress::new_with_roles(2, false, &roles),
+    ];
+    project.roles = roles;
+    project
+}
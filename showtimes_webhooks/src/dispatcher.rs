@@ -0,0 +1,741 @@
+//! The webhook dispatcher, listening to project events and delivering them
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt;
+use showtimes_db::{
+    DatabaseShared, ProjectHandler, WebhookHandler,
+    m::{
+        EpisodeProgress, Project, ProjectStatus, ProjectType, Webhook, WebhookAction, WebhookFilter,
+    },
+    mongodb::{
+        bson::{DateTime, doc},
+        options::ReturnDocument,
+    },
+};
+use showtimes_events::{
+    MemoryBroker, SharedSHClickHouse,
+    m::{
+        EventKind, ProjectCreatedEvent, ProjectEpisodeUpdatedEvent, ProjectUpdatedEvent, SHEvent,
+        WebhookDeliveryEvent, WebhookDisabledEvent,
+    },
+};
+use tokio::sync::Semaphore;
+
+use crate::{
+    engine::{WebhookEngine, WebhookEnginePayload, WebhookEnginePayloadError, create_engine},
+    http::{RetryPolicy, create_client, parse_retry_after},
+};
+
+/// The default amount of concurrent webhook delivery
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// The default amount of retries for a single webhook delivery
+pub const DEFAULT_MAX_RETRIES: u8 = 3;
+
+/// The default amount of consecutive failures before a webhook is disabled
+pub const DEFAULT_MAX_FAILURES: u32 = 5;
+
+/// A single event that can be delivered to the webhooks
+#[derive(Debug, Clone)]
+pub enum WebhookEvent {
+    /// A new project is created
+    ProjectCreate(Project),
+    /// A project episode progress is updated
+    ProjectProgress {
+        /// The project itself
+        project: Project,
+        /// The episode before the update
+        before: EpisodeProgress,
+        /// The episode after the update
+        after: EpisodeProgress,
+    },
+    /// One or more episodes are released
+    ProjectRelease {
+        /// The project itself
+        project: Project,
+        /// The released episodes
+        episodes: Vec<EpisodeProgress>,
+    },
+    /// One or more episodes are unreleased
+    ProjectUnreleased {
+        /// The project itself
+        project: Project,
+        /// The unreleased episodes
+        episodes: Vec<EpisodeProgress>,
+    },
+    /// A project is dropped
+    ProjectDropped(Project),
+    /// A project is resumed
+    ProjectResumed(Project),
+}
+
+impl WebhookEvent {
+    /// The matching [`WebhookAction`] for this event
+    pub fn action(&self) -> WebhookAction {
+        match self {
+            WebhookEvent::ProjectCreate(_) => WebhookAction::ProjectCreate,
+            WebhookEvent::ProjectProgress { .. } => WebhookAction::ProjectProgress,
+            WebhookEvent::ProjectRelease { .. } => WebhookAction::ProjectRelease,
+            WebhookEvent::ProjectUnreleased { .. } => WebhookAction::ProjectUnreleased,
+            WebhookEvent::ProjectDropped(_) => WebhookAction::ProjectDropped,
+            WebhookEvent::ProjectResumed(_) => WebhookAction::ProjectResumed,
+        }
+    }
+
+    /// The project associated with this event
+    pub fn project(&self) -> &Project {
+        match self {
+            WebhookEvent::ProjectCreate(project)
+            | WebhookEvent::ProjectDropped(project)
+            | WebhookEvent::ProjectResumed(project) => project,
+            WebhookEvent::ProjectProgress { project, .. }
+            | WebhookEvent::ProjectRelease { project, .. }
+            | WebhookEvent::ProjectUnreleased { project, .. } => project,
+        }
+    }
+
+    /// Check if the event is accepted by the webhook filter
+    ///
+    /// The role filter only applies to the progress event.
+    pub fn matches_filter(&self, filter: &WebhookFilter) -> bool {
+        if !filter.matches_project(self.project()) {
+            return false;
+        }
+
+        match self {
+            WebhookEvent::ProjectProgress { before, after, .. } => {
+                filter.matches_progress(before, after)
+            }
+            _ => true,
+        }
+    }
+
+    /// Create a sample event for the provided action
+    ///
+    /// This is used to test the webhook without needing an actual project.
+    pub fn sample(action: WebhookAction, creator: showtimes_shared::ulid::Ulid) -> Self {
+        let mut project = Project::new("Sample Project", ProjectType::Series, creator)
+            .expect("Sample project title should always be valid");
+
+        let roles = project.kind.kind().default_roles();
+        project.progress = vec![
+            EpisodeProgress::new_with_roles(1, false, &roles),
+            EpisodeProgress::new_with_roles(2, false, &roles),
+        ];
+        project.roles = roles;
+
+        match action {
+            WebhookAction::ProjectCreate => WebhookEvent::ProjectCreate(project),
+            WebhookAction::ProjectProgress => {
+                let before = project.progress[0].clone();
+                let mut after = before.clone();
+                if let Some(status) = after.statuses.first_mut() {
+                    status.set_finished(true);
+                }
+
+                WebhookEvent::ProjectProgress {
+                    project,
+                    before,
+                    after,
+                }
+            }
+            WebhookAction::ProjectRelease => WebhookEvent::ProjectRelease {
+                episodes: vec![project.progress[0].clone()],
+                project,
+            },
+            WebhookAction::ProjectUnreleased => WebhookEvent::ProjectUnreleased {
+                episodes: vec![project.progress[0].clone()],
+                project,
+            },
+            WebhookAction::ProjectDropped => WebhookEvent::ProjectDropped(project),
+            WebhookAction::ProjectResumed => WebhookEvent::ProjectResumed(project),
+        }
+    }
+
+    /// Render the event into the payload with the provided engine
+    pub fn render(
+        &self,
+        engine: &dyn WebhookEnginePayload,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        match self {
+            WebhookEvent::ProjectCreate(project) => engine.project_create(project),
+            WebhookEvent::ProjectProgress {
+                project,
+                before,
+                after,
+            } => engine.project_progress(project, before, after),
+            WebhookEvent::ProjectRelease { project, episodes } => match episodes.as_slice() {
+                [episode] => engine.project_release(project, episode),
+                _ => engine.project_release_multi(project, episodes),
+            },
+            WebhookEvent::ProjectUnreleased { project, episodes } => match episodes.as_slice() {
+                [episode] => engine.project_unrelease(project, episode),
+                _ => engine.project_unrelease_multi(project, episodes),
+            },
+            WebhookEvent::ProjectDropped(project) => engine.project_dropped(project),
+            WebhookEvent::ProjectResumed(project) => engine.project_resumed(project),
+        }
+    }
+}
+
+/// An error that can happen when delivering a webhook
+#[derive(Debug)]
+pub enum WebhookError {
+    /// Failed to generate the payload
+    Payload(WebhookEnginePayloadError),
+    /// Failed to send the request
+    Request(reqwest::Error),
+    /// The target returned a non-success status code
+    Status(reqwest::StatusCode),
+}
+
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::Payload(err) => write!(f, "{err}"),
+            WebhookError::Request(err) => write!(f, "Request error: {err}"),
+            WebhookError::Status(status) => write!(f, "Unexpected status code: {status}"),
+        }
+    }
+}
+
+impl WebhookError {
+    /// Check if the delivery can be retried
+    ///
+    /// Only server errors, rate limits, timeouts and connection errors are retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            WebhookError::Payload(_) => false,
+            WebhookError::Request(err) => err.is_timeout() || err.is_connect(),
+            WebhookError::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+impl From<WebhookEnginePayloadError> for WebhookError {
+    fn from(err: WebhookEnginePayloadError) -> Self {
+        WebhookError::Payload(err)
+    }
+}
+
+impl From<reqwest::Error> for WebhookError {
+    fn from(err: reqwest::Error) -> Self {
+        WebhookError::Request(err)
+    }
+}
+
+/// The result of a single webhook delivery
+#[derive(Debug)]
+pub struct WebhookDelivery {
+    /// The webhook ID
+    pub id: showtimes_shared::ulid::Ulid,
+    /// The delivered action
+    pub action: WebhookAction,
+    /// Every delivery attempt that has been made, in order
+    pub attempts: Vec<WebhookDeliveryEvent>,
+    /// The delivery result, the status code if success
+    pub result: Result<reqwest::StatusCode, WebhookError>,
+}
+
+/// The internal merged events from the broker
+enum BrokerEvent {
+    Created(SHEvent<ProjectCreatedEvent>),
+    Updated(SHEvent<ProjectUpdatedEvent>),
+    Episode(SHEvent<ProjectEpisodeUpdatedEvent>),
+}
+
+/// The webhook dispatcher
+///
+/// This will render and deliver the [`WebhookEvent`] into all matching webhooks
+/// with a bounded concurrency.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    limiter: Arc<Semaphore>,
+    locale: showtimes_i18n::Language,
+    retry: RetryPolicy,
+    max_failures: u32,
+    asset_url: Option<String>,
+}
+
+impl WebhookDispatcher {
+    /// Create a new webhook dispatcher with [`DEFAULT_CONCURRENCY`]
+    pub fn new() -> Result<Self, reqwest::Error> {
+        Self::with_concurrency(DEFAULT_CONCURRENCY)
+    }
+
+    /// Create a new webhook dispatcher with custom concurrency
+    pub fn with_concurrency(concurrency: usize) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: create_client()?,
+            limiter: Arc::new(Semaphore::new(concurrency.max(1))),
+            locale: showtimes_i18n::Language::default(),
+            retry: RetryPolicy::with_max_retries(DEFAULT_MAX_RETRIES),
+            max_failures: DEFAULT_MAX_FAILURES,
+            asset_url: None,
+        })
+    }
+
+    /// Set the fallback locale when the webhook has no locale set
+    pub fn with_locale(mut self, locale: showtimes_i18n::Language) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Set the maximum retries and the base delay of the exponential backoff
+    ///
+    /// The delay is doubled on each retry, unless the target
+    /// returned a `Retry-After` header. The retries is capped to 10.
+    pub fn with_retries(mut self, max_retries: u8, base_delay: Duration) -> Self {
+        self.retry = RetryPolicy::new(max_retries, base_delay);
+        self
+    }
+
+    /// Set the amount of consecutive failures before a webhook is disabled
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Set the public base URL of the server, used to show the project poster
+    ///
+    /// Without this, the engines will not include the poster in the message.
+    pub fn with_asset_url(mut self, asset_url: impl Into<String>) -> Self {
+        self.asset_url = Some(asset_url.into());
+        self
+    }
+
+    /// Deliver the event to all of the webhooks that listen to the event action
+    ///
+    /// Webhooks that are disabled, do not listen to the event action or
+    /// filtered out the event will be skipped and will not be included in the results.
+    ///
+    /// The `actor` is the user ID that initiated the event, `None` for system/owner.
+    pub async fn dispatch(
+        &self,
+        webhooks: &[Webhook],
+        event: &WebhookEvent,
+        actor: Option<&str>,
+    ) -> Vec<WebhookDelivery> {
+        let action = event.action();
+        let event = Arc::new(event.clone());
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for webhook in webhooks
+            .iter()
+            .filter(|w| w.enabled && w.actions.contains(&action) && event.matches_filter(&w.filter))
+        {
+            let this = self.clone();
+            let event = Arc::clone(&event);
+            let webhook = webhook.clone();
+            let actor = actor.map(|a| a.to_string());
+            tasks.spawn(async move { this.deliver(&webhook, &event, actor.as_deref()).await });
+        }
+
+        let mut results = Vec::with_capacity(tasks.len());
+        while let Some(delivery) = tasks.join_next().await {
+            match delivery {
+                Ok(delivery) => results.push(delivery),
+                Err(err) => tracing::error!("Webhook delivery task failed: {}", err),
+            }
+        }
+
+        results
+    }
+
+    /// Deliver the event to a single webhook
+    ///
+    /// Retryable failures will be retried with exponential backoff,
+    /// every attempt is recorded in [`WebhookDelivery::attempts`].
+    pub async fn deliver(
+        &self,
+        webhook: &Webhook,
+        event: &WebhookEvent,
+        actor: Option<&str>,
+    ) -> WebhookDelivery {
+        let action = event.action();
+        let mut attempts = vec![];
+
+        let mut engine = create_engine(webhook, self.locale);
+        engine.set_actor(actor.map(|a| a.to_string()));
+        engine.set_asset_url(self.asset_url.clone());
+        let mut body = match event.render(&*engine) {
+            Ok(body) => body,
+            Err(err) => {
+                return WebhookDelivery {
+                    id: webhook.id,
+                    action,
+                    attempts,
+                    result: Err(err.into()),
+                };
+            }
+        };
+
+        let mut attempt: u8 = 1;
+        let result = loop {
+            // Streaming body cannot be cloned, in that case we only try once
+            let next_body = body.try_clone();
+            let (log, result, retry_after) =
+                self.send(webhook, &*engine, action, attempt, body).await;
+            attempts.push(log);
+
+            match (result, next_body) {
+                (Err(err), Some(next_body))
+                    if err.is_retryable() && self.retry.can_retry(attempt) =>
+                {
+                    let delay = self.retry.delay(attempt, retry_after);
+                    tracing::debug!(
+                        "Retrying webhook {} delivery in {:?} (attempt {}): {}",
+                        webhook.id,
+                        delay,
+                        attempt,
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                    body = next_body;
+                    attempt += 1;
+                }
+                (result, _) => break result,
+            }
+        };
+
+        WebhookDelivery {
+            id: webhook.id,
+            action,
+            attempts,
+            result,
+        }
+    }
+
+    /// Send a single delivery attempt
+    async fn send(
+        &self,
+        webhook: &Webhook,
+        engine: &(dyn WebhookEnginePayload + Send + Sync),
+        action: WebhookAction,
+        attempt: u8,
+        body: reqwest::Body,
+    ) -> (
+        WebhookDeliveryEvent,
+        Result<reqwest::StatusCode, WebhookError>,
+        Option<Duration>,
+    ) {
+        // Only limit the actual network request
+        let _permit = self
+            .limiter
+            .acquire()
+            .await
+            .expect("Webhook limiter should never be closed");
+
+        // Headers are generated per attempt, since it might be time sensitive
+        let headers = engine.headers(body.as_bytes().unwrap_or_default());
+
+        let start = std::time::Instant::now();
+        let response = self
+            .client
+            .request(engine.method(), engine.url())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .headers(headers)
+            .body(body)
+            .send()
+            .await;
+        let log = WebhookDeliveryEvent::new(
+            webhook.id,
+            webhook.creator,
+            action.to_name(),
+            attempt,
+            start.elapsed(),
+        );
+
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                let log = log.with_error(err.to_string());
+                return (log, Err(WebhookError::Request(err)), None);
+            }
+        };
+
+        let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
+        let log = log.with_status(status.as_u16());
+        let log = match response.text().await {
+            Ok(text) => log.with_body(&text),
+            Err(err) => {
+                tracing::debug!("Failed to read webhook {} response: {}", webhook.id, err);
+                log
+            }
+        };
+
+        if status.is_success() {
+            (log, Ok(status), None)
+        } else {
+            let err = WebhookError::Status(status);
+            (log.with_error(err.to_string()), Err(err), retry_after)
+        }
+    }
+
+    /// Start listening to the project events from the [`MemoryBroker`]
+    ///
+    /// Each event will be processed in its own task, the actual
+    /// delivery is bounded by the dispatcher concurrency.
+    /// Every delivery attempt will be recorded into ClickHouse, and webhooks that
+    /// failed consecutively will be disabled automatically.
+    pub fn listen(
+        self,
+        db: DatabaseShared,
+        clickhouse: SharedSHClickHouse,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::task::spawn(async move {
+            let created =
+                MemoryBroker::<ProjectCreatedEvent>::subscribe().map(BrokerEvent::Created);
+            let updated =
+                MemoryBroker::<ProjectUpdatedEvent>::subscribe().map(BrokerEvent::Updated);
+            let episodes =
+                MemoryBroker::<ProjectEpisodeUpdatedEvent>::subscribe().map(BrokerEvent::Episode);
+
+            let stream = futures_util::stream::select(
+                created,
+                futures_util::stream::select(updated, episodes),
+            );
+            let mut stream = std::pin::pin!(stream);
+
+            while let Some(event) = stream.next().await {
+                let this = self.clone();
+                let db = Arc::clone(&db);
+                let clickhouse = Arc::clone(&clickhouse);
+                tokio::task::spawn(async move {
+                    if let Err(err) = this.handle_event(&db, &clickhouse, event).await {
+                        tracing::error!("Failed to process webhook event: {}", err);
+                    }
+                });
+            }
+        })
+    }
+
+    async fn handle_event(
+        &self,
+        db: &DatabaseShared,
+        clickhouse: &SharedSHClickHouse,
+        event: BrokerEvent,
+    ) -> Result<(), showtimes_db::mongodb::error::Error> {
+        let project_id = match &event {
+            BrokerEvent::Created(ev) => ev.data().id(),
+            BrokerEvent::Updated(ev) => ev.data().id(),
+            BrokerEvent::Episode(ev) => {
+                if ev.data().silent() {
+                    return Ok(());
+                }
+                ev.data().id()
+            }
+        };
+
+        let project_handler = ProjectHandler::new(db);
+        let project = match project_handler
+            .find_by(doc! { "id": project_id.to_string() })
+            .await?
+        {
+            Some(project) => project,
+            None => {
+                tracing::warn!("Project {} not found for webhook delivery", project_id);
+                return Ok(());
+            }
+        };
+
+        let actor = match &event {
+            BrokerEvent::Created(ev) => ev.actor(),
+            BrokerEvent::Updated(ev) => ev.actor(),
+            BrokerEvent::Episode(ev) => ev.actor(),
+        }
+        .map(|a| a.to_string());
+
+        let webhook_events = match event {
+            BrokerEvent::Created(_) => vec![WebhookEvent::ProjectCreate(project.clone())],
+            BrokerEvent::Updated(ev) => map_updated_event(ev.data(), &project),
+            BrokerEvent::Episode(ev) => map_episode_event(ev.data(), &project),
+        };
+
+        if webhook_events.is_empty() {
+            return Ok(());
+        }
+
+        let webhook_handler = WebhookHandler::new(db);
+        let webhooks = webhook_handler
+            .find_all_by(doc! {
+                "creator": project.creator.to_string(),
+                "enabled": { "$ne": false },
+            })
+            .await?;
+
+        if webhooks.is_empty() {
+            return Ok(());
+        }
+
+        for webhook_event in webhook_events {
+            let results = self
+                .dispatch(&webhooks, &webhook_event, actor.as_deref())
+                .await;
+
+            let attempts: Vec<WebhookDeliveryEvent> = results
+                .iter()
+                .flat_map(|delivery| delivery.attempts.iter().cloned())
+                .collect();
+            if !attempts.is_empty() {
+                if let Err(err) = clickhouse.create_webhook_deliveries(attempts).await {
+                    tracing::error!("Failed to record webhook deliveries: {}", err);
+                }
+            }
+
+            for delivery in results {
+                if let Err(err) = &delivery.result {
+                    tracing::warn!(
+                        "Failed to deliver webhook {} for project {}: {}",
+                        delivery.id,
+                        project.id,
+                        err
+                    );
+                }
+
+                if let Err(err) = self
+                    .track_failures(&webhook_handler, clickhouse, &delivery)
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to track failures of webhook {}: {}",
+                        delivery.id,
+                        err
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Track the consecutive failures of the webhook
+    ///
+    /// A successful delivery will reset the counter, while a failed one will
+    /// increment it and disable the webhook when it reach the maximum failures.
+    async fn track_failures(
+        &self,
+        handler: &WebhookHandler,
+        clickhouse: &SharedSHClickHouse,
+        delivery: &WebhookDelivery,
+    ) -> Result<(), showtimes_db::mongodb::error::Error> {
+        let collection = handler.get_collection();
+        let err = match &delivery.result {
+            Ok(_) => {
+                collection
+                    .update_one(
+                        doc! { "id": delivery.id.to_string(), "failures": { "$gt": 0 } },
+                        doc! { "$set": { "failures": 0 } },
+                    )
+                    .await?;
+                return Ok(());
+            }
+            // Payload error is our fault, not the target
+            Err(WebhookError::Payload(_)) => return Ok(()),
+            Err(err) => err,
+        };
+
+        let webhook = collection
+            .find_one_and_update(
+                doc! { "id": delivery.id.to_string() },
+                doc! { "$inc": { "failures": 1 } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?;
+
+        let mut webhook = match webhook {
+            Some(webhook) if webhook.enabled && webhook.failures >= self.max_failures => webhook,
+            _ => return Ok(()),
+        };
+
+        tracing::warn!(
+            "Disabling webhook {} after {} consecutive failures",
+            webhook.id,
+            webhook.failures
+        );
+        collection
+            .update_one(
+                doc! { "id": webhook.id.to_string() },
+                doc! { "$set": { "enabled": false, "updated": DateTime::now() } },
+            )
+            .await?;
+        webhook.enabled = false;
+
+        if let Err(ch_err) = clickhouse
+            .create_event(
+                EventKind::WebhookDisabled,
+                WebhookDisabledEvent::new(&webhook, Some(err.to_string())),
+                None,
+            )
+            .await
+        {
+            tracing::error!("Failed to create webhook disabled event: {}", ch_err);
+        }
+
+        Ok(())
+    }
+}
+
+/// Map the project updated event into the dropped or resumed event
+fn map_updated_event(event: &ProjectUpdatedEvent, project: &Project) -> Vec<WebhookEvent> {
+    match (event.before().status(), event.after().status()) {
+        (Some(before), Some(ProjectStatus::Archived)) if before != ProjectStatus::Archived => {
+            vec![WebhookEvent::ProjectDropped(project.clone())]
+        }
+        (Some(ProjectStatus::Archived), Some(after)) if after != ProjectStatus::Archived => {
+            vec![WebhookEvent::ProjectResumed(project.clone())]
+        }
+        _ => vec![],
+    }
+}
+
+/// Map the episode updated event into progress, release or unrelease event
+fn map_episode_event(event: &ProjectEpisodeUpdatedEvent, project: &Project) -> Vec<WebhookEvent> {
+    let episode = match project.find_episode(event.number()) {
+        Some(episode) => episode,
+        None => return vec![],
+    };
+
+    let mut events = vec![];
+
+    if !event.before().is_empty() && event.before() != event.after() {
+        // Rebuild the previous state by replacing the changed statuses
+        let before_statuses = episode
+            .statuses
+            .iter()
+            .map(|status| {
+                event
+                    .before()
+                    .iter()
+                    .find(|b| b.key() == status.key())
+                    .unwrap_or(status)
+                    .clone()
+            })
+            .collect();
+
+        events.push(WebhookEvent::ProjectProgress {
+            project: project.clone(),
+            before: episode.with_statuses(before_statuses),
+            after: episode.clone(),
+        });
+    }
+
+    match event.finished() {
+        Some(true) => events.push(WebhookEvent::ProjectRelease {
+            project: project.clone(),
+            episodes: vec![episode.clone()],
+        }),
+        Some(false) => events.push(WebhookEvent::ProjectUnreleased {
+            project: project.clone(),
+            episodes: vec![episode.clone()],
+        }),
+        None => {}
+    }
+
+    events
+}
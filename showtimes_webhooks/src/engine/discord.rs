@@ -1,11 +1,20 @@
-#![allow(dead_code, unused_variables)]
-use super::{WebhookEngine, WebhookEnginePayload};
+//! The Discord webhook engine
 
+use super::{
+    WebhookEngine, WebhookEnginePayload,
+    rich::{RichMessage, render_text},
+};
+
+/// The maximum amount of embeds that Discord allow in a single message
+const MAX_EMBEDS: usize = 10;
+
+/// The Discord webhook engine, using the embed as the main message
 pub struct DiscordEngine {
     pub(crate) url: String,
     pub(crate) name: String,
     pub(crate) avatar: Option<String>,
     pub(crate) locale: showtimes_i18n::Language,
+    pub(crate) asset_url: Option<String>,
 }
 
 impl DiscordEngine {
@@ -16,7 +25,61 @@ impl DiscordEngine {
             name: name.into(),
             avatar,
             locale: showtimes_i18n::Language::default(),
+            asset_url: None,
+        }
+    }
+
+    fn embed(&self, message: &RichMessage) -> serde_json::Value {
+        let render = |text: &str| render_text(text, |t| t.to_string(), ("**", "**"));
+
+        let mut embed = serde_json::json!({
+            "title": render(&message.title),
+            "color": message.color,
+        });
+
+        if let Some(description) = &message.description {
+            embed["description"] = serde_json::json!(render(description));
+        }
+
+        if !message.fields.is_empty() {
+            let fields: Vec<serde_json::Value> = message
+                .fields
+                .iter()
+                .map(|field| {
+                    serde_json::json!({
+                        "name": render(&field.name),
+                        "value": render(&field.value),
+                        "inline": false,
+                    })
+                })
+                .collect();
+            embed["fields"] = serde_json::json!(fields);
+        }
+
+        if let Some(thumbnail) = &message.thumbnail {
+            embed["thumbnail"] = serde_json::json!({ "url": thumbnail });
         }
+
+        embed
+    }
+
+    fn payload(
+        &self,
+        messages: &[RichMessage],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let embeds: Vec<serde_json::Value> = messages
+            .iter()
+            .take(MAX_EMBEDS)
+            .map(|message| self.embed(message))
+            .collect();
+
+        let payload = serde_json::json!({
+            "username": self.name,
+            "avatar_url": self.avatar,
+            "embeds": embeds,
+        });
+
+        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
     }
 }
 
@@ -25,9 +88,17 @@ impl WebhookEngine for DiscordEngine {
         reqwest::Method::POST
     }
 
+    fn url(&self) -> &str {
+        &self.url
+    }
+
     fn set_locale(&mut self, locale: showtimes_i18n::Language) {
         self.locale = locale;
     }
+
+    fn set_asset_url(&mut self, asset_url: Option<String>) {
+        self.asset_url = asset_url;
+    }
 }
 
 impl WebhookEnginePayload for DiscordEngine {
@@ -35,78 +106,33 @@ impl WebhookEnginePayload for DiscordEngine {
         &self,
         project: &showtimes_db::m::Project,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        let description = showtimes_i18n::tr(
-            "project-create-desc",
-            Some(self.locale),
-            &[
-                ("kind", project.kind.to_locale().to_string()),
-                ("name", format!("**{}**", project.title)),
-            ],
-        );
-
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-            "embeds": [
-                {
-                    "title": showtimes_i18n::t("project-create", Some(self.locale)),
-                    "description": description,
-                    "color": 0x33FFAD,
-                }
-            ]
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_create(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_dropped(
         &self,
         project: &showtimes_db::m::Project,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        let description = showtimes_i18n::tr(
-            "project-dropped-desc",
-            Some(self.locale),
-            &[("name", format!("**{}**", project.title))],
-        );
-
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-            "embeds": [
-                {
-                    "title": showtimes_i18n::t("project-dropped", Some(self.locale)),
-                    "description": description,
-                    "color": 0xFF3333,
-                }
-            ]
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_dropped(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_resumed(
         &self,
         project: &showtimes_db::m::Project,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        let description = showtimes_i18n::tr(
-            "project-resumed-desc",
-            Some(self.locale),
-            &[("name", format!("**{}**", project.title))],
-        );
-
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-            "embeds": [
-                {
-                    "title": showtimes_i18n::t("project-resumed", Some(self.locale)),
-                    "description": description,
-                    "color": 0x33FFAD,
-                }
-            ]
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_resumed(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_release(
@@ -114,15 +140,7 @@ impl WebhookEnginePayload for DiscordEngine {
         project: &showtimes_db::m::Project,
         episode: &showtimes_db::m::EpisodeProgress,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.project_release_multi(project, std::slice::from_ref(episode))
     }
 
     fn project_release_multi(
@@ -130,15 +148,12 @@ impl WebhookEnginePayload for DiscordEngine {
         project: &showtimes_db::m::Project,
         episodes: &[showtimes_db::m::EpisodeProgress],
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_release(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_unrelease(
@@ -146,15 +161,7 @@ impl WebhookEnginePayload for DiscordEngine {
         project: &showtimes_db::m::Project,
         episode: &showtimes_db::m::EpisodeProgress,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.project_unrelease_multi(project, std::slice::from_ref(episode))
     }
 
     fn project_unrelease_multi(
@@ -162,15 +169,12 @@ impl WebhookEnginePayload for DiscordEngine {
         project: &showtimes_db::m::Project,
         episodes: &[showtimes_db::m::EpisodeProgress],
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_unrelease(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_progress(
@@ -179,15 +183,13 @@ impl WebhookEnginePayload for DiscordEngine {
         before: &showtimes_db::m::EpisodeProgress,
         after: &showtimes_db::m::EpisodeProgress,
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        self.payload(&[RichMessage::project_progress(
+            project,
+            before,
+            after,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
     }
 
     fn project_progress_batch(
@@ -198,14 +200,20 @@ impl WebhookEnginePayload for DiscordEngine {
             showtimes_db::m::EpisodeProgress,
         )],
     ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
-        // TODO: Make adjustment
-
-        // TODO: Full payload
-        let payload = serde_json::json!({
-            "username": self.name,
-            "avatar_url": self.avatar,
-        });
-
-        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+        let messages: Vec<RichMessage> = pairs
+            .iter()
+            .take(MAX_EMBEDS)
+            .map(|(before, after)| {
+                RichMessage::project_progress(
+                    project,
+                    before,
+                    after,
+                    self.locale,
+                    self.asset_url.as_deref(),
+                )
+            })
+            .collect();
+
+        self.payload(&messages)
     }
 }
@@ -0,0 +1,307 @@
+//! The generic JSON webhook engine
+//!
+//! This engine sends a machine-readable and versioned JSON payload,
+//! each payload is signed with HMAC-SHA256 using the webhook secret.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use showtimes_db::m::WebhookAction;
+
+use super::{WebhookEngine, WebhookEnginePayload, WebhookEnginePayloadError, role_name};
+
+/// The current version of the generic payload schema
+///
+/// This will be bumped every time there is a breaking change in the schema.
+pub const GENERIC_SCHEMA_VERSION: u32 = 1;
+
+/// The header containing the HMAC-SHA256 signature of the payload
+pub const SIGNATURE_HEADER: &str = "x-showtimes-signature";
+/// The header containing the UNIX timestamp used when signing the payload
+pub const TIMESTAMP_HEADER: &str = "x-showtimes-timestamp";
+
+/// The generic JSON webhook engine
+pub struct GenericEngine {
+    pub(crate) url: String,
+    pub(crate) secret: Option<String>,
+    pub(crate) actor: Option<String>,
+}
+
+impl GenericEngine {
+    /// Create a new generic engine
+    ///
+    /// When the `secret` is not provided, the payload will not be signed.
+    pub fn new(url: impl Into<String>, secret: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret,
+            actor: None,
+        }
+    }
+
+    /// Sign the payload with the provided timestamp
+    ///
+    /// The signed message is `{timestamp}.{body}`, the result is a lowercase hex string.
+    /// Receiver should compute the same signature and reject old timestamp to prevent replay.
+    pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn render(
+        &self,
+        action: WebhookAction,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+        changes: &[(
+            &showtimes_db::m::EpisodeProgress,
+            &showtimes_db::m::EpisodeProgress,
+        )],
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        let payload = GenericPayload {
+            version: GENERIC_SCHEMA_VERSION,
+            event: action.to_name(),
+            timestamp: jiff::Timestamp::now(),
+            actor: self.actor.as_deref(),
+            project: GenericProject::new(project),
+            episodes: episodes
+                .iter()
+                .map(|episode| GenericEpisode::new(project, episode))
+                .collect(),
+            changes: changes
+                .iter()
+                .map(|(before, after)| GenericChange {
+                    before: GenericEpisode::new(project, before),
+                    after: GenericEpisode::new(project, after),
+                })
+                .collect(),
+        };
+
+        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+    }
+}
+
+impl WebhookEngine for GenericEngine {
+    fn method(&self) -> reqwest::Method {
+        reqwest::Method::POST
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn set_locale(&mut self, _locale: showtimes_i18n::Language) {
+        // The generic payload is not localized
+    }
+
+    fn set_actor(&mut self, actor: Option<String>) {
+        self.actor = actor;
+    }
+
+    fn headers(&self, body: &[u8]) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(secret) = &self.secret {
+            let timestamp = jiff::Timestamp::now().as_second();
+            let signature = Self::sign(secret, timestamp, body);
+
+            headers.insert(
+                TIMESTAMP_HEADER,
+                reqwest::header::HeaderValue::from(timestamp),
+            );
+            if let Ok(value) =
+                reqwest::header::HeaderValue::from_str(&format!("sha256={signature}"))
+            {
+                headers.insert(SIGNATURE_HEADER, value);
+            }
+        }
+
+        headers
+    }
+}
+
+impl WebhookEnginePayload for GenericEngine {
+    fn project_create(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(WebhookAction::ProjectCreate, project, &[], &[])
+    }
+
+    fn project_progress(
+        &self,
+        project: &showtimes_db::m::Project,
+        before: &showtimes_db::m::EpisodeProgress,
+        after: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(
+            WebhookAction::ProjectProgress,
+            project,
+            &[],
+            &[(before, after)],
+        )
+    }
+
+    fn project_progress_batch(
+        &self,
+        project: &showtimes_db::m::Project,
+        pairs: &[(
+            showtimes_db::m::EpisodeProgress,
+            showtimes_db::m::EpisodeProgress,
+        )],
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        let changes: Vec<_> = pairs
+            .iter()
+            .map(|(before, after)| (before, after))
+            .collect();
+        self.render(WebhookAction::ProjectProgress, project, &[], &changes)
+    }
+
+    fn project_release(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.project_release_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_release_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(WebhookAction::ProjectRelease, project, episodes, &[])
+    }
+
+    fn project_unrelease(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.project_unrelease_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_unrelease_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(WebhookAction::ProjectUnreleased, project, episodes, &[])
+    }
+
+    fn project_dropped(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(WebhookAction::ProjectDropped, project, &[], &[])
+    }
+
+    fn project_resumed(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, WebhookEnginePayloadError> {
+        self.render(WebhookAction::ProjectResumed, project, &[], &[])
+    }
+}
+
+/// The root of the generic payload
+#[derive(Serialize)]
+struct GenericPayload<'a> {
+    /// The schema version, see [`GENERIC_SCHEMA_VERSION`]
+    version: u32,
+    /// The webhook action name (e.g. `project-release`)
+    event: &'static str,
+    /// When the payload is generated
+    timestamp: jiff::Timestamp,
+    /// The user ID that initiated the event, `null` for system/owner
+    actor: Option<&'a str>,
+    project: GenericProject<'a>,
+    /// Released or unreleased episodes
+    episodes: Vec<GenericEpisode<'a>>,
+    /// Progress changes of the episodes
+    changes: Vec<GenericChange<'a>>,
+}
+
+#[derive(Serialize)]
+struct GenericProject<'a> {
+    id: String,
+    server: String,
+    title: &'a str,
+    kind: showtimes_db::m::ProjectType,
+    status: showtimes_db::m::ProjectStatus,
+    poster: GenericPoster,
+    total_episodes: usize,
+}
+
+impl<'a> GenericProject<'a> {
+    fn new(project: &'a showtimes_db::m::Project) -> Self {
+        Self {
+            id: project.id.to_string(),
+            server: project.creator.to_string(),
+            title: &project.title,
+            kind: project.kind,
+            status: project.status,
+            poster: GenericPoster {
+                path: project.poster.image.as_url(),
+                color: project.poster.color,
+            },
+            total_episodes: project.progress.len(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GenericPoster {
+    /// The path of the poster, relative to the API host
+    path: String,
+    color: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct GenericEpisode<'a> {
+    number: u64,
+    finished: bool,
+    aired: Option<jiff::Timestamp>,
+    delay_reason: Option<&'a str>,
+    statuses: Vec<GenericStatus<'a>>,
+}
+
+impl<'a> GenericEpisode<'a> {
+    fn new(
+        project: &showtimes_db::m::Project,
+        episode: &'a showtimes_db::m::EpisodeProgress,
+    ) -> Self {
+        Self {
+            number: episode.number,
+            finished: episode.finished,
+            aired: episode.aired,
+            delay_reason: episode.delay_reason.as_deref(),
+            statuses: episode
+                .statuses
+                .iter()
+                .map(|status| GenericStatus {
+                    key: status.key(),
+                    name: role_name(project, status.key()),
+                    finished: status.finished(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GenericStatus<'a> {
+    key: &'a str,
+    name: String,
+    finished: bool,
+}
+
+#[derive(Serialize)]
+struct GenericChange<'a> {
+    before: GenericEpisode<'a>,
+    after: GenericEpisode<'a>,
+}
@@ -0,0 +1,247 @@
+//! The Matrix webhook engine
+//!
+//! This targets the generic webhook of [matrix-hookshot](https://github.com/matrix-org/matrix-hookshot)
+//! or any bridge that accept the same `text` and `html` body.
+//!
+//! Matrix only render `mxc://` images, so the poster is added as a link instead.
+
+use super::{
+    WebhookEngine, WebhookEnginePayload,
+    rich::{RichMessage, plain_text, render_text},
+};
+
+/// The maximum amount of messages we merge in a single payload
+const MAX_MESSAGES: usize = 10;
+
+/// The Matrix webhook engine, using the HTML body as the main message
+pub struct MatrixEngine {
+    pub(crate) url: String,
+    pub(crate) name: String,
+    pub(crate) locale: showtimes_i18n::Language,
+    pub(crate) asset_url: Option<String>,
+}
+
+impl MatrixEngine {
+    /// Create a new Matrix engine
+    pub fn new(url: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            name: name.into(),
+            locale: showtimes_i18n::Language::default(),
+            asset_url: None,
+        }
+    }
+
+    fn html(&self, message: &RichMessage) -> String {
+        let render = |text: &str| {
+            render_text(text, escape_html, ("<strong>", "</strong>")).replace('\n', "<br>")
+        };
+
+        let mut html = format!(
+            "<h4><font data-mx-color=\"{}\">{}</font></h4>",
+            message.hex_color(),
+            render(&message.title)
+        );
+
+        if let Some(description) = &message.description {
+            html.push_str(&format!("<p>{}</p>", render(description)));
+        }
+
+        for field in &message.fields {
+            html.push_str(&format!(
+                "<p><strong>{}</strong><br>{}</p>",
+                render(&field.name),
+                render(&field.value)
+            ));
+        }
+
+        if let Some(thumbnail) = &message.thumbnail {
+            html.push_str(&format!(
+                "<p><a href=\"{}\">Poster</a></p>",
+                escape_html(thumbnail)
+            ));
+        }
+
+        html
+    }
+
+    fn text(&self, message: &RichMessage) -> String {
+        let mut lines = vec![plain_text(&message.title)];
+
+        if let Some(description) = &message.description {
+            lines.push(plain_text(description));
+        }
+
+        for field in &message.fields {
+            lines.push(format!(
+                "{}\n{}",
+                plain_text(&field.name),
+                plain_text(&field.value)
+            ));
+        }
+
+        lines.join("\n\n")
+    }
+
+    fn payload(
+        &self,
+        messages: &[RichMessage],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let messages = &messages[..messages.len().min(MAX_MESSAGES)];
+
+        let html: Vec<String> = messages.iter().map(|message| self.html(message)).collect();
+        let text: Vec<String> = messages.iter().map(|message| self.text(message)).collect();
+
+        let payload = serde_json::json!({
+            "username": self.name,
+            "text": text.join("\n\n"),
+            "html": html.join("<hr>"),
+        });
+
+        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+    }
+}
+
+/// Escape the text for HTML body
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+impl WebhookEngine for MatrixEngine {
+    fn method(&self) -> reqwest::Method {
+        reqwest::Method::POST
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn set_locale(&mut self, locale: showtimes_i18n::Language) {
+        self.locale = locale;
+    }
+
+    fn set_asset_url(&mut self, asset_url: Option<String>) {
+        self.asset_url = asset_url;
+    }
+}
+
+impl WebhookEnginePayload for MatrixEngine {
+    fn project_create(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_create(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_dropped(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_dropped(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_resumed(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_resumed(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_release(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_release_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_release_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_release(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_unrelease(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_unrelease_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_unrelease_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_unrelease(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress(
+        &self,
+        project: &showtimes_db::m::Project,
+        before: &showtimes_db::m::EpisodeProgress,
+        after: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_progress(
+            project,
+            before,
+            after,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress_batch(
+        &self,
+        project: &showtimes_db::m::Project,
+        pairs: &[(
+            showtimes_db::m::EpisodeProgress,
+            showtimes_db::m::EpisodeProgress,
+        )],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let messages: Vec<RichMessage> = pairs
+            .iter()
+            .take(MAX_MESSAGES)
+            .map(|(before, after)| {
+                RichMessage::project_progress(
+                    project,
+                    before,
+                    after,
+                    self.locale,
+                    self.asset_url.as_deref(),
+                )
+            })
+            .collect();
+
+        self.payload(&messages)
+    }
+}
@@ -1,19 +1,42 @@
-#![allow(dead_code)]
-pub(crate) mod discord;
+//! The engine or payload generator for each webhook target
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum AvailableEngine {
-    Discord,
-}
+pub mod discord;
+pub mod generic;
+pub mod matrix;
+pub(crate) mod rich;
+pub mod slack;
+pub mod telegram;
+
+pub use discord::DiscordEngine;
+pub use generic::GenericEngine;
+pub use matrix::MatrixEngine;
+pub use slack::SlackEngine;
+pub use telegram::TelegramEngine;
 
 /// Super trait for all webhook engines
 pub trait WebhookEngine {
     /// The request method for the webhook
     fn method(&self) -> reqwest::Method;
+    /// The target URL of the webhook
+    fn url(&self) -> &str;
     /// Store the prefered locale
     ///
     /// This will force each engine to use the same locale
     fn set_locale(&mut self, locale: showtimes_i18n::Language);
+    /// Store the user ID that initiated the event
+    ///
+    /// Only used by engine that include the actor in the payload
+    fn set_actor(&mut self, _actor: Option<String>) {}
+    /// Store the public base URL used to resolve the project poster
+    ///
+    /// Only used by engine that can show the poster as a thumbnail
+    fn set_asset_url(&mut self, _asset_url: Option<String>) {}
+    /// Extra headers for the request, called on each delivery attempt
+    ///
+    /// The `body` is the rendered payload, this can be used to sign the request.
+    fn headers(&self, _body: &[u8]) -> reqwest::header::HeaderMap {
+        reqwest::header::HeaderMap::new()
+    }
 }
 
 /// The list of payload generator for an engine
@@ -99,3 +122,177 @@ impl From<serde_json::Error> for WebhookEnginePayloadError {
         WebhookEnginePayloadError::InvalidPayload(err)
     }
 }
+
+/// Create the engine for the provided [`showtimes_db::m::Webhook`]
+///
+/// The locale will be taken from the webhook, if it's not set or invalid
+/// the provided `fallback` locale will be used instead.
+pub fn create_engine(
+    webhook: &showtimes_db::m::Webhook,
+    fallback: showtimes_i18n::Language,
+) -> Box<dyn WebhookEnginePayload + Send + Sync> {
+    let locale = webhook
+        .locale
+        .as_deref()
+        .and_then(showtimes_i18n::Language::from_code)
+        .unwrap_or(fallback);
+
+    let mut engine: Box<dyn WebhookEnginePayload + Send + Sync> = match webhook.kind {
+        showtimes_db::m::WebhookTarget::Discord => Box::new(DiscordEngine::new(
+            &webhook.url,
+            &webhook.name,
+            webhook.avatar.clone(),
+        )),
+        showtimes_db::m::WebhookTarget::Slack => Box::new(SlackEngine::new(
+            &webhook.url,
+            &webhook.name,
+            webhook.avatar.clone(),
+        )),
+        showtimes_db::m::WebhookTarget::Matrix => {
+            Box::new(MatrixEngine::new(&webhook.url, &webhook.name))
+        }
+        showtimes_db::m::WebhookTarget::Telegram => Box::new(TelegramEngine::new(&webhook.url)),
+        showtimes_db::m::WebhookTarget::Generic => {
+            Box::new(GenericEngine::new(&webhook.url, webhook.secret.clone()))
+        }
+    };
+    engine.set_locale(locale);
+
+    engine
+}
+
+/// Get the `mode` used for the `project-progress` translation
+pub(crate) fn progress_mode(project: &showtimes_db::m::Project) -> &'static str {
+    match project.kind {
+        showtimes_db::m::ProjectType::Series => "series",
+        showtimes_db::m::ProjectType::Movies => "movie",
+        showtimes_db::m::ProjectType::OVAs => {
+            if project.progress.len() > 1 {
+                "ova-numbered"
+            } else {
+                "ova"
+            }
+        }
+        showtimes_db::m::ProjectType::Books => "books",
+        showtimes_db::m::ProjectType::Manga => "manga",
+        showtimes_db::m::ProjectType::LightNovel => "light-novel",
+        showtimes_db::m::ProjectType::Games => "games",
+        showtimes_db::m::ProjectType::VisualNovel => "vn",
+        showtimes_db::m::ProjectType::Unknown => "other",
+    }
+}
+
+/// Get the `kind` used for the `project-release-desc` translation
+pub(crate) fn release_kind(project: &showtimes_db::m::Project) -> &'static str {
+    match project.kind {
+        showtimes_db::m::ProjectType::Movies
+        | showtimes_db::m::ProjectType::Games
+        | showtimes_db::m::ProjectType::VisualNovel => "single",
+        _ => {
+            if project.progress.len() > 1 {
+                "episodic"
+            } else {
+                "single"
+            }
+        }
+    }
+}
+
+/// Format a list of episodes into a compact translated text.
+///
+/// Consecutive episodes will be collapsed into a single range.
+pub(crate) fn format_episodes(
+    episodes: &[showtimes_db::m::EpisodeProgress],
+    locale: showtimes_i18n::Language,
+) -> String {
+    let mut numbers: Vec<u64> = episodes.iter().map(|ep| ep.number).collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+
+    let mut ranges: Vec<(u64, u64)> = vec![];
+    for number in numbers {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == number => *end = number,
+            _ => ranges.push((number, number)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                showtimes_i18n::tr(
+                    "project-episode-single",
+                    Some(locale),
+                    &[("episode", start.to_string())],
+                )
+            } else {
+                showtimes_i18n::tr(
+                    "project-episode-range",
+                    Some(locale),
+                    &[
+                        ("episode-start", start.to_string()),
+                        ("episode-end", end.to_string()),
+                    ],
+                )
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Get the role name from the project, fallback to the key if not found
+pub(crate) fn role_name(project: &showtimes_db::m::Project, key: &str) -> String {
+    project
+        .roles
+        .iter()
+        .find(|role| role.key() == key)
+        .map(|role| role.name().to_string())
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Create the translated status lines between two episode progress.
+///
+/// Newly finished roles will be marked as done, reverted roles will be marked as reverted
+/// and the rest of the unfinished roles will be marked as ongoing.
+pub(crate) fn progress_statuses(
+    project: &showtimes_db::m::Project,
+    before: &showtimes_db::m::EpisodeProgress,
+    after: &showtimes_db::m::EpisodeProgress,
+    locale: showtimes_i18n::Language,
+) -> Vec<String> {
+    let mut statuses: Vec<&showtimes_db::m::RoleStatus> = after.statuses.iter().collect();
+    statuses.sort_by_key(|status| {
+        project
+            .roles
+            .iter()
+            .find(|role| role.key() == status.key())
+            .map(|role| role.order())
+            .unwrap_or(i32::MAX)
+    });
+
+    statuses
+        .into_iter()
+        .filter_map(|status| {
+            let was_finished = before
+                .statuses
+                .iter()
+                .find(|b| b.key() == status.key())
+                .map(|b| b.finished())
+                .unwrap_or(false);
+
+            let msg_id = match (was_finished, status.finished()) {
+                (false, true) => "project-progress-done",
+                (true, false) => "project-progress-revert",
+                (false, false) => "project-progress-ongoing",
+                (true, true) => return None,
+            };
+
+            Some(showtimes_i18n::tr(
+                msg_id,
+                Some(locale),
+                &[("role", role_name(project, status.key()))],
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,250 @@
+//! A platform agnostic rich message, modelled after the Discord embed
+//!
+//! Each chat engine will map this message into their native rich format.
+
+use super::{format_episodes, progress_mode, progress_statuses, release_kind};
+
+/// The marker for the start of a bold text
+const BOLD_START: char = '\u{E000}';
+/// The marker for the end of a bold text
+const BOLD_END: char = '\u{E001}';
+
+/// The color used for positive actions
+const COLOR_POSITIVE: u32 = 0x33FFAD;
+/// The color used for negative actions
+const COLOR_NEGATIVE: u32 = 0xFF3333;
+
+/// A single field of the rich message
+pub(crate) struct RichField {
+    /// The name of the field
+    pub(crate) name: String,
+    /// The value of the field, might be multiple lines
+    pub(crate) value: String,
+}
+
+/// A platform agnostic rich message
+///
+/// Bold text is marked with private-use characters so each engine
+/// can escape the text first before applying their own bold syntax.
+pub(crate) struct RichMessage {
+    /// The title of the message
+    pub(crate) title: String,
+    /// The description of the message
+    pub(crate) description: Option<String>,
+    /// The extra fields of the message
+    pub(crate) fields: Vec<RichField>,
+    /// The accent color of the message
+    pub(crate) color: u32,
+    /// The absolute URL of the project poster
+    pub(crate) thumbnail: Option<String>,
+}
+
+impl RichMessage {
+    fn new(title: String, color: u32) -> Self {
+        Self {
+            title,
+            description: None,
+            fields: vec![],
+            color,
+            thumbnail: None,
+        }
+    }
+
+    fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    fn with_field(mut self, name: String, value: String) -> Self {
+        self.fields.push(RichField { name, value });
+        self
+    }
+
+    fn with_thumbnail(
+        mut self,
+        project: &showtimes_db::m::Project,
+        asset_url: Option<&str>,
+    ) -> Self {
+        self.thumbnail = asset_url.map(|base| {
+            format!(
+                "{}{}",
+                base.trim_end_matches('/'),
+                project.poster.image.as_url()
+            )
+        });
+        self
+    }
+
+    /// The project creation message
+    pub(crate) fn project_create(
+        project: &showtimes_db::m::Project,
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let description = showtimes_i18n::tr(
+            "project-create-desc",
+            Some(locale),
+            &[
+                ("kind", project.kind.to_locale().to_string()),
+                ("name", bold(&project.title)),
+            ],
+        );
+
+        Self::new(
+            showtimes_i18n::t("project-create", Some(locale)),
+            COLOR_POSITIVE,
+        )
+        .with_description(description)
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The project dropped message
+    pub(crate) fn project_dropped(
+        project: &showtimes_db::m::Project,
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let description = showtimes_i18n::tr(
+            "project-dropped-desc",
+            Some(locale),
+            &[("name", bold(&project.title))],
+        );
+
+        Self::new(
+            showtimes_i18n::t("project-dropped", Some(locale)),
+            COLOR_NEGATIVE,
+        )
+        .with_description(description)
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The project resumed message
+    pub(crate) fn project_resumed(
+        project: &showtimes_db::m::Project,
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let description = showtimes_i18n::tr(
+            "project-resumed-desc",
+            Some(locale),
+            &[("name", bold(&project.title))],
+        );
+
+        Self::new(
+            showtimes_i18n::t("project-resumed", Some(locale)),
+            COLOR_POSITIVE,
+        )
+        .with_description(description)
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The project release message
+    pub(crate) fn project_release(
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let description = showtimes_i18n::tr(
+            "project-release-desc",
+            Some(locale),
+            &[
+                ("kind", release_kind(project).to_string()),
+                ("name", bold(&project.title)),
+                ("episodes", format_episodes(episodes, locale)),
+            ],
+        );
+
+        Self::new(
+            showtimes_i18n::t("project-release-desc-header", Some(locale)),
+            COLOR_POSITIVE,
+        )
+        .with_description(description)
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The project un-release message
+    pub(crate) fn project_unrelease(
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let description = showtimes_i18n::tr(
+            "project-release-revert-desc",
+            Some(locale),
+            &[
+                ("kind", release_kind(project).to_string()),
+                ("name", bold(&project.title)),
+                ("episodes", format_episodes(episodes, locale)),
+            ],
+        );
+
+        Self::new(
+            showtimes_i18n::t("project-release-revert-desc-header", Some(locale)),
+            COLOR_NEGATIVE,
+        )
+        .with_description(description)
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The project progress message
+    pub(crate) fn project_progress(
+        project: &showtimes_db::m::Project,
+        before: &showtimes_db::m::EpisodeProgress,
+        after: &showtimes_db::m::EpisodeProgress,
+        locale: showtimes_i18n::Language,
+        asset_url: Option<&str>,
+    ) -> Self {
+        let title = showtimes_i18n::tr(
+            "project-progress",
+            Some(locale),
+            &[
+                ("mode", progress_mode(project).to_string()),
+                ("name", project.title.clone()),
+                ("episode", after.number.to_string()),
+            ],
+        );
+
+        let statuses = progress_statuses(project, before, after, locale);
+
+        Self::new(
+            title,
+            project
+                .poster
+                .color
+                .unwrap_or(showtimes_db::m::Poster::DEFAULT_COLOR),
+        )
+        .with_field(
+            showtimes_i18n::t("project-progress-desc", Some(locale)),
+            statuses.join("\n"),
+        )
+        .with_thumbnail(project, asset_url)
+    }
+
+    /// The color in `#RRGGBB` format
+    pub(crate) fn hex_color(&self) -> String {
+        format!("#{:06X}", self.color & 0xFFFFFF)
+    }
+}
+
+/// Mark the text as bold
+fn bold(text: &str) -> String {
+    format!("{BOLD_START}{text}{BOLD_END}")
+}
+
+/// Escape the text and then replace the bold markers with the provided syntax
+pub(crate) fn render_text(
+    text: &str,
+    escape: impl Fn(&str) -> String,
+    bold: (&str, &str),
+) -> String {
+    escape(text)
+        .replace(BOLD_START, bold.0)
+        .replace(BOLD_END, bold.1)
+}
+
+/// Remove all formatting markers from the text
+pub(crate) fn plain_text(text: &str) -> String {
+    text.replace([BOLD_START, BOLD_END], "")
+}
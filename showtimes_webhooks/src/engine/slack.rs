@@ -0,0 +1,255 @@
+//! The Slack webhook engine
+//!
+//! This use the Slack incoming webhook with Block Kit layout,
+//! the color is applied using the legacy attachment.
+
+use super::{
+    WebhookEngine, WebhookEnginePayload,
+    rich::{RichMessage, plain_text, render_text},
+};
+
+/// The maximum amount of attachments we send in a single message
+const MAX_ATTACHMENTS: usize = 10;
+
+/// The Slack webhook engine, using the Block Kit as the main message
+pub struct SlackEngine {
+    pub(crate) url: String,
+    pub(crate) name: String,
+    pub(crate) avatar: Option<String>,
+    pub(crate) locale: showtimes_i18n::Language,
+    pub(crate) asset_url: Option<String>,
+}
+
+impl SlackEngine {
+    /// Create a new Slack engine
+    pub fn new(url: impl Into<String>, name: impl Into<String>, avatar: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            name: name.into(),
+            avatar,
+            locale: showtimes_i18n::Language::default(),
+            asset_url: None,
+        }
+    }
+
+    fn attachment(&self, message: &RichMessage) -> serde_json::Value {
+        let mut blocks = vec![serde_json::json!({
+            "type": "header",
+            "text": {
+                "type": "plain_text",
+                "text": plain_text(&message.title),
+            }
+        })];
+
+        if let Some(description) = &message.description {
+            let mut section = serde_json::json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": render_text(description, escape_mrkdwn, ("*", "*")),
+                }
+            });
+            if let Some(thumbnail) = &message.thumbnail {
+                section["accessory"] = serde_json::json!({
+                    "type": "image",
+                    "image_url": thumbnail,
+                    "alt_text": plain_text(&message.title),
+                });
+            }
+            blocks.push(section);
+        } else if let Some(thumbnail) = &message.thumbnail {
+            blocks.push(serde_json::json!({
+                "type": "image",
+                "image_url": thumbnail,
+                "alt_text": plain_text(&message.title),
+            }));
+        }
+
+        for field in &message.fields {
+            blocks.push(serde_json::json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!(
+                        "*{}*\n{}",
+                        render_text(&field.name, escape_mrkdwn, ("", "")),
+                        render_text(&field.value, escape_mrkdwn, ("*", "*")),
+                    ),
+                }
+            }));
+        }
+
+        serde_json::json!({
+            "color": message.hex_color(),
+            "blocks": blocks,
+        })
+    }
+
+    fn payload(
+        &self,
+        messages: &[RichMessage],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let attachments: Vec<serde_json::Value> = messages
+            .iter()
+            .take(MAX_ATTACHMENTS)
+            .map(|message| self.attachment(message))
+            .collect();
+
+        // The text is used as the notification fallback
+        let text = messages
+            .first()
+            .map(|message| plain_text(&message.title))
+            .unwrap_or_default();
+
+        let payload = serde_json::json!({
+            "username": self.name,
+            "icon_url": self.avatar,
+            "text": text,
+            "attachments": attachments,
+        });
+
+        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+    }
+}
+
+/// Escape the control characters of Slack `mrkdwn`
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl WebhookEngine for SlackEngine {
+    fn method(&self) -> reqwest::Method {
+        reqwest::Method::POST
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn set_locale(&mut self, locale: showtimes_i18n::Language) {
+        self.locale = locale;
+    }
+
+    fn set_asset_url(&mut self, asset_url: Option<String>) {
+        self.asset_url = asset_url;
+    }
+}
+
+impl WebhookEnginePayload for SlackEngine {
+    fn project_create(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_create(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_dropped(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_dropped(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_resumed(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_resumed(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_release(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_release_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_release_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_release(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_unrelease(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_unrelease_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_unrelease_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_unrelease(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress(
+        &self,
+        project: &showtimes_db::m::Project,
+        before: &showtimes_db::m::EpisodeProgress,
+        after: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_progress(
+            project,
+            before,
+            after,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress_batch(
+        &self,
+        project: &showtimes_db::m::Project,
+        pairs: &[(
+            showtimes_db::m::EpisodeProgress,
+            showtimes_db::m::EpisodeProgress,
+        )],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let messages: Vec<RichMessage> = pairs
+            .iter()
+            .take(MAX_ATTACHMENTS)
+            .map(|(before, after)| {
+                RichMessage::project_progress(
+                    project,
+                    before,
+                    after,
+                    self.locale,
+                    self.asset_url.as_deref(),
+                )
+            })
+            .collect();
+
+        self.payload(&messages)
+    }
+}
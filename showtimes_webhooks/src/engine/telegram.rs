@@ -0,0 +1,271 @@
+//! The Telegram webhook engine
+//!
+//! This use the Bot API `sendMessage` method, the webhook URL should be in the form of
+//! `https://api.telegram.org/bot<token>/sendMessage?chat_id=<chat_id>`.
+//!
+//! Telegram does not support colored message, the poster is shown as the link preview.
+
+use super::{
+    WebhookEngine, WebhookEnginePayload,
+    rich::{RichMessage, render_text},
+};
+
+/// The maximum amount of messages we merge in a single payload
+const MAX_MESSAGES: usize = 10;
+
+/// The Telegram webhook engine, using the MarkdownV2 as the main message
+pub struct TelegramEngine {
+    pub(crate) url: String,
+    pub(crate) chat_id: Option<String>,
+    pub(crate) thread_id: Option<i64>,
+    pub(crate) locale: showtimes_i18n::Language,
+    pub(crate) asset_url: Option<String>,
+}
+
+impl TelegramEngine {
+    /// Create a new Telegram engine
+    ///
+    /// The `chat_id` and `message_thread_id` will be taken from the URL query
+    pub fn new(url: impl Into<String>) -> Self {
+        let url: String = url.into();
+        let (chat_id, thread_id) = match reqwest::Url::parse(&url) {
+            Ok(parsed) => {
+                let find = |key: &str| {
+                    parsed
+                        .query_pairs()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, v)| v.to_string())
+                };
+                let thread_id = find("message_thread_id").and_then(|id| id.parse().ok());
+                (find("chat_id"), thread_id)
+            }
+            Err(_) => (None, None),
+        };
+
+        Self {
+            url,
+            chat_id,
+            thread_id,
+            locale: showtimes_i18n::Language::default(),
+            asset_url: None,
+        }
+    }
+
+    fn markdown(&self, message: &RichMessage) -> String {
+        let render = |text: &str| render_text(text, escape_markdown_v2, ("*", "*"));
+
+        let mut lines = vec![format!("*{}*", render(&message.title))];
+
+        if let Some(description) = &message.description {
+            lines.push(render(description));
+        }
+
+        for field in &message.fields {
+            lines.push(format!(
+                "*{}*\n{}",
+                render(&field.name),
+                render(&field.value)
+            ));
+        }
+
+        lines.join("\n\n")
+    }
+
+    fn payload(
+        &self,
+        messages: &[RichMessage],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let text: Vec<String> = messages
+            .iter()
+            .take(MAX_MESSAGES)
+            .map(|message| self.markdown(message))
+            .collect();
+
+        let preview = match messages.iter().find_map(|m| m.thumbnail.as_deref()) {
+            Some(thumbnail) => serde_json::json!({
+                "url": thumbnail,
+                "prefer_small_media": true,
+            }),
+            None => serde_json::json!({ "is_disabled": true }),
+        };
+
+        let mut payload = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text.join("\n\n"),
+            "parse_mode": "MarkdownV2",
+            "link_preview_options": preview,
+        });
+        if let Some(thread_id) = &self.thread_id {
+            payload["message_thread_id"] = serde_json::json!(thread_id);
+        }
+
+        Ok(reqwest::Body::from(serde_json::to_string(&payload)?))
+    }
+}
+
+/// Escape the reserved characters of Telegram `MarkdownV2`
+fn escape_markdown_v2(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '_' | '*'
+                | '['
+                | ']'
+                | '('
+                | ')'
+                | '~'
+                | '`'
+                | '>'
+                | '#'
+                | '+'
+                | '-'
+                | '='
+                | '|'
+                | '{'
+                | '}'
+                | '.'
+                | '!'
+                | '\\'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl WebhookEngine for TelegramEngine {
+    fn method(&self) -> reqwest::Method {
+        reqwest::Method::POST
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn set_locale(&mut self, locale: showtimes_i18n::Language) {
+        self.locale = locale;
+    }
+
+    fn set_asset_url(&mut self, asset_url: Option<String>) {
+        self.asset_url = asset_url;
+    }
+}
+
+impl WebhookEnginePayload for TelegramEngine {
+    fn project_create(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_create(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_dropped(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_dropped(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_resumed(
+        &self,
+        project: &showtimes_db::m::Project,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_resumed(
+            project,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_release(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_release_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_release_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_release(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_unrelease(
+        &self,
+        project: &showtimes_db::m::Project,
+        episode: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.project_unrelease_multi(project, std::slice::from_ref(episode))
+    }
+
+    fn project_unrelease_multi(
+        &self,
+        project: &showtimes_db::m::Project,
+        episodes: &[showtimes_db::m::EpisodeProgress],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_unrelease(
+            project,
+            episodes,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress(
+        &self,
+        project: &showtimes_db::m::Project,
+        before: &showtimes_db::m::EpisodeProgress,
+        after: &showtimes_db::m::EpisodeProgress,
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        self.payload(&[RichMessage::project_progress(
+            project,
+            before,
+            after,
+            self.locale,
+            self.asset_url.as_deref(),
+        )])
+    }
+
+    fn project_progress_batch(
+        &self,
+        project: &showtimes_db::m::Project,
+        pairs: &[(
+            showtimes_db::m::EpisodeProgress,
+            showtimes_db::m::EpisodeProgress,
+        )],
+    ) -> Result<reqwest::Body, super::WebhookEnginePayloadError> {
+        let messages: Vec<RichMessage> = pairs
+            .iter()
+            .take(MAX_MESSAGES)
+            .map(|(before, after)| {
+                RichMessage::project_progress(
+                    project,
+                    before,
+                    after,
+                    self.locale,
+                    self.asset_url.as_deref(),
+                )
+            })
+            .collect();
+
+        self.payload(&messages)
+    }
+}
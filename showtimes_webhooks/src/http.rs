@@ -0,0 +1,93 @@
+//! The shared HTTP client and retry policy for the dispatchers
+
+use std::time::Duration;
+
+/// The request timeout for a single delivery
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The upper limit of the retries for a single delivery
+const MAX_RETRIES_LIMIT: u8 = 10;
+
+/// The default base delay for the exponential backoff
+const DEFAULT_RETRY_BASE: Duration = Duration::from_secs(1);
+
+/// The maximum delay between each retry, this also caps the `Retry-After` header
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Create the HTTP client used for the deliveries
+pub(crate) fn create_client() -> Result<reqwest::Client, reqwest::Error> {
+    let ua = format!(
+        "showtimes-webhooks-rs/{} (+https://github.com/naoTimesdev/showtimes-rs)",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    reqwest::ClientBuilder::new()
+        .user_agent(ua)
+        .timeout(REQUEST_TIMEOUT)
+        .http2_adaptive_window(true)
+        .use_rustls_tls()
+        .build()
+}
+
+/// The retry policy of a single delivery
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    max_retries: u8,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a new retry policy, the retries is capped to 10
+    pub(crate) fn new(max_retries: u8, base_delay: Duration) -> Self {
+        Self {
+            max_retries: max_retries.min(MAX_RETRIES_LIMIT),
+            base_delay,
+        }
+    }
+
+    /// Create a new retry policy with the default base delay
+    pub(crate) fn with_max_retries(max_retries: u8) -> Self {
+        Self::new(max_retries, DEFAULT_RETRY_BASE)
+    }
+
+    /// Check if the provided attempt can still be retried
+    pub(crate) fn can_retry(&self, attempt: u8) -> bool {
+        attempt <= self.max_retries
+    }
+
+    /// The delay before retrying the provided attempt
+    ///
+    /// The `Retry-After` delay is preferred, otherwise the exponential backoff is used.
+    pub(crate) fn delay(&self, attempt: u8, retry_after: Option<Duration>) -> Duration {
+        retry_after.unwrap_or_else(|| self.backoff(attempt))
+    }
+
+    /// The exponential backoff delay for the provided attempt
+    fn backoff(&self, attempt: u8) -> Duration {
+        let factor = 1u32 << u32::from(attempt.saturating_sub(1)).min(16);
+        self.base_delay.saturating_mul(factor).min(MAX_RETRY_DELAY)
+    }
+}
+
+/// Parse the `Retry-After` header, either in seconds or HTTP date
+pub(crate) fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    let delay = match value.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+            Duration::from_secs_f64(seconds.min(MAX_RETRY_DELAY.as_secs_f64()))
+        }
+        Ok(_) => return None,
+        Err(_) => {
+            let date = jiff::fmt::rfc2822::parse(value).ok()?;
+            let remaining = date.timestamp().duration_since(jiff::Timestamp::now());
+            Duration::try_from(remaining).unwrap_or(Duration::ZERO)
+        }
+    };
+
+    Some(delay.min(MAX_RETRY_DELAY))
+}
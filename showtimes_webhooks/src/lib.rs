@@ -1 +1,10 @@
-mod engine;
+#![warn(missing_docs, clippy::empty_docs, rustdoc::broken_intra_doc_links)]
+#![doc = include_str!("../README.md")]
+
+mod dispatcher;
+pub mod engine;
+mod http;
+mod rss;
+
+pub use dispatcher::*;
+pub use rss::*;
@@ -0,0 +1,443 @@
+//! The RSS delivery, posting new feed entries into the Discord channel webhooks
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ahash::{HashMap, HashMapExt};
+use showtimes_db::m::{RSSFeed, RSSFeedDisplay, RSSFeedEmbedDisplay, RSSFeedWebhook};
+use showtimes_rss::{FeedEntryCloned, FeedValue, template::TemplateError};
+use tokio::sync::Mutex;
+
+use crate::http::{RetryPolicy, create_client, parse_retry_after};
+
+/// The default amount of retries for a single RSS delivery
+pub const DEFAULT_RSS_MAX_RETRIES: u8 = 3;
+
+/// The default minimum interval between each message to the same destination
+///
+/// Discord allows around 30 messages per minute for each channel.
+pub const DEFAULT_RSS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Discord message content limit
+const MAX_CONTENT_LENGTH: usize = 2000;
+/// Discord embed title and author name limit
+const MAX_EMBED_TITLE_LENGTH: usize = 256;
+/// Discord embed description limit
+const MAX_EMBED_DESCRIPTION_LENGTH: usize = 4096;
+/// Discord embed footer limit
+const MAX_EMBED_FOOTER_LENGTH: usize = 2048;
+
+/// An error that can happen when delivering a RSS entry
+#[derive(Debug)]
+pub enum RSSDeliveryError {
+    /// Failed to render the display template
+    Template {
+        /// The display part that failed to render
+        on: &'static str,
+        /// The actual error
+        error: TemplateError,
+    },
+    /// The rendered message is empty, Discord will reject it
+    Empty,
+    /// Failed to serialize the payload
+    Payload(serde_json::Error),
+    /// Failed to send the request
+    Request(reqwest::Error),
+    /// The target returned a non-success status code
+    Status(reqwest::StatusCode),
+}
+
+impl std::fmt::Display for RSSDeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RSSDeliveryError::Template { on, error } => {
+                write!(f, "Failed to render {on}: {error}")
+            }
+            RSSDeliveryError::Empty => write!(f, "Rendered message is empty"),
+            RSSDeliveryError::Payload(err) => write!(f, "Payload error: {err}"),
+            RSSDeliveryError::Request(err) => write!(f, "Request error: {err}"),
+            RSSDeliveryError::Status(status) => write!(f, "Unexpected status code: {status}"),
+        }
+    }
+}
+
+impl std::error::Error for RSSDeliveryError {}
+
+impl RSSDeliveryError {
+    /// Check if the delivery can be retried
+    ///
+    /// Only server errors, rate limits, timeouts and connection errors are retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RSSDeliveryError::Request(err) => err.is_timeout() || err.is_connect(),
+            RSSDeliveryError::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RSSDeliveryError {
+    fn from(err: serde_json::Error) -> Self {
+        RSSDeliveryError::Payload(err)
+    }
+}
+
+impl From<reqwest::Error> for RSSDeliveryError {
+    fn from(err: reqwest::Error) -> Self {
+        RSSDeliveryError::Request(err)
+    }
+}
+
+/// The result of delivering a single RSS entry into a channel
+#[derive(Debug)]
+pub struct RSSDelivery {
+    /// The Discord channel ID
+    pub channel: String,
+    /// The hash key of the delivered entry
+    pub hash: String,
+    /// The delivery result, the status code if success
+    pub result: Result<reqwest::StatusCode, RSSDeliveryError>,
+}
+
+/// Truncate the text into the maximum amount of characters
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+fn render_single(
+    on: &'static str,
+    template: &str,
+    entries: &BTreeMap<String, FeedValue>,
+) -> Result<String, RSSDeliveryError> {
+    let args: Vec<FeedValue> = vec![];
+    showtimes_rss::format_text(template, &args, entries)
+        .map(|text| text.trim().to_string())
+        .map_err(|error| RSSDeliveryError::Template { on, error })
+}
+
+fn render_optional(
+    on: &'static str,
+    template: Option<&str>,
+    entries: &BTreeMap<String, FeedValue>,
+) -> Result<Option<String>, RSSDeliveryError> {
+    match template {
+        Some(template) => {
+            let rendered = render_single(on, template, entries)?;
+            Ok((!rendered.is_empty()).then_some(rendered))
+        }
+        None => Ok(None),
+    }
+}
+
+fn render_embed(
+    embed: &RSSFeedEmbedDisplay,
+    entries: &BTreeMap<String, FeedValue>,
+) -> Result<serde_json::Value, RSSDeliveryError> {
+    let mut payload = serde_json::json!({});
+
+    if let Some(title) = render_optional("embed.title", embed.title.as_deref(), entries)? {
+        payload["title"] = serde_json::json!(truncate(&title, MAX_EMBED_TITLE_LENGTH));
+    }
+    if let Some(description) =
+        render_optional("embed.description", embed.description.as_deref(), entries)?
+    {
+        payload["description"] =
+            serde_json::json!(truncate(&description, MAX_EMBED_DESCRIPTION_LENGTH));
+    }
+    if let Some(url) = render_optional("embed.url", embed.url.as_deref(), entries)? {
+        payload["url"] = serde_json::json!(url);
+    }
+    if let Some(thumbnail) =
+        render_optional("embed.thumbnail", embed.thumbnail.as_deref(), entries)?
+    {
+        payload["thumbnail"] = serde_json::json!({ "url": thumbnail });
+    }
+    if let Some(image) = render_optional("embed.image", embed.image.as_deref(), entries)? {
+        payload["image"] = serde_json::json!({ "url": image });
+    }
+    if let Some(footer) = render_optional("embed.footer", embed.footer.as_deref(), entries)? {
+        let mut footer = serde_json::json!({ "text": truncate(&footer, MAX_EMBED_FOOTER_LENGTH) });
+        if let Some(icon) =
+            render_optional("embed.footer_image", embed.footer_image.as_deref(), entries)?
+        {
+            footer["icon_url"] = serde_json::json!(icon);
+        }
+        payload["footer"] = footer;
+    }
+    if let Some(author) = render_optional("embed.author", embed.author.as_deref(), entries)? {
+        let mut author = serde_json::json!({ "name": truncate(&author, MAX_EMBED_TITLE_LENGTH) });
+        if let Some(icon) =
+            render_optional("embed.author_image", embed.author_image.as_deref(), entries)?
+        {
+            author["icon_url"] = serde_json::json!(icon);
+        }
+        payload["author"] = author;
+    }
+    if let Some(color) = embed.color {
+        payload["color"] = serde_json::json!(color);
+    }
+    if embed.timestamped {
+        let timestamp = ["published", "updated"]
+            .iter()
+            .find_map(|key| match entries.get(*key) {
+                Some(FeedValue::Timestamp(ts)) => Some(*ts),
+                _ => None,
+            })
+            .unwrap_or_else(jiff::Timestamp::now);
+        payload["timestamp"] = serde_json::json!(timestamp.to_string());
+    }
+
+    Ok(payload)
+}
+
+/// Render the RSS feed display of an entry into the Discord webhook payload
+///
+/// The message and the embed are rendered with [`showtimes_rss::format_text`],
+/// mentions are always disabled since the content came from an external feed.
+pub fn render_rss_payload(
+    display: &RSSFeedDisplay,
+    entry: &FeedEntryCloned,
+) -> Result<serde_json::Value, RSSDeliveryError> {
+    let entries: BTreeMap<String, FeedValue> =
+        entry.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+
+    let message = display
+        .message
+        .as_deref()
+        .unwrap_or_else(RSSFeedDisplay::default_message);
+    let content = render_single("message", message, &entries)?;
+
+    let embed = match &display.embed {
+        Some(embed) if embed.displayable() => Some(render_embed(embed, &entries)?),
+        _ => None,
+    };
+
+    if content.is_empty() && embed.is_none() {
+        return Err(RSSDeliveryError::Empty);
+    }
+
+    let mut payload = serde_json::json!({
+        "allowed_mentions": { "parse": [] },
+    });
+    if !content.is_empty() {
+        payload["content"] = serde_json::json!(truncate(&content, MAX_CONTENT_LENGTH));
+    }
+    if let Some(embed) = embed {
+        payload["embeds"] = serde_json::json!([embed]);
+    }
+
+    Ok(payload)
+}
+
+/// A simple per-destination rate limiter
+///
+/// Each destination is only allowed to receive a message every `interval`,
+/// a rate limited response will push back the next allowed time.
+#[derive(Clone)]
+pub struct RSSRateLimiter {
+    interval: Duration,
+    next_allowed: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl RSSRateLimiter {
+    /// Create a new rate limiter with the provided interval
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_allowed: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Reserve the next slot for the destination, returning how long to wait for it
+    pub async fn reserve(&self, destination: &str) -> Duration {
+        let mut next_allowed = self.next_allowed.lock().await;
+        let now = Instant::now();
+        // Forget destinations that are already free
+        next_allowed.retain(|_, at| *at > now);
+
+        let slot = next_allowed
+            .get(destination)
+            .copied()
+            .unwrap_or(now)
+            .max(now);
+        next_allowed.insert(destination.to_string(), slot + self.interval);
+
+        slot - now
+    }
+
+    /// Wait until the destination can receive the next message
+    pub async fn acquire(&self, destination: &str) {
+        let wait = self.reserve(destination).await;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Push back the destination after being rate limited
+    pub async fn penalize(&self, destination: &str, delay: Duration) {
+        let mut next_allowed = self.next_allowed.lock().await;
+        let until = Instant::now() + delay;
+        let entry = next_allowed.entry(destination.to_string()).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+}
+
+/// The RSS dispatcher
+///
+/// This will render the feed display for each new entry and post them into
+/// every Discord channel webhook of the feed, rate limited per destination.
+#[derive(Clone)]
+pub struct RSSDispatcher {
+    client: reqwest::Client,
+    limiter: RSSRateLimiter,
+    retry: RetryPolicy,
+}
+
+impl RSSDispatcher {
+    /// Create a new RSS dispatcher with [`DEFAULT_RSS_INTERVAL`]
+    pub fn new() -> Result<Self, reqwest::Error> {
+        Self::with_interval(DEFAULT_RSS_INTERVAL)
+    }
+
+    /// Create a new RSS dispatcher with custom interval for each destination
+    pub fn with_interval(interval: Duration) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: create_client()?,
+            limiter: RSSRateLimiter::new(interval),
+            retry: RetryPolicy::with_max_retries(DEFAULT_RSS_MAX_RETRIES),
+        })
+    }
+
+    /// Set the maximum retries and the base delay of the exponential backoff
+    ///
+    /// The delay is doubled on each retry, unless the target
+    /// returned a `Retry-After` header. The retries is capped to 10.
+    pub fn with_retries(mut self, max_retries: u8, base_delay: Duration) -> Self {
+        self.retry = RetryPolicy::new(max_retries, base_delay);
+        self
+    }
+
+    /// Deliver the entries into every Discord channel webhook of the feed
+    ///
+    /// Each channel is delivered concurrently, while the entries are sent
+    /// in the provided order to keep them ordered in the channel.
+    pub async fn deliver(
+        &self,
+        feed: &RSSFeed,
+        entries: &[showtimes_events::m::RSSEvent],
+    ) -> Vec<RSSDelivery> {
+        let webhooks: Vec<RSSFeedWebhook> = feed.delivery_webhooks().into_iter().cloned().collect();
+        if webhooks.is_empty() || entries.is_empty() {
+            return vec![];
+        }
+
+        let display = Arc::new(feed.display.clone());
+        let entries: Arc<Vec<showtimes_events::m::RSSEvent>> = Arc::new(entries.to_vec());
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for webhook in webhooks {
+            let this = self.clone();
+            let display = Arc::clone(&display);
+            let entries = Arc::clone(&entries);
+            tasks.spawn(async move {
+                let mut results = Vec::with_capacity(entries.len());
+                for entry in entries.iter() {
+                    let result = match render_rss_payload(&display, entry.entry())
+                        .and_then(|payload| serde_json::to_string(&payload).map_err(Into::into))
+                    {
+                        Ok(payload) => this.deliver_single(&webhook.url, &payload).await,
+                        Err(err) => Err(err),
+                    };
+
+                    results.push(RSSDelivery {
+                        channel: webhook.channel.clone(),
+                        hash: entry.hash_key().to_string(),
+                        result,
+                    });
+                }
+                results
+            });
+        }
+
+        let mut results = Vec::with_capacity(tasks.len() * entries.len());
+        while let Some(delivery) = tasks.join_next().await {
+            match delivery {
+                Ok(delivery) => results.extend(delivery),
+                Err(err) => tracing::error!("RSS delivery task failed: {}", err),
+            }
+        }
+
+        results
+    }
+
+    /// Deliver a single rendered payload, retrying retryable failures
+    async fn deliver_single(
+        &self,
+        url: &str,
+        payload: &str,
+    ) -> Result<reqwest::StatusCode, RSSDeliveryError> {
+        let mut attempt: u8 = 1;
+        loop {
+            self.limiter.acquire(url).await;
+
+            let (result, retry_after) = self.send(url, payload).await;
+            match result {
+                Err(err) if err.is_retryable() && self.retry.can_retry(attempt) => {
+                    let delay = self.retry.delay(attempt, retry_after);
+                    tracing::debug!(
+                        "Retrying RSS delivery in {:?} (attempt {}): {}",
+                        delay,
+                        attempt,
+                        err
+                    );
+                    // Push back every other entries to the same destination too
+                    self.limiter.penalize(url, delay).await;
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        }
+    }
+
+    /// Send a single delivery attempt
+    async fn send(
+        &self,
+        url: &str,
+        payload: &str,
+    ) -> (
+        Result<reqwest::StatusCode, RSSDeliveryError>,
+        Option<Duration>,
+    ) {
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload.to_string())
+            .send()
+            .await;
+
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => return (Err(RSSDeliveryError::Request(err)), None),
+        };
+
+        let status = response.status();
+        if status.is_success() {
+            (Ok(status), None)
+        } else {
+            let retry_after = parse_retry_after(response.headers());
+            (Err(RSSDeliveryError::Status(status)), retry_after)
+        }
+    }
+}
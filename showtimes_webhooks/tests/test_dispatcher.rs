@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use showtimes_db::m::{ProjectStatus, ProjectType, Webhook, WebhookFilter, WebhookTarget};
+use showtimes_test_support::{make_project, spawn_stand_in_status};
+use showtimes_webhooks::{WebhookDispatcher, WebhookError, WebhookEvent};
+
+#[tokio::test]
+async fn test_dispatch_release() {
+    let (url, mut rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator).with_locale("id-ID");
+
+    let event = WebhookEvent::ProjectRelease {
+        episodes: vec![project.progress[0].clone()],
+        project,
+    };
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher.dispatch(&[webhook.clone()], &event, None).await;
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, webhook.id);
+    assert!(results[0].result.is_ok());
+
+    let body = rx.recv().await.unwrap().body;
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["username"], "naoTimes");
+
+    let description = payload["embeds"][0]["description"].as_str().unwrap();
+    assert!(description.contains("**Test Project**"));
+    assert!(description.contains("#1"));
+}
+
+#[tokio::test]
+async fn test_dispatch_progress() {
+    let (url, mut rx) = spawn_stand_in_status("/webhook", vec![204]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let before = project.progress[0].clone();
+    let mut after = before.clone();
+    after.statuses[0].set_finished(true);
+
+    let event = WebhookEvent::ProjectProgress {
+        project,
+        before,
+        after,
+    };
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].result.is_ok());
+
+    let body = rx.recv().await.unwrap().body;
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let statuses = payload["embeds"][0]["fields"][0]["value"].as_str().unwrap();
+    assert!(statuses.contains("Translator"));
+    assert!(statuses.contains("Editor"));
+}
+
+#[tokio::test]
+async fn test_dispatch_skip_unmatched_action() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    // Default actions does not include project creation
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let event = WebhookEvent::ProjectCreate(project);
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_dispatch_failed_status() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![500]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let event = WebhookEvent::ProjectDropped(project);
+
+    let dispatcher = WebhookDispatcher::new()
+        .unwrap()
+        .with_retries(1, Duration::from_millis(10));
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+
+    assert_eq!(results.len(), 1);
+    match &results[0].result {
+        Err(WebhookError::Status(status)) => assert_eq!(status.as_u16(), 500),
+        other => panic!("Expected a status error, got {other:?}"),
+    }
+
+    // The first attempt and a single retry
+    let attempts = &results[0].attempts;
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0].attempt(), 1);
+    assert_eq!(attempts[1].attempt(), 2);
+    assert!(attempts.iter().all(|a| a.status() == Some(500)));
+    assert!(attempts.iter().all(|a| a.error().is_some()));
+    assert_eq!(attempts[0].action(), "project-dropped");
+}
+
+#[tokio::test]
+async fn test_dispatch_retry_then_success() {
+    let (url, mut rx) = spawn_stand_in_status("/webhook", vec![503, 429, 200]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    let dispatcher = WebhookDispatcher::new()
+        .unwrap()
+        .with_retries(3, Duration::from_millis(10));
+    let delivery = dispatcher.deliver(&webhook, &event, None).await;
+
+    assert!(delivery.result.is_ok());
+    let statuses: Vec<_> = delivery.attempts.iter().map(|a| a.status()).collect();
+    assert_eq!(statuses, vec![Some(503), Some(429), Some(200)]);
+    assert!(delivery.attempts[2].is_success());
+    assert_eq!(delivery.attempts[2].webhook_id(), webhook.id);
+
+    // Every attempt send the same payload
+    let first = rx.recv().await.unwrap().body;
+    for _ in 1..3 {
+        assert_eq!(rx.recv().await.unwrap().body, first);
+    }
+}
+
+#[tokio::test]
+async fn test_dispatch_honour_retry_after() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![429, 204]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    // A long backoff, the `retry-after: 0` should be used instead
+    let dispatcher = WebhookDispatcher::new()
+        .unwrap()
+        .with_retries(1, Duration::from_secs(30));
+    let delivery = tokio::time::timeout(
+        Duration::from_secs(5),
+        dispatcher.deliver(&webhook, &event, None),
+    )
+    .await
+    .expect("Retry-After header should be honoured");
+
+    assert!(delivery.result.is_ok());
+    assert_eq!(delivery.attempts.len(), 2);
+}
+
+#[tokio::test]
+async fn test_dispatch_no_retry_client_error() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![404]).await;
+    let project = make_project();
+    let webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    let dispatcher = WebhookDispatcher::new()
+        .unwrap()
+        .with_retries(3, Duration::from_millis(10));
+    let delivery = dispatcher.deliver(&webhook, &event, None).await;
+
+    assert!(matches!(delivery.result, Err(WebhookError::Status(_))));
+    assert_eq!(delivery.attempts.len(), 1);
+}
+
+#[tokio::test]
+async fn test_dispatch_skip_disabled() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    let mut webhook = Webhook::new(url, WebhookTarget::Discord, project.creator);
+    webhook.set_enabled(false);
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_dispatch_filter_project() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    let other = make_project();
+
+    let by_id = Webhook::new(url.clone(), WebhookTarget::Discord, project.creator).with_filter(
+        WebhookFilter {
+            projects: vec![other.id],
+            ..Default::default()
+        },
+    );
+    let by_kind =
+        Webhook::new(url, WebhookTarget::Discord, project.creator).with_filter(WebhookFilter {
+            kinds: vec![ProjectType::Movies, ProjectType::Series],
+            ..Default::default()
+        });
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher
+        .dispatch(&[by_id, by_kind.clone()], &event, None)
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, by_kind.id);
+}
+
+#[tokio::test]
+async fn test_dispatch_filter_roles() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    let webhook =
+        Webhook::new(url, WebhookTarget::Discord, project.creator).with_filter(WebhookFilter {
+            roles: vec!["ED".to_string()],
+            ..Default::default()
+        });
+
+    let before = project.progress[0].clone();
+    let mut translated = before.clone();
+    translated.statuses[0].set_finished(true);
+    let mut edited = translated.clone();
+    edited.statuses[1].set_finished(true);
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+
+    // Translator finished, not in the filter
+    let event = WebhookEvent::ProjectProgress {
+        project: project.clone(),
+        before: before.clone(),
+        after: translated.clone(),
+    };
+    let results = dispatcher
+        .dispatch(std::slice::from_ref(&webhook), &event, None)
+        .await;
+    assert!(results.is_empty());
+
+    // Editor finished
+    let event = WebhookEvent::ProjectProgress {
+        project,
+        before: translated,
+        after: edited,
+    };
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn test_dispatch_filter_skip_paused() {
+    let (url, _rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let mut project = make_project();
+    project.status = ProjectStatus::Paused;
+    let webhook =
+        Webhook::new(url, WebhookTarget::Discord, project.creator).with_filter(WebhookFilter {
+            skip_paused: true,
+            ..Default::default()
+        });
+
+    let event = WebhookEvent::ProjectRelease {
+        episodes: vec![project.progress[0].clone()],
+        project,
+    };
+
+    let dispatcher = WebhookDispatcher::new().unwrap();
+    let results = dispatcher.dispatch(&[webhook], &event, None).await;
+
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_dispatch_bounded_concurrency() {
+    let (url, mut rx) = spawn_stand_in_status("/webhook", vec![200]).await;
+    let project = make_project();
+    let webhooks: Vec<Webhook> = (0..6)
+        .map(|_| Webhook::new(url.clone(), WebhookTarget::Discord, project.creator))
+        .collect();
+
+    let event = WebhookEvent::ProjectResumed(project);
+
+    let dispatcher = WebhookDispatcher::with_concurrency(2).unwrap();
+    let results = dispatcher.dispatch(&webhooks, &event, None).await;
+
+    assert_eq!(results.len(), webhooks.len());
+    assert!(results.iter().all(|r| r.result.is_ok()));
+
+    for _ in 0..webhooks.len() {
+        assert!(rx.recv().await.is_some());
+    }
+}
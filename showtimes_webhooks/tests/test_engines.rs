@@ -0,0 +1,134 @@
+use showtimes_db::m::Project;
+use showtimes_test_support::make_project;
+use showtimes_webhooks::{
+    WebhookEvent,
+    engine::{
+        DiscordEngine, MatrixEngine, SlackEngine, TelegramEngine, WebhookEngine,
+        WebhookEnginePayload,
+    },
+};
+
+fn progress_event(project: &Project) -> WebhookEvent {
+    let before = project.progress[0].clone();
+    let mut after = before.clone();
+    after.statuses[0].set_finished(true);
+
+    WebhookEvent::ProjectProgress {
+        project: project.clone(),
+        before,
+        after,
+    }
+}
+
+fn render(engine: &dyn WebhookEnginePayload, event: &WebhookEvent) -> serde_json::Value {
+    let body = event.render(engine).unwrap();
+    serde_json::from_slice(body.as_bytes().unwrap()).unwrap()
+}
+
+#[test]
+fn test_discord_thumbnail() {
+    let project = make_project();
+    let mut engine = DiscordEngine::new("https://example.com/hook", "naoTimes", None);
+
+    let payload = render(&engine, &WebhookEvent::ProjectCreate(project.clone()));
+    assert!(payload["embeds"][0].get("thumbnail").is_none());
+
+    engine.set_asset_url(Some("https://showtimes.example.com/".to_string()));
+    let payload = render(&engine, &WebhookEvent::ProjectCreate(project.clone()));
+    assert_eq!(
+        payload["embeds"][0]["thumbnail"]["url"],
+        format!(
+            "https://showtimes.example.com{}",
+            project.poster.image.as_url()
+        )
+    );
+    assert!(
+        payload["embeds"][0]["description"]
+            .as_str()
+            .unwrap()
+            .contains("**Test Project**")
+    );
+}
+
+#[test]
+fn test_slack_blocks() {
+    let project = make_project();
+    let mut engine = SlackEngine::new("https://hooks.slack.com/services/T/B/X", "naoTimes", None);
+    engine.set_asset_url(Some("https://showtimes.example.com".to_string()));
+
+    let payload = render(&engine, &progress_event(&project));
+    assert_eq!(payload["username"], "naoTimes");
+
+    let attachment = &payload["attachments"][0];
+    assert!(attachment["color"].as_str().unwrap().starts_with('#'));
+
+    let blocks = attachment["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["type"], "header");
+    assert!(
+        blocks[0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Test Project")
+    );
+    assert_eq!(blocks[1]["type"], "image");
+    assert!(
+        blocks[2]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Translator")
+    );
+}
+
+#[test]
+fn test_slack_release_bold() {
+    let project = make_project();
+    let engine = SlackEngine::new("https://hooks.slack.com/services/T/B/X", "naoTimes", None);
+
+    let event = WebhookEvent::ProjectRelease {
+        episodes: vec![project.progress[0].clone()],
+        project,
+    };
+    let payload = render(&engine, &event);
+    let section = &payload["attachments"][0]["blocks"][1];
+    assert_eq!(section["type"], "section");
+    assert!(
+        section["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("*Test Project*")
+    );
+}
+
+#[test]
+fn test_matrix_html() {
+    let mut project = make_project();
+    project.title = "Test <Project>".to_string();
+    let engine = MatrixEngine::new("https://hookshot.example.com/webhook/abc", "naoTimes");
+
+    let payload = render(&engine, &WebhookEvent::ProjectDropped(project));
+    let html = payload["html"].as_str().unwrap();
+    assert!(html.contains("data-mx-color=\"#FF3333\""));
+    assert!(html.contains("<strong>Test &lt;Project&gt;</strong>"));
+
+    let text = payload["text"].as_str().unwrap();
+    assert!(text.contains("Test <Project>"));
+    assert!(!text.contains("<strong>"));
+}
+
+#[test]
+fn test_telegram_markdown() {
+    let mut project = make_project();
+    project.title = "Test Project (2nd Season)".to_string();
+    let engine = TelegramEngine::new(
+        "https://api.telegram.org/bot123:ABC/sendMessage?chat_id=-100123&message_thread_id=5",
+    );
+
+    let payload = render(&engine, &WebhookEvent::ProjectResumed(project));
+    assert_eq!(payload["chat_id"], "-100123");
+    assert_eq!(payload["message_thread_id"], 5);
+    assert_eq!(payload["parse_mode"], "MarkdownV2");
+    assert_eq!(payload["link_preview_options"]["is_disabled"], true);
+
+    let text = payload["text"].as_str().unwrap();
+    assert!(text.contains("*Test Project \\(2nd Season\\)*"));
+}
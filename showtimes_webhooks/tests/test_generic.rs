@@ -0,0 +1,124 @@
+use showtimes_db::m::{Webhook, WebhookTarget};
+use showtimes_test_support::make_project;
+use showtimes_webhooks::{
+    WebhookEvent,
+    engine::{
+        GenericEngine, WebhookEngine,
+        generic::{GENERIC_SCHEMA_VERSION, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+    },
+};
+
+fn render(engine: &GenericEngine, event: &WebhookEvent) -> Vec<u8> {
+    let body = event.render(engine).unwrap();
+    body.as_bytes().unwrap().to_vec()
+}
+
+#[test]
+fn test_generic_secret_generated() {
+    let webhook = Webhook::new(
+        "https://example.com/hook",
+        WebhookTarget::Generic,
+        showtimes_shared::ulid::Ulid::new(),
+    );
+    let secret = webhook.secret.as_deref().unwrap();
+    assert!(secret.starts_with("whsec_"));
+
+    let discord = Webhook::new(
+        "https://example.com/hook",
+        WebhookTarget::Discord,
+        showtimes_shared::ulid::Ulid::new(),
+    );
+    assert!(discord.secret.is_none());
+}
+
+#[test]
+fn test_generic_progress_payload() {
+    let project = make_project();
+    let mut engine = GenericEngine::new("https://example.com/hook", None);
+    engine.set_actor(Some("01J0000000000000000000TEST".to_string()));
+
+    let before = project.progress[0].clone();
+    let mut after = before.clone();
+    after.statuses[0].set_finished(true);
+
+    let event = WebhookEvent::ProjectProgress {
+        project: project.clone(),
+        before,
+        after,
+    };
+
+    let body = render(&engine, &event);
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(payload["version"], GENERIC_SCHEMA_VERSION);
+    assert_eq!(payload["event"], "project-progress");
+    assert_eq!(payload["actor"], "01J0000000000000000000TEST");
+    assert_eq!(payload["project"]["id"], project.id.to_string());
+    assert_eq!(payload["project"]["title"], "Test Project");
+    assert_eq!(payload["project"]["total_episodes"], 2);
+    assert_eq!(payload["episodes"].as_array().unwrap().len(), 0);
+
+    let change = &payload["changes"][0];
+    assert_eq!(change["before"]["number"], 1);
+    assert_eq!(change["before"]["statuses"][0]["finished"], false);
+    assert_eq!(change["after"]["statuses"][0]["finished"], true);
+    assert_eq!(change["after"]["statuses"][0]["name"], "Translator");
+}
+
+#[test]
+fn test_generic_release_payload() {
+    let project = make_project();
+    let engine = GenericEngine::new("https://example.com/hook", None);
+
+    let event = WebhookEvent::ProjectRelease {
+        episodes: vec![project.progress[0].clone(), project.progress[1].clone()],
+        project,
+    };
+
+    let body = render(&engine, &event);
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(payload["event"], "project-release");
+    assert!(payload["actor"].is_null());
+    assert_eq!(payload["episodes"].as_array().unwrap().len(), 2);
+    assert_eq!(payload["changes"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_generic_signature() {
+    let project = make_project();
+    let secret = Webhook::generate_secret();
+    let engine = GenericEngine::new("https://example.com/hook", Some(secret.clone()));
+
+    let event = WebhookEvent::ProjectDropped(project);
+    let body = render(&engine, &event);
+
+    let headers = engine.headers(&body);
+    let timestamp: i64 = headers
+        .get(TIMESTAMP_HEADER)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let signature = headers.get(SIGNATURE_HEADER).unwrap().to_str().unwrap();
+
+    let expected = GenericEngine::sign(&secret, timestamp, &body);
+    assert_eq!(signature, format!("sha256={expected}"));
+
+    // Different secret or timestamp should not match
+    assert_ne!(
+        GenericEngine::sign("whsec_other", timestamp, &body),
+        expected
+    );
+    assert_ne!(GenericEngine::sign(&secret, timestamp + 1, &body), expected);
+}
+
+#[test]
+fn test_generic_unsigned() {
+    let engine = GenericEngine::new("https://example.com/hook", None);
+    let headers = engine.headers(b"{}");
+
+    assert!(headers.get(SIGNATURE_HEADER).is_none());
+    assert!(headers.get(TIMESTAMP_HEADER).is_none());
+}
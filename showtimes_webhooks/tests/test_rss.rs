@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use ahash::{HashMap, HashMapExt};
+use showtimes_db::m::{
+    IntegrationId, IntegrationType, RSSFeed, RSSFeedDisplay, RSSFeedEmbedDisplay, RSSFeedWebhook,
+};
+use showtimes_events::m::RSSEvent;
+use showtimes_rss::{FeedEntry, FeedValue};
+use showtimes_test_support::spawn_stand_in_status;
+use showtimes_webhooks::{RSSDeliveryError, RSSDispatcher, RSSRateLimiter, render_rss_payload};
+
+fn make_entry(title: &str) -> FeedEntry<'static> {
+    let mut entry = HashMap::new();
+    entry.insert("title", FeedValue::from(title.to_string()));
+    entry.insert(
+        "link",
+        FeedValue::from("https://example.com/release".to_string()),
+    );
+    entry.insert(
+        "published",
+        FeedValue::from("2024-05-01T12:00:00Z".parse::<jiff::Timestamp>().unwrap()),
+    );
+    entry
+}
+
+#[test]
+fn test_render_default_message() {
+    let entry = showtimes_rss::transform_to_cloned_feed(&make_entry("Episode 01"));
+    let payload = render_rss_payload(&RSSFeedDisplay::default(), &entry).unwrap();
+
+    let content = payload["content"].as_str().unwrap();
+    assert!(content.contains("**Episode 01**"));
+    assert!(content.contains("https://example.com/release"));
+    assert!(payload.get("embeds").is_none());
+    assert_eq!(payload["allowed_mentions"]["parse"], serde_json::json!([]));
+}
+
+#[test]
+fn test_render_embed() {
+    let embed = RSSFeedEmbedDisplay {
+        title: Some("{title}".to_string()),
+        url: Some("{link}".to_string()),
+        color: Some(0xFF0000),
+        ..Default::default()
+    };
+    let display = RSSFeedDisplay::new_with_embed("", embed);
+    let entry = showtimes_rss::transform_to_cloned_feed(&make_entry("Episode 02"));
+    let payload = render_rss_payload(&display, &entry).unwrap();
+
+    assert!(payload.get("content").is_none());
+    let embed = &payload["embeds"][0];
+    assert_eq!(embed["title"], "Episode 02");
+    assert_eq!(embed["url"], "https://example.com/release");
+    assert_eq!(embed["color"], 0xFF0000);
+    assert_eq!(embed["author"]["name"], "naoTimes Feed");
+    assert_eq!(embed["timestamp"], "2024-05-01T12:00:00Z");
+}
+
+#[test]
+fn test_render_truncate_content() {
+    let entry = showtimes_rss::transform_to_cloned_feed(&make_entry(&"a".repeat(3000)));
+    let payload = render_rss_payload(&RSSFeedDisplay::new("{title}"), &entry).unwrap();
+
+    let content = payload["content"].as_str().unwrap();
+    assert_eq!(content.chars().count(), 2000);
+    assert!(content.ends_with('…'));
+}
+
+#[test]
+fn test_render_empty() {
+    let entry = showtimes_rss::transform_to_cloned_feed(&make_entry("Episode 03"));
+    let result = render_rss_payload(&RSSFeedDisplay::new("  "), &entry);
+
+    assert!(matches!(result, Err(RSSDeliveryError::Empty)));
+}
+
+#[tokio::test]
+async fn test_rate_limiter_per_destination() {
+    let limiter = RSSRateLimiter::new(Duration::from_secs(2));
+
+    assert!(limiter.reserve("a").await.is_zero());
+    assert!(limiter.reserve("b").await.is_zero());
+
+    let wait = limiter.reserve("a").await;
+    assert!(wait > Duration::from_secs(1) && wait <= Duration::from_secs(2));
+
+    limiter.penalize("b", Duration::from_secs(10)).await;
+    assert!(limiter.reserve("b").await > Duration::from_secs(9));
+}
+
+#[tokio::test]
+async fn test_deliver_channel_webhook() {
+    let (url, mut rx) = spawn_stand_in_status("/api/webhooks/1/token", vec![204]).await;
+
+    let mut feed = RSSFeed::new(
+        "https://example.com/feed.xml".parse().unwrap(),
+        showtimes_shared::ulid::Ulid::new(),
+    );
+    feed.add_integration(IntegrationId::new("1234", IntegrationType::DiscordChannel));
+    feed.add_integration(IntegrationId::new("5678", IntegrationType::DiscordChannel));
+    feed.webhooks = vec![
+        RSSFeedWebhook::new("1234", &url),
+        // Not an integration of the feed, should be skipped
+        RSSFeedWebhook::new("9999", &url),
+    ];
+
+    let events: Vec<RSSEvent> = ["Episode 01", "Episode 02"]
+        .iter()
+        .map(|title| RSSEvent::from_entry(feed.id, feed.creator, &make_entry(title)))
+        .collect();
+
+    let dispatcher = RSSDispatcher::with_interval(Duration::ZERO).unwrap();
+    let results = dispatcher.deliver(&feed, &events).await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|d| d.channel == "1234"));
+    assert!(results.iter().all(|d| d.result.is_ok()));
+
+    let first = rx.recv().await.unwrap().body;
+    let second = rx.recv().await.unwrap().body;
+    assert!(first.contains("Episode 01"));
+    assert!(second.contains("Episode 02"));
+}
+
+#[tokio::test]
+async fn test_deliver_without_webhook() {
+    let mut feed = RSSFeed::new(
+        "https://example.com/feed.xml".parse().unwrap(),
+        showtimes_shared::ulid::Ulid::new(),
+    );
+    feed.add_integration(IntegrationId::new("1234", IntegrationType::DiscordChannel));
+
+    let events = vec![RSSEvent::from_entry(
+        feed.id,
+        feed.creator,
+        &make_entry("Episode 01"),
+    )];
+
+    let dispatcher = RSSDispatcher::new().unwrap();
+    assert!(dispatcher.deliver(&feed, &events).await.is_empty());
+}